@@ -3,11 +3,14 @@ pub mod utils;
 use {
     crate::{
         prelude::*,
+        runtime_cfg,
         graphics::{
             Graphics,
             camera::Camera,
             RenderDescriptor,
             debug_visuals,
+            sky,
+            grid,
         },
     },
 
@@ -41,6 +44,8 @@ impl App {
     pub async fn new() -> Self {
         let _work_guard = logger::work("app", "initialize");
 
+        runtime_cfg::load_or_default(cfg::runtime_cfg::FILE_PATH).await;
+
         let graphics = Graphics::new()
             .await
             .expect("failed to create graphics");
@@ -65,6 +70,9 @@ impl App {
             logger::spawn_window,
             loading::spawn_info_window,
             crate::terrain::voxel::generator::spawn_control_window,
+            runtime_cfg::spawn_control_window,
+            sky::spawn_control_window,
+            grid::spawn_control_window,
         ];
 
         Self {
@@ -114,8 +122,9 @@ impl App {
 
                 WindowEvent::Resized(new_size) => {
                     let (width, height) = (new_size.width, new_size.height);
-                    // self.camera.aspect_ratio = height as f32
-                    //                          / width  as f32;
+                    if width > 0 {
+                        self.camera.aspect_ratio = height as f32 / width as f32;
+                    }
                     
                     // for light in self.lights.iter_mut() {
                     //     light.cam.aspect_ratio = 1.0;
@@ -124,6 +133,16 @@ impl App {
                     self.graphics.on_window_resize(UInt2::new(width, height));
                 },
 
+                WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size } => {
+                    let (width, height) = (new_inner_size.width, new_inner_size.height);
+                    if width > 0 {
+                        self.camera.aspect_ratio = height as f32 / width as f32;
+                    }
+
+                    self.graphics.on_scale_factor_changed(scale_factor);
+                    self.graphics.on_window_resize(UInt2::new(width, height));
+                },
+
                 _ => (),
             },
 
@@ -142,10 +161,12 @@ impl App {
 
     /// Main events cleared.
     async fn main_events_cleared(&mut self, control_flow: &mut ControlFlow) {
-        // ImGui can capture keyboard, if needed.
-        keyboard::set_input_capture(
-            self.graphics.imgui.context.io().want_text_input
-        );
+        // ImGui can capture keyboard and mouse, if needed.
+        let io = self.graphics.imgui.context.io();
+        user_io::set_ui_capture(user_io::UiCapture {
+            keyboard: io.want_text_input,
+            mouse: io.want_capture_mouse && !self.camera.grabbes_cursor,
+        });
         
         // Close window if `escape` pressed
         if keyboard::just_pressed(cfg::key_bindings::APP_EXIT) {
@@ -168,6 +189,11 @@ impl App {
             self.camera.grabbes_cursor = !self.camera.grabbes_cursor;
         }
 
+        if keyboard::just_pressed(cfg::key_bindings::SWITCH_WINDOW_MODE) {
+            let mode = self.graphics.window_mode.mode.next();
+            self.graphics.set_window_mode(mode);
+        }
+
         // if keyboard::just_pressed(cfg::key_bindings::SWITCH_RENDER_SHADOWS) {
         //     self.render_shadows = !self.render_shadows;
         // }
@@ -183,8 +209,10 @@ impl App {
         //     }
         }
 
+        self.graphics.reload_changed_textures().await;
+
         // // Update save/load tasks of `ChunkArray`
-        // self.chunk_arr.update(self.graphics.display.as_ref().get_ref(), &self.camera).await
+        // self.chunk_arr.update(self.graphics.display.as_ref().get_ref(), &mut self.camera, self.update_timer.dt).await
         //     .log_error("app", "failed to update chunk array");
 
         // Display FPS
@@ -203,6 +231,13 @@ impl App {
     async fn redraw_requested(&mut self, window_id: WindowId) {
         if window_id != self.graphics.window.id() { return }
 
+        let size = self.graphics.window.inner_size();
+        let sun_screen_pos = sky::sun_screen_pos(
+            sky::get().sun_dir(),
+            &self.camera,
+            [size.width as f32, size.height as f32],
+        );
+
         // InGui draw data
         let use_ui = |ui: &mut imgui::Ui| {
             // Camera window
@@ -214,6 +249,9 @@ impl App {
             // Chunk array control window
             // self.chunk_arr.spawn_control_window(ui);
 
+            // Render statistics overlay
+            // self.chunk_arr.render_stats.spawn_overlay(ui, self.draw_timer.fps);
+
             // Draw all windows by callbacks.
             for builder in self.imgui_window_builders.iter() {
                 builder(ui)
@@ -229,6 +267,8 @@ impl App {
             RenderDescriptor {
                 use_imgui_ui: use_ui,
                 time: self.draw_timer.time,
+                grid: grid::get(),
+                sun_screen_pos,
             }
         ).expect("failed to render graphics");
 
@@ -236,6 +276,9 @@ impl App {
         self.graphics.imgui.context
             .io_mut()
             .update_delta_time(self.draw_timer.duration());
+
+        // End profiler frame.
+        profiler::update();
     }
 
     /// Updates things.
@@ -244,7 +287,11 @@ impl App {
 
         // Rotating camera.
         self.camera.update(self.update_timer.dt);
+
+        // Day-night cycle.
+        sky::advance(self.update_timer.dt);
         // for light in self.lights.iter_mut() {
+        //     light.follow_sun(sky::get().hours);
         //     light.update(self.camera.pos);
         // }
 
@@ -0,0 +1,100 @@
+//! Additions to [`math_linear`] bounding boxes.
+
+use math_linear::prelude::*;
+
+pub trait AabbExt: Sized {
+    /// Smallest box enclosing both boxes.
+    fn union(&self, other: &Self) -> Self;
+
+    /// Common part of both boxes. Gives [`None`] if boxes are disjoint.
+    /// Boxes touching by a face give flat intersection.
+    fn intersection(&self, other: &Self) -> Option<Self>;
+
+    /// Expands box by `amount` on each side.
+    fn grow(&self, amount: vec3) -> Self;
+
+    /// Checks that `point` is inside the box. Bounds are inclusive.
+    fn contains_point(&self, point: vec3) -> bool;
+}
+
+impl AabbExt for AABB {
+    fn union(&self, other: &Self) -> Self {
+        Self::from_float3(min(self.lo, other.lo), max(self.hi, other.hi))
+    }
+
+    fn intersection(&self, other: &Self) -> Option<Self> {
+        let (lo, hi) = (max(self.lo, other.lo), min(self.hi, other.hi));
+
+        (lo.x <= hi.x && lo.y <= hi.y && lo.z <= hi.z)
+            .then(|| Self::from_float3(lo, hi))
+    }
+
+    fn grow(&self, amount: vec3) -> Self {
+        Self::from_float3(self.lo - amount, self.hi + amount)
+    }
+
+    fn contains_point(&self, point: vec3) -> bool {
+        self.lo.x <= point.x && point.x <= self.hi.x
+            && self.lo.y <= point.y && point.y <= self.hi.y
+            && self.lo.z <= point.z && point.z <= self.hi.z
+    }
+}
+
+/// Component-wise minimum.
+fn min(lhs: vec3, rhs: vec3) -> vec3 {
+    vec3::new(lhs.x.min(rhs.x), lhs.y.min(rhs.y), lhs.z.min(rhs.z))
+}
+
+/// Component-wise maximum.
+fn max(lhs: vec3, rhs: vec3) -> vec3 {
+    vec3::new(lhs.x.max(rhs.x), lhs.y.max(rhs.y), lhs.z.max(rhs.z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aabb(lo: (f32, f32, f32), hi: (f32, f32, f32)) -> AABB {
+        AABB::from_float3(vecf!(lo.0, lo.1, lo.2), vecf!(hi.0, hi.1, hi.2))
+    }
+
+    #[test]
+    fn union_encloses_both_boxes() {
+        let lhs = aabb((0.0, 0.0, 0.0), (1.0, 2.0, 1.0));
+        let rhs = aabb((-3.0, 1.0, 0.5), (0.5, 4.0, 0.75));
+
+        let union = lhs.union(&rhs);
+        assert_eq!((union.lo, union.hi), (vecf!(-3.0, 0.0, 0.0), vecf!(1.0, 4.0, 1.0)));
+
+        for corner in [lhs.lo, lhs.hi, rhs.lo, rhs.hi] {
+            assert!(union.contains_point(corner), "{corner:?} should be in union");
+        }
+    }
+
+    #[test]
+    fn intersection_of_overlapping_boxes() {
+        let lhs = aabb((0.0, 0.0, 0.0), (2.0, 2.0, 2.0));
+        let rhs = aabb((1.0, 1.0, 1.0), (3.0, 3.0, 3.0));
+
+        let common = lhs.intersection(&rhs).expect("boxes should intersect");
+        assert_eq!((common.lo, common.hi), (vecf!(1.0, 1.0, 1.0), vecf!(2.0, 2.0, 2.0)));
+    }
+
+    #[test]
+    fn intersection_of_disjoint_boxes_is_none() {
+        let lhs = aabb((0.0, 0.0, 0.0), (2.0, 2.0, 2.0));
+        let far = aabb((0.0, 5.0, 0.0), (2.0, 6.0, 2.0));
+
+        assert!(lhs.intersection(&far).is_none());
+        assert!(far.intersection(&lhs).is_none());
+    }
+
+    #[test]
+    fn grow_expands_each_side() {
+        let grown = aabb((0.0, 0.0, 0.0), (1.0, 1.0, 1.0)).grow(vecf!(1.0, 0.5, 0.0));
+
+        assert_eq!((grown.lo, grown.hi), (vecf!(-1.0, -0.5, 0.0), vecf!(2.0, 1.5, 1.0)));
+        assert!(grown.contains_point(vecf!(-1.0, 1.5, 0.5)));
+        assert!(!grown.contains_point(vecf!(-1.0, 1.5, 1.5)));
+    }
+}
@@ -6,6 +6,26 @@ pub mod save {
     pub const META_FILE_NAME: &str = "meta.off";
     pub const STACK_FILE_EXTENSION: &str = "stk";
     pub const HEAP_FILE_EXTENSION:  &str = "hp";
+
+    /// Number of previous saves kept as backups.
+    pub const N_BACKUPS: usize = 3;
+
+    /// Encode all chunks with one Huffman dictionary stored in the save header.
+    pub const SHARE_HUFFMAN_DICTIONARY: bool = false;
+
+    /// Encode and write chunks by batches instead of encoding the whole world first.
+    pub const STREAM_CHUNKS: bool = true;
+
+    /// Number of chunks encoded at once by streamed save.
+    pub const STREAM_BATCH_SIZE: usize = 64;
+
+    /// File for recorded commands to replay them later.
+    pub const COMMAND_RECORDING_PATH: &str = "commands.rec";
+}
+
+pub mod runtime_cfg {
+    /// File with values overriding some of these constants at startup.
+    pub const FILE_PATH: &str = "terramine.cfg";
 }
 
 pub mod camera {
@@ -14,14 +34,24 @@ pub mod camera {
     pub const LIGHT_NEAR_PLANE: f32 = 1.0;
     pub const LIGHT_FAR_PLANE:  f32 = 200.0;
 
+    /// Bounds of near and far plane sliders.
+    pub const MIN_NEAR_PLANE: f32 = 0.01;
+    pub const MAX_NEAR_PLANE: f32 = 10.0;
+    pub const MAX_FAR_PLANE:  f32 = 100_000.0;
+
+    /// Far plane is kept at least that far from the near one.
+    pub const MIN_PLANES_GAP: f32 = 1.0;
+
     pub mod default {
-        /// These constants are shared with shader file. See `postprocessing.frag`.
         pub const NEAR_PLANE:     f32 = 0.5;
         pub const FAR_PLANE:      f32 = 10_000.0;
 
         pub const SPEED:	      f32 = 10.0;
         pub const SPEED_FALLOFF:  f32 = 0.88;
         pub const FOV_IN_DEGREES: f32 = 60.0;
+
+        /// Write logarithmic depth to spread its precision over the whole view distance.
+        pub const IS_LOG_DEPTH_ENABLED: bool = false;
     }
 }
 
@@ -32,6 +62,20 @@ pub mod window {
         pub const WIDTH:  usize = 1024;
         pub const HEIGHT: usize = 768;
         pub const SIZES: USize2 = vecs!(WIDTH, HEIGHT);
+
+        pub const PRESENT_MODE: crate::app::utils::graphics::present_mode::PresentMode
+            = crate::app::utils::graphics::present_mode::PresentMode::Vsync;
+    }
+}
+
+pub mod ui {
+    /// ImGui font size in logical pixels.
+    pub const FONT_SIZE_IN_PIXELS: f32 = 13.0;
+
+    /// Crosshair half-sizes in logical pixels.
+    pub mod crosshair {
+        pub const HALF_THICKNESS: f32 = 3.5;
+        pub const HALF_LENGTH:    f32 = 21.5;
     }
 }
 
@@ -67,7 +111,25 @@ pub mod terrain {
     pub const LEFT_IDX:   usize = 5;
 
     pub const MAX_TASKS: usize = 10_000;
+    /// Number of chunk LOD meshes kept in memory per chunk.
+    pub const N_CACHED_LODS: usize = 3;
     pub const MAX_CHUNKS: usize = 100_000;
+    /// Maximal distance in voxels from camera to nudged placement position.
+    pub const PLACEMENT_REACH_IN_VOXELS: f32 = 12.0;
+    /// Minimal time between voxel edits while mouse button is held.
+    pub const INTERACTION_COOLDOWN_IN_MS: u64 = 200;
+    /// Chunk is stored as octree if it takes at most this part of dense voxel array size.
+    pub const SPARSE_CHUNK_SIZE_RATIO: f32 = 0.25;
+    /// Chunks farther from camera keep only compressed voxels.
+    pub const VOXEL_EVICTION_DISTANCE_IN_CHUNKS: f32 = 12.0;
+    /// Evicted chunks closer to camera get their voxels back.
+    pub const VOXEL_RESTORE_DISTANCE_IN_CHUNKS: f32 = 9.0;
+    /// Minimal time between voxel eviction passes.
+    pub const VOXEL_EVICTION_PERIOD_IN_MS: u64 = 1000;
+    /// Minimal time between storage optimization passes of edited chunks.
+    pub const CHUNK_OPTIMIZATION_PERIOD_IN_MS: u64 = 1000;
+    /// World space margin added to chunk bounds in visibility check.
+    pub const VISIBILITY_MARGIN: f32 = 8.0;
 
     pub mod voxel_types {
         use {
@@ -75,12 +137,16 @@ pub mod terrain {
             math_linear::prelude::Color,
         };
 
-        pub const VOXEL_DATA: [VoxelData; 5] = [
-            VoxelData { name: "Air",    id: 0, avarage_color: Color::new(0.00, 0.00, 0.00), textures: TextureSides::all(0) },
-            VoxelData { name: "Log",    id: 1, avarage_color: Color::new(0.62, 0.52, 0.30), textures: TextureSides::vertical(3, 1, 1) },
-            VoxelData { name: "Stone",  id: 2, avarage_color: Color::new(0.45, 0.45, 0.45), textures: TextureSides::all(2) },
-            VoxelData { name: "Grass",  id: 3, avarage_color: Color::new(0.40, 0.64, 0.24), textures: TextureSides::vertical(4, 6, 5) },
-            VoxelData { name: "Dirt",   id: 4, avarage_color: Color::new(0.59, 0.42, 0.29), textures: TextureSides::all(5) },
+        /// Optional voxel table file, see [`parse_table`][crate::app::utils::terrain::voxel::voxel_data::parse_table].
+        pub const TABLE_PATH: &str = "voxels.txt";
+
+        pub const VOXEL_DATA: [VoxelData; 6] = [
+            VoxelData { name: "Air",    id: 0, avarage_color: Color::new(0.00, 0.00, 0.00), textures: TextureSides::all(0), is_transparent: true, unbreakable: false, hardness: 0.0, on_interact: None },
+            VoxelData { name: "Log",    id: 1, avarage_color: Color::new(0.62, 0.52, 0.30), textures: TextureSides::vertical(3, 1, 1), is_transparent: false, unbreakable: false, hardness: 0.6, on_interact: None },
+            VoxelData { name: "Stone",  id: 2, avarage_color: Color::new(0.45, 0.45, 0.45), textures: TextureSides::all(2), is_transparent: false, unbreakable: false, hardness: 1.0, on_interact: None },
+            VoxelData { name: "Grass",  id: 3, avarage_color: Color::new(0.40, 0.64, 0.24), textures: TextureSides::vertical(4, 6, 5), is_transparent: false, unbreakable: false, hardness: 0.4, on_interact: None },
+            VoxelData { name: "Dirt",   id: 4, avarage_color: Color::new(0.59, 0.42, 0.29), textures: TextureSides::all(5), is_transparent: false, unbreakable: false, hardness: 0.3, on_interact: None },
+            VoxelData { name: "Bedrock", id: 5, avarage_color: Color::new(0.23, 0.23, 0.26), textures: TextureSides::all(7), is_transparent: false, unbreakable: true, hardness: f32::INFINITY, on_interact: None },
         ];
     }
 
@@ -88,6 +154,11 @@ pub mod terrain {
         use math_linear::prelude::Int3;
         pub const WORLD_SIZES_IN_CHUNKS: Int3 = veci!(7, 1, 7);
         pub const LOD_THREASHOLD: f32 = 5.8;
+
+        /// Height of bedrock floor. Voxels below it are air.
+        pub const WORLD_MIN_Y: i32 = 0;
+        /// Voxels above this height are air.
+        pub const WORLD_MAX_Y: i32 = 255;
     }
 
     #[cfg(test)]
@@ -99,9 +170,25 @@ pub mod terrain {
     }
 }
 
+pub mod sky {
+    /// Real seconds in one world day.
+    pub const DAY_LENGTH_IN_SECONDS: f32 = 600.0;
+    pub const START_HOURS: f32 = 10.0;
+
+    pub const DAY_COLOR:     (f32, f32, f32) = (0.45, 0.65, 0.95);
+    pub const NIGHT_COLOR:   (f32, f32, f32) = (0.02, 0.02, 0.05);
+    pub const HORIZON_COLOR: (f32, f32, f32) = (0.95, 0.55, 0.30);
+
+    /// Sun disc radius relative to screen height.
+    pub const SUN_RADIUS: f32 = 0.035;
+}
+
 pub mod texture {
     pub const DIRECTORY: &str = "src/image/";
 
+    /// Voxel textures atlas in [`DIRECTORY`].
+    pub const ATLAS_FILE_NAME: &str = "texture_atlas.png";
+
     pub mod atlas {
         pub const ITEM_SIZE_IN_PIXELS:    usize = 8;
         pub const ITEM_PADDING_IN_PIXELS: usize = 4;
@@ -130,6 +217,12 @@ pub mod shader {
             pub const RIGHT:  f32 = 0.7;
         }
     }
+
+    pub mod grid {
+        pub const COLOR: [f32; 4] = [0.1, 0.3, 0.8, 1.0];
+        /// Line thickness in world units.
+        pub const THICKNESS: f32 = 0.03;
+    }
 }
 
 pub mod key_bindings {
@@ -142,6 +235,53 @@ pub mod key_bindings {
     pub const ENABLE_PROFILER_WINDOW:         Key = Key::E;
     pub const SWITCH_RENDER_SHADOWS:          Key = Key::U;
     pub const RELOAD_RESOURCES:               Key = Key::H;
+    pub const MEASURE:                        Key = Key::M;
+    pub const CANCEL_READING:                 Key = Key::Back;
+    pub const PICKING_DEBUG_SWITCH:           Key = Key::K;
+    pub const FILL_MODE_SWITCH:               Key = Key::F;
+    pub const FREEZE_LOD_SWITCH:              Key = Key::L;
+    pub const PICK_VOXEL:                     Key = Key::G;
+    pub const SWITCH_WINDOW_MODE:             Key = Key::F11;
+    pub const COPY_POSITION:                  Key = Key::C;
+    pub const DROP_HELD_VOXEL:                Key = Key::Q;
+    pub const NUDGE_NEG_X:                    Key = Key::Left;
+    pub const NUDGE_POS_X:                    Key = Key::Right;
+    pub const NUDGE_NEG_Z:                    Key = Key::Up;
+    pub const NUDGE_POS_Z:                    Key = Key::Down;
+    pub const NUDGE_POS_Y:                    Key = Key::PageUp;
+    pub const NUDGE_NEG_Y:                    Key = Key::PageDown;
+    pub const CONFIRM_PLACEMENT:              Key = Key::Return;
+    pub const EXPORT_FLAMEGRAPH:              Key = Key::J;
+}
+
+pub mod profiler {
+    /// File for folded scope stacks of one frame. It can be turned to flamegraph by `inferno`.
+    pub const FLAMEGRAPH_PATH: &str = "profile.folded";
+}
+
+pub mod console {
+    /// Number of executed commands kept in console history.
+    pub const HISTORY_CAPACITY: usize = 64;
+
+    /// Time after which running script is interrupted.
+    pub const SCRIPT_TIMEOUT_IN_SECONDS: f32 = 5.0;
+
+    /// Identical log messages repeated within this time are merged into one entry.
+    pub const LOG_COALESCE_WINDOW_IN_SECONDS: f32 = 2.0;
+
+    /// Records of `log` crate up to this level are shown in console.
+    /// Less important ones are left to `env_logger`.
+    pub const LOG_BRIDGE_LEVEL: ::log::LevelFilter = ::log::LevelFilter::Warn;
+
+    /// Enables writing log messages to file in [`LOG_FILE_DIR`]. Log files are
+    /// not rotated, so it is off by default and meant for debugging crashes.
+    pub const IS_LOG_FILE_ENABLED: bool = false;
+
+    /// Directory for log files.
+    pub const LOG_FILE_DIR: &str = "logs";
+
+    /// Maximal time log messages can wait in file buffer. Errors are flushed immediately.
+    pub const LOG_FILE_FLUSH_INTERVAL_IN_SECONDS: f32 = 1.0;
 }
 
 pub mod timer {
@@ -2,7 +2,7 @@
 
 use {
     std::{
-        collections::HashMap,
+        collections::{HashMap, HashSet},
         sync::Mutex,
     },
     tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
@@ -28,6 +28,7 @@ pub enum LoadingError {
 #[derive(Debug)]
 pub struct Loadings {
     pub list: HashMap<String, f32>,
+    pub cancelled: HashSet<String>,
 }
 
 impl Default for Loadings {
@@ -38,10 +39,12 @@ impl Default for Loadings {
 
 impl Loadings {
     pub fn new() -> Self {
-        Self { list: HashMap::new() }
+        Self { list: HashMap::new(), cancelled: HashSet::new() }
     }
 
     pub fn add(&mut self, name: String) -> Result<(), LoadingError> {
+        self.cancelled.remove(&name);
+
         match self.list.insert(name.clone(), 0.0) {
             None => Ok(()),
             Some(dropped_value) => Err(
@@ -51,6 +54,8 @@ impl Loadings {
     }
 
     pub fn refresh(&mut self, name: &str, new_val: f32) -> Result<(), LoadingError> {
+        if self.cancelled.contains(name) { return Ok(()) }
+
         match self.list.get_mut(name) {
             None => Err(
                 LoadingError::RefreshFailed(name.into(), new_val)
@@ -66,6 +71,8 @@ impl Loadings {
     }
 
     pub fn finish(&mut self, name: &str) -> Result<(), LoadingError> {
+        if self.cancelled.remove(name) { return Ok(()) }
+
         match self.list.remove(name) {
             None =>
                 Err(LoadingError::LoadingNotExist(name.into())),
@@ -77,6 +84,18 @@ impl Loadings {
         }
     }
 
+    /// Removes loading from the list. Following refreshes and finish of it will be ignored.
+    pub fn cancel(&mut self, name: &str) {
+        if self.list.remove(name).is_some() {
+            self.cancelled.insert(name.to_owned());
+        }
+    }
+
+    /// Gives current value of loading if it exists.
+    pub fn progress(&self, name: &str) -> Option<f32> {
+        self.list.get(name).copied()
+    }
+
     pub fn spawn_info_window(&self, ui: &imgui::Ui) {
         use crate::app::utils::graphics::ui::imgui_constructor::make_window;
 
@@ -99,6 +118,19 @@ pub fn spawn_info_window(ui: &imgui::Ui) {
         .spawn_info_window(ui)
 }
 
+pub fn progress(name: &str) -> Option<f32> {
+    LOADINGS.lock()
+        .unwrap()
+        .loads
+        .progress(name)
+}
+
+pub fn cancel(name: &'static str) {
+    make_sender()
+        .send(Command::Cancel(name))
+        .expect("failed to send cancel command to loading");
+}
+
 pub fn recv_all() -> Result<(), LoadingError> {
     LOADINGS.lock()
         .unwrap()
@@ -110,6 +142,7 @@ pub enum Command<'s> {
     Add(String),
     Refresh(&'s str, f32),
     Finish(&'s str),
+    Cancel(&'s str),
 }
 
 #[derive(Debug)]
@@ -163,6 +196,9 @@ impl<'s> ChannelLoadings<'s> {
 
                 Command::Finish(name) =>
                     self.loads.finish(name)?,
+
+                Command::Cancel(name) =>
+                    self.loads.cancel(name),
             }
         }
 
@@ -13,8 +13,19 @@ use {
         },
     },
     frustum::Frustum,
+    spin::Mutex,
+    math_linear::math::ray::space_3d::Line,
+    glium::uniforms::{Uniforms, UniformValue},
 };
 
+/// Position requested from console. It is applied on next [update][Camera::update].
+static POSITION_REQUEST: Mutex<Option<vec3>> = Mutex::new(None);
+
+/// Requests camera to move to `pos` on next [update][Camera::update].
+pub fn request_position(pos: vec3) {
+    *POSITION_REQUEST.lock() = Some(pos);
+}
+
 /// Camera handler.
 #[derive(Debug)]
 pub struct Camera {
@@ -23,6 +34,7 @@ pub struct Camera {
     pub aspect_ratio: f32,
     pub near_plane_dist: f32,
     pub far_plane_dist: f32,
+    pub is_log_depth_enabled: bool,
 
     /* Additional control */
     pub speed_factor: f32,
@@ -132,6 +144,10 @@ impl Camera {
 
     /// Updates camera (key press checking, etc).
     pub fn update(&mut self, dt: f32) {
+        if let Some(pos) = POSITION_REQUEST.lock().take() {
+            self.set_position(pos.x, pos.y, pos.z);
+        }
+
         /* Camera move vector */
         let mut new_speed = vec3::all(0.0);
 
@@ -187,6 +203,67 @@ impl Camera {
             .as_2d_array()
     }
 
+    /// Gives screen position in pixels of `world` point. Screen origin is top-left corner
+    /// of `viewport` of `[width, height]` pixels. Gives [`None`] if the point is behind the camera.
+    pub fn project(&self, world: vec3, viewport: [f32; 2]) -> Option<[f32; 2]> {
+        let view_pos = transform(&self.get_view(), Float4::from_vec3(world, 1.0).to_array());
+        let [x, y, _, w] = transform(&self.get_proj(), view_pos);
+
+        if w <= 0.0 { return None }
+
+        let (ndc_x, ndc_y) = (x / w, y / w);
+        Some([
+            (ndc_x + 1.0) * 0.5 * viewport[0],
+            (1.0 - ndc_y) * 0.5 * viewport[1],
+        ])
+    }
+
+    /// Gives world-space ray from camera through `screen` position in pixels.
+    /// Screen origin is top-left corner of `viewport` of `[width, height]` pixels.
+    pub fn unproject(&self, screen: [f32; 2], viewport: [f32; 2]) -> Line {
+        let (view, proj) = (self.get_view(), self.get_proj());
+
+        let ndc_x = screen[0] / viewport[0] * 2.0 - 1.0;
+        let ndc_y = 1.0 - screen[1] / viewport[1] * 2.0;
+
+        // Perspective projection keeps view-space depth in `w`, so ray direction has unit depth.
+        let view_dir = [
+            (ndc_x - proj[2][0]) / proj[0][0],
+            (ndc_y - proj[2][1]) / proj[1][1],
+            1.0,
+        ];
+
+        // View rotation is orthonormal, so its transpose turns the direction back to world space.
+        let world_dir = (0..3)
+            .map(|row| vec3::new(view[0][row], view[1][row], view[2][row]) * view_dir[row])
+            .fold(vec3::zero(), |sum, axis| sum + axis);
+
+        Line::from_2_points(self.pos, self.pos + world_dir)
+    }
+
+    /// Sets near and far planes. Far plane is kept behind the near one.
+    pub fn set_planes(&mut self, near: f32, far: f32) {
+        let near = near.clamp(cfg::camera::MIN_NEAR_PLANE, cfg::camera::MAX_NEAR_PLANE);
+        let far = far.clamp(near + cfg::camera::MIN_PLANES_GAP, cfg::camera::MAX_FAR_PLANE);
+
+        (self.near_plane_dist, self.far_plane_dist) = (near, far);
+        self.frustum = Some(Frustum::new(self));
+    }
+
+    /// Gives `log_depth_coef` shader uniform. Shaders replace clip-space depth with
+    /// `(log2(1 + w) * coef - 1) * w` if it is positive. Zero disables logarithmic depth.
+    pub fn log_depth_coef(&self) -> f32 {
+        match self.is_log_depth_enabled {
+            true => 2.0 / (self.far_plane_dist + 1.0).log2(),
+            false => 0.0,
+        }
+    }
+
+    /// Gives `z_near` and `z_far` shader uniforms of current planes.
+    pub fn planes_uniforms(&self) -> PlanesUniforms {
+        PlanesUniforms { z_near: self.near_plane_dist, z_far: self.far_plane_dist }
+    }
+
     pub fn get_ortho(&self, width: f32, height: f32) -> [[f32; 4]; 4] {
         mat4::orthographic_lh(
             width, height,
@@ -233,6 +310,11 @@ impl Camera {
                 z = self.get_z(),
             ));
 
+            ui.same_line();
+            if ui.button("Copy") {
+                user_io::clipboard::copy(logger::camera_set_command(self.pos));
+            }
+
             ui.text("Rotation");
             ui.text(format!(
                 "roll: {roll:.3}, pitch: {pitch:.3}, yaw: {yaw:.3}",
@@ -257,10 +339,33 @@ impl Camera {
                 .build(&mut fov);
 
             self.fov.set_degrees(fov);
+
+            let (mut near, mut far) = (self.near_plane_dist, self.far_plane_dist);
+
+            let is_near_changed = ui.slider_config("Near plane", cfg::camera::MIN_NEAR_PLANE, cfg::camera::MAX_NEAR_PLANE)
+                .display_format("%.2f")
+                .flags(imgui::SliderFlags::LOGARITHMIC)
+                .build(&mut near);
+
+            let is_far_changed = ui.slider_config("Far plane", cfg::camera::MIN_PLANES_GAP, cfg::camera::MAX_FAR_PLANE)
+                .display_format("%.0f")
+                .flags(imgui::SliderFlags::LOGARITHMIC)
+                .build(&mut far);
+
+            if is_near_changed || is_far_changed {
+                self.set_planes(near, far);
+            }
+
+            ui.checkbox("Logarithmic depth", &mut self.is_log_depth_enabled);
         });
     }
 }
 
+/// Multiplies column-major `matrix` by `vector`.
+fn transform(matrix: &[[f32; 4]; 4], vector: [f32; 4]) -> [f32; 4] {
+    array_init(|row| (0..4).map(|col| matrix[col][row] * vector[col]).sum())
+}
+
 impl Default for Camera {
     fn default() -> Self {
         let mut cam = Camera {
@@ -272,6 +377,7 @@ impl Default for Camera {
 
             near_plane_dist: cam_def::NEAR_PLANE,
             far_plane_dist: cam_def::FAR_PLANE,
+            is_log_depth_enabled: cam_def::IS_LOG_DEPTH_ENABLED,
 
             grabbes_cursor: false,
 
@@ -294,4 +400,84 @@ impl Default for Camera {
 
         cam
     }
-}
\ No newline at end of file
+}
+
+/// Near and far planes of [camera][Camera] for shaders that linearize depth.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlanesUniforms {
+    pub z_near: f32,
+    pub z_far: f32,
+}
+
+impl Uniforms for PlanesUniforms {
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut output: F) {
+        output("z_near", UniformValue::Float(self.z_near));
+        output("z_far", UniformValue::Float(self.z_far));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gives NDC depth of a point `dist` units in front of the camera.
+    /// Projection matrix is column-major as glium expects.
+    fn ndc_depth(proj: [[f32; 4]; 4], dist: f32) -> f32 {
+        let point = [0.0, 0.0, dist, 1.0];
+        let clip = |row: usize| (0..4).map(|col| proj[col][row] * point[col]).sum::<f32>();
+
+        clip(2) / clip(3)
+    }
+
+    #[test]
+    fn far_plane_changes_projection_depth_mapping() {
+        let mut cam = Camera::new();
+
+        cam.set_planes(0.5, 100.0);
+        let proj = cam.get_proj();
+        assert!((ndc_depth(proj, 100.0) - 1.0).abs() < 1e-3);
+        assert!(ndc_depth(proj, 500.0) > 1.0);
+
+        cam.set_planes(0.5, 1000.0);
+        let proj = cam.get_proj();
+        assert!(ndc_depth(proj, 500.0) < 1.0);
+        assert!((ndc_depth(proj, 1000.0) - 1.0).abs() < 1e-3);
+
+        cam.set_planes(5.0, 1.0);
+        assert!(cam.far_plane_dist >= cam.near_plane_dist + cfg::camera::MIN_PLANES_GAP);
+
+        let mut planes = vec![];
+        cam.planes_uniforms().visit_values(|name, value| match value {
+            UniformValue::Float(value) => planes.push((name.to_owned(), value)),
+            _ => panic!("plane uniform {name} should be float"),
+        });
+        assert_eq!(planes, [("z_near".to_owned(), cam.near_plane_dist), ("z_far".to_owned(), cam.far_plane_dist)]);
+
+        assert_eq!(cam.log_depth_coef(), 0.0);
+        cam.is_log_depth_enabled = true;
+        assert!(((cam.far_plane_dist + 1.0).log2() * cam.log_depth_coef() - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn unprojected_ray_passes_through_projected_point() {
+        let cam = Camera::new()
+            .with_position(1.0, 2.0, 3.0)
+            .with_rotation(0.0, 0.3, 1.1);
+        let viewport = [800.0, 600.0];
+
+        let point = cam.pos + cam.front * 10.0 + cam.right * 2.0 + cam.up;
+        let screen = cam.project(point, viewport).expect("point should be in front of camera");
+
+        assert!((0.0..viewport[0]).contains(&screen[0]) && (0.0..viewport[1]).contains(&screen[1]));
+
+        let ray = cam.unproject(screen, viewport);
+        let dir = ray.point_along(1.0) - ray.origin;
+        let dist = (point - ray.origin).cross(dir).len() / dir.len();
+        assert!(dist < 1e-3, "ray passes {dist} away from the point");
+
+        let center = cam.project(cam.pos + cam.front * 5.0, viewport).unwrap();
+        assert!((center[0] - 400.0).abs() < 1e-2 && (center[1] - 300.0).abs() < 1e-2);
+
+        assert_eq!(cam.project(cam.pos - cam.front * 10.0, viewport), None);
+    }
+}
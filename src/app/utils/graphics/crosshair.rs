@@ -0,0 +1,58 @@
+//! Screen-space crosshair sizing.
+
+use crate::prelude::*;
+
+/// Crosshair half-sizes in pixels. Pixel centers are compared against them
+/// so they are kept on half-pixel boundaries.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Crosshair {
+    pub half_thickness: f32,
+    pub half_length: f32,
+}
+
+impl Crosshair {
+    /// Crosshair sizes in logical pixels.
+    pub const DEFAULT: Self = Self {
+        half_thickness: cfg::ui::crosshair::HALF_THICKNESS,
+        half_length: cfg::ui::crosshair::HALF_LENGTH,
+    };
+
+    /// Gives crosshair sizes in physical pixels for given window `scale_factor`.
+    /// Full size `2 * size + 1` is scaled, so the center pixel scales with the rest.
+    pub fn scaled(self, scale_factor: f64) -> Self {
+        let scale = |size: f32| (((size + 0.5) * scale_factor as f32).round() - 0.5).max(0.5);
+
+        Self {
+            half_thickness: scale(self.half_thickness),
+            half_length: scale(self.half_length),
+        }
+    }
+
+    pub fn as_array(self) -> [f32; 2] {
+        [self.half_thickness, self.half_length]
+    }
+}
+
+impl Default for Crosshair {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crosshair_is_scaled_to_physical_pixels() {
+        assert_eq!(Crosshair::DEFAULT.scaled(1.0), Crosshair::DEFAULT);
+
+        assert_eq!(
+            Crosshair::DEFAULT.scaled(2.0),
+            Crosshair { half_thickness: 7.5, half_length: 43.5 },
+        );
+
+        assert_eq!(
+            Crosshair::DEFAULT.scaled(1.5),
+            Crosshair { half_thickness: 5.5, half_length: 32.5 },
+        );
+    }
+}
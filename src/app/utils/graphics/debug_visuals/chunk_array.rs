@@ -154,6 +154,30 @@ pub mod data {
             ] as &mut SmallVec<[_; 24]>);
         }
 
+        if let Some((from, to)) = chunk_arr.fill_preview_box() {
+            let bias = cfg::topology::Z_FIGHTING_BIAS;
+            let lo = vec3::from(from) * Voxel::SIZE - vec3::all(0.5 * Voxel::SIZE + bias);
+            let hi = vec3::from(to) * Voxel::SIZE - vec3::all(0.5 * Voxel::SIZE - bias);
+
+            vertices.extend(box_lines(lo, hi, [0.9, 0.9, 0.2, 1.0]));
+        }
+
+        if let Some(pos) = chunk_arr.placement.target() {
+            let bias = cfg::topology::Z_FIGHTING_BIAS;
+            let lo = vec3::from(pos) * Voxel::SIZE - vec3::all(0.5 * Voxel::SIZE + bias);
+            let hi = lo + vec3::all(Voxel::SIZE + 2.0 * bias);
+
+            vertices.extend(box_lines(lo, hi, [0.2, 0.8, 0.9, 1.0]));
+        }
+
+        if let Some((pos, progress)) = chunk_arr.breaking_voxel() {
+            let bias = cfg::topology::Z_FIGHTING_BIAS;
+            let lo = vec3::from(pos) * Voxel::SIZE - vec3::all(0.5 * Voxel::SIZE + bias);
+            let hi = lo + vec3::all(Voxel::SIZE + 2.0 * bias);
+
+            vertices.extend(crack_lines(lo, hi, progress, [0.05, 0.05, 0.05, 1.0]));
+        }
+
         let vbuffer = VertexBuffer::new(facade, &vertices)
             .expect("failed to create vertex buffer");
         
@@ -161,6 +185,50 @@ pub mod data {
     }
 }
 
+/// Gives lines of box edges from `lo` to `hi` corners.
+fn box_lines(lo: vec3, hi: vec3, color: [f32; 4]) -> [Vertex; 24] {
+    let corner = |x: f32, y: f32, z: f32| Vertex { pos: [x, y, z], color };
+
+    let lll = corner(lo.x, lo.y, lo.z);
+    let llh = corner(lo.x, lo.y, hi.z);
+    let lhl = corner(lo.x, hi.y, lo.z);
+    let lhh = corner(lo.x, hi.y, hi.z);
+    let hll = corner(hi.x, lo.y, lo.z);
+    let hlh = corner(hi.x, lo.y, hi.z);
+    let hhl = corner(hi.x, hi.y, lo.z);
+    let hhh = corner(hi.x, hi.y, hi.z);
+
+    [
+        lll, lhl,  llh, lhh,  hlh, hhh,  hll, hhl,
+        lll, hll,  lhl, hhl,  lhh, hhh,  llh, hlh,
+        lll, llh,  hll, hlh,  hhl, hhh,  lhl, lhh,
+    ]
+}
+
+/// Gives crack lines on faces of box from `lo` to `hi`. Face diagonals
+/// appear one by one as breaking `progress` goes from 0 to 1.
+fn crack_lines(lo: vec3, hi: vec3, progress: f32, color: [f32; 4]) -> impl Iterator<Item = Vertex> {
+    let corner = |x: f32, y: f32, z: f32| Vertex { pos: [x, y, z], color };
+
+    let diagonals = [
+        corner(lo.x, lo.y, lo.z), corner(hi.x, hi.y, lo.z),
+        corner(lo.x, lo.y, hi.z), corner(hi.x, hi.y, hi.z),
+        corner(lo.x, lo.y, lo.z), corner(lo.x, hi.y, hi.z),
+        corner(hi.x, lo.y, lo.z), corner(hi.x, hi.y, hi.z),
+        corner(lo.x, lo.y, lo.z), corner(hi.x, lo.y, hi.z),
+        corner(lo.x, hi.y, lo.z), corner(hi.x, hi.y, hi.z),
+        corner(hi.x, lo.y, lo.z), corner(lo.x, hi.y, lo.z),
+        corner(hi.x, lo.y, hi.z), corner(lo.x, hi.y, hi.z),
+        corner(lo.x, hi.y, lo.z), corner(lo.x, lo.y, hi.z),
+        corner(hi.x, hi.y, lo.z), corner(hi.x, lo.y, hi.z),
+        corner(hi.x, lo.y, lo.z), corner(lo.x, lo.y, hi.z),
+        corner(hi.x, hi.y, lo.z), corner(lo.x, hi.y, hi.z),
+    ];
+
+    let n_lines = (progress * (diagonals.len() / 2) as f32).ceil() as usize;
+    diagonals.into_iter().take(2 * n_lines)
+}
+
 impl<'s> DebugVisualized<'s, ChunkArray> {
     pub async fn new_chunk_array(
         chunk_array: ChunkArray,
@@ -47,6 +47,11 @@ where
     pub fn is_empty(&self) -> bool {
         self.vertices.len() == 0
     }
+
+    /// Gives number of vertices in the mesh.
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
 }
 
 impl<V: Vertex> UnindexedMesh<V> {
@@ -58,4 +63,33 @@ impl<V: Vertex> UnindexedMesh<V> {
         let vertices = VertexBuffer::new(facade, &[])?;
         Ok(Self::new_unindexed(vertices, primitive_type))
     }
+
+    /// Gives number of triangles in the mesh. It is zero for non-triangle primitives.
+    pub fn triangle_count(&self) -> usize {
+        triangle_count(self.indices.0, self.vertex_count())
+    }
+}
+
+/// Gives number of triangles made of `n_vertices` vertices of `primitive_type`.
+/// It is zero for non-triangle primitives.
+pub fn triangle_count(primitive_type: PrimitiveType, n_vertices: usize) -> usize {
+    match primitive_type {
+        PrimitiveType::TrianglesList => n_vertices / 3,
+        PrimitiveType::TriangleStrip | PrimitiveType::TriangleFan => n_vertices.saturating_sub(2),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triangle_count_of_known_sizes() {
+        assert_eq!(triangle_count(PrimitiveType::TrianglesList, 36), 12);
+        assert_eq!(triangle_count(PrimitiveType::TrianglesList, 0), 0);
+        assert_eq!(triangle_count(PrimitiveType::TriangleStrip, 4), 2);
+        assert_eq!(triangle_count(PrimitiveType::TriangleFan, 1), 0);
+        assert_eq!(triangle_count(PrimitiveType::LinesList, 36), 0);
+    }
 }
\ No newline at end of file
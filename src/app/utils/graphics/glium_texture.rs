@@ -1,12 +1,14 @@
 use {
     crate::prelude::*,
-    std::{io::{Cursor, self}, fs, path::{Path, PathBuf}},
+    std::{io::{Cursor, self}, fs, path::{Path, PathBuf}, sync::mpsc},
     glium::{
         uniforms::SamplerWrapFunction,
-        texture::{RawImage2d, Texture2d, MipmapsOption},
+        texture::{RawImage2d, Texture2d, MipmapsOption, TextureCreationError},
         uniforms::{Sampler, MagnifySamplerFilter, MinifySamplerFilter},
         backend::Facade
     },
+    notify::{Watcher, RecommendedWatcher, RecursiveMode},
+    thiserror::Error,
 };
 
 /// Texture struct.
@@ -21,28 +23,44 @@ pub struct Texture {
 
 impl Texture {
     /// Loads texture from path.
-    pub fn from_path(path: impl AsRef<Path>, display: &dyn Facade) -> Result<Self, io::Error> {
+    pub fn from_path(path: impl AsRef<Path>, display: &dyn Facade) -> Result<Self, TextureError> {
         let _log_guard = logger::work!(from = "texture loader", "from {path:?}", path = path.as_ref());
 
-        let path_buf = path.as_ref().to_owned();
+        Ok(Self {
+            path: path.as_ref().to_owned(),
+            inner: Self::load_texture(path, display)?,
+        })
+    }
+
+    /// Reloads image from `path` and replaces GPU texture in place.
+    /// Texture is kept unchanged on error.
+    pub fn reload(&mut self, path: impl AsRef<Path>, display: &dyn Facade) -> Result<(), TextureError> {
+        let _log_guard = logger::work!(from = "texture loader", "reload from {path:?}", path = path.as_ref());
+
+        self.inner = Self::load_texture(&path, display)?;
+        self.path = path.as_ref().to_owned();
+
+        Ok(())
+    }
+
+    /// Decodes PNG image from `path` into RGBA pixels.
+    pub fn load_image(path: impl AsRef<Path>) -> Result<image::RgbaImage, TextureError> {
         let image_bytes = fs::read(path)?;
+        let image = image::load(Cursor::new(image_bytes), image::ImageFormat::Png)?;
 
-        let image = image::load(Cursor::new(image_bytes), image::ImageFormat::Png)
-            .expect("failed to load image")
-            .to_rgba8();
+        Ok(image.to_rgba8())
+    }
+
+    fn load_texture(path: impl AsRef<Path>, display: &dyn Facade) -> Result<Texture2d, TextureError> {
+        let image = Self::load_image(path)?;
         let image_size = image.dimensions();
         let image = RawImage2d::from_raw_rgba_reversed(&image.into_raw(), image_size);
-        
-        let texture = Texture2d::with_mipmaps(
+
+        Ok(Texture2d::with_mipmaps(
             display,
             image,
-            MipmapsOption::AutoGeneratedMipmapsMax(4)
-        ).expect("failed to add mipmaps to texture");
-
-        Ok(Self {
-            path: path_buf,
-            inner: texture,
-        })
+            MipmapsOption::AutoGeneratedMipmapsMax(4),
+        )?)
     }
 
     /// Adds mips to texture uniform.
@@ -53,4 +71,152 @@ impl Texture {
             .wrap_function(SamplerWrapFunction::Clamp)
             .anisotropy(4)
     }
-}
\ No newline at end of file
+}
+
+/// Watches texture file for changes. Its parent directory is watched, so editors
+/// that save by renaming a temporary file over the texture are noticed too.
+#[derive(Debug)]
+pub struct TextureWatcher {
+    pub path: PathBuf,
+
+    /// Watching stops when it is dropped.
+    _watcher: RecommendedWatcher,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+}
+
+impl TextureWatcher {
+    /// Starts watching file on `path`.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, TextureError> {
+        let (sender, events) = mpsc::channel();
+
+        let mut watcher = notify::recommended_watcher(move |event| {
+            // Receiver is dropped with watcher.
+            let _ = sender.send(event);
+        })?;
+
+        let dir = match path.as_ref().parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+        Ok(Self { path: path.as_ref().to_owned(), _watcher: watcher, events })
+    }
+
+    /// Checks if file was modified since last check.
+    pub fn is_changed(&self) -> bool {
+        let mut is_changed = false;
+
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) => is_changed |= (event.kind.is_modify() || event.kind.is_create())
+                    && event.paths.iter().any(|path| path.file_name() == self.path.file_name()),
+                Err(err) => logger::log!(Error, from = "texture watcher", "failed to watch {:?}: {err}", self.path),
+            }
+        }
+
+        is_changed
+    }
+
+    /// Reloads `texture` if its file was modified. Errors are logged.
+    pub fn reload_if_changed(&self, texture: &mut Texture, display: &dyn Facade) {
+        if !self.is_changed() { return }
+
+        if let Err(err) = texture.reload(&self.path, display) {
+            logger::log!(Error, from = "texture watcher", "failed to reload {:?}: {err}", self.path);
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TextureError {
+    #[error("failed to read texture file: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("failed to decode image: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("failed to create texture: {0}")]
+    Creation(#[from] TextureCreationError),
+
+    #[error("failed to watch texture file: {0}")]
+    Watch(#[from] notify::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reloaded_image_has_new_dimensions() {
+        let path = std::env::temp_dir().join("terramine_texture_reload_test.png");
+
+        image::RgbaImage::new(4, 2).save(&path).expect("failed to save image");
+        assert_eq!(Texture::load_image(&path).expect("image should load").dimensions(), (4, 2));
+
+        image::RgbaImage::new(8, 16).save(&path).expect("failed to save image");
+        assert_eq!(Texture::load_image(&path).expect("image should load").dimensions(), (8, 16));
+
+        fs::remove_file(&path).expect("failed to remove image");
+        assert!(matches!(Texture::load_image(&path), Err(TextureError::Io(_))));
+    }
+
+    #[test]
+    fn watcher_sees_modified_image() {
+        use std::time::{Duration, Instant};
+
+        let path = std::env::temp_dir().join("terramine_texture_watcher_test.png");
+        image::RgbaImage::new(4, 2).save(&path).expect("failed to save image");
+
+        let watcher = TextureWatcher::new(&path).expect("failed to watch image");
+        assert!(!watcher.is_changed());
+
+        image::RgbaImage::new(8, 16).save(&path).expect("failed to save image");
+
+        // Events are delivered from watcher thread.
+        let start = Instant::now();
+        while !watcher.is_changed() {
+            assert!(start.elapsed() < Duration::from_secs(5), "modification should be noticed");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(Texture::load_image(&watcher.path).expect("image should load").dimensions(), (8, 16));
+        fs::remove_file(&path).expect("failed to remove image");
+    }
+
+    #[test]
+    fn watcher_sees_renamed_over_image_and_ignores_neighbours() {
+        use std::time::{Duration, Instant};
+
+        let dir = std::env::temp_dir().join(format!("terramine_texture_rename_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("failed to create directory");
+
+        let path = dir.join("atlas.png");
+        image::RgbaImage::new(4, 2).save(&path).expect("failed to save image");
+
+        let watcher = TextureWatcher::new(&path).expect("failed to watch image");
+
+        // Other files in the directory are not the texture.
+        image::RgbaImage::new(2, 2).save(dir.join("other.png")).expect("failed to save image");
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!watcher.is_changed());
+
+        // Atomic save writes temporary file and renames it over the texture.
+        let temp_path = dir.join("atlas.png.tmp");
+        image::RgbaImage::new(8, 16).save_with_format(&temp_path, image::ImageFormat::Png)
+            .expect("failed to save image");
+        fs::rename(&temp_path, &path).expect("failed to rename image");
+
+        let start = Instant::now();
+        while !watcher.is_changed() {
+            assert!(start.elapsed() < Duration::from_secs(5), "rename should be noticed");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let image = Texture::load_image(&watcher.path);
+        fs::remove_dir_all(&dir).expect("failed to remove directory");
+
+        assert_eq!(image.expect("image should load").dimensions(), (8, 16));
+    }
+}
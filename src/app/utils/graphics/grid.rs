@@ -0,0 +1,96 @@
+//! Grid lines drawn on voxel faces along voxel boundaries.
+
+use {
+    crate::{
+        prelude::*,
+        terrain::voxel::{Voxel, atlas::{TEXTURE_SIZE_F, ATLAS_PADDING_F}},
+    },
+    spin::RwLock,
+};
+
+static GRID_LINES: RwLock<GridLines> = RwLock::new(GridLines::DEFAULT);
+
+/// Grid lines settings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridLines {
+    pub is_enabled: bool,
+    pub color: [f32; 4],
+
+    /// Line thickness in world units.
+    pub thickness: f32,
+}
+
+impl GridLines {
+    pub const DEFAULT: Self = Self {
+        is_enabled: false,
+        color: cfg::shader::grid::COLOR,
+        thickness: cfg::shader::grid::THICKNESS,
+    };
+
+    /// Gives line width in UV space. Zero width means no lines.
+    pub fn uv_width(&self) -> f32 {
+        match self.is_enabled {
+            true => thickness_to_uv_width(self.thickness),
+            false => 0.0,
+        }
+    }
+
+    /// Gives line width in world units. Zero width means no lines.
+    pub fn world_width(&self) -> f32 {
+        match self.is_enabled {
+            true => self.thickness.clamp(0.0, 0.5 * Voxel::SIZE),
+            false => 0.0,
+        }
+    }
+}
+
+impl Default for GridLines {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+/// Size of one voxel face in texture atlas UV space.
+pub const FACE_UV_SIZE: f32 = TEXTURE_SIZE_F - 2.0 * ATLAS_PADDING_F;
+
+/// Maps line `thickness` in world units to line width in UV space.
+/// Lines are drawn from both sides of the boundary, so width is at most half of the face.
+pub fn thickness_to_uv_width(thickness: f32) -> f32 {
+    (thickness / Voxel::SIZE).clamp(0.0, 0.5) * FACE_UV_SIZE
+}
+
+/// Gives current grid lines settings.
+pub fn get() -> GridLines {
+    *GRID_LINES.read()
+}
+
+pub fn spawn_control_window(ui: &imgui::Ui) {
+    use crate::app::utils::graphics::ui::imgui_constructor::make_window;
+
+    make_window(ui, "Grid lines").build(|| {
+        let mut grid = get();
+
+        ui.checkbox("Enabled", &mut grid.is_enabled);
+        ui.color_edit4("Color", &mut grid.color);
+        ui.slider("Thickness", 0.0, 0.5 * Voxel::SIZE, &mut grid.thickness);
+
+        *GRID_LINES.write() = grid;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thickness_maps_to_face_fraction() {
+        assert_eq!(thickness_to_uv_width(0.0), 0.0);
+        assert!((thickness_to_uv_width(0.25 * Voxel::SIZE) - 0.25 * FACE_UV_SIZE).abs() < 1e-7);
+        assert_eq!(thickness_to_uv_width(10.0 * Voxel::SIZE), 0.5 * FACE_UV_SIZE);
+        assert_eq!(thickness_to_uv_width(-1.0), 0.0);
+    }
+
+    #[test]
+    fn disabled_grid_has_no_width() {
+        let grid = GridLines { is_enabled: false, thickness: 0.1, ..GridLines::DEFAULT };
+        assert_eq!(grid.uv_width(), 0.0);
+    }
+}
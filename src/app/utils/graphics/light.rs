@@ -41,6 +41,11 @@ impl DirectionalLight {
         });
     }
 
+    /// Makes the light shine from the sun at `hours` time of day.
+    pub fn follow_sun(&mut self, hours: f32) {
+        self.cam.front = super::sky::sun_dir(hours) * -1.0;
+    }
+
     pub fn update(&mut self, cam_pos: vec3) {
         let interest_pos = cam_pos;
         
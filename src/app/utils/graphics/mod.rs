@@ -9,17 +9,26 @@ pub mod surface;
 pub mod failed_mesh;
 pub mod shader;
 pub mod texture;
+pub mod sky;
+pub mod grid;
+pub mod present_mode;
+pub mod crosshair;
 
 use {
     crate::{
         prelude::*,
-        window::Window,
+        window::{Window, WindowMode, WindowModeState, WindowGeometry},
+        runtime_cfg,
     },
     failed_mesh::{Mesh, Bufferizable, MeshDescriptor, Renderable},
-    shader::Shader, texture::Texture,
+    shader::Shader, texture::Texture, glium_texture::TextureWatcher,
     wgpu::{*, util::DeviceExt},
-    winit::event_loop::EventLoop,
-    std::path::PathBuf,
+    winit::{
+        event_loop::EventLoop,
+        window::Fullscreen,
+        dpi::{PhysicalPosition, PhysicalSize},
+    },
+    std::path::{Path, PathBuf},
 };
 
 #[repr(C)]
@@ -50,11 +59,85 @@ const TEST_VERTICES: &[TestVertex] = &[
     TestVertex { position: [-0.5,  0.5], tex_coords: [0.0, 0.0] },
 ];
 
+/// Vertex of full-screen quad used to draw the sky and screen overlays.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Default, Pod, Zeroable)]
+pub struct ScreenVertex {
+    position: [f32; 2],
+}
+
+impl Bufferizable for ScreenVertex {
+    const ATTRS: &'static [VertexAttribute] =
+        &vertex_attr_array![0 => Float32x2];
+
+    const BUFFER_LAYOUT: VertexBufferLayout<'static> = VertexBufferLayout {
+        array_stride: mem::size_of::<Self>() as u64,
+        step_mode: VertexStepMode::Vertex,
+        attributes: Self::ATTRS,
+    };
+}
+
+const SCREEN_QUAD_VERTICES: &[ScreenVertex] = &[
+    ScreenVertex { position: [-1.0, -1.0] },
+    ScreenVertex { position: [ 1.0, -1.0] },
+    ScreenVertex { position: [ 1.0,  1.0] },
+
+    ScreenVertex { position: [-1.0, -1.0] },
+    ScreenVertex { position: [ 1.0,  1.0] },
+    ScreenVertex { position: [-1.0,  1.0] },
+];
+
+/// Uniforms shared by all shaders. Field order matches WGSL layout rules.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct CommonUniforms {
-    pub time: f32,
+    /// Direction to the sun, `w` is unused. Shaders take directional light from it,
+    /// so the light follows [time of day][sky::Sky::hours].
+    pub sun_dir: [f32; 4],
+    pub sky_color: [f32; 4],
+    pub grid_color: [f32; 4],
+
+    /// Sun disc screen position in pixels, its radius relative to screen height
+    /// and visibility flag which is zero if the sun is behind the camera.
+    pub sun_disc: [f32; 4],
+
     pub screen_resolution: vec2,
+    pub time: f32,
+
+    /// Grid line width in UV space, zero disables grid lines.
+    pub grid_uv_width: f32,
+
+    /// Crosshair half-sizes in pixels, see [`crosshair::Crosshair`].
+    pub crosshair_sizes: [f32; 2],
+    pub _padding: [f32; 2],
+}
+
+impl CommonUniforms {
+    pub fn new(
+        time: f32, screen_resolution: vec2, sky: &sky::Sky,
+        grid: &grid::GridLines, sun_screen_pos: Option<[f32; 2]>,
+        crosshair: &crosshair::Crosshair,
+    ) -> Self {
+        let sun_dir = sky.sun_dir();
+        let (r, g, b, a) = sky.sky_color();
+
+        let sun_disc = match sun_screen_pos {
+            Some([x, y]) if sky.is_enabled => [x, y, cfg::sky::SUN_RADIUS, 1.0],
+            _ => [0.0; 4],
+        };
+
+        Self {
+            sun_dir: Float4::from_vec3(sun_dir, 0.0).to_array(),
+            sky_color: [r, g, b, a],
+            grid_color: grid.color,
+            sun_disc,
+            screen_resolution,
+            time,
+            grid_uv_width: grid.uv_width(),
+            crosshair_sizes: crosshair.as_array(),
+            _padding: [0.0; 2],
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -123,11 +206,33 @@ pub struct Graphics {
     pub queue: Arc<Queue>,
     pub config: SurfaceConfiguration,
 
+    /// Requested present mode. Backend one is in [`config`][Graphics::config].
+    pub present_mode: present_mode::PresentMode,
+
+    pub window_mode: WindowModeState,
+
+    /// Window scale factor, ratio of physical pixels to logical ones.
+    pub scale_factor: f64,
+
+    /// Crosshair sizes in physical pixels.
+    pub crosshair: crosshair::Crosshair,
+
     pub common_uniforms: CommonUniformsBuffer,
     
     pub test_texture: Texture,
+
+    /// Reloads [`test_texture`][Graphics::test_texture] when its file changes.
+    /// It is [`None`] if watching has failed to start.
+    pub test_texture_watcher: Option<TextureWatcher>,
+
     pub test_mesh: Mesh<TestVertex>,
 
+    /// Full-screen quad that draws the sun disc over the sky.
+    pub sky_mesh: Mesh<ScreenVertex>,
+
+    /// Full-screen quad that inverts colors under the crosshair.
+    pub crosshair_mesh: Mesh<ScreenVertex>,
+
     pub event_loop:	Option<EventLoop<()>>,
 
     pub imgui: ImGui,
@@ -143,6 +248,8 @@ impl Graphics {
         // Window creation
         let event_loop = EventLoop::new();
         let window = Window::from(&event_loop, DEFAULT_SIZES)?;
+        let scale_factor = window.scale_factor();
+        let crosshair = crosshair::Crosshair::DEFAULT.scaled(scale_factor);
 
         // ------------ WGPU initialization ------------
 
@@ -183,6 +290,7 @@ impl Graphics {
         let queue = Arc::new(queue);
 
         let swapchain_capabilities = surface.get_capabilities(&adapter);
+        let present_mode = runtime_cfg::get().present_mode;
         let swapchain_format = *swapchain_capabilities.formats.get(0)
             .expect("failed to get swap chain format 0: the surface is incompatible with the adapter");
         
@@ -191,7 +299,7 @@ impl Graphics {
             format: swapchain_format,
             width: DEFAULT_SIZES.x as u32,
             height: DEFAULT_SIZES.y as u32,
-            present_mode: swapchain_capabilities.present_modes[0],
+            present_mode: present_mode.select(&swapchain_capabilities.present_modes),
             alpha_mode: swapchain_capabilities.alpha_modes[0],
             view_formats: vec![],
         };
@@ -209,9 +317,16 @@ impl Graphics {
         ).await
             .expect("failed to load an image");
 
+        let test_texture_watcher = TextureWatcher::new(Path::new(cfg::texture::DIRECTORY).join("TerramineIcon32p.png"))
+            .map_err(|err| logger::log!(Error, from = "graphics", "failed to watch test texture: {err}"))
+            .ok();
+
         let common_uniforms = CommonUniformsBuffer::new(
             &device,
-            CommonUniforms { time: 0.0, screen_resolution: vec2::from(DEFAULT_SIZES) },
+            CommonUniforms::new(
+                0.0, vec2::from(DEFAULT_SIZES), &sky::get(),
+                &grid::GridLines::DEFAULT, None, &crosshair,
+            ),
         );
 
         let shader = Shader::load_from_file(Arc::clone(&device), "triangle shader", "shader.wgsl")
@@ -238,6 +353,60 @@ impl Graphics {
             TEST_VERTICES
         );
 
+        let sky_shader = Shader::load_from_file(Arc::clone(&device), "sky shader", "sky.wgsl")
+            .await
+            .expect("failed to load sky shader from file");
+
+        let sky_mesh = Mesh::new(
+            MeshDescriptor {
+                device: Arc::clone(&device),
+                shader: Arc::new(sky_shader),
+                label: Arc::new(String::from("sky mesh")),
+                fragment_targets: Arc::new([Some(ColorTargetState {
+                    format: config.format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })]),
+                primitive_topology: PrimitiveTopology::TriangleList,
+                polygon_mode: PolygonMode::Fill,
+                bind_group_layouts: Arc::new([
+                    Arc::clone(&common_uniforms.bind_group_layout),
+                ]),
+            },
+            SCREEN_QUAD_VERTICES
+        );
+
+        let crosshair_shader = Shader::load_from_file(Arc::clone(&device), "crosshair shader", "crosshair.wgsl")
+            .await
+            .expect("failed to load crosshair shader from file");
+
+        let crosshair_mesh = Mesh::new(
+            MeshDescriptor {
+                device: Arc::clone(&device),
+                shader: Arc::new(crosshair_shader),
+                label: Arc::new(String::from("crosshair mesh")),
+                fragment_targets: Arc::new([Some(ColorTargetState {
+                    format: config.format,
+                    // Gives `1 - dst` for white fragments, so crosshair is visible on any background.
+                    blend: Some(BlendState {
+                        color: BlendComponent {
+                            src_factor: BlendFactor::OneMinusDst,
+                            dst_factor: BlendFactor::OneMinusSrcAlpha,
+                            operation: BlendOperation::Add,
+                        },
+                        alpha: BlendComponent::OVER,
+                    }),
+                    write_mask: ColorWrites::ALL,
+                })]),
+                primitive_topology: PrimitiveTopology::TriangleList,
+                polygon_mode: PolygonMode::Fill,
+                bind_group_layouts: Arc::new([
+                    Arc::clone(&common_uniforms.bind_group_layout),
+                ]),
+            },
+            SCREEN_QUAD_VERTICES
+        );
+
         // ------------ Dear ImGui initialization ------------
 
         // Create ImGui context and set `.ini` file name.
@@ -249,8 +418,7 @@ impl Graphics {
         winit_platform.attach_window(imgui_context.io_mut(), &window, imgui_winit_support::HiDpiMode::Rounded);
 
         // Style configuration.
-        imgui_context.fonts().add_font(&[imgui::FontSource::DefaultFontData { config: None }]);
-        imgui_context.io_mut().font_global_scale = (1.0 / winit_platform.hidpi_factor()) as f32;
+        ImGui::load_fonts(&mut imgui_context, winit_platform.hidpi_factor());
         imgui_context.style_mut().window_rounding = 16.0;
 
         // Create ImGui renderer.
@@ -267,14 +435,21 @@ impl Graphics {
         Ok(Self {
             event_loop: Some(event_loop),
             test_mesh: mesh,
+            sky_mesh,
+            crosshair_mesh,
             window,
             surface,
             adapter,
             device,
             queue,
             config,
+            present_mode,
+            window_mode: WindowModeState::default(),
+            scale_factor,
+            crosshair,
             common_uniforms,
             test_texture,
+            test_texture_watcher,
             imgui: ImGui {
                 context: imgui_context,
                 platform: winit_platform,
@@ -296,14 +471,34 @@ impl Graphics {
         }
     }
 
+    /// Reloads textures whose files were changed. Errors are logged.
+    pub async fn reload_changed_textures(&mut self) {
+        let Some(watcher) = &self.test_texture_watcher else { return };
+        if !watcher.is_changed() { return }
+
+        if let Err(err) = self.test_texture.reload(&watcher.path).await {
+            logger::log!(Error, from = "graphics", "failed to reload test texture: {err}");
+        }
+    }
+
     pub fn render<UseUi: FnOnce(&mut imgui::Ui)>(
         &mut self, desc: RenderDescriptor<UseUi>,
     ) -> Result<(), SurfaceError> {
+        let present_mode = runtime_cfg::get().present_mode;
+        if present_mode != self.present_mode {
+            self.set_present_mode(present_mode);
+        }
+
         let size = self.window.inner_size();
-        self.common_uniforms.update(&self.queue, CommonUniforms {
-            time: desc.time,
-            screen_resolution: (size.width as f32, size.height as f32).into(),
-        });
+        let sky = sky::get();
+        self.common_uniforms.update(&self.queue, CommonUniforms::new(
+            desc.time,
+            (size.width as f32, size.height as f32).into(),
+            &sky,
+            &desc.grid,
+            desc.sun_screen_pos,
+            &self.crosshair,
+        ));
 
         let output = self.surface.get_current_texture()?;
         let view = output.texture.create_view(&Default::default());
@@ -314,7 +509,7 @@ impl Graphics {
         );
 
         {
-            let (r, g, b, a) = cfg::shader::CLEAR_COLOR;
+            let (r, g, b, a) = sky.sky_color();
             let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
                 label: Some("render_pass"),
                 color_attachments: &[Some(RenderPassColorAttachment {
@@ -334,8 +529,11 @@ impl Graphics {
             });
 
             render_pass.set_bind_group(0, &self.common_uniforms.bind_group, &[]);
+            let Ok(()) = self.sky_mesh.render(&mut render_pass);
+
             render_pass.set_bind_group(1, &self.test_texture.bind_group, &[]);
             let Ok(()) = self.test_mesh.render(&mut render_pass);
+            let Ok(()) = self.crosshair_mesh.render(&mut render_pass);
         }
 
         {
@@ -375,6 +573,72 @@ impl Graphics {
         }
     }
 
+    /// Applies new window scale factor to ImGui fonts and screen-space overlays.
+    pub fn on_scale_factor_changed(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+        self.crosshair = crosshair::Crosshair::DEFAULT.scaled(scale_factor);
+
+        ImGui::load_fonts(&mut self.imgui.context, self.imgui.platform.hidpi_factor());
+        self.imgui.renderer.0.reload_font_texture(&mut self.imgui.context, &self.device, &self.queue);
+    }
+
+    /// Reconfigures surface with new present mode. Unsupported modes fall back to vsync.
+    pub fn set_present_mode(&mut self, mode: present_mode::PresentMode) {
+        let supported = self.surface.get_capabilities(&self.adapter).present_modes;
+        self.config.present_mode = mode.select(&supported);
+        self.surface.configure(&self.device, &self.config);
+        self.present_mode = mode;
+
+        if self.config.present_mode != mode.to_wgpu() {
+            logger::log!(Error, from = "graphics", "present mode {mode} is not supported, using vsync");
+        }
+    }
+
+    /// Switches window to `mode`. Windowed size and position are restored after fullscreen.
+    pub fn set_window_mode(&mut self, mode: WindowMode) {
+        let pos = self.window.outer_position().unwrap_or_default();
+        let size = self.window.inner_size();
+        let current = WindowGeometry {
+            pos: Int2::new(pos.x, pos.y),
+            size: UInt2::new(size.width, size.height),
+        };
+
+        let restored = self.window_mode.switch(mode, current);
+        let monitor = self.window.current_monitor();
+
+        let fullscreen = match mode {
+            WindowMode::Windowed => None,
+
+            WindowMode::Borderless => Some(Fullscreen::Borderless(monitor)),
+
+            WindowMode::Exclusive => {
+                let video_mode = monitor.and_then(|monitor| monitor.video_modes()
+                    .max_by_key(|video_mode| (
+                        video_mode.size().width * video_mode.size().height,
+                        video_mode.refresh_rate_millihertz(),
+                    ))
+                );
+
+                match video_mode {
+                    Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+                    None => {
+                        logger::log!(Error, from = "graphics", "no video modes available, using borderless fullscreen");
+                        Some(Fullscreen::Borderless(None))
+                    },
+                }
+            },
+        };
+
+        self.window.set_fullscreen(fullscreen);
+
+        if let Some(geometry) = restored {
+            self.window.set_outer_position(PhysicalPosition::new(geometry.pos.x, geometry.pos.y));
+            self.window.set_inner_size(PhysicalSize::new(geometry.size.x, geometry.size.y));
+        }
+
+        logger::log!(Info, from = "graphics", "window mode is set to {mode}");
+    }
+
     /// Gives event_loop and removes it from graphics struct.
     pub fn take_event_loop(&mut self) -> EventLoop<()> {
         self.event_loop.take()
@@ -394,6 +658,23 @@ pub struct ImGui {
     pub renderer: ImGuiRendererWrapper,
 }
 
+impl ImGui {
+    /// Rasterizes fonts for `hidpi_factor` and scales them back to logical pixels,
+    /// so text stays sharp and keeps its size on high-DPI displays.
+    pub fn load_fonts(context: &mut imgui::Context, hidpi_factor: f64) {
+        let fonts = context.fonts();
+        fonts.clear();
+        fonts.add_font(&[imgui::FontSource::DefaultFontData {
+            config: Some(imgui::FontConfig {
+                size_pixels: cfg::ui::FONT_SIZE_IN_PIXELS * hidpi_factor as f32,
+                ..Default::default()
+            }),
+        }]);
+
+        context.io_mut().font_global_scale = (1.0 / hidpi_factor) as f32;
+    }
+}
+
 #[derive(Deref)]
 pub struct ImGuiRendererWrapper(imgui_wgpu::Renderer);
 
@@ -407,4 +688,8 @@ impl std::fmt::Debug for ImGuiRendererWrapper {
 pub struct RenderDescriptor<UseImguiUi> {
     pub use_imgui_ui: UseImguiUi,
     pub time: f32,
+    pub grid: grid::GridLines,
+
+    /// Sun disc center in pixels, see [`sky::sun_screen_pos`].
+    pub sun_screen_pos: Option<[f32; 2]>,
 }
\ No newline at end of file
@@ -0,0 +1,61 @@
+//! Swapchain present mode selection.
+
+use crate::prelude::*;
+
+/// Present mode chosen by user. Backend falls back to vsync if the mode is not supported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display, FromStr)]
+#[display(style = "snake_case")]
+pub enum PresentMode {
+    /// Frames wait for vertical blank. Always supported.
+    #[default]
+    Vsync,
+
+    /// Frames are presented immediately and may tear.
+    Immediate,
+
+    /// Frames wait for vertical blank but the latest one replaces queued ones.
+    Mailbox,
+}
+
+impl PresentMode {
+    pub const ALL: [Self; 3] = [Self::Vsync, Self::Immediate, Self::Mailbox];
+
+    /// Gives backend present mode.
+    pub fn to_wgpu(self) -> wgpu::PresentMode {
+        match self {
+            Self::Vsync => wgpu::PresentMode::Fifo,
+            Self::Immediate => wgpu::PresentMode::Immediate,
+            Self::Mailbox => wgpu::PresentMode::Mailbox,
+        }
+    }
+
+    /// Gives backend present mode if it is `supported`, otherwise gives [`wgpu::PresentMode::Fifo`].
+    pub fn select(self, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+        let mode = self.to_wgpu();
+
+        match supported.contains(&mode) {
+            true => mode,
+            false => wgpu::PresentMode::Fifo,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes_map_to_backend_with_fallback() {
+        assert_eq!(PresentMode::Vsync.to_wgpu(), wgpu::PresentMode::Fifo);
+        assert_eq!(PresentMode::Immediate.to_wgpu(), wgpu::PresentMode::Immediate);
+        assert_eq!(PresentMode::Mailbox.to_wgpu(), wgpu::PresentMode::Mailbox);
+
+        let supported = [wgpu::PresentMode::Fifo, wgpu::PresentMode::Immediate];
+        assert_eq!(PresentMode::Immediate.select(&supported), wgpu::PresentMode::Immediate);
+        assert_eq!(PresentMode::Mailbox.select(&supported), wgpu::PresentMode::Fifo);
+
+        for mode in PresentMode::ALL {
+            assert_eq!(mode.to_string().parse::<PresentMode>().ok(), Some(mode));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Day-night cycle. World time drives sun direction and sky color.
+
+use {
+    crate::{prelude::*, graphics::camera::Camera},
+    spin::RwLock,
+    std::f32::consts::TAU,
+};
+
+static SKY: RwLock<Sky> = RwLock::new(Sky::DEFAULT);
+
+/// World time state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sky {
+    /// Time of day in hours in range `0.0..24.0`.
+    pub hours: f32,
+    pub is_paused: bool,
+
+    /// If disabled then [runtime config][crate::runtime_cfg] clear color is used as sky color.
+    pub is_enabled: bool,
+}
+
+impl Sky {
+    pub const HOURS_PER_DAY: f32 = 24.0;
+
+    pub const DEFAULT: Self = Self {
+        hours: cfg::sky::START_HOURS,
+        is_paused: false,
+        is_enabled: true,
+    };
+
+    /// Advances world time by `dt` real seconds.
+    pub fn advance(&mut self, dt: f32) {
+        if self.is_paused { return }
+
+        let hours_per_second = Self::HOURS_PER_DAY / cfg::sky::DAY_LENGTH_IN_SECONDS;
+        self.hours = (self.hours + dt * hours_per_second).rem_euclid(Self::HOURS_PER_DAY);
+    }
+
+    /// Direction from the world to the sun.
+    pub fn sun_dir(&self) -> vec3 {
+        sun_dir(self.hours)
+    }
+
+    /// Color of the sky.
+    pub fn sky_color(&self) -> (f32, f32, f32, f32) {
+        match self.is_enabled {
+            true => sky_color(self.hours),
+            false => crate::runtime_cfg::get().clear_color,
+        }
+    }
+}
+
+impl Default for Sky {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+/// Gives direction to the sun at `hours` time of day. The sun rises
+/// at 6 o'clock on `+x`, reaches zenith at noon and sets at 18 o'clock on `-x`.
+pub fn sun_dir(hours: f32) -> vec3 {
+    let angle = (hours - 6.0) / Sky::HOURS_PER_DAY * TAU;
+    vec3::new(f32::cos(angle), f32::sin(angle), 0.0)
+}
+
+/// Gives sky color at `hours` time of day. It blends night and day colors
+/// by sun height and adds horizon glow while the sun is low.
+pub fn sky_color(hours: f32) -> (f32, f32, f32, f32) {
+    use cfg::sky::{DAY_COLOR, NIGHT_COLOR, HORIZON_COLOR};
+
+    let height = sun_dir(hours).y;
+    let day_factor = ((height + 0.1) / 0.4).clamp(0.0, 1.0);
+    let glow_factor = (1.0 - height.abs() * 4.0).max(0.0);
+
+    let blend = |night: f32, day: f32, horizon: f32| {
+        let base = night + (day - night) * day_factor;
+        base + (horizon - base) * glow_factor * 0.6
+    };
+
+    (
+        blend(NIGHT_COLOR.0, DAY_COLOR.0, HORIZON_COLOR.0),
+        blend(NIGHT_COLOR.1, DAY_COLOR.1, HORIZON_COLOR.1),
+        blend(NIGHT_COLOR.2, DAY_COLOR.2, HORIZON_COLOR.2),
+        1.0,
+    )
+}
+
+/// Gives screen position in pixels of the sun disc center seen by `cam` in `viewport`
+/// of `[width, height]` pixels. The sun is infinitely far so only camera rotation matters.
+/// Gives [`None`] if the sun is behind the camera.
+pub fn sun_screen_pos(sun_dir: vec3, cam: &Camera, viewport: [f32; 2]) -> Option<[f32; 2]> {
+    cam.project(cam.pos + sun_dir, viewport)
+}
+
+/// Gives current world time state.
+pub fn get() -> Sky {
+    *SKY.read()
+}
+
+/// Advances world time by `dt` real seconds.
+pub fn advance(dt: f32) {
+    SKY.write().advance(dt);
+}
+
+/// Sets time of day to `hours`. Values are wrapped to `0.0..24.0`.
+pub fn set_time_of_day(hours: f32) {
+    SKY.write().hours = hours.rem_euclid(Sky::HOURS_PER_DAY);
+}
+
+pub fn spawn_control_window(ui: &imgui::Ui) {
+    use crate::app::utils::graphics::ui::imgui_constructor::make_window;
+
+    make_window(ui, "Sky").build(|| {
+        let mut sky = get();
+
+        ui.slider("Time of day", 0.0, Sky::HOURS_PER_DAY, &mut sky.hours);
+        ui.checkbox("Paused", &mut sky.is_paused);
+        ui.checkbox("Day-night cycle", &mut sky.is_enabled);
+
+        *SKY.write() = sky;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_dir_eq(lhs: vec3, rhs: vec3) {
+        assert!((lhs - rhs).len() < 1e-5, "{lhs:?} != {rhs:?}");
+    }
+
+    #[test]
+    fn sun_dir_at_dawn_noon_dusk() {
+        assert_dir_eq(sun_dir(6.0),  vec3::new( 1.0,  0.0, 0.0));
+        assert_dir_eq(sun_dir(12.0), vec3::new( 0.0,  1.0, 0.0));
+        assert_dir_eq(sun_dir(18.0), vec3::new(-1.0,  0.0, 0.0));
+        assert_dir_eq(sun_dir(0.0),  vec3::new( 0.0, -1.0, 0.0));
+    }
+
+    #[test]
+    fn directional_light_follows_sun() {
+        use crate::app::utils::graphics::light::DirectionalLight;
+
+        let mut light = DirectionalLight::default();
+
+        light.follow_sun(12.0);
+        assert_dir_eq(light.cam.front, vec3::new(0.0, -1.0, 0.0));
+
+        light.follow_sun(6.0);
+        assert_dir_eq(light.cam.front, vec3::new(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn advance_wraps_around_midnight() {
+        let mut sky = Sky { hours: 23.0, ..Sky::DEFAULT };
+        sky.advance(cfg::sky::DAY_LENGTH_IN_SECONDS / 12.0);
+
+        assert!((sky.hours - 1.0).abs() < 1e-3, "hours = {}", sky.hours);
+    }
+
+    #[test]
+    fn sun_screen_pos_follows_camera_rotation() {
+        let viewport = [800.0, 600.0];
+        let cam = Camera::new().with_rotation(0.0, 0.4, 0.7);
+        let moved_cam = Camera::new().with_position(100.0, -20.0, 5.0).with_rotation(0.0, 0.4, 0.7);
+
+        let center = sun_screen_pos(cam.front, &cam, viewport).expect("sun should be in front of camera");
+        assert!((center[0] - 400.0).abs() < 1e-2 && (center[1] - 300.0).abs() < 1e-2, "{center:?}");
+
+        let sun_dir = (cam.front + cam.up * 0.2).normalized();
+        let [x, y] = sun_screen_pos(sun_dir, &cam, viewport).unwrap();
+        let [moved_x, moved_y] = sun_screen_pos(sun_dir, &moved_cam, viewport).unwrap();
+        assert!(y < 300.0, "sun above view direction should be above screen center");
+        assert!((x - moved_x).abs() < 1e-2 && (y - moved_y).abs() < 1e-2);
+
+        assert_eq!(sun_screen_pos(-cam.front, &cam, viewport), None);
+    }
+}
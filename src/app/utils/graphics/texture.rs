@@ -17,6 +17,9 @@ pub struct Texture {
     pub label: String,
     pub device: Arc<Device>,
     pub queue: Arc<Queue>,
+
+    pub texture_binding: u32,
+    pub sampler_binding: u32,
 }
 
 static_assertions::assert_impl_all!(Texture: Send, Sync);
@@ -27,12 +30,20 @@ impl Texture {
         image_bytes: &[u8], label: impl Into<String>,
         texture_binding: u32, sampler_binding: u32,
     ) -> Self {
-        let label = label.into();
-
         let image = image::load_from_memory(image_bytes)
             .expect("failed to load test image")
             .to_rgba8();
 
+        Self::from_image(device, queue, &image, label, texture_binding, sampler_binding)
+    }
+
+    pub fn from_image(
+        device: Arc<Device>, queue: Arc<Queue>,
+        image: &image::RgbaImage, label: impl Into<String>,
+        texture_binding: u32, sampler_binding: u32,
+    ) -> Self {
+        let label = label.into();
+
         let (width, height) = image.dimensions();
 
         let size = Extent3d { width, height, depth_or_array_layers: 1 };
@@ -57,7 +68,7 @@ impl Texture {
                 origin: Origin3d::ZERO,
                 aspect: TextureAspect::All,
             },
-            &image,
+            image,
             ImageDataLayout {
                 offset: 0,
                 bytes_per_row: std::num::NonZeroU32::new(width * mem::size_of::<f32>() as u32),
@@ -122,7 +133,10 @@ impl Texture {
             },
         );
 
-        Self { size, inner: texture, bind_group, label, device, queue, bind_group_layout: Arc::new(layout) }
+        Self {
+            size, inner: texture, bind_group, label, device, queue,
+            bind_group_layout: Arc::new(layout), texture_binding, sampler_binding,
+        }
     }
 
     pub async fn load_from_file(
@@ -138,4 +152,27 @@ impl Texture {
 
         Ok(Self::from_image_bytes(device, queue, &image_bytes, label, texture_binding, sampler_binding))
     }
+
+    /// Reloads image from `path` and replaces GPU texture in place.
+    /// Texture is kept unchanged on error.
+    ///
+    /// New bind group layout has the same entries, so pipelines made with the old one
+    /// stay compatible and meshes are not recreated.
+    pub async fn reload(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+
+        let _work_guard = logger::work!(from = "texture-loader", "reloading from {path:?}");
+
+        let image_bytes = fs::read(path).await?;
+        let image = image::load_from_memory(&image_bytes)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+            .to_rgba8();
+
+        *self = Self::from_image(
+            Arc::clone(&self.device), Arc::clone(&self.queue), &image,
+            mem::take(&mut self.label), self.texture_binding, self.sampler_binding,
+        );
+
+        Ok(())
+    }
 }
\ No newline at end of file
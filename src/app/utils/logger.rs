@@ -5,15 +5,23 @@ use {
     crate::{
         prelude::*,
         concurrency::channel::Channel,
+        terrain::{chunk::commands::{Command, command_and_wait}, voxel::voxel_data::Id},
+    },
+    cpython::{Python, PyResult, PyDict, PyErr, ObjectProtocol, NoArgs, py_fn, exc::RuntimeError},
+    std::{
+        sync::{Mutex, mpsc}, thread, time::{Duration, Instant, SystemTime},
+        fs::{self, File}, io::{self, Write, BufWriter}, path::{Path, PathBuf},
     },
-    std::sync::Mutex,
 };
 
 lazy_static! {
     static ref CHANNEL: Mutex<Channel<Message>> = Mutex::new(Channel::default());
+    static ref START_TIME: Instant = Instant::now();
 }
 
-static LOG_MESSAGES: Mutex<VecDeque<Message>> = Mutex::new(VecDeque::new());
+static LOG_MESSAGES: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+
+static FILE_SINK: Mutex<Option<FileSink>> = Mutex::new(None);
 
 pub type CowStr = Cow<'static, str>;
 
@@ -23,6 +31,36 @@ pub struct Message {
     pub content: CowStr,
     pub from: CowStr,
     pub msg_type: MsgType,
+
+    /// Time since logger start.
+    pub timestamp: Duration,
+}
+
+impl Message {
+    /// Constructs message with current timestamp.
+    pub fn new(msg_type: MsgType, from: impl Into<CowStr>, content: impl Into<CowStr>) -> Self {
+        Self { msg_type, from: from.into(), content: content.into(), timestamp: START_TIME.elapsed() }
+    }
+
+    /// Checks that messages are equal ignoring their timestamps.
+    pub fn is_same(&self, other: &Self) -> bool {
+        self.msg_type == other.msg_type && self.from == other.from && self.content == other.content
+    }
+
+    /// Gives [displayable][std::fmt::Display] message prefixed with its timestamp.
+    pub fn timestamped(&self) -> TimestampedMessage<'_> {
+        TimestampedMessage(self)
+    }
+}
+
+/// [Message] displayed with its timestamp in seconds.
+#[derive(Clone, Copy, Debug)]
+pub struct TimestampedMessage<'m>(pub &'m Message);
+
+impl std::fmt::Display for TimestampedMessage<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{time:9.3}] {msg}", time = self.0.timestamp.as_secs_f32(), msg = self.0)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Default, Display)]
@@ -33,52 +71,317 @@ pub enum MsgType {
     Error,
 }
 
+/// [Message] shown in log list with number of its repeats.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogEntry {
+    pub msg: Message,
+    pub count: usize,
+    pub last_time: Instant,
+}
+
+impl std::fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.count {
+            1 => write!(f, "{}", self.msg.timestamped()),
+            count => write!(f, "{} (x{count})", self.msg.timestamped()),
+        }
+    }
+}
+
+/// Pushes `msg` to `entries` or increments counter of identical entry
+/// if it was logged less than `window` before `now`.
+pub fn push_coalesced(entries: &mut VecDeque<LogEntry>, msg: Message, now: Instant, window: Duration) {
+    let same = entries.iter_mut()
+        .rev()
+        .take_while(|entry| now.saturating_duration_since(entry.last_time) <= window)
+        .find(|entry| entry.msg.is_same(&msg));
+
+    match same {
+        Some(entry) => {
+            entry.count += 1;
+            entry.last_time = now;
+        },
+        None => entries.push_back(LogEntry { msg, count: 1, last_time: now }),
+    }
+}
+
 pub fn recv_all() {
+    if let Some(sink) = FILE_SINK.lock().expect("file sink mutex should be not poisoned").as_mut() {
+        sink.flush_if_due()
+            .unwrap_or_else(|err| eprintln!("failed to flush log file {:?}: {err}", sink.path));
+    }
+
     let mut channel = CHANNEL.lock()
         .expect("channel mutex should be not poisoned");
 
     let mut messages = LOG_MESSAGES.lock()
         .expect("messages mutex should be not poisoned");
 
+    let window = Duration::from_secs_f32(cfg::console::LOG_COALESCE_WINDOW_IN_SECONDS);
+    let now = Instant::now();
+
     while let Ok(msg) = channel.receiver.try_recv() {
-        messages.push_back(msg);
+        push_coalesced(&mut messages, msg, now, window);
     }
 }
 
 pub fn log(msg_type: MsgType, from: impl Into<CowStr>, content: impl Into<CowStr>) {
-    let (from, content) = (from.into(), content.into());
+    let msg = Message::new(msg_type, from, content);
+    eprintln!("{} from {}: {}", msg.msg_type, msg.from, msg.content);
+
+    if let Some(sink) = FILE_SINK.lock().expect("file sink mutex should be not poisoned").as_mut() {
+        sink.write(&msg)
+            .unwrap_or_else(|err| eprintln!("failed to write log message to {:?}: {err}", sink.path));
+    }
 
-    eprintln!("{msg_type} from {from}: {content}");
     CHANNEL.lock()
         .expect("channel mutex should be not poisoned")
         .sender
-        .send(Message { msg_type, from, content})
+        .send(msg)
         .expect("failed to send message");
 }
 
+/// Appends log messages to a file. Messages are buffered and flushed on [errors][MsgType::Error],
+/// once flush interval passes on write or on [receiving][recv_all] and on drop.
+#[derive(Debug)]
+pub struct FileSink {
+    pub path: PathBuf,
+    writer: BufWriter<File>,
+    last_flush_time: Instant,
+    flush_interval: Duration,
+}
+
+impl FileSink {
+    /// Creates file on `path` or appends to existing one.
+    pub fn open(path: impl Into<PathBuf>, flush_interval: Duration) -> io::Result<Self> {
+        let path = path.into();
+
+        let file = File::options()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+            last_flush_time: Instant::now(),
+            flush_interval,
+        })
+    }
+
+    /// Creates `terramine-<timestamp>.log` file in `dir`.
+    pub fn create_in(dir: impl AsRef<Path>, flush_interval: Duration) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Self::open(dir.join(format!("terramine-{timestamp}.log")), flush_interval)
+    }
+
+    /// Writes [timestamped][Message::timestamped] message line.
+    pub fn write(&mut self, msg: &Message) -> io::Result<()> {
+        writeln!(self.writer, "{}", msg.timestamped())?;
+
+        if msg.msg_type == MsgType::Error {
+            self.flush()?;
+        }
+
+        self.flush_if_due()
+    }
+
+    /// Flushes if flush interval has passed since last flush.
+    pub fn flush_if_due(&mut self) -> io::Result<()> {
+        match self.last_flush_time.elapsed() >= self.flush_interval {
+            true => self.flush(),
+            false => Ok(()),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.last_flush_time = Instant::now();
+        self.writer.flush()
+    }
+}
+
+impl Drop for FileSink {
+    fn drop(&mut self) {
+        self.flush()
+            .unwrap_or_else(|err| eprintln!("failed to flush log file {:?}: {err}", self.path));
+    }
+}
+
+/// Replaces file sink. [`None`] disables writing to file. Gives previous sink.
+pub fn set_file_sink(sink: Option<FileSink>) -> Option<FileSink> {
+    let mut current = FILE_SINK.lock()
+        .expect("file sink mutex should be not poisoned");
+
+    mem::replace(&mut current, sink)
+}
+
+/// Enables file sink in [`cfg::console::LOG_FILE_DIR`] if it is [enabled][cfg::console::IS_LOG_FILE_ENABLED].
+pub fn init_file_sink() {
+    if !cfg::console::IS_LOG_FILE_ENABLED { return }
+
+    let flush_interval = Duration::from_secs_f32(cfg::console::LOG_FILE_FLUSH_INTERVAL_IN_SECONDS);
+
+    match FileSink::create_in(cfg::console::LOG_FILE_DIR, flush_interval) {
+        Ok(sink) => {
+            let path = sink.path.clone();
+            set_file_sink(Some(sink));
+            log!(Info, from = "logger", "writing log to {path:?}");
+        },
+        Err(err) => log!(Error, from = "logger", "failed to create log file: {err}"),
+    }
+}
+
+/// Flushes file sink if it is not used by another thread. Used by panic hook.
+pub fn try_flush_file_sink() {
+    if let Ok(mut sink) = FILE_SINK.try_lock() {
+        if let Some(sink) = sink.as_mut() {
+            let _ = sink.flush();
+        }
+    }
+}
+
+/// Forwards records of `log` crate from dependencies to console.
+/// Records above [`cfg::console::LOG_BRIDGE_LEVEL`] are passed to `env_logger`.
+/// If `RUST_LOG` is set its filter decides which records are shown, otherwise
+/// records up to [`cfg::console::LOG_BRIDGE_LEVEL`] are.
+#[derive(Debug)]
+pub struct LogBridge {
+    pub fallback: env_logger::Logger,
+    pub is_env_filtered: bool,
+}
+
+impl LogBridge {
+    pub fn new() -> Self {
+        Self {
+            fallback: env_logger::Logger::from_default_env(),
+            is_env_filtered: std::env::var_os(env_logger::DEFAULT_FILTER_ENV).is_some(),
+        }
+    }
+
+    /// Gives the most verbose level that can be shown.
+    pub fn max_level(&self) -> ::log::LevelFilter {
+        match self.is_env_filtered {
+            true => self.fallback.filter(),
+            false => cfg::console::LOG_BRIDGE_LEVEL,
+        }
+    }
+
+    /// Gives [message type][MsgType] of record `level`.
+    pub fn msg_type(level: ::log::Level) -> MsgType {
+        use ::log::Level;
+
+        match level {
+            Level::Error | Level::Warn => MsgType::Error,
+            Level::Info | Level::Debug | Level::Trace => MsgType::Info,
+        }
+    }
+}
+
+impl Default for LogBridge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ::log::Log for LogBridge {
+    fn enabled(&self, metadata: &::log::Metadata) -> bool {
+        match self.is_env_filtered {
+            true => self.fallback.enabled(metadata),
+            false => metadata.level() <= cfg::console::LOG_BRIDGE_LEVEL,
+        }
+    }
+
+    fn log(&self, record: &::log::Record) {
+        if !self.enabled(record.metadata()) { return }
+
+        if record.level() <= cfg::console::LOG_BRIDGE_LEVEL {
+            log(Self::msg_type(record.level()), record.target().to_owned(), record.args().to_string());
+        } else {
+            self.fallback.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.fallback.flush();
+    }
+}
+
+/// Sets [`LogBridge`] as `log` crate logger.
+pub fn init_log_bridge() -> Result<(), ::log::SetLoggerError> {
+    let bridge = LogBridge::new();
+    let max_level = bridge.max_level();
+
+    ::log::set_boxed_logger(Box::new(bridge))?;
+    ::log::set_max_level(max_level);
+
+    Ok(())
+}
+
 pub fn work(from: impl Into<CowStr>, work: impl Into<CowStr>) -> WorkLogGuard {
     WorkLogGuard::new(from, work)
 }
 
+/// Starts [work][WorkLogGuard] that is not nested into works of current thread.
+/// Use it in async tasks: their guards can be held across `.await` and dropped on another thread.
+pub fn work_detached(from: impl Into<CowStr>, work: impl Into<CowStr>) -> WorkLogGuard {
+    WorkLogGuard::detached(from, work)
+}
+
+thread_local! {
+    /// Number of nested [work guards][WorkLogGuard] alive on current thread.
+    static WORK_DEPTH: Cell<usize> = Cell::new(0);
+}
+
+/// Indentation of one nesting level of [work][WorkLogGuard] messages.
+const WORK_INDENT: &str = "  ";
+
+/// Logs start and end of work. Nested guards should be dropped on the thread they are created,
+/// see [`work_detached`] otherwise.
 #[must_use]
 #[derive(Debug)]
 pub struct WorkLogGuard {
     pub from: CowStr,
     pub work: CowStr,
+    pub depth: usize,
+
+    /// Guard is counted in [`WORK_DEPTH`] of its thread.
+    pub is_nested: bool,
 }
 
 impl WorkLogGuard {
     pub fn new(from: impl Into<CowStr>, work: impl Into<CowStr>) -> Self {
-        let (from, work) = (from.into(), work.into());
-        log!(Info, from = from.clone(), "Start {work}");
-        Self { from, work }
+        let depth = WORK_DEPTH.with(|depth| depth.replace(depth.get() + 1));
+        Self::start(from.into(), work.into(), depth, true)
+    }
+
+    pub fn detached(from: impl Into<CowStr>, work: impl Into<CowStr>) -> Self {
+        Self::start(from.into(), work.into(), 0, false)
+    }
+
+    fn start(from: CowStr, work: CowStr, depth: usize, is_nested: bool) -> Self {
+        let indent = WORK_INDENT.repeat(depth);
+
+        log!(Info, from = from.clone(), "{indent}Start {work}");
+        Self { from, work, depth, is_nested }
     }
 }
 
 impl Drop for WorkLogGuard {
     fn drop(&mut self) {
+        if self.is_nested {
+            WORK_DEPTH.with(|depth| depth.set(depth.get().saturating_sub(1)));
+        }
+
         let from = mem::take(&mut self.from);
-        log!(Info, from = from, "End {work}", work = self.work);
+        let indent = WORK_INDENT.repeat(self.depth);
+        log!(Info, from = from, "{indent}End {work}", work = self.work);
     }
 }
 
@@ -111,12 +414,7 @@ macro_rules! work {
 pub use crate::{log, log_dbg, work};
 
 pub fn spawn_window(ui: &imgui::Ui) {
-    use {
-        crate::app::utils::{
-            graphics::ui::imgui_constructor::make_window,
-        },
-        cpython::{Python, PyResult, py_fn, PyDict},
-    };
+    use crate::app::utils::graphics::ui::imgui_constructor::make_window;
 
     const ERROR_COLOR: [f32; 4] = [0.8, 0.1, 0.05, 1.0];
     const INFO_COLOR:  [f32; 4] = [1.0, 1.0, 1.0,  1.0];
@@ -135,8 +433,6 @@ pub fn spawn_window(ui: &imgui::Ui) {
         .position_pivot([0.0, 1.0])
         .size([width - 2.0 * PADDING, HEIGHT], imgui::Condition::Always)
         .build(|| {
-            use crate::app::utils::terrain::chunk::commands::{Command, command};
-
             let messages = LOG_MESSAGES.lock()
                 .expect("messages lock should be not poisoned");
 
@@ -144,66 +440,503 @@ pub fn spawn_window(ui: &imgui::Ui) {
             let mut input = INPUT.lock()
                 .unwrap();
 
-            let is_enter_pressed = ui.input_text("Console", &mut input)
-                .enter_returns_true(true)
-                .build();
+            static HISTORY: Mutex<ConsoleHistory> = Mutex::new(
+                ConsoleHistory::new(cfg::console::HISTORY_CAPACITY)
+            );
+            let mut history = HISTORY.lock()
+                .unwrap();
 
-            let buf = input.replace("^;", "\n");
+            static IS_MULTILINE: AtomicBool = AtomicBool::new(false);
+            let mut is_multiline = IS_MULTILINE.load(Relaxed);
+            if ui.checkbox("Multi-line", &mut is_multiline) {
+                IS_MULTILINE.store(is_multiline, Relaxed);
+            }
 
-            let gil = Python::acquire_gil();
-            let py = gil.python();
-        
-            let voxel_set = py_fn!(py, voxel_set(x: i32, y: i32, z: i32, new_id: u16) -> PyResult<i32> {
-                command(Command::SetVoxel { pos: veci!(x, y, z), new_id });
-                Ok(0)
-            });
-
-            let voxel_fill = py_fn!(py, voxel_fill(
-                sx: i32, sy: i32, sz: i32,
-                ex: i32, ey: i32, ez: i32, new_id: u16
-            ) -> PyResult<i32> {
-                command(Command::FillVoxels { pos_from: veci!(sx, sy, sz), pos_to: veci!(ex, ey, ez), new_id });
-                Ok(0)
-            });
-
-            let drop_all_meshes = py_fn!(py, drop_all_meshes() -> PyResult<i32> {
-                command(Command::DropAllMeshes);
-                Ok(0)
-            });
-
-            let locals = PyDict::new(py);
-
-            locals.set_item(py, "voxel_set", voxel_set)
-                .unwrap_or_else(|err|
-                    log!(Error, from = "logger", "failed to set 'voxel_set' item: {err:?}")
-                );
+            // Multi-line editor can't navigate history, so it is only in single-line input.
+            let is_run_requested = if is_multiline {
+                const MIN_LINES: usize = 3;
+                const MAX_LINES: usize = 20;
 
-            locals.set_item(py, "voxel_fill", voxel_fill)
-                .unwrap_or_else(|err|
-                    log!(Error, from = "logger", "failed to set 'voxel_fill' item: {err:?}")
-                );
-                
-            locals.set_item(py, "drop_all_meshes", drop_all_meshes)
-                .unwrap_or_else(|err|
-                    log!(Error, from = "logger", "failed to set 'drop_all_meshes' item: {err:?}")
-                );
+                let n_lines = input.lines().count().clamp(MIN_LINES, MAX_LINES) + 1;
+                let editor_height = n_lines as f32 * ui.text_line_height_with_spacing();
+
+                let is_ctrl_enter_pressed = ui.input_text_multiline("Script", &mut input, [-1.0, editor_height])
+                    .enter_returns_true(true)
+                    .build();
+
+                ui.button("Run (Ctrl+Enter)") || is_ctrl_enter_pressed
+            } else {
+                ui.input_text("Console", &mut input)
+                    .enter_returns_true(true)
+                    .callback(
+                        imgui::InputTextCallback::HISTORY | imgui::InputTextCallback::COMPLETION,
+                        ConsoleCallbacks { history: &mut history },
+                    )
+                    .build()
+            };
+
+            if is_run_requested {
+                let src = prepare_script(&input);
+                history.push(mem::take(&mut *input));
 
-            if is_enter_pressed {
-                py.run(&buf, None, Some(&locals))
-                    .unwrap_or_else(|err| log!(Error, from = "logger", "{err:?}"));
+                // Script waits for its commands to be processed on this thread so it runs on another.
+                let timeout = crate::runtime_cfg::get().script_timeout();
+                thread::spawn(move || run_script_with_timeout(src, timeout)
+                    .unwrap_or_else(|err| log!(Error, from = "logger", "{err}"))
+                );
             }
 
-            for msg in messages.iter().rev() {
-                let color = match msg.msg_type {
+            for entry in messages.iter().rev() {
+                let color = match entry.msg.msg_type {
                     MsgType::Error => ERROR_COLOR,
                     MsgType::Info  => INFO_COLOR,
                 };
 
-                ui.text_colored(color, &format!("[LOG]: {msg}"));
+                ui.text_colored(color, &format!("[LOG]: {entry}"));
             }
         });
 }
 
+/// Names of functions available in console.
+pub const CONSOLE_FUNCTIONS: &[&str] = &[
+    "voxel_set",
+    "voxel_fill",
+    "voxel_interact",
+    "camera_set",
+    "explode",
+    "force_full_detail",
+    "set_time_of_day",
+    "drop_all_meshes",
+    "rebuild_meshes",
+    "world_seed",
+    "world_set_seed",
+    "world_sizes",
+    "run_script",
+    "verify_world",
+];
+
+/// Converts console input to Python source.
+/// `^;` separates lines for compatibility with single-line input.
+pub fn prepare_script(input: &str) -> String {
+    input.replace("^;", "\n")
+}
+
+/// Formats [`camera_set`][CONSOLE_FUNCTIONS] call that moves camera to `pos`.
+pub fn camera_set_command(pos: vec3) -> String {
+    format!("camera_set({x:.3}, {y:.3}, {z:.3})", x = pos.x, y = pos.y, z = pos.z)
+}
+
+/// Formats [`voxel_set`][CONSOLE_FUNCTIONS] call that sets voxel in `pos` to `id`.
+pub fn voxel_set_command(pos: Int3, id: Id) -> String {
+    format!("voxel_set({x}, {y}, {z}, {id})", x = pos.x, y = pos.y, z = pos.z)
+}
+
+/// Builtins available in console besides [console functions][CONSOLE_FUNCTIONS].
+/// There is no `__import__` and `open` so scripts can't reach modules and files.
+pub const SAFE_BUILTINS: &[&str] = &[
+    "abs", "all", "any", "bool", "dict", "enumerate", "float", "int", "isinstance",
+    "len", "list", "max", "min", "print", "range", "reversed", "round", "set",
+    "sorted", "str", "sum", "tuple", "zip",
+    "Exception", "ValueError", "TypeError", "AssertionError", "TimeoutError",
+];
+
+/// Runs Python `src` with [console functions][CONSOLE_FUNCTIONS] and [safe builtins][SAFE_BUILTINS] available.
+pub fn run_script(py: Python, src: &str) -> Result<(), ScriptError> {
+    let globals = console_globals(py);
+
+    py.run(src, Some(&globals), None)
+        .map_err(|mut err| {
+            let line = error_line(py, &mut err);
+            let msg = format!(
+                "{ty}: {value}",
+                ty = err.get_type(py).name(py),
+                value = err.instance(py),
+            );
+
+            match line {
+                Some(line) => ScriptError::AtLine { line, msg },
+                None => ScriptError::Python(msg),
+            }
+        })
+}
+
+/// Runs Python `src` by [`run_script`] on separate thread. If script runs longer than `timeout`
+/// then it is interrupted with `TimeoutError`. Script that ignores interruption is abandoned.
+///
+/// Caller should not hold GIL, otherwise script thread can't start.
+pub fn run_script_with_timeout(src: String, timeout: Duration) -> Result<(), ScriptError> {
+    /// Time for interrupted script to stop.
+    const INTERRUPT_GRACE: Duration = Duration::from_millis(500);
+
+    let (id_sender, id_receiver) = mpsc::sync_channel(1);
+    let (result_sender, result_receiver) = mpsc::sync_channel(1);
+
+    thread::Builder::new()
+        .name("console-script".into())
+        .spawn(move || {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+
+            let _ = id_sender.send(python_thread_id(py));
+            let _ = result_sender.send(run_script(py, &src));
+        })
+        .map_err(ScriptError::Spawn)?;
+
+    let thread_id = id_receiver.recv()
+        .map_err(|_| ScriptError::Python("script thread has stopped unexpectedly".into()))?;
+
+    match result_receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+
+        Err(mpsc::RecvTimeoutError::Disconnected) =>
+            Err(ScriptError::Python("script thread has stopped unexpectedly".into())),
+
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            if let Some(thread_id) = thread_id {
+                interrupt_python_thread(thread_id);
+            }
+
+            if result_receiver.recv_timeout(INTERRUPT_GRACE).is_err() {
+                log!(Error, from = "logger", "script ignores interruption and is abandoned");
+            }
+
+            Err(ScriptError::Timeout(timeout))
+        },
+    }
+}
+
+/// Gives Python identifier of current thread.
+fn python_thread_id(py: Python) -> Option<u64> {
+    py.import("threading")
+        .and_then(|threading| threading.call(py, "get_ident", NoArgs, None))
+        .and_then(|id| id.extract(py))
+        .map_err(|err| log!(Error, from = "logger", "failed to get script thread id: {err:?}"))
+        .ok()
+}
+
+/// Raises `TimeoutError` in Python thread with `thread_id` on its next instruction.
+fn interrupt_python_thread(thread_id: u64) {
+    use cpython::_detail::ffi;
+
+    let _gil = Python::acquire_gil();
+
+    // Safety: GIL is held and exception type is a static Python object.
+    let n_changed = unsafe { ffi::PyThreadState_SetAsyncExc(thread_id as _, ffi::PyExc_TimeoutError) };
+
+    if n_changed != 1 {
+        log!(Error, from = "logger", "failed to interrupt script thread {thread_id}");
+    }
+}
+
+/// Reads Python script from file on `path` and runs it by [`run_script`].
+pub fn run_script_file(py: Python, path: impl AsRef<std::path::Path>) -> Result<(), ScriptError> {
+    let src = std::fs::read_to_string(path)?;
+    run_script(py, &src)
+}
+
+#[derive(Debug, Error)]
+pub enum ScriptError {
+    #[error("failed to read script: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("line {line}: {msg}")]
+    AtLine {
+        line: i64,
+        msg: String,
+    },
+
+    #[error("{0}")]
+    Python(String),
+
+    #[error("failed to spawn script thread: {0}")]
+    Spawn(std::io::Error),
+
+    #[error("script was interrupted after {0:?} timeout")]
+    Timeout(Duration),
+}
+
+/// Gives line where Python error happened. Syntax errors have the line in the exception,
+/// other errors have it in the innermost traceback frame.
+fn error_line(py: Python, err: &mut PyErr) -> Option<i64> {
+    let syntax_error_line = err.instance(py)
+        .getattr(py, "lineno")
+        .and_then(|line| line.extract(py));
+
+    if let Ok(line) = syntax_error_line {
+        return Some(line);
+    }
+
+    let mut traceback = err.ptraceback.as_ref()?.clone_ref(py);
+    while let Ok(next) = traceback.getattr(py, "tb_next") {
+        if next == py.None() { break }
+        traceback = next;
+    }
+
+    traceback.getattr(py, "tb_lineno").ok()?
+        .extract(py).ok()
+}
+
+/// Sends [command][Command] and waits for its result. Failure is raised as Python `RuntimeError`.
+fn command_or_raise(command: Command) -> PyResult<i32> {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    // Releases GIL so other Python threads run while it waits. The wait is bounded by
+    // script timeout because interruption is only delivered between Python instructions.
+    let timeout = crate::runtime_cfg::get().script_timeout();
+    py.allow_threads(|| command_and_wait(command, timeout))
+        .map(|()| 0)
+        .map_err(|err| PyErr::new::<RuntimeError, _>(py, err.to_string()))
+}
+
+/// Makes Python globals with [console functions][CONSOLE_FUNCTIONS] and [safe builtins][SAFE_BUILTINS].
+fn console_globals(py: Python) -> PyDict {
+    use crate::app::utils::terrain::{chunk::commands, voxel::generator};
+
+    let voxel_set = py_fn!(py, voxel_set(x: i32, y: i32, z: i32, new_id: u16) -> PyResult<i32> {
+        command_or_raise(Command::SetVoxel { pos: veci!(x, y, z), new_id })
+    });
+
+    let voxel_fill = py_fn!(py, voxel_fill(
+        sx: i32, sy: i32, sz: i32,
+        ex: i32, ey: i32, ez: i32, new_id: u16
+    ) -> PyResult<i32> {
+        command_or_raise(Command::FillVoxels { pos_from: veci!(sx, sy, sz), pos_to: veci!(ex, ey, ez), new_id })
+    });
+
+    let voxel_interact = py_fn!(py, voxel_interact(x: i32, y: i32, z: i32) -> PyResult<i32> {
+        command_or_raise(Command::Interact { pos: veci!(x, y, z) })
+    });
+
+    let camera_set = py_fn!(py, camera_set(x: f32, y: f32, z: f32) -> PyResult<i32> {
+        crate::graphics::camera::request_position(vecf!(x, y, z));
+        Ok(0)
+    });
+
+    let explode = py_fn!(py, explode(x: i32, y: i32, z: i32, radius: f32) -> PyResult<i32> {
+        command_or_raise(Command::Explode { center: veci!(x, y, z), radius, seed: rand::random() })
+    });
+
+    let force_full_detail = py_fn!(py, force_full_detail(
+        sx: i32, sy: i32, sz: i32,
+        ex: i32, ey: i32, ez: i32, enabled: bool
+    ) -> PyResult<i32> {
+        command_or_raise(Command::ForceFullDetail { pos_from: veci!(sx, sy, sz), pos_to: veci!(ex, ey, ez), enabled })
+    });
+
+    let set_time_of_day = py_fn!(py, set_time_of_day(hours: f32) -> PyResult<i32> {
+        crate::graphics::sky::set_time_of_day(hours);
+        Ok(0)
+    });
+
+    let drop_all_meshes = py_fn!(py, drop_all_meshes() -> PyResult<i32> {
+        command_or_raise(Command::DropAllMeshes)
+    });
+
+    let rebuild_meshes = py_fn!(py, rebuild_meshes() -> PyResult<i32> {
+        command_or_raise(Command::RebuildMeshes)
+    });
+
+    // Queries read shared state directly so they don't wait for command processing.
+    let world_seed = py_fn!(py, world_seed() -> PyResult<u64> {
+        Ok(generator::seed())
+    });
+
+    let world_set_seed = py_fn!(py, world_set_seed(seed: u64) -> PyResult<i32> {
+        command_or_raise(Command::SetSeed { seed })
+    });
+
+    let world_sizes = py_fn!(py, world_sizes() -> PyResult<(i32, i32, i32)> {
+        let sizes = commands::world_sizes();
+        Ok((sizes.x as i32, sizes.y as i32, sizes.z as i32))
+    });
+
+    let run_script = py_fn!(py, run_script(path: String) -> PyResult<i32> {
+        // GIL is already held by the caller, acquiring it again is allowed.
+        let gil = Python::acquire_gil();
+        run_script_file(gil.python(), &path)
+            .map_err(|err| PyErr::new::<RuntimeError, _>(py, format!("script '{path}' failed: {err}")))?;
+        Ok(0)
+    });
+
+    let verify_world = py_fn!(py, verify_world(name: String) -> PyResult<i32> {
+        use crate::terrain::chunk::chunk_array::ChunkArray;
+
+        // Save of world `name` is stored in directory with the same name.
+        let report = py.allow_threads(|| RUNTIME.block_on(ChunkArray::verify_file(&name, &name)))
+            .map_err(|err| PyErr::new::<RuntimeError, _>(py, err.to_string()))?;
+
+        Ok(report.bad_chunks.len() as i32 + i32::from(!report.is_volume_valid()))
+    });
+
+    let bindings = [
+        ("voxel_set", voxel_set),
+        ("voxel_fill", voxel_fill),
+        ("voxel_interact", voxel_interact),
+        ("camera_set", camera_set),
+        ("explode", explode),
+        ("force_full_detail", force_full_detail),
+        ("set_time_of_day", set_time_of_day),
+        ("drop_all_meshes", drop_all_meshes),
+        ("rebuild_meshes", rebuild_meshes),
+        ("world_seed", world_seed),
+        ("world_set_seed", world_set_seed),
+        ("world_sizes", world_sizes),
+        ("run_script", run_script),
+        ("verify_world", verify_world),
+    ];
+
+    let globals = PyDict::new(py);
+
+    for (name, binding) in bindings {
+        debug_assert!(
+            CONSOLE_FUNCTIONS.contains(&name),
+            "'{name}' should be listed in console functions to be completed",
+        );
+
+        globals.set_item(py, name, binding)
+            .unwrap_or_else(|err|
+                log!(Error, from = "logger", "failed to set '{name}' item: {err:?}")
+            );
+    }
+
+    let builtins = PyDict::new(py);
+
+    match py.import("builtins") {
+        Ok(all_builtins) => for &name in SAFE_BUILTINS {
+            let _ = all_builtins.get(py, name)
+                .and_then(|builtin| builtins.set_item(py, name, builtin))
+                .map_err(|err| log!(Error, from = "logger", "failed to set '{name}' builtin: {err:?}"));
+        },
+        Err(err) => log!(Error, from = "logger", "failed to import builtins: {err:?}"),
+    }
+
+    globals.set_item(py, "__builtins__", builtins)
+        .unwrap_or_else(|err| log!(Error, from = "logger", "failed to set builtins: {err:?}"));
+
+    globals
+}
+
+/// Bounded list of executed console commands with navigation cursor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsoleHistory {
+    pub entries: VecDeque<String>,
+    pub capacity: usize,
+
+    /// Index of shown entry. [`None`] means new command is edited.
+    pub cursor: Option<usize>,
+}
+
+impl ConsoleHistory {
+    pub const fn new(capacity: usize) -> Self {
+        Self { entries: VecDeque::new(), capacity, cursor: None }
+    }
+
+    /// Stores executed command. Empty commands and repeats of the last one are skipped.
+    /// If there are more than `capacity` commands then the oldest is removed.
+    pub fn push(&mut self, command: String) {
+        self.cursor = None;
+
+        if command.trim().is_empty() || self.entries.back() == Some(&command) {
+            return;
+        }
+
+        self.entries.push_back(command);
+
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Moves to older command and gives it.
+    pub fn prev(&mut self) -> Option<&str> {
+        let idx = match self.cursor {
+            None => self.entries.len().checked_sub(1)?,
+            Some(idx) => idx.saturating_sub(1),
+        };
+
+        self.cursor = Some(idx);
+        self.entries.get(idx).map(String::as_str)
+    }
+
+    /// Moves to newer command and gives it. Gives empty command after the newest one.
+    pub fn next(&mut self) -> Option<&str> {
+        let idx = self.cursor?;
+
+        if idx + 1 < self.entries.len() {
+            self.cursor = Some(idx + 1);
+            self.entries.get(idx + 1).map(String::as_str)
+        } else {
+            self.cursor = None;
+            Some("")
+        }
+    }
+}
+
+/// Gives `names` that start with `prefix`.
+pub fn completion_candidates<'n>(prefix: &str, names: &[&'n str]) -> Vec<&'n str> {
+    names.iter()
+        .copied()
+        .filter(|name| name.starts_with(prefix))
+        .collect()
+}
+
+/// Completes identifier at the end of `input` by `names`. Single candidate is completed
+/// to a call, multiple candidates are completed to their common prefix.
+/// Gives [`None`] if there is nothing to complete.
+pub fn complete(input: &str, names: &[&str]) -> Option<String> {
+    let token_start = input.rfind(|c: char| !c.is_alphanumeric() && c != '_')
+        .map_or(0, |idx| idx + 1);
+    let (head, prefix) = input.split_at(token_start);
+
+    let candidates = completion_candidates(prefix, names);
+
+    let completion = match candidates[..] {
+        [] => return None,
+        [single] => format!("{single}("),
+        [first, ..] => {
+            let common_len = candidates.iter()
+                .map(|name| first.chars().zip(name.chars()).take_while(|(a, b)| a == b).count())
+                .min()
+                .unwrap_or(0);
+
+            if common_len <= prefix.len() {
+                log!(Info, from = "logger", "candidates: {}", candidates.join(", "));
+            }
+
+            first[..common_len].to_owned()
+        },
+    };
+
+    Some(format!("{head}{completion}"))
+}
+
+/// Handles history navigation and completion in console input.
+struct ConsoleCallbacks<'h> {
+    history: &'h mut ConsoleHistory,
+}
+
+impl imgui::InputTextCallbackHandler for ConsoleCallbacks<'_> {
+    fn on_history(&mut self, dir: imgui::HistoryDirection, mut data: imgui::TextCallbackData) {
+        let command = match dir {
+            imgui::HistoryDirection::Up => self.history.prev(),
+            imgui::HistoryDirection::Down => self.history.next(),
+        };
+
+        if let Some(command) = command {
+            data.clear();
+            data.push_str(command);
+        }
+    }
+
+    fn on_completion(&mut self, mut data: imgui::TextCallbackData) {
+        if let Some(completed) = complete(data.str(), CONSOLE_FUNCTIONS) {
+            data.clear();
+            data.push_str(&completed);
+        }
+    }
+}
+
 pub trait LogError<T> {
     fn log_error(self, from: impl Into<CowStr>, msg: impl Into<CowStr>) -> T where T: Default;
     fn log_error_or(self, from: impl Into<CowStr>, msg: impl Into<CowStr>, default: T) -> T;
@@ -243,4 +976,253 @@ impl<T, E: std::error::Error> LogError<T> for Result<T, E> {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Takes messages sent `from` out of the channel. Other messages are sent back.
+    fn take_sent_messages(from: &str) -> Vec<Message> {
+        let mut channel = CHANNEL.lock().unwrap();
+
+        let (taken, others): (Vec<Message>, Vec<Message>) =
+            std::iter::from_fn(|| channel.receiver.try_recv().ok())
+                .partition(|msg| msg.from == from);
+
+        for msg in others {
+            channel.sender.send(msg).expect("failed to send message back");
+        }
+
+        taken
+    }
+
+    #[test]
+    fn history_is_bounded_and_navigable() {
+        let mut history = ConsoleHistory::new(2);
+
+        for command in ["a", "b", "b", "", "c"] {
+            history.push(command.to_owned());
+        }
+
+        assert_eq!(history.entries, ["b", "c"]);
+
+        assert_eq!(history.prev(), Some("c"));
+        assert_eq!(history.prev(), Some("b"));
+        assert_eq!(history.prev(), Some("b"));
+        assert_eq!(history.next(), Some("c"));
+        assert_eq!(history.next(), Some(""));
+        assert_eq!(history.next(), None);
+    }
+
+    #[test]
+    fn repeated_messages_are_coalesced() {
+        let window = Duration::from_secs(1);
+        let start = Instant::now();
+        let mut entries = VecDeque::new();
+
+        let msg = Message::new(MsgType::Error, "test", "oops");
+        for i in 0..100 {
+            let msg = Message { timestamp: msg.timestamp + Duration::from_millis(i), ..msg.clone() };
+            push_coalesced(&mut entries, msg, start + Duration::from_millis(i), window);
+        }
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].count, 100);
+        assert_eq!(entries[0].to_string(), format!("{} (x100)", msg.timestamped()));
+
+        let info = Message { msg_type: MsgType::Info, ..msg.clone() };
+        push_coalesced(&mut entries, info, start, window);
+        push_coalesced(&mut entries, msg.clone(), start + 2 * window, window);
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[2].count, 1);
+    }
+
+    #[test]
+    fn file_sink_writes_logged_lines() {
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let path = std::env::temp_dir()
+            .join(format!("terramine_file_sink_test_{}_{nanos}.log", std::process::id()));
+
+        let sink = FileSink::open(&path, Duration::from_secs(60))
+            .expect("failed to open log file");
+        let previous = set_file_sink(Some(sink));
+
+        log!(Info, from = "file-sink-test", "first line");
+        log!(Error, from = "file-sink-test", "second line");
+
+        // Buffered info line is flushed on drop.
+        drop(set_file_sink(previous).expect("sink should be set"));
+
+        let text = fs::read_to_string(&path).expect("failed to read log file");
+        let lines: Vec<&str> = text.lines()
+            .filter(|line| line.contains("file-sink-test"))
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("[INFO]-[file-sink-test]: first line"));
+        assert!(lines[1].ends_with("[ERROR]-[file-sink-test]: second line"));
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn log_crate_records_reach_console() {
+        const TARGET: &str = "log-bridge-test";
+
+        // Logger may be already set by another test.
+        let _ = init_log_bridge();
+
+        ::log::warn!(target: TARGET, "deprecated thing used");
+        ::log::trace!(target: TARGET, "too verbose for console");
+
+        let messages = take_sent_messages(TARGET);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].msg_type, MsgType::Error);
+        assert_eq!(messages[0].content, "deprecated thing used");
+    }
+
+    #[test]
+    fn env_filter_takes_precedence_over_bridge_level() {
+        use ::log::{Log, Level, MetadataBuilder};
+
+        let bridge = |filters: Option<&str>| LogBridge {
+            fallback: env_logger::Builder::new().parse_filters(filters.unwrap_or_default()).build(),
+            is_env_filtered: filters.is_some(),
+        };
+        let enabled = |bridge: &LogBridge, level: Level| bridge.enabled(
+            &MetadataBuilder::new().level(level).target("env-filter-test").build()
+        );
+
+        let default = bridge(None);
+        assert!(enabled(&default, Level::Warn));
+        assert!(!enabled(&default, Level::Info));
+
+        let silenced = bridge(Some("off"));
+        assert!(!enabled(&silenced, Level::Error));
+        assert_eq!(silenced.max_level(), ::log::LevelFilter::Off);
+
+        let verbose = bridge(Some("debug"));
+        assert!(enabled(&verbose, Level::Debug));
+        assert!(!enabled(&verbose, Level::Trace));
+    }
+
+    #[test]
+    fn nested_work_is_indented() {
+        const FROM: &str = "nested-work-test";
+
+        {
+            let _outer = work(FROM, "outer");
+            let _inner = work(FROM, "inner");
+        }
+
+        let contents: Vec<CowStr> = take_sent_messages(FROM).into_iter()
+            .map(|msg| msg.content)
+            .collect();
+
+        assert_eq!(contents, ["Start outer", "  Start inner", "  End inner", "End outer"]);
+    }
+
+    #[test]
+    fn detached_work_keeps_thread_depth() {
+        const FROM: &str = "detached-work-test";
+
+        let detached = work_detached(FROM, "task");
+        let outer = work(FROM, "outer");
+
+        // Detached guard may be dropped out of order or on another thread.
+        std::thread::spawn(move || drop(detached)).join().unwrap();
+        drop(outer);
+
+        drop(work(FROM, "after"));
+
+        let contents: Vec<CowStr> = take_sent_messages(FROM).into_iter()
+            .map(|msg| msg.content)
+            .collect();
+
+        assert_eq!(contents, ["Start task", "Start outer", "End task", "End outer", "Start after", "End after"]);
+    }
+
+    #[test]
+    fn messages_logged_apart_have_increasing_timestamps() {
+        const FROM: &str = "timestamp-test";
+
+        log!(Info, from = FROM, "first");
+        thread::sleep(Duration::from_millis(5));
+        log!(Info, from = FROM, "second");
+
+        let messages = take_sent_messages(FROM);
+
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].timestamp < messages[1].timestamp);
+        assert!(messages[1].timestamped().to_string().ends_with("[INFO]-[timestamp-test]: second"));
+    }
+
+    #[test]
+    fn script_keeps_line_separator() {
+        assert_eq!(prepare_script("a = 1^;b = a + 1"), "a = 1\nb = a + 1");
+        assert_eq!(prepare_script("a = 1\nb = 2"), "a = 1\nb = 2");
+    }
+
+    #[test]
+    fn multi_statement_script_runs() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        run_script(py, &prepare_script("a = 1^;b = a + 1^;assert b == 2"))
+            .expect("script should run");
+
+        assert!(matches!(
+            run_script(py, "a = 1\nraise ValueError('oops')"),
+            Err(ScriptError::AtLine { line: 2, .. }),
+        ));
+    }
+
+    #[test]
+    fn builtins_are_restricted() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        run_script(py, "assert len(range(3)) == 3").expect("safe builtins should be available");
+        assert!(run_script(py, "import os").is_err());
+        assert!(run_script(py, "open('file.txt')").is_err());
+    }
+
+    #[test]
+    fn long_script_is_interrupted() {
+        let timeout = Duration::from_millis(200);
+
+        assert!(matches!(
+            run_script_with_timeout("while True: pass".into(), timeout),
+            Err(ScriptError::Timeout(_)),
+        ));
+
+        run_script_with_timeout("assert world_seed() >= 0".into(), timeout)
+            .expect("console should keep working after interruption");
+    }
+
+    #[test]
+    fn completion_matches_prefix() {
+        assert_eq!(completion_candidates("voxel_", CONSOLE_FUNCTIONS), ["voxel_set", "voxel_fill"]);
+        assert!(completion_candidates("foo", CONSOLE_FUNCTIONS).is_empty());
+
+        assert_eq!(complete("x = dr", CONSOLE_FUNCTIONS).as_deref(), Some("x = drop_all_meshes("));
+        assert_eq!(complete("vox", CONSOLE_FUNCTIONS).as_deref(), Some("voxel_"));
+        assert_eq!(complete("foo", CONSOLE_FUNCTIONS), None);
+    }
+
+    #[test]
+    fn position_commands_formatting() {
+        assert_eq!(camera_set_command(vecf!(1.5, -2.0, 300.125)), "camera_set(1.500, -2.000, 300.125)");
+        assert_eq!(voxel_set_command(veci!(-4, 0, 17), 3), "voxel_set(-4, 0, 17, 3)");
+
+        let script = prepare_script(&format!(
+            "{}^;{}", camera_set_command(vecf!(0, 1, 2)), voxel_set_command(veci!(0, 1, 2), 0),
+        ));
+        assert_eq!(script, "camera_set(0.000, 1.000, 2.000)\nvoxel_set(0, 1, 2, 0)");
+    }
+}
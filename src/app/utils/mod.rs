@@ -10,4 +10,7 @@ pub mod concurrency;
 pub mod runtime;
 pub mod werror;
 pub mod cfg;
+pub mod runtime_cfg;
+pub mod vec_ext;
+pub mod aabb_ext;
 pub mod logger;
\ No newline at end of file
@@ -11,6 +11,7 @@ use {
     std::{
         time::Instant,
         sync::Mutex,
+        io,
     },
 };
 
@@ -53,23 +54,49 @@ impl Profile {
     }
 }
 
+thread_local! {
+    /// Ids of [measures][Measure] alive on current thread from the outermost one.
+    static SCOPE_STACK: RefCell<Vec<MeasureId>> = RefCell::new(vec![]);
+}
+
 /// Represents a time measure with drop-stop.
+///
+/// Measures are expected to be dropped in reverse creation order on the thread that created
+/// them, like scopes are. Measure dropped on another thread (e.g. held across `.await` in a
+/// task) leaves that thread's [scope stack][Measure::stack] untouched.
 #[derive(Debug)]
 pub struct Measure {
     pub value: f64,
     pub now: Instant,
     pub id: MeasureId,
+
+    /// Ids of enclosing measures from the outermost one ending with [`Measure::id`].
+    pub stack: Vec<MeasureId>,
 }
 
 impl Measure {
     pub fn new(id: MeasureId) -> Self {
-        Self { value: 0.0, now: Instant::now(), id }
+        let stack = SCOPE_STACK.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            stack.push(id);
+            stack.clone()
+        });
+
+        Self { value: 0.0, now: Instant::now(), id, stack }
     }
 }
 
 impl Drop for Measure {
     fn drop(&mut self) {
         self.value = self.now.elapsed().as_secs_f64();
+        SCOPE_STACK.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            let depth = self.stack.len() - 1;
+
+            if stack.get(depth) == Some(&self.id) {
+                stack.truncate(depth);
+            }
+        });
         upload_measure(self);
     }
 }
@@ -78,13 +105,18 @@ impl Drop for Measure {
 #[derive(Debug)]
 pub struct Profiler {
     pub profiles: HashMap<MeasureId, Profile>,
+
+    /// Time of current frame spent in each [scope stack][Measure::stack].
+    pub stacks: HashMap<Vec<MeasureId>, f64>,
 }
 
 static IS_DRAWING_ENABLED: AtomicBool = AtomicBool::new(false);
+static IS_EXPORT_REQUESTED: AtomicBool = AtomicBool::new(false);
 
 lazy_static! {
     static ref PROFILER: Mutex<Profiler> = Mutex::new(Profiler {
         profiles: HashMap::new(),
+        stacks: HashMap::new(),
     });
 }
 
@@ -98,13 +130,15 @@ pub fn add_profile(profile: Profile, id: MeasureId) {
 
 /// Uploads measure
 pub fn upload_measure(measure: &Measure) {
-    PROFILER.lock()
-        .unwrap()
-        .profiles
+    let mut profiler = PROFILER.lock().unwrap();
+
+    profiler.profiles
         .get_mut(&measure.id)
         .unwrap_or_else(|| panic!("measure {measure:?} should be in measure map"))
         .measures
-        .push(measure.value)
+        .push(measure.value);
+
+    *profiler.stacks.entry(measure.stack.clone()).or_insert(0.0) += measure.value;
 }
 
 /// Starting capturing to to profile under given `id`.
@@ -128,6 +162,10 @@ pub fn update_and_build_window(ui: &imgui::Ui, timer: &Timer) {
         let _ = IS_DRAWING_ENABLED.fetch_update(AcqRel, Relaxed, |prev| Some(!prev));
     }
 
+    if keyboard::just_pressed(cfg::key_bindings::EXPORT_FLAMEGRAPH) {
+        IS_EXPORT_REQUESTED.store(true, Release);
+    }
+
     let mut lock = PROFILER.lock().unwrap();
     let data = lock.profiles
         .iter_mut()
@@ -152,18 +190,66 @@ pub fn update_and_build_window(ui: &imgui::Ui, timer: &Timer) {
         .collect();
     
     build_window(ui, data);
-    drop(lock);
-
-    update();
 }
 
-/// Updates profiler:
+/// Ends profiler frame. Should be called on the frame thread after the whole frame is measured:
+/// * Writes [flamegraph][export_flamegraph] if it was requested during the frame
 /// * Clears measures
+/// * Clears scope stacks
 pub fn update() {
+    if IS_EXPORT_REQUESTED.swap(false, AcqRel) {
+        let path = cfg::profiler::FLAMEGRAPH_PATH;
+
+        match export_flamegraph(path) {
+            Ok(()) => logger::log!(Info, from = "profiler", "frame scopes are written to {path}"),
+            Err(err) => logger::log!(Error, from = "profiler", "failed to write frame scopes to {path}: {err}"),
+        }
+    }
+
+    SCOPE_STACK.with(|stack| stack.borrow_mut().clear());
+
     let mut lock = PROFILER.lock().unwrap();
     for (_, profile) in lock.profiles.iter_mut() {
         profile.measures.clear()
     }
+
+    lock.stacks.clear();
+}
+
+/// Makes folded stacks consumable by `inferno` or `flamegraph.pl` out of scope times in seconds
+/// keyed by scope name stacks. Scope time includes its children. Each line has self time in microseconds.
+pub fn folded_stacks<'s>(scopes: impl IntoIterator<Item = (Vec<&'s str>, f64)>) -> String {
+    let mut times = HashMap::<Vec<&str>, f64>::new();
+    for (stack, time) in scopes {
+        *times.entry(stack).or_insert(0.0) += time;
+    }
+
+    times.iter()
+        .map(|(stack, &time)| {
+            let children_time: f64 = times.iter()
+                .filter(|(child, _)| child.len() == stack.len() + 1 && child.starts_with(stack))
+                .map(|(_, &time)| time)
+                .sum();
+
+            let self_time = ((time - children_time).max(0.0) * 1_000_000.0).round() as u64;
+            format!("{stack} {self_time}\n", stack = stack.join(";"))
+        })
+        .sorted()
+        .collect()
+}
+
+/// Gives [folded stacks][folded_stacks] of scopes measured in current frame.
+pub fn frame_folded_stacks() -> String {
+    let profiler = PROFILER.lock().unwrap();
+    let name_of = |id: &MeasureId| profiler.profiles.get(id)
+        .map_or("unknown", |profile| profile.target_name.as_str());
+
+    folded_stacks(profiler.stacks.iter().map(|(stack, &time)| (stack.iter().map(name_of).collect(), time)))
+}
+
+/// Writes [folded stacks][frame_folded_stacks] of current frame to `path`.
+pub fn export_flamegraph(path: &str) -> io::Result<()> {
+    std::fs::write(path, frame_folded_stacks())
 }
 
 /// Builds ImGui window of capturing results
@@ -198,4 +284,48 @@ pub fn build_window(ui: &imgui::Ui, profiler_result: DataSummary) {
             }
         });
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_scopes_are_folded() {
+        let scopes = [
+            (vec!["frame"], 0.010),
+            (vec!["frame", "update"], 0.006),
+            (vec!["frame", "update", "mesh"], 0.004),
+            (vec!["frame", "render"], 0.002),
+            (vec!["frame", "render"], 0.001),
+        ];
+
+        assert_eq!(
+            folded_stacks(scopes),
+            "frame 1000\nframe;render 3000\nframe;update 2000\nframe;update;mesh 4000\n",
+        );
+    }
+
+    #[test]
+    fn measure_dropped_on_other_thread_keeps_its_stack() {
+        let (moved_id, local_id) = (MeasureId::MAX - 1, MeasureId::MAX - 2);
+        let moved = start_capture("moved", moved_id);
+
+        std::thread::spawn(move || {
+            let _local = start_capture("local", local_id);
+            drop(moved);
+
+            SCOPE_STACK.with(|stack| assert_eq!(*stack.borrow(), [local_id]));
+        }).join().expect("thread should not panic");
+    }
+
+    #[test]
+    fn frame_end_resets_scope_stack() {
+        let stale = start_capture("stale", MeasureId::MAX - 3);
+        std::mem::forget(stale);
+
+        update();
+
+        SCOPE_STACK.with(|stack| assert!(stack.borrow().is_empty()));
+    }
+}
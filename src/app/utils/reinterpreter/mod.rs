@@ -4,7 +4,10 @@
 //! Provides some `type-byte` and `byte-type` reinterpretations to common types
 //!
 
-use crate::prelude::*;
+use crate::{
+    prelude::*,
+    terrain::voxel::voxel_data::Id,
+};
 
 /// Composes input list of `IntoIterator`s with `Item = u8`
 /// into one large iterator by sequetially calling `.chain()`,
@@ -101,6 +104,9 @@ pub enum ReinterpretError {
 
     #[error("failed to convert types: {0}")]
     Conversion(String),
+
+    #[error("invalid voxel id {0}")]
+    InvalidVoxelId(Id),
 }
 
 #[repr(transparent)]
@@ -128,6 +134,41 @@ impl<'s> ByteReader<'s> {
 
         Ok(result)
     }
+
+    /// Reads exactly `len` bytes borrowing them from the source.
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'s [u8], ReinterpretError> {
+        if self.bytes.len() < len {
+            return Err(ReinterpretError::NotEnoughBytes {
+                idx: format!("{:?}", ..len),
+                len: self.bytes.len(),
+            });
+        }
+
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+
+        Ok(bytes)
+    }
+
+    /// Reads exactly `out.len()` bytes into `out`.
+    pub fn read_slice(&mut self, out: &mut [u8]) -> Result<(), ReinterpretError> {
+        out.copy_from_slice(self.read_bytes(out.len())?);
+        Ok(())
+    }
+
+    /// Reads `n` values of type `T` one after another.
+    pub fn read_vec<T>(&mut self, n: usize) -> Result<Vec<T>, ReinterpretError>
+    where
+        T: FromBytes + DynamicSize,
+    {
+        let mut result = Vec::with_capacity(n);
+
+        for _ in 0..n {
+            result.push(self.read()?);
+        }
+
+        Ok(result)
+    }
 }
 
 macro_rules! impl_nums {
@@ -144,7 +185,13 @@ macro_rules! impl_nums {
                     use std::array::TryFromSliceError;
 
                     let size = mem::size_of::<Self>();
-                    Ok(Self::from_ne_bytes(source[..size].try_into().map_err(|err: TryFromSliceError|
+                    let bytes = source.get(..size)
+                        .ok_or_else(|| ReinterpretError::NotEnoughBytes {
+                            idx: format!("{:?}", ..size),
+                            len: source.len(),
+                        })?;
+
+                    Ok(Self::from_ne_bytes(bytes.try_into().map_err(|err: TryFromSliceError|
                         ReinterpretError::Conversion(err.to_string())
                     )?))
                 }
@@ -276,13 +323,7 @@ impl<T: FromBytes + DynamicSize> FromBytes for Vec<T> {
         let mut reader = ByteReader::new(source);
         let len = reader.read()?;
 
-        let mut result = Self::with_capacity(len);
-
-        for _ in 0..len {
-            result.push(reader.read()?)
-        }
-
-        Ok(result)
+        reader.read_vec(len)
     }
 }
 
@@ -306,9 +347,11 @@ impl AsBytes for bit_vec::BitVec {
 impl FromBytes for bit_vec::BitVec {
     fn from_bytes(source: &[u8]) -> Result<Self, ReinterpretError> {
         let mut reader = ByteReader::new(source);
-        let len = reader.read()?;
+        let len: usize = reader.read()?;
 
-        let mut result = Self::from_bytes(reader.bytes);
+        let bytes = reader.read_bytes(len.div_ceil(8))?;
+
+        let mut result = Self::from_bytes(bytes);
         result.truncate(len);
 
         Ok(result)
@@ -317,7 +360,7 @@ impl FromBytes for bit_vec::BitVec {
 
 impl DynamicSize for bit_vec::BitVec {
     fn dynamic_size(&self) -> usize {
-        self.storage().len() + usize::static_size()
+        usize::static_size() + self.len().div_ceil(8)
     }
 }
 
@@ -546,6 +589,16 @@ mod tests {
         assert_eq!(i32::static_size(), 4);
     }
 
+    #[test]
+    fn reinterpret_truncated_number() {
+        let bytes = 2454_u64.as_bytes();
+
+        assert!(matches!(
+            u64::from_bytes(&bytes[..5]),
+            Err(ReinterpretError::NotEnoughBytes { len: 5, .. }),
+        ));
+    }
+
     #[test]
     fn reinterpret_u64() {
         let before: u64 = 234;
@@ -619,6 +672,39 @@ mod tests {
         assert_eq!(before, after);
     }
 
+    #[test]
+    fn byte_reader_read_slice() {
+        let source: Vec<u8> = (0..10).collect();
+        let mut reader = ByteReader::new(&source);
+
+        let mut head = [0; 4];
+        reader.read_slice(&mut head).unwrap();
+        assert_eq!(head, [0, 1, 2, 3]);
+
+        let next: u8 = reader.read().unwrap();
+        assert_eq!(next, 4);
+
+        let mut too_long = [0; 6];
+        assert!(reader.read_slice(&mut too_long).is_err());
+
+        assert_eq!(reader.read_bytes(2).unwrap(), [5, 6]);
+
+        let mut tail = [0; 3];
+        reader.read_slice(&mut tail).unwrap();
+        assert_eq!(tail, [7, 8, 9]);
+        assert!(reader.bytes.is_empty());
+    }
+
+    #[test]
+    fn byte_reader_read_vec() {
+        let before: Vec<u16> = vec![1, 42, 1024, 7];
+        let bytes: Vec<u8> = before.iter().flat_map(AsBytes::as_bytes).collect();
+
+        let after: Vec<u16> = ByteReader::new(&bytes).read_vec(before.len()).unwrap();
+
+        assert_eq!(before, after);
+    }
+
     #[test]
     fn reinterpret_some() {
         let before: Option<i32> = Some(213);
@@ -0,0 +1,238 @@
+//! Runtime configuration that overrides some of [`cfg`] constants.
+//! It is loaded from file at startup and can be tweaked with UI.
+
+use {
+    crate::{prelude::*, graphics::present_mode::PresentMode},
+    spin::RwLock,
+    std::{io, path::Path, time::Duration},
+};
+
+static RUNTIME_CFG: RwLock<RuntimeCfg> = RwLock::new(RuntimeCfg::DEFAULT);
+
+/// Values that are safe to change while app is running.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RuntimeCfg {
+    pub max_tasks: usize,
+    pub lod_threashold: f32,
+    pub clear_color: (f32, f32, f32, f32),
+    pub script_timeout_in_seconds: f32,
+    pub present_mode: PresentMode,
+}
+
+impl RuntimeCfg {
+    /// Config made of [`cfg`] constants.
+    pub const DEFAULT: Self = Self {
+        max_tasks: cfg::terrain::MAX_TASKS,
+        lod_threashold: cfg::terrain::default::LOD_THREASHOLD,
+        clear_color: cfg::shader::CLEAR_COLOR,
+        script_timeout_in_seconds: cfg::console::SCRIPT_TIMEOUT_IN_SECONDS,
+        present_mode: cfg::window::default::PRESENT_MODE,
+    };
+
+    /// Parses config from `key = value` lines. Lines starting with `#` are comments.
+    /// Keys that are not present keep their [default][RuntimeCfg::DEFAULT] values.
+    pub fn parse(src: &str) -> Result<Self, RuntimeCfgError> {
+        let mut result = Self::DEFAULT;
+
+        for (line_idx, text) in src.lines().enumerate() {
+            let text = text.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+
+            let line = line_idx + 1;
+            let parse_error = |msg: String| RuntimeCfgError::Parse { line, msg };
+
+            let (key, value) = text.split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| parse_error("expected `key = value`".into()))?;
+
+            match key {
+                "max_tasks" => result.max_tasks = value.parse()
+                    .map_err(|err| parse_error(format!("invalid max_tasks '{value}': {err}")))?,
+
+                "lod_threashold" => result.lod_threashold = value.parse()
+                    .map_err(|err| parse_error(format!("invalid lod_threashold '{value}': {err}")))?,
+
+                "clear_color" => {
+                    let components: Vec<f32> = value.split(',')
+                        .map(|component| component.trim().parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(|err| parse_error(format!("invalid clear_color '{value}': {err}")))?;
+
+                    let [r, g, b, a] = components[..] else {
+                        return Err(parse_error(format!(
+                            "clear_color should have 4 components but got {n}", n = components.len(),
+                        )));
+                    };
+
+                    result.clear_color = (r, g, b, a);
+                },
+
+                "script_timeout_in_seconds" => {
+                    let timeout: f32 = value.parse()
+                        .map_err(|err| parse_error(format!("invalid script_timeout_in_seconds '{value}': {err}")))?;
+
+                    if timeout.is_nan() || timeout <= 0.0 {
+                        return Err(parse_error(format!("script_timeout_in_seconds should be positive but got {timeout}")));
+                    }
+
+                    result.script_timeout_in_seconds = timeout;
+                },
+
+                "present_mode" => result.present_mode = value.parse()
+                    .map_err(|err| parse_error(format!("invalid present_mode '{value}': {err}")))?,
+
+                _ => return Err(RuntimeCfgError::UnknownKey { line, key: key.to_owned() }),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Gives [script timeout][RuntimeCfg::script_timeout_in_seconds] as [`Duration`].
+    pub fn script_timeout(&self) -> Duration {
+        Duration::from_secs_f32(self.script_timeout_in_seconds.max(0.0))
+    }
+
+    /// Reads config from file.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, RuntimeCfgError> {
+        let src = tokio::fs::read_to_string(path).await?;
+        Self::parse(&src)
+    }
+}
+
+impl Default for RuntimeCfg {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+#[derive(Debug, Error)]
+pub enum RuntimeCfgError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("failed to parse line {line}: {msg}")]
+    Parse {
+        line: usize,
+        msg: String,
+    },
+
+    #[error("unknown key '{key}' on line {line}")]
+    UnknownKey {
+        line: usize,
+        key: String,
+    },
+}
+
+/// Gives current runtime config.
+pub fn get() -> RuntimeCfg {
+    *RUNTIME_CFG.read()
+}
+
+/// Replaces current runtime config.
+pub fn set(new_cfg: RuntimeCfg) {
+    *RUNTIME_CFG.write() = new_cfg;
+}
+
+/// Loads runtime config from file. If file is missing then [default][RuntimeCfg::DEFAULT]
+/// config is kept, other errors are logged.
+pub async fn load_or_default(path: impl AsRef<Path>) {
+    let path = path.as_ref();
+
+    match RuntimeCfg::load(path).await {
+        Ok(new_cfg) => set(new_cfg),
+
+        Err(RuntimeCfgError::Io(err)) if err.kind() == io::ErrorKind::NotFound =>
+            logger::log!(Info, from = "runtime-cfg", "no config in {path:?}, using defaults"),
+
+        Err(err) =>
+            logger::log!(Error, from = "runtime-cfg", "failed to load config from {path:?}: {err}"),
+    }
+}
+
+pub fn spawn_control_window(ui: &imgui::Ui) {
+    use crate::app::utils::graphics::ui::imgui_constructor::make_window;
+
+    make_window(ui, "Runtime config").build(|| {
+        let mut runtime_cfg = get();
+        let mut is_changed = false;
+
+        is_changed |= ui.input_scalar("Max tasks", &mut runtime_cfg.max_tasks).build();
+        is_changed |= ui.input_float("Lod threashold", &mut runtime_cfg.lod_threashold).build();
+        is_changed |= ui.input_float("Script timeout (s)", &mut runtime_cfg.script_timeout_in_seconds).build();
+
+        let (r, g, b, a) = runtime_cfg.clear_color;
+        let mut clear_color = [r, g, b, a];
+        if ui.color_edit4("Clear color", &mut clear_color) {
+            let [r, g, b, a] = clear_color;
+            runtime_cfg.clear_color = (r, g, b, a);
+            is_changed = true;
+        }
+
+        if let Some(_combo) = ui.begin_combo("Present mode", runtime_cfg.present_mode.to_string()) {
+            for mode in PresentMode::ALL {
+                if ui.selectable_config(mode.to_string())
+                    .selected(mode == runtime_cfg.present_mode)
+                    .build()
+                {
+                    runtime_cfg.present_mode = mode;
+                    is_changed = true;
+                }
+            }
+        }
+
+        if is_changed {
+            set(runtime_cfg);
+        }
+
+        if ui.button("Reset") {
+            set(RuntimeCfg::DEFAULT);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_overrides_only_given_keys() {
+        let runtime_cfg = RuntimeCfg::parse("# comment\n\nmax_tasks = 16\n")
+            .expect("config should be valid");
+
+        assert_eq!(runtime_cfg, RuntimeCfg { max_tasks: 16, ..RuntimeCfg::DEFAULT });
+    }
+
+    #[test]
+    fn parse_clear_color() {
+        let runtime_cfg = RuntimeCfg::parse("clear_color = 0.1, 0.2, 0.3, 1.0")
+            .expect("config should be valid");
+
+        assert_eq!(runtime_cfg.clear_color, (0.1, 0.2, 0.3, 1.0));
+    }
+
+    #[test]
+    fn parse_rejects_non_positive_script_timeout() {
+        assert!(matches!(
+            RuntimeCfg::parse("script_timeout_in_seconds = 0"),
+            Err(RuntimeCfgError::Parse { line: 1, .. }),
+        ));
+    }
+
+    #[test]
+    fn parse_present_mode() {
+        let runtime_cfg = RuntimeCfg::parse("present_mode = mailbox")
+            .expect("config should be valid");
+
+        assert_eq!(runtime_cfg.present_mode, PresentMode::Mailbox);
+        assert!(RuntimeCfg::parse("present_mode = fast").is_err());
+    }
+
+    #[test]
+    fn parse_unknown_key() {
+        assert!(matches!(
+            RuntimeCfg::parse("max_taks = 16"),
+            Err(RuntimeCfgError::UnknownKey { line: 1, .. }),
+        ));
+    }
+}
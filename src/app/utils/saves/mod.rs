@@ -5,15 +5,17 @@ pub mod stack_heap;
 use {
     crate::{
         prelude::*,
-        cfg::save::META_FILE_NAME,
+        cfg::save::{META_FILE_NAME, STACK_FILE_EXTENSION, HEAP_FILE_EXTENSION},
     },
     std::{
         marker::PhantomData, 
         future::Future,
+        path::Path,
+        ops::Range,
     },
     tokio::{
         io::{self, SeekFrom, AsyncReadExt, AsyncWriteExt},
-        fs::{File, OpenOptions},
+        fs::{self, File, OpenOptions},
     },
     stack_heap::{StackHeap, StackHeapError},
 };
@@ -38,6 +40,18 @@ pub enum SaveError {
 
     #[error("Trying to write same key of some data to another place. Old enumerator value: {0}.")]
     DataOverride(Enumerator),
+
+    #[error("there is no data enumerated by {0}")]
+    NoData(Enumerator),
+}
+
+impl From<SaveError> for io::Error {
+    fn from(value: SaveError) -> Self {
+        match value {
+            SaveError::Io(err) | SaveError::StackHeap(StackHeapError::Io(err)) => err,
+            other => io::Error::new(io::ErrorKind::InvalidData, other),
+        }
+    }
 }
 
 pub type SaveResult<T> = Result<T, SaveError>;
@@ -94,7 +108,7 @@ impl<E: Copy + Into<Enumerator>> SaveBuilder<E> {
             offsets_save.read_exact(&mut buffer).await?;
 
             Size::from_bytes(&buffer)
-                .expect("failed to make offsets out of bytes")
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
         };
 
         /* Read all offsets to HashMap */
@@ -106,7 +120,7 @@ impl<E: Copy + Into<Enumerator>> SaveBuilder<E> {
                 offsets_save.read_exact(&mut buffer).await?;
                 
                 Enumerator::from_bytes(&buffer)
-                    .expect("failed to make enumerator from bytes")
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
             };
 
             let offset = {
@@ -114,7 +128,7 @@ impl<E: Copy + Into<Enumerator>> SaveBuilder<E> {
                 offsets_save.read_exact(&mut buffer).await?;
 
                 Offset::from_bytes(&buffer)
-                    .expect("failed to make offset from bytes")
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
             };
 
             self.offsets.insert(enumerator, offset);
@@ -142,6 +156,61 @@ impl<E: Copy + Into<Enumerator>> Save<E> {
         format!("{path}/{META_FILE_NAME}")
     }
 
+    /// Checks if save with `name` exists in `path` directory.
+    pub fn exists(name: &str, path: &str) -> bool {
+        let files_path = Path::new(path).join(name);
+
+        files_path.with_extension(STACK_FILE_EXTENSION).exists() &&
+        files_path.with_extension(HEAP_FILE_EXTENSION).exists() &&
+        Path::new(&Self::get_meta_path(path)).exists()
+    }
+
+    /// Gives directory of backup with index `idx`. Index `0` is the most recent backup.
+    pub fn backup_path(name: &str, path: &str, idx: usize) -> String {
+        match idx {
+            0 => format!("{path}/{name}.bak"),
+            idx => format!("{path}/{name}.bak.{idx}"),
+        }
+    }
+
+    /// Moves existing save to backup directory, shifting older backups.
+    /// Keeps at most `n_backups` backups. Does nothing if there's no save.
+    pub async fn backup(name: &str, path: &str, n_backups: usize) -> io::Result<()> {
+        if n_backups == 0 || !Self::exists(name, path) { return Ok(()) }
+
+        /* Drop the oldest backup */
+        let oldest = Self::backup_path(name, path, n_backups - 1);
+        if Path::new(&oldest).exists() {
+            fs::remove_dir_all(&oldest).await?;
+        }
+
+        /* Shift other backups */
+        for idx in (0..n_backups - 1).rev() {
+            let from = Self::backup_path(name, path, idx);
+            if Path::new(&from).exists() {
+                fs::rename(&from, Self::backup_path(name, path, idx + 1)).await?;
+            }
+        }
+
+        /* Move save files to the newest backup */
+        let newest = Self::backup_path(name, path, 0);
+        fs::create_dir(&newest).await?;
+
+        let files_path = Path::new(path).join(name);
+        let backup_files_path = Path::new(&newest).join(name);
+
+        for extension in [STACK_FILE_EXTENSION, HEAP_FILE_EXTENSION] {
+            fs::rename(
+                files_path.with_extension(extension),
+                backup_files_path.with_extension(extension),
+            ).await?;
+        }
+
+        fs::rename(Self::get_meta_path(path), Self::get_meta_path(&newest)).await?;
+
+        Ok(())
+    }
+
     /// Writes enum-named value to stack file.
     pub async fn write<T: AsBytes>(mut self, value: &T, enumerator: E) -> Self {
         /* Write value to file stack */
@@ -171,11 +240,17 @@ impl<E: Copy + Into<Enumerator>> Save<E> {
 
     /// Reads enum-named value from stack file.
     pub async fn read<T: FromBytes + StaticSize>(&mut self, enumerator: E) -> T {
-        self.file.read_from_stack(self.load_offset(enumerator))
+        self.try_read(enumerator)
             .await
             .expect("failed to read from stack")
     }
 
+    /// Same as [`Save::read`] but gives [`Err`] on missing or malformed data.
+    pub async fn try_read<T: FromBytes + StaticSize>(&mut self, enumerator: E) -> SaveResult<T> {
+        let offset = self.try_load_offset(enumerator)?;
+        Ok(self.file.read_from_stack(offset).await?)
+    }
+
     /// Writes enum-named array of values to stack file.
     #[allow(dead_code)]
     pub async fn array<'t, T, F>(mut self, length: usize, enumerator: E, mut elem: F) -> Self
@@ -323,6 +398,11 @@ impl<E: Copy + Into<Enumerator>> Save<E> {
         )
     }
 
+    /// Checks that save has data with `enumerator` key.
+    pub fn contains(&self, enumerator: E) -> bool {
+        self.offsets.contains_key(&enumerator.into())
+    }
+
     /// Allocates data on heap of file with pointer on stack and writes all given bytes.
     #[allow(dead_code)]
     pub async fn pointer(mut self, bytes: Vec<u8>, enumerator: E) -> Self {
@@ -365,18 +445,21 @@ impl<E: Copy + Into<Enumerator>> Save<E> {
     /// Reads data from heap by stack pointer to heap on.
     #[allow(dead_code)]
     pub async fn read_from_pointer<T, F: FnOnce(&[u8]) -> T>(&mut self, enumerator: E, item: F) -> T {
-        /* Load offsets */
-        let stack_offset = self.load_offset(enumerator);
-        let heap_offset: Offset = self.file.read_from_stack(stack_offset)
+        self.try_read_from_pointer(enumerator, item)
             .await
-            .expect("failed to read from stack");
+            .expect("failed to read from pointer")
+    }
+
+    /// Same as [`Save::read_from_pointer`] but gives [`Err`] on missing or malformed data.
+    pub async fn try_read_from_pointer<T, F: FnOnce(&[u8]) -> T>(&mut self, enumerator: E, item: F) -> SaveResult<T> {
+        /* Load offsets */
+        let stack_offset = self.try_load_offset(enumerator)?;
+        let heap_offset: Offset = self.file.read_from_stack(stack_offset).await?;
 
         /* Read data */
-        let bytes = self.file.read_from_heap(heap_offset)
-            .await
-            .expect("failed to read from heap");
+        let bytes = self.file.read_from_heap(heap_offset).await?;
 
-        item(&bytes)
+        Ok(item(&bytes))
     }
 
     /// Allocates an array of pinters on stack and array of data on heap.
@@ -408,6 +491,47 @@ impl<E: Copy + Into<Enumerator>> Save<E> {
         self
     }
 
+    /// Same as [`pointer_array`][Save::pointer_array] but elements are made by `batch`es
+    /// of up to `batch_size` elements. Each batch is written right after it is made,
+    /// so only one batch is kept in memory.
+    pub async fn pointer_array_batched<F, Fut>(
+        mut self, len: usize, enumerator: E, batch_size: usize, mut batch: F,
+    ) -> Self
+    where
+        F: FnMut(Range<usize>) -> Fut,
+        Fut: Future<Output = Vec<Vec<u8>>>,
+    {
+        /* Push size to stack and store its offset */
+        let stack_offset = self.file.push(&(len as Size).as_bytes())
+            .await
+            .expect("failed to push");
+
+        self.store_offset(enumerator, stack_offset)
+            .expect("failed to store offset");
+
+        /* Write elements to heap batch by batch */
+        let batch_size = batch_size.max(1);
+        for start in (0..len).step_by(batch_size) {
+            let range = start..usize::min(start + batch_size, len);
+            let n_elems = range.len();
+
+            let elems = batch(range).await;
+            assert_eq!(elems.len(), n_elems, "batch should have {n_elems} elements");
+
+            for data in elems {
+                let alloc = self.file.alloc(data.len() as Size)
+                    .await
+                    .expect("alloc failed");
+
+                self.file.write_to_heap(alloc, &data)
+                    .await
+                    .expect("failed to write to a heap");
+            }
+        }
+
+        self
+    }
+
     /// Assigns new array of pointers to existed one.
     #[allow(dead_code)]
     pub async fn assign_pointer_array<F>(&mut self, enumerator: E, elem: F)
@@ -463,40 +587,63 @@ impl<E: Copy + Into<Enumerator>> Save<E> {
     }
 
     /// Reads an array of data from heap.
-    pub async fn read_pointer_array<T, F, Fut>(&mut self, enumerator: E, mut elem: F) -> Vec<T>
+    pub async fn read_pointer_array<T, F, Fut>(&mut self, enumerator: E, elem: F) -> Vec<T>
+    where
+        F: FnMut(usize, Vec<u8>) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        self.read_pointer_array_until(enumerator, || false, elem)
+            .await
+            .expect("reading should not be interrupted")
+    }
+
+    /// Reads an array of data from heap. Checks `is_interrupted` before reading each element
+    /// and stops reading with [`None`] if it gives `true`.
+    pub async fn read_pointer_array_until<T, F, Fut>(
+        &mut self, enumerator: E, is_interrupted: impl FnMut() -> bool, elem: F,
+    ) -> Option<Vec<T>>
+    where
+        F: FnMut(usize, Vec<u8>) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        self.try_read_pointer_array_until(enumerator, is_interrupted, elem)
+            .await
+            .expect("failed to read pointer array")
+    }
+
+    /// Same as [`Save::read_pointer_array_until`] but gives [`Err`] on missing or malformed data.
+    pub async fn try_read_pointer_array_until<T, F, Fut>(
+        &mut self, enumerator: E, mut is_interrupted: impl FnMut() -> bool, mut elem: F,
+    ) -> SaveResult<Option<Vec<T>>>
     where
         F: FnMut(usize, Vec<u8>) -> Fut,
         Fut: Future<Output = T>,
     {
         /* Load stack data offset */
-        let length_offset = self.load_offset(enumerator);
+        let length_offset = self.try_load_offset(enumerator)?;
 
         /* Read array length */ 
-        let length: Size = self.file.read_from_stack(length_offset)
-            .await
-            .expect("failed to read from stack");
+        let length: Size = self.file.read_from_stack(length_offset).await?;
 
-        /* Resulting vector */
-        let mut result = Vec::with_capacity(length as usize);
+        /* Resulting vector. Length is not trusted for preallocation, it can be corrupted */
+        let mut result = Vec::new();
 
         /* Read all elements */
         let offset_size = Size::static_size() as Size;
         for i in 1..=length {
+            if is_interrupted() { return Ok(None) }
+
             /* Read offset on heap */
-            let heap_offset: Offset = self.file.read_from_stack(length_offset + i * offset_size)
-                .await
-                .expect("failed to read from stack");
+            let heap_offset: Offset = self.file.read_from_stack(length_offset + i * offset_size).await?;
 
             /* Read data bytes */
-            let bytes = self.file.read_from_heap(heap_offset)
-                .await
-                .expect("failed to read from heap");
+            let bytes = self.file.read_from_heap(heap_offset).await?;
 
             /* Reinterpret them and push to result */
             result.push(elem(i as usize - 1, bytes).await);
         }
 
-        result
+        Ok(Some(result))
     }
 
     /// Reads a pointer array element at index `idx`.
@@ -542,10 +689,16 @@ impl<E: Copy + Into<Enumerator>> Save<E> {
 
     /// Loads offset by enumerator.
     fn load_offset(&self, enumerator: E) -> Offset {
-        *self.offsets
+        self.try_load_offset(enumerator)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Loads offset by enumerator. Gives [`Err`] if there is no such data.
+    fn try_load_offset(&self, enumerator: E) -> SaveResult<Offset> {
+        self.offsets
             .get(&enumerator.into())
-            .unwrap_or_else(|| panic!("There is no data enumerated by {}",
-                enumerator.into()))
+            .copied()
+            .ok_or_else(|| SaveError::NoData(enumerator.into()))
     }
 
     async fn offsets_async_write(file: &mut File, bytes: &[u8], offset: Offset) -> io::Result<()> {
@@ -599,3 +752,126 @@ impl<E: Copy + Into<Enumerator>> Save<E> {
         }
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gives unique save directory in temporary directory. It is removed if it exists.
+    fn temp_save_path(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("terramine_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        dir.to_str().expect("temp dir should be valid UTF-8").to_owned()
+    }
+
+    #[test]
+    fn exists_after_save() {
+        RUNTIME.block_on(async {
+            let name = "exists_after_save";
+            let path = temp_save_path(name);
+
+            let result = Save::<Enumerator>::builder(name)
+                .create(&path).await
+                .expect("failed to create save")
+                .write(&42_u32, 0).await
+                .save().await;
+
+            let exists = Save::<Enumerator>::exists(name, &path);
+            let _ = fs::remove_dir_all(&path).await;
+
+            result.expect("failed to save");
+            assert!(exists);
+        });
+    }
+
+    #[test]
+    fn backup_keeps_previous_save() {
+        RUNTIME.block_on(async {
+            let name = "backup_keeps_previous_save";
+            let path = temp_save_path(name);
+
+            let values = async {
+                for value in [1_u32, 2] {
+                    Save::<Enumerator>::backup(name, &path, 2).await?;
+
+                    Save::<Enumerator>::builder(name)
+                        .create(&path).await?
+                        .write(&value, 0).await
+                        .save().await?;
+                }
+
+                let backup_path = Save::<Enumerator>::backup_path(name, &path, 0);
+                let mut backup = Save::<Enumerator>::builder(name).open(&backup_path).await?;
+                let mut save = Save::<Enumerator>::builder(name).open(&path).await?;
+
+                let is_oldest_dropped = !Path::new(&Save::<Enumerator>::backup_path(name, &path, 1)).exists();
+
+                io::Result::Ok((backup.read::<u32>(0).await, save.read::<u32>(0).await, is_oldest_dropped))
+            }.await;
+
+            let _ = fs::remove_dir_all(&path).await;
+
+            assert_eq!(values.expect("failed to backup and save"), (1, 2, true));
+        });
+    }
+
+    #[test]
+    fn not_exists_for_fresh_name() {
+        let name = "not_exists_for_fresh_name";
+        assert!(!Save::<Enumerator>::exists(name, &temp_save_path(name)));
+    }
+
+    #[test]
+    fn batched_pointer_array_reads_same_as_plain() {
+        RUNTIME.block_on(async {
+            let elems: Vec<Vec<u8>> = (0..10_u8)
+                .map(|i| vec![i; i as usize + 1])
+                .collect();
+
+            let plain = "batched_pointer_array_reads_same_as_plain_0";
+            let batched = "batched_pointer_array_reads_same_as_plain_1";
+            let (plain_path, batched_path) = (temp_save_path(plain), temp_save_path(batched));
+
+            Save::<Enumerator>::builder(plain)
+                .create(&plain_path).await
+                .expect("failed to create save")
+                .pointer_array(elems.len(), 0, |i| {
+                    let bytes = elems[i].clone();
+                    async move { bytes }
+                }).await
+                .save().await
+                .expect("failed to save");
+
+            Save::<Enumerator>::builder(batched)
+                .create(&batched_path).await
+                .expect("failed to create save")
+                .pointer_array_batched(elems.len(), 0, 3, |range| {
+                    let batch = elems[range].to_vec();
+                    async move { batch }
+                }).await
+                .save().await
+                .expect("failed to save");
+
+            let mut reads = vec![];
+
+            for (name, path) in [(plain, &plain_path), (batched, &batched_path)] {
+                let read = match Save::<Enumerator>::builder(name).open(path).await {
+                    Ok(mut save) => Ok(save.read_pointer_array(0, |_, bytes| async move { bytes }).await),
+                    Err(err) => Err(err),
+                };
+
+                reads.push(read);
+            }
+
+            for path in [plain_path, batched_path] {
+                let _ = fs::remove_dir_all(path).await;
+            }
+
+            for read in reads {
+                assert_eq!(read.expect("failed to open save"), elems);
+            }
+        });
+    }
+}
@@ -130,7 +130,8 @@ impl StackHeap {
         Self::seek_read(&mut self.stack_file, &mut buffer, offset).await?;
 
         /* Reinterpret */
-        Ok(T::from_bytes(&buffer).expect("failed to make T from bytes"))
+        T::from_bytes(&buffer)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
     }
 
     /// Reads value from heap by `heap_offset`.
@@ -141,9 +142,18 @@ impl StackHeap {
             let mut buffer = vec![0; Size::static_size()];
             Self::seek_read(&mut self.heap_file, &mut buffer, heap_offset).await?;
             Size::from_bytes(&buffer)
-                .expect("failed to make Size from bytes")
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
         };
 
+        /* Corrupted size should not make huge allocation */
+        let heap_len = self.heap_file.metadata().await?.len();
+        if size > heap_len.saturating_sub(heap_offset + Size::static_size() as Size) {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("heap data of size {size} at {heap_offset} is out of heap of size {heap_len}"),
+            ));
+        }
+
         /* Read data */
         let mut buffer = vec![0; size as usize];
         Self::seek_read(&mut self.heap_file, &mut buffer, heap_offset + Size::static_size() as Size).await?;
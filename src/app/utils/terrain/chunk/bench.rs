@@ -0,0 +1,155 @@
+//! Meshing and voxel lookup throughput benchmarks. Run with `cargo bench meshing` or `cargo bench voxel_lookup`.
+//!
+//! Benchmarks report vertices or lookups per second as `MB/s`: one "byte" is one vertex or lookup.
+
+extern crate test;
+
+use {
+    crate::{
+        prelude::*,
+        terrain::voxel::voxel_data::{Id, data::*},
+    },
+    super::{Chunk, Lod, chunk_array::{ChunkArray, ChunkAdj}},
+    test::{Bencher, black_box},
+};
+
+/// Seed of benchmark terrain.
+const SEED: u64 = 0x7e77_a1ae;
+
+/// Gives same pseudo-random number for same `pos`.
+fn hash(pos: Int3) -> u64 {
+    let mut value = SEED
+        ^ (pos.x as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        ^ (pos.y as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f)
+        ^ (pos.z as u64).wrapping_mul(0x1656_67b1_9e37_79f9);
+
+    // SplitMix64 finalizer.
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+/// Hilly terrain with grass, dirt, stone and small caves.
+fn voxel_id(pos: Int3) -> Id {
+    let half_size = Chunk::SIZE as f32 / 2.0;
+    let height = (half_size
+        + 8.0 * f32::sin(pos.x as f32 * 0.11) * f32::cos(pos.z as f32 * 0.07)
+    ) as i32;
+
+    if pos.y > height || hash(pos) % 16 == 0 {
+        AIR_VOXEL_DATA.id
+    } else if pos.y == height {
+        GRASS_VOXEL_DATA.id
+    } else if pos.y > height - 4 {
+        DIRT_VOXEL_DATA.id
+    } else {
+        STONE_VOXEL_DATA.id
+    }
+}
+
+/// Builds `3x3x3` chunk array and gives its center chunk with adjacent ones.
+fn make_chunk_with_adj() -> (Arc<Chunk>, ChunkAdj) {
+    let sizes = USize3::all(3);
+
+    let chunks = ChunkArray::pos_iter(sizes)
+        .map(|chunk_pos| Chunk::from_voxels(
+            Chunk::global_pos_iter(chunk_pos)
+                .map(|pos| Atomic::new(voxel_id(pos)))
+                .collect(),
+            chunk_pos,
+        ).expect("voxel count should be valid"))
+        .map(Arc::new)
+        .collect();
+
+    let arr = ChunkArray::from_chunks(sizes, chunks)
+        .expect("sizes should be valid");
+
+    let chunk = arr.get_chunk_by_pos(Int3::ZERO)
+        .expect("center chunk should exist");
+
+    (chunk, arr.get_adj_chunks(Int3::ZERO))
+}
+
+/// Vertex counts of benchmark chunk. Changing them means meshing output
+/// has changed, so benchmark results are not comparable with older ones.
+const N_DETAILED_VERTICES: usize = 323_940;
+const N_LOW_VERTICES: [(Lod, usize); 2] = [(1, 17_646), (2, 7_104)];
+
+#[test]
+fn meshing_vertex_count_is_stable() {
+    let (chunk, adj) = make_chunk_with_adj();
+
+    assert_eq!(chunk.make_vertices_detailed(adj.clone()).len(), N_DETAILED_VERTICES);
+
+    for (lod, n_vertices) in N_LOW_VERTICES {
+        assert_eq!(chunk.make_vertices_low(adj.clone(), lod).len(), n_vertices, "LOD {lod}");
+    }
+}
+
+#[bench]
+fn meshing_detailed(b: &mut Bencher) {
+    let (chunk, adj) = make_chunk_with_adj();
+    let n_vertices = chunk.make_vertices_detailed(adj.clone()).len();
+
+    b.bytes = n_vertices as u64;
+    b.iter(|| {
+        let vertices = chunk.make_vertices_detailed(black_box(adj.clone()));
+        assert_eq!(vertices.len(), n_vertices);
+        vertices
+    });
+}
+
+fn bench_low(b: &mut Bencher, lod: u32) {
+    let (chunk, adj) = make_chunk_with_adj();
+    let n_vertices = chunk.make_vertices_low(adj.clone(), lod).len();
+
+    b.bytes = n_vertices as u64;
+    b.iter(|| {
+        let vertices = chunk.make_vertices_low(black_box(adj.clone()), lod);
+        assert_eq!(vertices.len(), n_vertices);
+        vertices
+    });
+}
+
+#[bench]
+fn meshing_low_lod1(b: &mut Bencher) {
+    bench_low(b, 1)
+}
+
+#[bench]
+fn meshing_low_lod2(b: &mut Bencher) {
+    bench_low(b, 2)
+}
+
+/// Scattered voxel positions over `3x3x3` chunk array.
+fn scattered_positions() -> Vec<Int3> {
+    let extent = 3 * Chunk::SIZE as i32;
+    let coord = |seed: Int3, axis: i32| (hash(seed + Int3::all(axis)) % extent as u64) as i32;
+
+    (0..4096)
+        .map(|i| veci!(i, 0, 0))
+        .map(|seed| veci!(coord(seed, 0), coord(seed, 1), coord(seed, 2)) - Int3::all(extent / 2))
+        .collect()
+}
+
+#[bench]
+fn voxel_lookup(b: &mut Bencher) {
+    let (chunk, _) = make_chunk_with_adj();
+    let chunks = ChunkArray::pos_iter(USize3::all(3))
+        .map(|pos| match pos == Int3::ZERO {
+            true => Arc::clone(&chunk),
+            false => Arc::new(Chunk::new_same_filled(pos, STONE_VOXEL_DATA.id)),
+        })
+        .collect();
+
+    let arr = ChunkArray::from_chunks(USize3::all(3), chunks)
+        .expect("sizes should be valid");
+
+    let positions = scattered_positions();
+
+    b.bytes = positions.len() as u64;
+    b.iter(|| positions.iter()
+        .filter_map(|&pos| arr.get_voxel(black_box(pos)))
+        .count()
+    );
+}
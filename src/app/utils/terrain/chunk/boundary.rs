@@ -0,0 +1,104 @@
+//! Owned copy of adjacent chunks voxels that meshing task can use
+//! without sharing adjacent chunks with the task.
+
+use {
+    crate::{
+        prelude::*,
+        terrain::voxel::voxel_data::{Id, data::*},
+    },
+    super::{
+        Chunk,
+        chunk_array::ChunkAdj,
+        iterator::{Sides, Direction},
+    },
+};
+
+/// Voxel ids of adjacent [chunks][Chunk] planes that touch center [chunk][Chunk].
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct AdjBoundary {
+    /// Planes in [`Sides`] order. [`None`] means there is no generated adjacent chunk.
+    pub planes: Sides<Option<Vec<Id>>>,
+
+    /// Is all adjacent chunks are present and filled with non-air voxels.
+    pub is_all_filled: bool,
+}
+
+impl AdjBoundary {
+    /// Number of voxels in one plane.
+    pub const PLANE_AREA: usize = Chunk::SIZE * Chunk::SIZE;
+
+    /// Copies boundary planes from [adjacent chunks][ChunkAdj] of chunk on `chunk_pos`.
+    pub fn new(chunk_pos: Int3, chunk_adj: &ChunkAdj) -> Self {
+        let planes = Direction::ALL.into_iter()
+            .map(|dir| {
+                let adj = chunk_adj.get(dir)?;
+
+                if !adj.is_generated() {
+                    return None;
+                }
+
+                if let Some(id) = adj.fill_id() {
+                    return Some(vec![id; Self::PLANE_AREA]);
+                }
+
+                let plane = (0..Self::PLANE_AREA)
+                    .map(|plane_idx| {
+                        let local_pos = Self::adj_local_pos(dir, plane_idx);
+
+                        match adj.get_voxel_local(local_pos) {
+                            Some(voxel) => voxel.data.id,
+                            None => {
+                                logger::log!(
+                                    Error, from = "chunk",
+                                    "failed to copy voxel in {local_pos} of chunk adjacent to {chunk_pos}",
+                                );
+                                AIR_VOXEL_DATA.id
+                            },
+                        }
+                    })
+                    .collect();
+
+                Some(plane)
+            })
+            .collect();
+
+        Self { planes, is_all_filled: Chunk::is_adj_filled(chunk_adj) }
+    }
+
+    /// Gives id of voxel in `global_pos` that lies in adjacent [chunk][Chunk] in `dir` direction
+    /// from chunk on `chunk_pos`. Returns [`None`] if there is no such adjacent [chunk][Chunk].
+    pub fn get_id(&self, chunk_pos: Int3, dir: Direction, global_pos: Int3) -> Option<Id> {
+        let plane = self.planes.inner[dir.idx()].as_ref()?;
+
+        let local_pos = Chunk::global_to_local_pos(chunk_pos + dir.offset(), global_pos);
+        let plane_idx = Self::plane_idx(dir, local_pos);
+
+        plane.get(plane_idx).copied()
+    }
+
+    /// Gives local position in adjacent [chunk][Chunk] in `dir` direction by index in plane.
+    fn adj_local_pos(dir: Direction, plane_idx: usize) -> Int3 {
+        let (u, v) = ((plane_idx / Chunk::SIZE) as i32, (plane_idx % Chunk::SIZE) as i32);
+        let far = Chunk::SIZE as i32 - 1;
+
+        match dir {
+            Direction::PosX => veci!(0,   u, v),
+            Direction::NegX => veci!(far, u, v),
+            Direction::PosY => veci!(u, 0,   v),
+            Direction::NegY => veci!(u, far, v),
+            Direction::PosZ => veci!(u, v, 0),
+            Direction::NegZ => veci!(u, v, far),
+        }
+    }
+
+    /// Gives index in plane by local position in adjacent [chunk][Chunk] in `dir` direction.
+    fn plane_idx(dir: Direction, local_pos: Int3) -> usize {
+        let (u, v) = match dir {
+            Direction::PosX | Direction::NegX => (local_pos.y, local_pos.z),
+            Direction::PosY | Direction::NegY => (local_pos.x, local_pos.z),
+            Direction::PosZ | Direction::NegZ => (local_pos.x, local_pos.y),
+        };
+
+        u as usize * Chunk::SIZE + v as usize
+    }
+}
@@ -0,0 +1,68 @@
+//! Multi-hit voxel breaking.
+
+use crate::prelude::*;
+
+/// Accumulated breaking time of aimed voxels. Voxel breaks when its time
+/// reaches [hardness][crate::terrain::voxel::voxel_data::VoxelData::hardness].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BreakingProgress {
+    pub progress: HashMap<Int3, f32>,
+}
+
+impl BreakingProgress {
+    /// Hits voxel on `pos` for `dt` seconds. Progress of other voxels is reset as aim has moved.
+    /// Returns `true` if voxel is broken.
+    pub fn hit(&mut self, pos: Int3, hardness: f32, dt: f32) -> bool {
+        self.progress.retain(|&other, _| other == pos);
+
+        let progress = self.progress.entry(pos).or_default();
+        *progress += dt;
+
+        let is_broken = *progress >= hardness;
+        if is_broken {
+            self.progress.remove(&pos);
+        }
+
+        is_broken
+    }
+
+    /// Resets progress of all voxels.
+    pub fn reset(&mut self) {
+        self.progress.clear();
+    }
+
+    /// Gives breaking voxel position and its progress from 0 to 1.
+    pub fn current(&self, hardness_of: impl Fn(Int3) -> Option<f32>) -> Option<(Int3, f32)> {
+        let (&pos, &progress) = self.progress.iter().next()?;
+        let hardness = hardness_of(pos)?;
+
+        Some((pos, (progress / hardness).clamp(0.0, 1.0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_reaches_hardness() {
+        let mut breaking = BreakingProgress::default();
+        let pos = veci!(1, 2, 3);
+
+        for _ in 0..3 {
+            assert!(!breaking.hit(pos, 1.0, 0.25));
+        }
+
+        assert_eq!(breaking.current(|_| Some(1.0)), Some((pos, 0.75)));
+        assert!(breaking.hit(pos, 1.0, 0.25));
+        assert!(breaking.progress.is_empty());
+
+        // Moving aim resets progress.
+        assert!(!breaking.hit(pos, 1.0, 0.5));
+        assert!(!breaking.hit(veci!(1, 2, 4), 1.0, 0.5));
+        assert!(!breaking.hit(pos, 1.0, 0.5));
+        assert!(breaking.hit(pos, 1.0, 0.5));
+
+        assert!(!breaking.hit(pos, f32::INFINITY, 1000.0));
+    }
+}
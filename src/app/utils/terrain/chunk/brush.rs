@@ -0,0 +1,155 @@
+//! Brush for painting terrain with held left mouse button.
+
+use crate::{prelude::*, terrain::voxel::voxel_data::{Id, data::STONE_VOXEL_DATA}};
+
+/// Footprint of [`Brush`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Display)]
+pub enum BrushShape {
+    #[default]
+    Sphere,
+    Cube,
+
+    /// Horizontal circle one voxel thick.
+    Disc,
+}
+
+impl BrushShape {
+    pub const ALL: [Self; 3] = [Self::Sphere, Self::Cube, Self::Disc];
+}
+
+impl AsBytes for BrushShape {
+    fn as_bytes(&self) -> Vec<u8> {
+        vec![*self as u8]
+    }
+}
+
+impl FromBytes for BrushShape {
+    fn from_bytes(source: &[u8]) -> Result<Self, ReinterpretError> {
+        let mut reader = ByteReader::new(source);
+        let byte: u8 = reader.read()?;
+
+        Self::ALL.get(byte as usize).copied().ok_or_else(|| ReinterpretError::Conversion(
+            format!("conversion of too large byte ({byte}) to BrushShape")
+        ))
+    }
+}
+
+impl StaticSize for BrushShape {
+    fn static_size() -> usize {
+        u8::static_size()
+    }
+}
+
+/// Sets voxels under the aim point each [interaction cooldown][super::chunk_array::ChunkArray::interaction_cooldown] tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Brush {
+    pub is_enabled: bool,
+    pub shape: BrushShape,
+
+    /// Distance in voxels from center to the edge of the brush.
+    pub radius: i32,
+
+    /// Painted voxel. Air clears voxels under the brush.
+    pub id: Id,
+}
+
+impl Brush {
+    pub const MAX_RADIUS: i32 = 16;
+
+    pub const DEFAULT: Self = Self {
+        is_enabled: false,
+        shape: BrushShape::Sphere,
+        radius: 2,
+        id: STONE_VOXEL_DATA.id,
+    };
+
+    /// Gives positions of voxels covered by brush placed at `center`.
+    pub fn voxels(&self, center: Int3) -> impl Iterator<Item = Int3> + '_ {
+        let offset = Int3::all(self.radius);
+        let radius_squared = (self.radius as i64).pow(2);
+
+        SpaceIter::new(center - offset ..= center + offset)
+            .filter(move |&pos| {
+                let diff = pos - center;
+
+                match self.shape {
+                    BrushShape::Sphere => diff.len_squared() <= radius_squared,
+                    BrushShape::Cube => true,
+                    BrushShape::Disc => diff.y == 0 && diff.len_squared() <= radius_squared,
+                }
+            })
+    }
+
+    /// Builds brush controls inside current window.
+    pub fn build_controls(&mut self, ui: &imgui::Ui) {
+        if let Some(_combo) = ui.begin_combo("Brush shape", self.shape.to_string()) {
+            for shape in BrushShape::ALL {
+                if ui.selectable_config(shape.to_string())
+                    .selected(shape == self.shape)
+                    .build()
+                {
+                    self.shape = shape;
+                }
+            }
+        }
+
+        ui.slider("Brush radius", 0, Self::MAX_RADIUS, &mut self.radius);
+
+        let mut id = self.id as i32;
+        if ui.input_int("Brush voxel id", &mut id).build() {
+            match Id::try_from(id).ok().filter(|&id| crate::terrain::voxel::is_id_valid(id)) {
+                Some(id) => self.id = id,
+                None => logger::log!(Error, from = "brush", "invalid brush voxel id {id}"),
+            }
+        }
+    }
+}
+
+impl Default for Brush {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sphere_brush_covers_ball() {
+        let center = veci!(10, -3, 7);
+        let brush = Brush { shape: BrushShape::Sphere, radius: 2, ..Brush::DEFAULT };
+
+        let voxels: HashSet<Int3> = brush.voxels(center).collect();
+
+        // 1 center, 6 at distance 1, 12 at sqrt(2), 8 at sqrt(3), 6 at distance 2.
+        assert_eq!(voxels.len(), 33);
+
+        for pos in [center, center + veci!(2, 0, 0), center - veci!(0, 2, 0), center + veci!(1, 1, 1)] {
+            assert!(voxels.contains(&pos), "{pos} should be covered");
+        }
+
+        for pos in [center + veci!(2, 1, 0), center + veci!(2, 2, 2), center + veci!(3, 0, 0)] {
+            assert!(!voxels.contains(&pos), "{pos} should not be covered");
+        }
+
+        assert_eq!(Brush { radius: 0, ..brush }.voxels(center).collect::<Vec<_>>(), [center]);
+    }
+
+    #[test]
+    fn disc_brush_is_flat() {
+        let brush = Brush { shape: BrushShape::Disc, radius: 2, ..Brush::DEFAULT };
+        assert!(brush.voxels(Int3::ZERO).all(|pos| pos.y == 0));
+        assert_eq!(brush.voxels(Int3::ZERO).count(), 13);
+
+        let cube = Brush { shape: BrushShape::Cube, ..brush };
+        assert_eq!(cube.voxels(Int3::ZERO).count(), 125);
+    }
+
+    #[test]
+    fn reinterpret_brush_shape() {
+        for shape in BrushShape::ALL {
+            assert_eq!(BrushShape::from_bytes(&shape.as_bytes()).unwrap(), shape);
+        }
+
+        assert!(BrushShape::from_bytes(&[BrushShape::ALL.len() as u8]).is_err());
+    }
+}
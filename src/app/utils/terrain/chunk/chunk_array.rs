@@ -4,16 +4,31 @@ use {
         terrain::{
             chunk::{
                 prelude::*, EditError, Sides, Id,
-                tasks::{FullTask, LowTask, Task, GenTask, PartitionTask},
-                mesh::ChunkMesh,
+                tasks::{FullTask, LowTask, Task, GenTask, PartitionTask, EvictTask},
+                evicted::EvictedVoxels,
+                mesh::{ChunkMesh, ChunkVertices, ChunkUniforms, MeshingMode},
+                render_stats::{RenderStats, ChunkOutcome},
+                breaking::BreakingProgress,
+                placement::PlacementNudge,
+                brush::{Brush, BrushShape},
+                boundary::AdjBoundary,
+                commands::{self, Command, CommandRequest, CommandError, CommandResult},
+                events,
+            },
+            voxel::{
+                self, Voxel, voxel_data::data::*,
+                generator::{self, WorldGenerator, GeneratorKind, WorldBounds},
             },
-            voxel::{self, Voxel, voxel_data::data::*},
         },
         saves::Save,
-        graphics::camera::Camera,
+        graphics::{camera::Camera, grid},
+        runtime_cfg,
+        time::cooldown::Cooldown,
     },
     math_linear::math::ray::space_3d::Line,
-    std::{io, mem, sync::Mutex},
+    std::{io, mem, ops::Range, sync::Mutex},
+    bit_vec::BitVec,
+    huffman_compress as hc,
     glium::{self as gl, backend::Facade},
     tokio::task::{JoinHandle, JoinError},
 };
@@ -24,17 +39,80 @@ pub static GENERATOR_SIZES: Mutex<[usize; 3]> = Mutex::new(USize3::ZERO.as_array
 enum ChunkArrSaveType {
     Sizes,
     Array,
+    SharedDictionary,
+    FormatVersion,
 }
 
 impl From<ChunkArrSaveType> for u64 {
     fn from(value: ChunkArrSaveType) -> Self { value as u64 }
 }
 
+/// Huffman code built once from voxel frequencies of all [chunks][Chunk] in the save.
+#[derive(Clone, Debug)]
+pub struct SharedDictionary {
+    /// Sorted to make same chunks always give same bytes.
+    pub freqs: Vec<(Id, usize)>,
+    pub book: hc::Book<Id>,
+    pub tree: hc::Tree<Id>,
+}
+
+impl SharedDictionary {
+    pub fn new(freqs: HashMap<Id, usize>) -> Self {
+        let freqs: Vec<(Id, usize)> = freqs.into_iter()
+            .sorted()
+            .collect();
+
+        let (book, tree) = hc::CodeBuilder::from_iter(
+            freqs.iter().copied()
+        ).finish();
+
+        Self { freqs, book, tree }
+    }
+
+    /// Counts frequencies of non-uniform [chunks][Chunk]. Uniform ones are not Huffman-encoded.
+    pub fn from_chunks(chunks: &[ChunkRef]) -> Self {
+        let mut freqs = HashMap::new();
+
+        for chunk in chunks.iter().filter(|chunk| !chunk.is_same_filled()) {
+            ChunkArray::accumulate_voxel_frequencies(&mut freqs, chunk.ids());
+        }
+
+        Self::new(freqs)
+    }
+
+    /// Same layout as `HashMap::as_bytes()`.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        compose! {
+            self.freqs.len().as_bytes(),
+            self.freqs.iter()
+                .flat_map(|(id, freq)| compose! {
+                    id.as_bytes(),
+                    freq.as_bytes(),
+                }),
+        }.collect()
+    }
+
+    /// Reads dictionary from bytes made by [`SharedDictionary::as_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::try_from_bytes(bytes)
+            .expect("failed to read shared dictionary from bytes")
+    }
+
+    /// Same as [`SharedDictionary::from_bytes`] but gives [`Err`] on malformed bytes.
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, ReinterpretError> {
+        let freqs: HashMap<Id, usize> = ByteReader::new(bytes).read()?;
+        Ok(Self::new(freqs))
+    }
+}
+
 pub type ReadingHandle = JoinHandle<io::Result<(USize3, Vec<(Vec<Atomic<Id>>, FillType)>)>>;
 
 /// Represents 3d array of [`Chunk`]s. Can control their mesh generation, etc.
 #[derive(Debug)]
 pub struct ChunkArray {
+    /// Chunk on index `idx` is on [`ChunkArray::idx_to_pos`], so chunks go in
+    /// [`ChunkArray::pos_iter`] order. Code that needs stable order should use
+    /// [`ChunkArray::chunks_in_pos_order`] instead of relying on it.
     pub chunks: Vec<ChunkRef>,
     pub meshes: Vec<MeshRef>,
     pub sizes: USize3,
@@ -43,11 +121,89 @@ pub struct ChunkArray {
     pub low_tasks: HashMap<(Int3, Lod), LowTask>,
     pub voxels_gen_tasks: HashMap<Int3, GenTask>,
     pub partition_tasks: HashMap<Int3, PartitionTask>,
+    pub evict_tasks: HashMap<Int3, EvictTask>,
 
-    pub lod_threashold: f32,
+    /// Statistics of the last [render][ChunkArray::render].
+    pub render_stats: RenderStats,
 
     pub reading_handle: Option<ReadingHandle>,
+    pub reading_cancel: Arc<AtomicBool>,
     pub saving_handle: Option<JoinHandle<io::Result<()>>>,
+    pub is_overwrite_requested: bool,
+
+    /// Encodes all chunks with one [shared Huffman dictionary][SharedDictionary] on save.
+    pub is_dictionary_shared: bool,
+
+    /// Encodes and writes chunks by batches on save to bound memory usage.
+    pub is_save_streamed: bool,
+
+    /// Makes next update [rebuild all meshes][ChunkArray::rebuild_all_meshes].
+    pub is_mesh_rebuild_requested: bool,
+
+    pub measure_from: Option<Int3>,
+    pub is_picking_debug: bool,
+
+    /// Draws both sides of triangles to spot faces with wrong winding.
+    pub is_backface_culling_disabled: bool,
+
+    pub is_fill_mode: bool,
+
+    /// Progress of breaking the aimed voxel with held left mouse button.
+    pub breaking: BreakingProgress,
+
+    /// Held voxel. It is placed by fill mode and set by [picking][ChunkArray::pick_voxel_id].
+    pub fill_id: Id,
+
+    /// Voxel placed with right click. Right click with empty hand [interacts][ChunkArray::interact] instead.
+    pub held_id: Option<Id>,
+
+    /// Placement position of [held voxel][ChunkArray::held_id] nudged with arrow keys.
+    pub placement: PlacementNudge,
+
+    /// Paints terrain with held left mouse button instead of breaking.
+    pub brush: Brush,
+
+    /// Limits rate of breaking and placing while mouse button is held.
+    pub interaction_cooldown: Cooldown,
+
+    /// Limits rate of [voxel eviction][ChunkArray::evict_far_voxels] passes.
+    pub eviction_cooldown: Cooldown,
+
+    /// Edited [chunks][Chunk] waiting for [storage optimization][ChunkArray::optimize_edited_chunks].
+    pub edited_chunks: HashSet<Int3>,
+
+    /// Limits rate of [storage optimization][ChunkArray::optimize_edited_chunks] passes.
+    pub optimization_cooldown: Cooldown,
+
+    pub fill_drag: mouse::DragState<Int3>,
+
+    /// Keeps active [LOD][Lod] of each [chunk][Chunk] unchanged.
+    pub freeze_lod: bool,
+
+    /// Shape of full detail meshes.
+    pub meshing_mode: MeshingMode,
+
+    /// Makes meshes on the main thread during [render][ChunkArray::render] instead of
+    /// spawning tasks. Meshing panics and order become reproducible this way.
+    pub synchronous_meshing: bool,
+
+    /// Moves camera [onto the surface][ChunkArray::surface_teleport_pos] after world is read.
+    pub is_surface_teleport_enabled: bool,
+
+    /// Positions of [chunks][Chunk] that always request full detail [LOD][Lod].
+    pub full_detail_chunks: HashSet<Int3>,
+
+    /// Camera far plane distance taken on [update][ChunkArray::update]. Limits
+    /// [forced full detail][ChunkArray::force_full_detail] range.
+    pub render_distance: f32,
+
+    /// Source of voxels for new chunks.
+    pub generator: Arc<dyn WorldGenerator>,
+    pub generator_kind: GeneratorKind,
+    pub world_bounds: WorldBounds,
+
+    /// Message about invalid sizes entered in control window.
+    pub sizes_input_error: Option<String>,
 }
 
 impl Default for ChunkArray {
@@ -60,15 +216,67 @@ impl Default for ChunkArray {
             low_tasks: Default::default(),
             partition_tasks: Default::default(),
             voxels_gen_tasks: Default::default(),
-            lod_threashold: 5.8,
+            evict_tasks: Default::default(),
+            render_stats: RenderStats::default(),
             reading_handle: None,
+            reading_cancel: Default::default(),
             saving_handle: None,
+            is_overwrite_requested: false,
+            is_dictionary_shared: cfg::save::SHARE_HUFFMAN_DICTIONARY,
+            is_save_streamed: cfg::save::STREAM_CHUNKS,
+            is_mesh_rebuild_requested: false,
+            measure_from: None,
+            is_picking_debug: false,
+            is_backface_culling_disabled: false,
+            is_fill_mode: false,
+            breaking: BreakingProgress::default(),
+            fill_id: STONE_VOXEL_DATA.id,
+            held_id: None,
+            placement: PlacementNudge::default(),
+            brush: Brush::default(),
+            interaction_cooldown: Cooldown::new(
+                std::time::Duration::from_millis(cfg::terrain::INTERACTION_COOLDOWN_IN_MS),
+            ),
+            eviction_cooldown: Cooldown::new(
+                std::time::Duration::from_millis(cfg::terrain::VOXEL_EVICTION_PERIOD_IN_MS),
+            ),
+            edited_chunks: Default::default(),
+            optimization_cooldown: Cooldown::new(
+                std::time::Duration::from_millis(cfg::terrain::CHUNK_OPTIMIZATION_PERIOD_IN_MS),
+            ),
+            fill_drag: Default::default(),
+            freeze_lod: false,
+            synchronous_meshing: false,
+            meshing_mode: MeshingMode::default(),
+            is_surface_teleport_enabled: false,
+            full_detail_chunks: Default::default(),
+            render_distance: cfg::camera::default::FAR_PLANE,
+            generator: GeneratorKind::default().make(
+                USize3::from(cfg::terrain::default::WORLD_SIZES_IN_CHUNKS),
+                WorldBounds::DEFAULT,
+            ),
+            generator_kind: GeneratorKind::default(),
+            world_bounds: WorldBounds::DEFAULT,
+            sizes_input_error: None,
         }
     }
 }
 
 impl ChunkArray {
     const MAX_TRACE_STEPS: usize = 1024;
+    /// Ray tracing step measured in voxels.
+    const TRACE_STEP_IN_VOXELS: f32 = 0.125;
+    const WORLD_SAVE_NAME: &'static str = "world";
+    const WORLD_SAVE_PATH: &'static str = "world";
+    const SAVING_LOADING_NAME: &'static str = "Chunks saving";
+    const READING_LOADING_NAME: &'static str = "Chunks reading";
+    /// Version of chunks encoding written to saves. Saves without version have version `0`,
+    /// their per-chunk Huffman codes are built from frequencies in unsorted map order.
+    pub const SAVE_FORMAT_VERSION: u32 = 1;
+    /// Part of explosion radius that is cleared fully.
+    const EXPLOSION_CORE_RATIO: f32 = 0.5;
+    /// Camera deeper than this number of voxels under the surface is moved onto it after load.
+    const MAX_DEPTH_UNDER_SURFACE: i32 = 16;
 
     /// Generates new chunks.
     /// # Panic
@@ -105,7 +313,49 @@ impl ChunkArray {
             .map(|_| Rc::new(RefCell::new(ChunkMesh::default())))
             .collect();
         
-        Ok(Self { chunks, sizes, meshes, ..Default::default() })
+        Ok(Self {
+            chunks, sizes, meshes,
+            generator: GeneratorKind::default().make(sizes, WorldBounds::DEFAULT),
+            ..Default::default()
+        })
+    }
+
+    /// Checks that chunks and meshes match [sizes][ChunkArray::sizes], chunks are stored
+    /// on their positions and tasks are started for in-bounds positions.
+    /// # Panic
+    /// Panics with description of the first violated invariant.
+    pub fn validate_invariants(&self) {
+        let volume = Self::volume(self.sizes);
+
+        assert_eq!(
+            self.chunks.len(), volume,
+            "chunk count should be equal to volume of sizes {sizes}", sizes = self.sizes,
+        );
+
+        assert_eq!(
+            self.meshes.len(), self.chunks.len(),
+            "mesh count should be equal to chunk count",
+        );
+
+        for (idx, chunk) in self.chunks.iter().enumerate() {
+            let (pos, expected) = (chunk.pos.load(Relaxed), Self::idx_to_pos(idx, self.sizes));
+            assert_eq!(pos, expected, "chunk with index {idx} should be on {expected} but it is on {pos}");
+        }
+
+        let task_poses = itertools::chain!(
+            self.full_tasks.keys().map(|&pos| ("full", pos)),
+            self.low_tasks.keys().map(|&(pos, _)| ("low", pos)),
+            self.voxels_gen_tasks.keys().map(|&pos| ("voxels generation", pos)),
+            self.partition_tasks.keys().map(|&pos| ("partition", pos)),
+            self.evict_tasks.keys().map(|&pos| ("eviction", pos)),
+        );
+
+        for (kind, pos) in task_poses {
+            assert!(
+                Self::pos_to_idx(self.sizes, pos).is_some(),
+                "{kind} task on {pos} is out of array with sizes {sizes}", sizes = self.sizes,
+            );
+        }
     }
 
     /// Constructs [`ChunkArray`] with empty chunks.
@@ -131,6 +381,12 @@ impl ChunkArray {
         )
     }
 
+    /// Gives inclusive min and max voxel positions the array can address.
+    pub fn voxel_world_bounds(&self) -> (Int3, Int3) {
+        let (start_pos, end_pos) = Self::pos_bounds(self.sizes);
+        (Chunk::global_pos(start_pos), Chunk::global_pos(end_pos) - Int3::ONE)
+    }
+
     /// Checks that sizes is valid.
     /// # Panic
     /// Panics if `sizes.x * sizes.y * sizes.z` > `MAX_CHUNKS`.
@@ -143,17 +399,37 @@ impl ChunkArray {
         }
     }
 
+    /// Converts sizes entered by user to valid sizes. Zero dimensions are clamped to 1.
+    /// # Error
+    /// Returns [`Err`] if some dimension is negative or there are too many chunks.
+    pub fn sizes_from_input(input: Int3) -> Result<USize3, UserFacingError> {
+        let sizes = input.try_into_usize3()
+            .ok_or_else(|| UserFacingError::new("invalid chunk array sizes")
+                .reason(format!("sizes should not be negative, but got {input}"))
+            )?;
+
+        let sizes = USize3::new(sizes.x.max(1), sizes.y.max(1), sizes.z.max(1));
+        Self::validate_sizes(sizes)?;
+
+        Ok(sizes)
+    }
+
     /// Gives empty [`ChunkArray`].
     pub fn new_empty() -> Self {
         Self::default()
     }
 
+    /// Saves chunks to file. If `is_dictionary_shared` is set then all chunks are
+    /// encoded with one [Huffman dictionary][SharedDictionary] stored in the save header.
+    /// If `is_streamed` is set then chunks are encoded and written by batches of
+    /// [`STREAM_BATCH_SIZE`][cfg::save::STREAM_BATCH_SIZE], otherwise all of them are encoded first.
     pub async fn save_to_file(
-        sizes: USize3, chunks: Vec<ChunkRef>, save_name: impl Into<String>, save_path: &'static str,
+        sizes: USize3, chunks: Vec<ChunkRef>, save_name: impl Into<String>, save_path: &str,
+        is_dictionary_shared: bool, is_streamed: bool,
     ) -> io::Result<()> {
         let save_name = save_name.into();
 
-        let _work_guard = logger::work("chunk-array", format!("saving to {save_name} in {save_path}"));
+        let _work_guard = logger::work_detached("chunk-array", format!("saving to {save_name} in {save_path}"));
 
         let is_all_generated = {
             let mut result = true;
@@ -171,125 +447,520 @@ impl ChunkArray {
         let volume = Self::volume(sizes);
         assert_eq!(volume, chunks.len(), "chunks should have same length as sizes volume");
 
-        let loading = loading::start_new("Chunks saving");
+        let loading = Arc::new(loading::start_new(Self::SAVING_LOADING_NAME));
+        let chunks: Arc<[ChunkRef]> = chunks.into();
+
+        // Encoding is CPU-bound, so it runs on blocking threads. Unlike `block_in_place`
+        // it works on current-thread runtime too.
+        let dictionary = Arc::new(match is_dictionary_shared {
+            true => {
+                let chunks = Arc::clone(&chunks);
+                Some(tokio::task::spawn_blocking(move || SharedDictionary::from_chunks(&chunks)).await?)
+            },
+            false => None,
+        });
+
+        let mut encoded = match is_streamed {
+            true => vec![],
+            false => {
+                let (chunks, dictionary, loading) = (Arc::clone(&chunks), Arc::clone(&dictionary), Arc::clone(&loading));
+                tokio::task::spawn_blocking(move || Self::chunks_as_bytes_with(
+                    &chunks, dictionary.as_ref().as_ref(), |progress| loading.refresh(progress),
+                )).await?
+            },
+        };
+
+        Save::<ChunkArrSaveType>::backup(&save_name, save_path, cfg::save::N_BACKUPS).await?;
 
-        Save::builder(save_name.clone())
+        let mut save = Save::builder(save_name.clone())
             .create(save_path).await?
-            .write(&sizes, ChunkArrSaveType::Sizes).await
-            .pointer_array(volume, ChunkArrSaveType::Array, |i| {
-                let chunks = &chunks;
-                let loading = &loading;
+            .write(&Self::SAVE_FORMAT_VERSION, ChunkArrSaveType::FormatVersion).await
+            .write(&sizes, ChunkArrSaveType::Sizes).await;
+
+        if let Some(dictionary) = dictionary.as_ref() {
+            save = save.pointer(dictionary.as_bytes(), ChunkArrSaveType::SharedDictionary).await;
+        }
+
+        let save = match is_streamed {
+            true => save.pointer_array_batched(volume, ChunkArrSaveType::Array, cfg::save::STREAM_BATCH_SIZE, |range| {
+                let (start, len) = (range.start, range.len());
+                let (chunks, dictionary, loading) = (Arc::clone(&chunks), Arc::clone(&dictionary), Arc::clone(&loading));
 
                 async move {
-                    loading.refresh(i as f32 / (volume - 1) as f32);
-                    Self::chunk_as_bytes(&chunks[i])
+                    tokio::task::spawn_blocking(move || Self::chunks_as_bytes_with(
+                        &chunks[range], dictionary.as_ref().as_ref(),
+                        |progress| loading.refresh((start as f32 + progress * len as f32) / volume as f32),
+                    )).await.expect("failed to join chunks encoding task")
                 }
-            }).await
-            .save()
-            .await?;
+            }).await,
+
+            false => save.pointer_array(volume, ChunkArrSaveType::Array, |i| {
+                let bytes = mem::take(&mut encoded[i]);
+                async move { bytes }
+            }).await,
+        };
+
+        save.save().await?;
 
         Ok(())
     }
 
+    /// Reads chunks from save file. Reading stops between chunks if `cancel` is set.
+    /// # Error
+    /// Returns [`Err`] with [`io::ErrorKind::Interrupted`] if reading was cancelled
+    /// and with [`io::ErrorKind::InvalidData`] if save is malformed.
     pub async fn read_from_file(
-        save_name: &str, save_path: &str,
+        save_name: &str, save_path: &str, cancel: Arc<AtomicBool>,
     ) -> io::Result<(USize3, Vec<(Vec<Atomic<Id>>, FillType)>)> {
-        let _work_guard = logger::work("chunk-array", format!("reading chunks from {save_name} in {save_path}"));
+        let _work_guard = logger::work_detached("chunk-array", format!("reading chunks from {save_name} in {save_path}"));
 
-        let loading = loading::start_new("Chunks reading");
+        let loading = loading::start_new(Self::READING_LOADING_NAME);
 
         let mut save = Save::builder(save_name)
             .open(save_path)
             .await?;
         
-        let sizes = save.read(ChunkArrSaveType::Sizes).await;
+        let format_version = Self::read_format_version(&mut save).await?;
+
+        let sizes: USize3 = save.try_read(ChunkArrSaveType::Sizes).await?;
+        let volume = Self::checked_volume(sizes)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("sizes {sizes:?} are too big")))?;
+
+        // Older saves have no shared dictionary.
+        let dictionary = match save.contains(ChunkArrSaveType::SharedDictionary) {
+            true => Some(
+                save.try_read_from_pointer(ChunkArrSaveType::SharedDictionary, SharedDictionary::try_from_bytes)
+                    .await?
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+            ),
+            false => None,
+        };
 
-        let chunks = save.read_pointer_array(ChunkArrSaveType::Array, |i, bytes| {
-            let loading = &loading;
+        let chunks = save.try_read_pointer_array_until(
+            ChunkArrSaveType::Array,
+            || cancel.load(Acquire),
+            |i, bytes| {
+                let (loading, tree) = (&loading, dictionary.as_ref().map(|dictionary| &dictionary.tree));
 
-            async move {
-                loading.refresh(i as f32 / (Self::volume(sizes) - 1) as f32);
-                Self::array_filltype_from_bytes(&bytes)
-            }
-        }).await;
+                async move {
+                    loading.refresh(Self::progress_fraction(i, volume));
+                    Self::try_array_filltype_from_bytes_of_version(&bytes, tree, format_version)
+                        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("chunk {i}: {err}")))
+                }
+            },
+        ).await?;
+
+        let Some(chunks) = chunks else {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "chunks reading is cancelled"));
+        };
+
+        let chunks = chunks.into_iter().collect::<io::Result<Vec<_>>>()?;
+
+        if chunks.len() != volume {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("save has {n} chunks but sizes {sizes:?} need {volume}", n = chunks.len()),
+            ));
+        }
 
         Ok((sizes, chunks))
     }
 
+    /// Reads [format version][ChunkArray::SAVE_FORMAT_VERSION] of the save.
+    /// # Error
+    /// Returns [`Err`] if the version is newer than supported one.
+    async fn read_format_version(save: &mut Save<ChunkArrSaveType>) -> io::Result<u32> {
+        let version = match save.contains(ChunkArrSaveType::FormatVersion) {
+            true => save.try_read(ChunkArrSaveType::FormatVersion).await?,
+            false => 0,
+        };
+
+        match version <= Self::SAVE_FORMAT_VERSION {
+            true => Ok(version),
+            false => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("save format version {version} is newer than supported {}", Self::SAVE_FORMAT_VERSION),
+            )),
+        }
+    }
+
+    /// Gives loading progress after processing chunk with index `idx` of `volume` chunks.
+    /// Last chunk gives exactly `1.0`.
+    pub fn progress_fraction(idx: usize, volume: usize) -> f32 {
+        match volume {
+            0 => 1.0,
+            _ => usize::min(idx + 1, volume) as f32 / volume as f32,
+        }
+    }
+
+    /// Gives progress of currently running save, if any.
+    pub fn saving_progress(&self) -> Option<f32> {
+        self.saving_handle.as_ref()?;
+        Some(loading::progress(Self::SAVING_LOADING_NAME).unwrap_or(0.0))
+    }
+
+    /// Gives progress of currently running read, if any.
+    pub fn reading_progress(&self) -> Option<f32> {
+        self.reading_handle.as_ref()?;
+        Some(loading::progress(Self::READING_LOADING_NAME).unwrap_or(0.0))
+    }
+
+    /// Starts saving chunks to the world save. If save already exists and `force` is not set
+    /// then it will only request overwrite confirmation. See `ChunkArray::is_overwrite_requested`.
+    pub fn start_saving(&mut self, force: bool) {
+        if self.saving_handle.is_some() { return }
+
+        if !force && Save::<ChunkArrSaveType>::exists(Self::WORLD_SAVE_NAME, Self::WORLD_SAVE_PATH) {
+            self.is_overwrite_requested = true;
+            logger::log!(
+                Info, from = "chunk-array",
+                "save {name} already exists, confirm overwriting it",
+                name = Self::WORLD_SAVE_NAME,
+            );
+            return;
+        }
+
+        self.is_overwrite_requested = false;
+
+        let chunks: Vec<_> = self.chunks_in_pos_order()
+            .map(|(_, chunk)| chunk)
+            .collect();
+        let handle = tokio::spawn(
+            ChunkArray::save_to_file(
+                self.sizes, chunks, Self::WORLD_SAVE_NAME, Self::WORLD_SAVE_PATH,
+                self.is_dictionary_shared, self.is_save_streamed,
+            )
+        );
+        self.saving_handle = Some(handle);
+    }
+
+    /// Starts or stops [recording][commands::start_recording] of commands
+    /// to [`cfg::save::COMMAND_RECORDING_PATH`].
+    pub fn switch_command_recording() {
+        let path = cfg::save::COMMAND_RECORDING_PATH;
+
+        if commands::stop_recording() {
+            logger::log!(Info, from = "chunk-array", "commands are recorded to {path}");
+            return;
+        }
+
+        match commands::start_recording(path) {
+            Ok(()) => logger::log!(Info, from = "chunk-array", "recording commands to {path}"),
+            Err(err) => logger::log!(Error, from = "chunk-array", "failed to start recording to {path}: {err}"),
+        }
+    }
+
+    /// Spawns [replay][commands::replay] of commands recorded to [`cfg::save::COMMAND_RECORDING_PATH`].
+    pub fn start_replay() {
+        tokio::spawn(async {
+            let path = cfg::save::COMMAND_RECORDING_PATH;
+
+            match commands::replay(path, 1.0).await {
+                Ok(n_commands) => logger::log!(Info, from = "chunk-array", "replayed {n_commands} commands from {path}"),
+                Err(err) => logger::log!(Error, from = "chunk-array", "failed to replay commands from {path}: {err}"),
+            }
+        });
+    }
+
+    /// Aborts currently running save. Save files can be left incomplete.
+    pub fn cancel_saving(&mut self) {
+        if let Some(handle) = self.saving_handle.take() {
+            handle.abort();
+            loading::cancel(Self::SAVING_LOADING_NAME);
+            logger::log!(Info, from = "chunk-array", "saving is cancelled");
+        }
+    }
+
+    /// Asks currently running read to stop. Current chunks are left untouched.
+    pub fn cancel_reading(&mut self) {
+        if self.reading_handle.is_some() {
+            loading::cancel(Self::READING_LOADING_NAME);
+            self.reading_cancel.store(true, Release);
+        }
+    }
+
+    /// Applies result of [`ChunkArray::read_from_file`]. Cancelled reading keeps current chunks.
+    pub fn apply_read_result(
+        &mut self, result: io::Result<(USize3, Vec<(Vec<Atomic<Id>>, FillType)>)>,
+    ) -> Result<(), UpdateError> {
+        match result {
+            Ok((sizes, arr)) => Ok(self.apply_new(sizes, arr)?),
+
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {
+                logger::log!(Info, from = "chunk-array", "reading is cancelled: {err}");
+                Ok(())
+            },
+
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Reads chunks from save file. If it fails then tries to read backups from the most recent one.
+    /// # Error
+    /// Returns error of reading the save itself if all backups also failed.
+    pub async fn read_from_file_or_backup(
+        save_name: &str, save_path: &str, cancel: Arc<AtomicBool>,
+    ) -> io::Result<(USize3, Vec<(Vec<Atomic<Id>>, FillType)>)> {
+        let err = match Self::read_from_file(save_name, save_path, Arc::clone(&cancel)).await {
+            Err(err) if err.kind() != io::ErrorKind::Interrupted => err,
+            result => return result,
+        };
+
+        logger::log!(Error, from = "chunk-array", "failed to read {save_name}: {err}, trying backups");
+
+        for idx in 0..cfg::save::N_BACKUPS {
+            let backup_path = Save::<ChunkArrSaveType>::backup_path(save_name, save_path, idx);
+            if !Save::<ChunkArrSaveType>::exists(save_name, &backup_path) { continue }
+
+            match Self::read_from_file(save_name, &backup_path, Arc::clone(&cancel)).await {
+                Ok(result) => {
+                    logger::log!(Info, from = "chunk-array", "read backup from {backup_path}");
+                    return Ok(result);
+                },
+
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => return Err(err),
+
+                Err(err) => logger::log!(
+                    Error, from = "chunk-array", "failed to read backup from {backup_path}: {err}",
+                ),
+            }
+        }
+
+        Err(err)
+    }
+
+    /// Reinterprets all [chunk][Chunk]s as bytes in parallel. Output keeps chunks order.
+    /// `on_progress` is called with fraction of encoded chunks.
+    pub fn chunks_as_bytes(chunks: &[ChunkRef], on_progress: impl Fn(f32) + Sync) -> Vec<Vec<u8>> {
+        Self::chunks_as_bytes_with(chunks, None, on_progress)
+    }
+
+    /// Same as [`ChunkArray::chunks_as_bytes`] but encodes chunks with `dictionary` if it is given.
+    pub fn chunks_as_bytes_with(
+        chunks: &[ChunkRef], dictionary: Option<&SharedDictionary>, on_progress: impl Fn(f32) + Sync,
+    ) -> Vec<Vec<u8>> {
+        let n_encoded = AtomicUsize::new(0);
+
+        chunks.par_iter()
+            .map(|chunk| {
+                let bytes = match dictionary {
+                    Some(dictionary) => Self::chunk_as_bytes_shared(chunk, dictionary),
+                    None => Self::chunk_as_bytes(chunk),
+                };
+                let idx = n_encoded.fetch_add(1, AcqRel);
+                on_progress(Self::progress_fraction(idx, chunks.len()));
+                bytes
+            })
+            .collect()
+    }
+
     /// Reinterprets [chunk][Chunk] as bytes. It uses Huffman's compresstion.
     pub fn chunk_as_bytes(chunk: &Chunk) -> Vec<u8> {
-        use { bit_vec::BitVec, huffman_compress as hc };
-
         match chunk.info.load(Relaxed).fill_type {
             FillType::AllSame(id) =>
                 FillType::AllSame(id).as_bytes(),
 
-            FillType::Default => {
-                let n_voxels = chunk.voxel_ids.len();
+            // Evicted chunk already keeps its bytes.
+            FillType::Evicted if chunk.evicted.is_some() =>
+                chunk.evicted.as_ref().map(|evicted| evicted.bytes.clone()).unwrap_or_default(),
+
+            FillType::Default | FillType::Sparse | FillType::Evicted => {
+                let n_voxels = chunk.ids().count();
                 assert_eq!(
                     n_voxels, Chunk::VOLUME,
                     "cannot save unknown-sized chunk with size {n_voxels}",
                 );
 
-                let freqs = Self::count_voxel_frequencies(
-                    chunk.voxel_ids.iter()
-                        .map(|id| id.load(Relaxed))
-                );
+                let freqs = Self::count_voxel_frequencies(chunk.ids());
+
+                // Sorted to make same chunks always give same bytes.
+                let freqs: Vec<(Id, usize)> = freqs.into_iter()
+                    .sorted()
+                    .collect();
 
                 let (book, _) = hc::CodeBuilder::from_iter(
-                    freqs.iter().map(|(&k, &v)| (k, v))
+                    freqs.iter().copied()
                 ).finish();
-                let mut bits = BitVec::new();
-
-                for voxel_id in chunk.voxel_ids.iter() {
-                    let voxel_id = voxel_id.load(Relaxed);
-                    book.encode(&mut bits, &voxel_id)
-                        .expect("voxel id should be in the book");
-                }
+                let bits = Self::encode_voxel_ids(chunk, &book);
+
+                // Same layout as `HashMap::as_bytes()`.
+                let freqs_bytes = compose! {
+                    freqs.len().as_bytes(),
+                    freqs.iter()
+                        .flat_map(|(id, freq)| compose! {
+                            id.as_bytes(),
+                            freq.as_bytes(),
+                        }),
+                };
 
                 itertools::chain! {
                     FillType::Default.as_bytes(),
-                    freqs.as_bytes(),
+                    freqs_bytes,
                     bits.as_bytes(),
                 }.collect()
             }
         }
     }
 
+    /// Reinterprets [chunk][Chunk] as bytes encoded with shared `dictionary`.
+    /// Unlike [`ChunkArray::chunk_as_bytes`] the bytes have no frequencies map.
+    pub fn chunk_as_bytes_shared(chunk: &Chunk, dictionary: &SharedDictionary) -> Vec<u8> {
+        match chunk.info.load(Relaxed).fill_type {
+            FillType::AllSame(id) =>
+                FillType::AllSame(id).as_bytes(),
+
+            FillType::Default | FillType::Sparse | FillType::Evicted => {
+                let n_voxels = chunk.ids().count();
+                assert_eq!(
+                    n_voxels, Chunk::VOLUME,
+                    "cannot save unknown-sized chunk with size {n_voxels}",
+                );
+
+                let bits = Self::encode_voxel_ids(chunk, &dictionary.book);
+
+                itertools::chain! {
+                    FillType::Default.as_bytes(),
+                    bits.as_bytes(),
+                }.collect()
+            },
+        }
+    }
+
+    /// Encodes voxel ids of [chunk][Chunk] with Huffman's `book`.
+    fn encode_voxel_ids(chunk: &Chunk, book: &hc::Book<Id>) -> BitVec {
+        let mut bits = BitVec::new();
+
+        for voxel_id in chunk.ids() {
+            book.encode(&mut bits, &voxel_id)
+                .expect("voxel id should be in the book");
+        }
+
+        bits
+    }
+
     /// Reinterprets bytes as [chunk][Chunk] and reads [id][Id] array and [fill type][FillType] from it.
-    pub fn array_filltype_from_bytes(bytes: &[u8]) -> (Vec<Atomic<Id>>, FillType) {
-        use { bit_vec::BitVec, huffman_compress as hc };
+    /// If `shared_tree` is given then bytes are expected to be made by [`ChunkArray::chunk_as_bytes_shared`].
+    /// # Panic
+    /// Panics if bytes can not be [decoded][ChunkArray::try_array_filltype_from_bytes].
+    pub fn array_filltype_from_bytes(bytes: &[u8], shared_tree: Option<&hc::Tree<Id>>) -> (Vec<Atomic<Id>>, FillType) {
+        Self::try_array_filltype_from_bytes(bytes, shared_tree)
+            .unwrap_or_else(|err| panic!("failed to decode chunk: {err}"))
+    }
 
+    /// Same as [`ChunkArray::array_filltype_from_bytes`] but gives [`Err`] on malformed bytes.
+    pub fn try_array_filltype_from_bytes(
+        bytes: &[u8], shared_tree: Option<&hc::Tree<Id>>,
+    ) -> Result<(Vec<Atomic<Id>>, FillType), ChunkDecodeError> {
+        Self::try_array_filltype_from_bytes_of_version(bytes, shared_tree, Self::SAVE_FORMAT_VERSION)
+    }
+
+    /// Same as [`ChunkArray::try_array_filltype_from_bytes`] but decodes bytes written
+    /// with given [save format version][ChunkArray::SAVE_FORMAT_VERSION].
+    pub fn try_array_filltype_from_bytes_of_version(
+        bytes: &[u8], shared_tree: Option<&hc::Tree<Id>>, format_version: u32,
+    ) -> Result<(Vec<Atomic<Id>>, FillType), ChunkDecodeError> {
         let mut reader = ByteReader::new(bytes);
-        let fill_type: FillType = reader.read()
-            .expect("failed to reinterpret bytes");
+        let fill_type: FillType = reader.read()?;
 
         match fill_type {
-            FillType::Default => {
-                let freqs: HashMap<Id, usize> = reader.read()
-                    .expect("failed to read frequencies map from bytes");
+            FillType::Default | FillType::Sparse | FillType::Evicted => {
+                let tree = match shared_tree {
+                    Some(tree) => Cow::Borrowed(tree),
+                    None => {
+                        let freqs: HashMap<Id, usize> = reader.read()?;
+
+                        let (_, tree) = match format_version {
+                            0 => hc::CodeBuilder::from_iter(freqs).finish(),
+                            _ => hc::CodeBuilder::from_iter(freqs.into_iter().sorted()).finish(),
+                        };
+
+                        Cow::Owned(tree)
+                    },
+                };
 
-                let bits: BitVec = reader.read()
-                    .expect("failed to read `BitVec` from bytes");
+                let bits: BitVec = reader.read()?;
 
-                let (_, tree) = hc::CodeBuilder::from_iter(freqs).finish();
                 let voxel_ids: Vec<_> = tree.unbounded_decoder(bits)
                     .map(Atomic::new)
                     .collect();
 
-                let is_id_valid = voxel_ids.iter()
-                    .map(|id| id.load(Relaxed))
-                    .all(voxel::is_id_valid);
+                if let Some(id) = voxel_ids.iter().map(|id| id.load(Relaxed)).find(|&id| !voxel::is_id_valid(id)) {
+                    return Err(ChunkDecodeError::InvalidId(id));
+                }
 
-                assert!(is_id_valid, "Voxel ids in voxel array should be valid");
-                assert_eq!(voxel_ids.len(), Chunk::VOLUME, "There's should be Chunk::VOLUME voxels");
+                if voxel_ids.len() != Chunk::VOLUME {
+                    return Err(ChunkDecodeError::VoxelCount { count: voxel_ids.len() });
+                }
 
-                (voxel_ids, FillType::Default)
+                Ok((voxel_ids, FillType::Default))
             },
 
-            FillType::AllSame(id) =>
-                (vec![], FillType::AllSame(id)),
+            FillType::AllSame(id) => match voxel::is_id_valid(id) {
+                true => Ok((vec![], FillType::AllSame(id))),
+                false => Err(ChunkDecodeError::InvalidId(id)),
+            },
+        }
+    }
+
+    /// Reads save without applying it and checks that its sizes match chunk count,
+    /// every chunk decodes and all voxel ids are valid. Problems are logged per chunk.
+    /// Saves have no checksums, so they are not checked.
+    pub async fn verify_file(save_name: &str, save_path: &str) -> io::Result<VerifyReport> {
+        let _work_guard = logger::work_detached("chunk-array", format!("verifying {save_name} in {save_path}"));
+
+        let mut save = Save::<ChunkArrSaveType>::builder(save_name)
+            .open(save_path)
+            .await?;
+
+        if !save.contains(ChunkArrSaveType::Sizes) || !save.contains(ChunkArrSaveType::Array) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "save has no chunk array"));
+        }
+
+        let format_version = Self::read_format_version(&mut save).await?;
+        let sizes: USize3 = save.try_read(ChunkArrSaveType::Sizes).await?;
+
+        let dictionary = match save.contains(ChunkArrSaveType::SharedDictionary) {
+            true => Some(
+                save.try_read_from_pointer(ChunkArrSaveType::SharedDictionary, SharedDictionary::try_from_bytes)
+                    .await?
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("shared dictionary: {err}")))?
+            ),
+            false => None,
+        };
+
+        let tree = dictionary.as_ref().map(|dictionary| &dictionary.tree);
+
+        let statuses = save.try_read_pointer_array_until(ChunkArrSaveType::Array, || false, |_, bytes| async move {
+            Self::try_array_filltype_from_bytes_of_version(&bytes, tree, format_version).map(drop)
+        }).await?.unwrap_or_default();
+
+        let report = VerifyReport {
+            sizes,
+            n_chunks: statuses.len(),
+            bad_chunks: statuses.into_iter()
+                .enumerate()
+                .filter_map(|(idx, status)| Some((idx, status.err()?)))
+                .collect(),
+        };
+
+        if !report.is_volume_valid() {
+            logger::log!(
+                Error, from = "chunk-array",
+                "save has {n} chunks but sizes {sizes:?} need {volume:?}",
+                n = report.n_chunks, volume = Self::checked_volume(sizes),
+            );
+        }
+
+        for (idx, err) in report.bad_chunks.iter() {
+            logger::log!(Error, from = "chunk-array", "chunk {idx} is corrupted: {err}");
         }
+
+        logger::log!(
+            Info, from = "chunk-array", "verified {save_name}: {n_bad} of {n} chunks are corrupted",
+            n_bad = report.bad_chunks.len(), n = report.n_chunks,
+        );
+
+        Ok(report)
     }
 
     /// Sets voxel's id with position `pos` to `new_id` and returns old [`Id`]. If voxel is 
@@ -297,17 +968,90 @@ impl ChunkArray {
     /// # Error
     /// Returns [`Err`] if `new_id` is not valid or `pos` is not in this [chunk array][ChunkArray].
     pub fn set_voxel(&mut self, pos: Int3, new_id: Id) -> Result<Id, EditError> {
+        if !voxel::is_id_valid(new_id) {
+            return Err(EditError::InvalidId(new_id));
+        }
+
         let chunk_pos = Chunk::local_pos(pos);
         let chunk_idx = Self::pos_to_idx(self.sizes, chunk_pos)
             .ok_or(EditError::PosIdConversion(pos))?;
 
-        // We know that `chunk_idx` is valid so we can get-by-index.
-        let old_id = unsafe {
-            Arc::get_mut_unchecked(&mut self.chunks[chunk_idx])
-                .set_voxel(pos, new_id)?
+        if self.get_voxel(pos).is_some_and(|voxel| voxel.data.unbreakable) {
+            return Err(EditError::Unbreakable(pos));
+        }
+
+        self.chunk_for_edit(chunk_idx).set_voxel(pos, new_id)
+    }
+
+    /// Gives [chunk][Chunk] with index `idx` for an edit. Tasks reading it are dropped so they
+    /// don't upload outdated meshes or [evicted voxels][EvictedVoxels]. Chunk still held by
+    /// some task is copied first, so the edit never races with its reads.
+    fn chunk_for_edit(&mut self, idx: usize) -> &mut Chunk {
+        self.prepare_chunk_edit(idx);
+        Arc::make_mut(&mut self.chunks[idx])
+    }
+
+    /// Drops tasks reading [chunk][Chunk] with index `idx` and marks it as edited.
+    fn prepare_chunk_edit(&mut self, idx: usize) {
+        let chunk_pos = Self::idx_to_pos(idx, self.sizes);
+
+        Self::drop_reader_tasks(&mut self.full_tasks, &mut self.low_tasks, chunk_pos);
+        self.evict_tasks.remove(&chunk_pos);
+        self.edited_chunks.insert(chunk_pos);
+
+        for pos in SpaceIter::adj_iter(chunk_pos).chain(std::iter::once(chunk_pos)) {
+            self.partition_tasks.remove(&pos);
+        }
+    }
+
+    /// Calls [interaction hook][voxel::voxel_data::VoxelData::on_interact] of `voxel` and sets
+    /// the voxel to the [id][Id] it gives. Returns new id if voxel is changed.
+    /// # Error
+    /// Returns [`Err`] if new id can not be set.
+    pub fn interact(&mut self, voxel: Voxel) -> Result<Option<Id>, EditError> {
+        let Some(on_interact) = voxel.data.on_interact else {
+            return Ok(None);
         };
 
-        Ok(old_id)
+        match on_interact(&voxel) {
+            Some(new_id) if new_id != voxel.data.id => {
+                self.set_voxel(voxel.pos, new_id)?;
+                Ok(Some(new_id))
+            },
+
+            _ => Ok(None),
+        }
+    }
+
+    /// Gives position of some unbreakable voxel in volume from `pos_from` to `pos_to` (excluded).
+    /// Same-filled [chunks][Chunk] are checked by their fill id only, other ones are scanned
+    /// through their storage without per-voxel chunk lookup.
+    pub fn find_unbreakable(&self, pos_from: Int3, pos_to: Int3) -> Option<Int3> {
+        let is_unbreakable = |id: Id| VOXEL_DATA.get(id as usize).is_some_and(|data| data.unbreakable);
+
+        if !VOXEL_DATA.iter().any(|data| data.unbreakable) { return None }
+
+        let chunk_pos_from = Chunk::local_pos(pos_from);
+        let chunk_pos_to   = Chunk::local_pos(pos_to - Int3::ONE) + Int3::ONE;
+
+        SpaceIter::new(chunk_pos_from..chunk_pos_to).find_map(|chunk_pos| {
+            let chunk = &self.chunks[Self::pos_to_idx(self.sizes, chunk_pos)?];
+
+            // Intersection of the volume with the chunk.
+            let min_voxel_pos = Chunk::global_pos(chunk_pos);
+            let mut voxels = SpaceIter::new(
+                pos_from.max(min_voxel_pos)..pos_to.min(min_voxel_pos + Int3::from(Chunk::SIZES))
+            );
+
+            match chunk.fill_id() {
+                Some(id) => voxels.next().filter(|_| is_unbreakable(id)),
+                None if !chunk.is_generated() => None,
+                None => voxels.find(|&pos| {
+                    let idx = Chunk::voxel_pos_to_idx_unchecked(pos - min_voxel_pos);
+                    chunk.get_id(idx).is_some_and(is_unbreakable)
+                }),
+            }
+        })
     }
 
     /// Gives voxel if it is in the [array][ChunkArray].
@@ -316,14 +1060,19 @@ impl ChunkArray {
         let chunk_idx = Self::pos_to_idx(self.sizes, chunk_pos)?;
 
         match self.chunks[chunk_idx].get_voxel_global(pos) {
-            ChunkOption::Voxel(voxel) => Some(voxel),
             ChunkOption::OutsideChunk => unreachable!("pos {} is indeed in that chunk", pos),
-            ChunkOption::Failed => None,
+            inner => inner.into_option(),
         }
     }
 
     /// Fills volume of voxels to same [id][Id] and returnes `is_changed`.
+    /// # Error
+    /// Returns [`Err`] if `new_id` is not valid or positions are not in this [chunk array][ChunkArray].
     pub fn fill_voxels(&mut self, pos_from: Int3, pos_to: Int3, new_id: Id) -> Result<bool, EditError> {
+        if !voxel::is_id_valid(new_id) {
+            return Err(EditError::InvalidId(new_id));
+        }
+
         let chunk_pos_from = Chunk::local_pos(pos_from);
         let chunk_pos_to   = Chunk::local_pos(pos_to + Int3::from(Chunk::SIZES) - Int3::ONE);
 
@@ -333,6 +1082,10 @@ impl ChunkArray {
         Self::pos_to_idx(self.sizes, chunk_pos_to - Int3::ONE)
             .ok_or(EditError::PosIdConversion(chunk_pos_to - Int3::ONE))?;
 
+        if let Some(pos) = self.find_unbreakable(pos_from, pos_to) {
+            return Err(EditError::Unbreakable(pos));
+        }
+
         let mut is_changed = false;
 
         for chunk_pos in SpaceIter::new(chunk_pos_from..chunk_pos_to) {
@@ -342,27 +1095,17 @@ impl ChunkArray {
             let min_voxel_pos = Chunk::global_pos(chunk_pos);
             let end_voxel_pos = min_voxel_pos + Int3::from(Chunk::SIZES);
 
-            let pos_from = Int3::new(
-                Ord::max(pos_from.x, min_voxel_pos.x),
-                Ord::max(pos_from.y, min_voxel_pos.y),
-                Ord::max(pos_from.z, min_voxel_pos.z),
-            );
-
-            let pos_to = Int3::new(
-                Ord::min(pos_to.x, end_voxel_pos.x),
-                Ord::min(pos_to.y, end_voxel_pos.y),
-                Ord::min(pos_to.z, end_voxel_pos.z),
-            );
+            // Intersection of the fill box with the chunk.
+            let pos_from = pos_from.max(min_voxel_pos);
+            let pos_to   = pos_to.min(end_voxel_pos);
 
-            let chunk_changed = unsafe {
-                Arc::get_mut_unchecked(&mut self.chunks[idx])
-                    .fill_voxels(pos_from, pos_to, new_id)?
-            };
+            let chunk_changed = self.chunk_for_edit(idx)
+                .fill_voxels(pos_from, pos_to, new_id)?;
 
             if chunk_changed {
                 is_changed = true;
-                
-                for idx in Self::get_adj_chunks_idxs(self.sizes, chunk_pos).as_array().into_iter().flatten() {
+
+                for idx in Self::get_touched_adj_chunks_idxs(self.sizes, chunk_pos, pos_from, pos_to) {
                     self.meshes[idx].borrow_mut().drop_all();
                 }
             }
@@ -378,14 +1121,104 @@ impl ChunkArray {
         }
     }
 
-    fn count_voxel_frequencies(voxel_ids: impl IntoIterator<Item = Id>) -> HashMap<Id, usize> {
-        let mut result = HashMap::new();
+    /// Gives indices of [chunks][Chunk] whose meshes can be built with [LOD][Lod] to build them with.
+    /// Chunk mesh can be built if the chunk and its neighbors are generated.
+    pub fn mesh_rebuild_plan(&self, cam_pos: vec3) -> Vec<(usize, Lod)> {
+        self.chunks.iter()
+            .zip(self.target_lod_iter(cam_pos))
+            .enumerate()
+            .filter(|(_, (chunk, _))| chunk.is_generated() && self.neighbors_generated(chunk.pos.load(Relaxed)))
+            .map(|(idx, (_, lod))| (idx, lod))
+            .collect()
+    }
 
-        for id in voxel_ids.into_iter() {
-            match result.get_mut(&id) {
-                None => drop(result.insert(id, 1)),
-                Some(freq) => *freq += 1,
-            }
+    /// Drops all meshes and immediately builds them again with each [chunk][Chunk]'s target [LOD][Lod].
+    /// Unlike [`ChunkArray::drop_all_meshes`] meshes are ready to render when it returns.
+    pub fn rebuild_all_meshes(&mut self, facade: &dyn Facade, cam: &Camera) {
+        // Running tasks would upload meshes made before rebuild.
+        drop(mem::take(&mut self.full_tasks));
+        drop(mem::take(&mut self.low_tasks));
+        drop(mem::take(&mut self.partition_tasks));
+
+        self.drop_all_meshes();
+
+        let plan = self.mesh_rebuild_plan(cam.pos);
+        let n_rebuilt = plan.len();
+
+        for (idx, lod) in plan {
+            let chunk = &self.chunks[idx];
+            let chunk_pos = chunk.pos.load(Relaxed);
+            let mut mesh = self.meshes[idx].borrow_mut();
+
+            chunk.generate_mesh(&mut mesh, lod, self.get_adj_chunks(chunk_pos), self.meshing_mode, facade);
+            chunk.set_active_lod(&mut mesh, lod)
+                .unwrap_or_else(|err| logger::log!(
+                    Error, from = "chunk-array", "failed to set LOD of rebuilt chunk in {chunk_pos}: {err}",
+                ));
+        }
+
+        logger::log!(Info, from = "chunk-array", "{n_rebuilt} chunk meshes were rebuilt");
+    }
+
+    /// Gives [chunks][Chunk] that have voxels within `radius` (in voxels) of `center` voxel.
+    pub fn chunks_within_radius(&self, center: Int3, radius: f32) -> impl Iterator<Item = ChunkRef> + '_ {
+        self.chunks.iter()
+            .filter(move |chunk| Chunk::distance_to_voxel(chunk.pos.load(Relaxed), center) <= radius)
+            .cloned()
+    }
+
+    /// Gives mutable [chunks][Chunk] that have voxels within `radius` (in voxels) of `center` voxel.
+    /// Chunks are prepared [for edit][ChunkArray::chunk_for_edit], so ones shared with
+    /// running tasks are copied instead of being skipped.
+    pub fn chunks_within_radius_mut(&mut self, center: Int3, radius: f32) -> impl Iterator<Item = &mut Chunk> + '_ {
+        let is_near = move |chunk: &ChunkRef| Chunk::distance_to_voxel(chunk.pos.load(Relaxed), center) <= radius;
+
+        let near_idxs = self.chunks.iter()
+            .positions(is_near)
+            .collect_vec();
+
+        for idx in near_idxs {
+            self.prepare_chunk_edit(idx);
+        }
+
+        self.chunks.iter_mut()
+            .filter(move |chunk| is_near(chunk))
+            .map(Arc::make_mut)
+    }
+
+    /// Counts non-air voxels across all [chunks][Chunk].
+    pub fn count_non_air(&self) -> usize {
+        self.chunks.iter()
+            .map(|chunk| chunk.count_non_air())
+            .sum()
+    }
+
+    /// Counts how many times each [id][Id] occurs in `voxel_ids`.
+    pub fn count_voxel_frequencies(voxel_ids: impl IntoIterator<Item = Id>) -> HashMap<Id, usize> {
+        let mut result = HashMap::new();
+        Self::accumulate_voxel_frequencies(&mut result, voxel_ids);
+        result
+    }
+
+    /// Adds occurrences of each [id][Id] in `voxel_ids` to existing `freqs`.
+    pub fn accumulate_voxel_frequencies(
+        freqs: &mut HashMap<Id, usize>, voxel_ids: impl IntoIterator<Item = Id>,
+    ) {
+        for id in voxel_ids {
+            *freqs.entry(id).or_insert(0) += 1;
+        }
+    }
+
+    /// Counts voxel [id][Id] frequencies of all generated chunks.
+    pub fn count_world_voxel_frequencies(&self) -> HashMap<Id, usize> {
+        let mut result = HashMap::new();
+
+        for chunk in self.chunks.iter().filter(|chunk| chunk.is_generated()) {
+            match chunk.info.load(Relaxed).fill_type {
+                FillType::AllSame(id) => *result.entry(id).or_insert(0) += Chunk::VOLUME,
+                FillType::Default | FillType::Sparse | FillType::Evicted =>
+                    Self::accumulate_voxel_frequencies(&mut result, chunk.ids()),
+            }
         }
 
         result
@@ -401,8 +1234,8 @@ impl ChunkArray {
             .map(|(idx, (voxel_ids, fill_type))| {
                 let chunk_pos = Self::idx_to_pos(idx, sizes);
                 match fill_type {
-                    FillType::Default =>
-                        Chunk::from_voxels(voxel_ids, chunk_pos),
+                    FillType::Default | FillType::Sparse | FillType::Evicted =>
+                        Chunk::from_voxels_or_air(voxel_ids, chunk_pos),
                     FillType::AllSame(id) =>
                         Chunk::new_same_filled(chunk_pos, id),
                 }
@@ -422,6 +1255,11 @@ impl ChunkArray {
         arr_sizes.x * arr_sizes.y * arr_sizes.z
     }
 
+    /// Same as [`ChunkArray::volume`] but gives [`None`] on overflow.
+    pub fn checked_volume(arr_sizes: USize3) -> Option<usize> {
+        arr_sizes.x.checked_mul(arr_sizes.y)?.checked_mul(arr_sizes.z)
+    }
+
     pub fn voxel_pos_to_coord_idx(voxel_pos: Int3, chunk_array_sizes: USize3) -> Option<USize3> {
         let chunk_pos = Chunk::local_pos(voxel_pos);
         let local_voxel_pos = Chunk::global_to_local_pos(chunk_pos, voxel_pos);
@@ -494,6 +1332,14 @@ impl ChunkArray {
             .map(|opt| opt.map(|idx| Arc::clone(&chunks[idx])))
     }
 
+    /// Checks that all in-bounds neighbor chunks of chunk on `pos` are generated.
+    /// Neighbors out of array bounds count as generated.
+    pub fn neighbors_generated(&self, pos: Int3) -> bool {
+        Self::get_adj_chunks_idxs(self.sizes, pos).inner.iter()
+            .flatten()
+            .all(|&idx| self.chunks[idx].is_generated())
+    }
+
     /// Gives '`iterator`' over adjacent to `pos` array indices.
     pub fn get_adj_chunks_idxs(sizes: USize3, pos: Int3) -> Sides<Option<usize>> {
         SpaceIter::adj_iter(pos)
@@ -501,12 +1347,35 @@ impl ChunkArray {
             .collect()
     }
 
+    /// Gives indices of chunks adjacent to chunk on `chunk_pos` that share a face with
+    /// voxel volume from `pos_from` to `pos_to` (excluded). The volume should lie in that chunk.
+    pub fn get_touched_adj_chunks_idxs(
+        sizes: USize3, chunk_pos: Int3, pos_from: Int3, pos_to: Int3,
+    ) -> Vec<usize> {
+        let bounds = Int3::ZERO..Int3::from(Chunk::SIZES);
+        let local_from = Chunk::global_to_local_pos(chunk_pos, pos_from);
+        let local_last = Chunk::global_to_local_pos(chunk_pos, pos_to - Int3::ONE);
+
+        iterator::offsets_from_border(local_from, bounds.clone()).into_iter()
+            .chain(iterator::offsets_from_border(local_last, bounds))
+            .unique()
+            .filter_map(|offset| Self::pos_to_idx(sizes, chunk_pos + offset))
+            .collect()
+    }
+
     /// Gives iterator over chunk coordinates.
     pub fn pos_iter(sizes: USize3) -> SpaceIter {
         let (start, end) = Self::pos_bounds(sizes);
         SpaceIter::new(start..end)
     }
 
+    /// Gives iterator over chunks with their positions in [`ChunkArray::pos_iter`] order
+    /// regardless of how chunks are stored. Saves rely on this order.
+    pub fn chunks_in_pos_order(&self) -> impl Iterator<Item = (Int3, ChunkRef)> + '_ {
+        Self::pos_iter(self.sizes)
+            .filter_map(|pos| Some((pos, self.get_chunk_by_pos(pos)?)))
+    }
+
     /// Gives iterator over all chunk's adjacents.
     pub fn adj_iter(&self) -> impl Iterator<Item = ChunkAdj> + '_ {
         Self::adj_iter_unbounded(&self.chunks, self.sizes)
@@ -537,6 +1406,151 @@ impl ChunkArray {
             .map(move |chunk_pos| Self::desired_lod_at(chunk_pos, cam_pos, threashold))
     }
 
+    /// Gives iterator over [LOD][Lod] each [chunk][Chunk] should be rendered with.
+    /// If [LOD is frozen][ChunkArray::freeze_lod] then chunks with active [LOD][Lod] keep it.
+    /// [Forced][ChunkArray::force_full_detail] chunks always get full detail.
+    /// LOD threashold is taken from [runtime config][runtime_cfg::RuntimeCfg::lod_threashold].
+    pub fn target_lod_iter(&self, cam_pos: vec3) -> impl Iterator<Item = Lod> + '_ {
+        self.target_lod_iter_with(cam_pos, runtime_cfg::get().lod_threashold)
+    }
+
+    /// Same as [`target_lod_iter`][ChunkArray::target_lod_iter] but with given LOD threashold.
+    fn target_lod_iter_with(&self, cam_pos: vec3, lod_threashold: f32) -> impl Iterator<Item = Lod> + '_ {
+        self.chunks.iter()
+            .zip(Self::desired_lod_iter(self.sizes, cam_pos, lod_threashold))
+            .map(|(chunk, desired_lod)| {
+                if self.full_detail_chunks.contains(&chunk.pos.load(Relaxed)) {
+                    return 0;
+                }
+
+                match chunk.info.load(Relaxed).active_lod {
+                    Some(active_lod) if self.freeze_lod => active_lod,
+                    _ => desired_lod,
+                }
+            })
+    }
+
+    /// Makes [chunks][Chunk] that overlap voxel volume from `pos_from` to `pos_to` (excluded)
+    /// request full detail [LOD][Lod] regardless of camera distance or stop doing so.
+    /// Enabled volume is clamped to [render distance][ChunkArray::render_distance]
+    /// from `pos_from` and to chunk array bounds.
+    pub fn force_full_detail(&mut self, pos_from: Int3, pos_to: Int3, enabled: bool) {
+        let is_in_range = |pos: Int3, from: Int3, to: Int3| (0..3)
+            .all(|axis| from.axis(axis) <= pos.axis(axis) && pos.axis(axis) < to.axis(axis));
+
+        if !enabled {
+            let chunk_pos_from = Chunk::local_pos(pos_from);
+            let chunk_pos_to   = Chunk::local_pos(pos_to - Int3::ONE) + Int3::ONE;
+
+            self.full_detail_chunks.retain(|&pos| !is_in_range(pos, chunk_pos_from, chunk_pos_to));
+            return;
+        }
+
+        let max_extent = (self.render_distance / Voxel::SIZE).ceil() as i32;
+        let max_pos_to = Int3::new(
+            pos_from.x.saturating_add(max_extent),
+            pos_from.y.saturating_add(max_extent),
+            pos_from.z.saturating_add(max_extent),
+        );
+
+        let chunk_pos_from = Chunk::local_pos(pos_from);
+        let chunk_pos_to   = Chunk::local_pos(pos_to.min(max_pos_to) - Int3::ONE) + Int3::ONE;
+
+        // Chunks out of array are not iterated so volume size does not matter.
+        self.full_detail_chunks.extend(
+            Self::pos_iter(self.sizes).filter(|&pos| is_in_range(pos, chunk_pos_from, chunk_pos_to))
+        );
+    }
+
+    /// Evicts voxels of [chunks][Chunk] farther than [`cfg::terrain::VOXEL_EVICTION_DISTANCE_IN_CHUNKS`]
+    /// from camera and restores them for chunks closer than [`cfg::terrain::VOXEL_RESTORE_DISTANCE_IN_CHUNKS`].
+    /// Meshes are kept. Chunks used by tasks and [forced][ChunkArray::force_full_detail] chunks are skipped.
+    /// Runs once per [eviction cooldown][ChunkArray::eviction_cooldown], voxels are compressed in tasks.
+    pub async fn evict_far_voxels(&mut self, cam_pos: vec3, dt: f32) {
+        self.try_finish_evict_tasks().await;
+
+        self.eviction_cooldown.tick(dt);
+        if !self.eviction_cooldown.try_trigger() {
+            return;
+        }
+
+        let cam_pos_in_chunks = cam_pos / Chunk::GLOBAL_SIZE;
+
+        for chunk in self.chunks.iter_mut() {
+            let chunk_pos = chunk.pos.load(Relaxed);
+            let dist = (vec3::from(chunk_pos) - cam_pos_in_chunks + vec3::all(0.5)).len();
+
+            if self.full_detail_chunks.contains(&chunk_pos)
+                || dist < cfg::terrain::VOXEL_RESTORE_DISTANCE_IN_CHUNKS
+            {
+                self.evict_tasks.remove(&chunk_pos);
+
+                if let Some(chunk) = Arc::get_mut(chunk) {
+                    chunk.ensure_voxels();
+                }
+            } else if cfg::terrain::VOXEL_EVICTION_DISTANCE_IN_CHUNKS < dist
+                && !self.evict_tasks.contains_key(&chunk_pos)
+            {
+                if chunk.is_evicted() {
+                    // Voxels read since the last pass stay decoded.
+                    if let Some(evicted) = Arc::get_mut(chunk).and_then(|chunk| chunk.evicted.as_mut()) {
+                        evicted.forget_unused_decoded();
+                    }
+                } else if chunk.can_evict_voxels() {
+                    let chunk = Arc::clone(chunk);
+                    self.evict_tasks.insert(
+                        chunk_pos, Task::spawn(async move { EvictedVoxels::new(&chunk) }),
+                    );
+                }
+            }
+        }
+    }
+
+    /// [Optimizes][Chunk::optimize] storage of [edited][ChunkArray::edited_chunks] chunks once per
+    /// [optimization cooldown][ChunkArray::optimization_cooldown], so edits themselves don't rebuild it.
+    /// Chunks held by tasks wait for the next pass.
+    pub fn optimize_edited_chunks(&mut self, dt: f32) {
+        self.optimization_cooldown.tick(dt);
+        if !self.optimization_cooldown.try_trigger() {
+            return;
+        }
+
+        let (chunks, sizes) = (&mut self.chunks, self.sizes);
+
+        self.edited_chunks.retain(|&chunk_pos| {
+            let Some(idx) = Self::pos_to_idx(sizes, chunk_pos) else { return false };
+
+            match Arc::get_mut(&mut chunks[idx]) {
+                Some(chunk) => {
+                    // Evicted voxels are already compressed.
+                    if !chunk.is_evicted() {
+                        chunk.optimize();
+                    }
+
+                    false
+                },
+                None => true,
+            }
+        });
+    }
+
+    /// Replaces voxels of [chunks][Chunk] with compressed ones from finished eviction tasks.
+    /// Chunks still used by other tasks get their voxels compressed again on next pass.
+    pub async fn try_finish_evict_tasks(&mut self) {
+        let iter = self.evict_tasks.iter_mut()
+            .map(|(&pos, task)| (pos, task));
+
+        for (pos, evicted) in Task::try_take_results(iter).await {
+            self.evict_tasks.remove(&pos);
+
+            let idx = Self::pos_to_idx(self.sizes, pos).expect("pos should be valid");
+
+            if let Some(chunk) = Arc::get_mut(&mut self.chunks[idx]) {
+                chunk.set_evicted(evicted);
+            }
+        }
+    }
+
     /// Gives iterator over all voxels in [`ChunkArray`].
     pub fn voxels(&self) -> impl Iterator<Item = Voxel> + '_ {
         self.chunks.iter()
@@ -561,13 +1575,13 @@ impl ChunkArray {
     fn get_targets_sorted(&self, cam_pos: vec3) -> Vec<(ChunkRef, ChunkAdj, MeshRef, Lod)> {
         let mut result: Vec<_> = self.chunks_with_adj()
             .zip(self.meshes.iter().cloned())
-            .zip(Self::desired_lod_iter(self.sizes, cam_pos, self.lod_threashold))
+            .zip(self.target_lod_iter(cam_pos))
             .map(|(((a, b), c), d)| (a, b, c, d))
             .collect();
 
         result.sort_by_key(|(chunk, _, _, _)| {
-            let pos = Chunk::global_pos(chunk.pos.load(Relaxed));
-            let dot = vec3::sqr(cam_pos - pos.into());
+            let pos = vec3::from(Chunk::global_pos(chunk.pos.load(Relaxed))) * Voxel::SIZE;
+            let dot = vec3::sqr(cam_pos - pos);
             
             match NotNan::new(dot) {
                 Ok(result) => result,
@@ -586,7 +1600,7 @@ impl ChunkArray {
     /// If task is incomplete then it will render active [LOD][Lod]
     /// of concrete [chunk][Chunk]. If it can't then it will do nothing.
     pub async fn render(
-        &mut self, target: &mut impl gl::Surface, draw_bundle: &ChunkDrawBundle<'_>,
+        &mut self, target: &mut impl gl::Surface, draw_bundle: &mut ChunkDrawBundle<'_>,
         uniforms: &impl gl::uniforms::Uniforms, facade: &dyn gl::backend::Facade, cam: &mut Camera,
     ) -> Result<(), ChunkRenderError> {
         #![allow(clippy::await_holding_refcell_ref)]
@@ -594,9 +1608,18 @@ impl ChunkArray {
         let sizes = self.sizes;
         if sizes == USize3::ZERO { return Ok(()) }
 
+        let uniforms = &ChunkUniforms::new(uniforms, grid::get(), cam.log_depth_coef());
+
+        let mut stats = RenderStats::default();
+
+        let n_tasks = self.n_tasks();
         self.try_finish_all_tasks(facade).await;
+        stats.n_tasks_finished += n_tasks.saturating_sub(self.n_tasks());
+
+        draw_bundle.set_backface_culling(!self.is_backface_culling_disabled);
 
         let targets = self.get_targets_sorted(cam.pos);
+        let visible: HashSet<Int3> = self.visible_chunks(cam).collect();
 
         for (mut chunk, chunk_adj, mesh, lod) in targets {
             let chunk_pos = chunk.pos.load(Relaxed);
@@ -604,38 +1627,47 @@ impl ChunkArray {
             if !chunk.is_generated() {
                 if Self::is_voxels_gen_task_running(&self.voxels_gen_tasks, chunk_pos) {
                     if let Some(new_chunk) = Self::try_finish_voxels_gen_task(&mut self.voxels_gen_tasks, chunk_pos).await {
+                        stats.n_tasks_finished += 1;
                         Self::drop_reader_tasks(&mut self.full_tasks, &mut self.low_tasks, chunk_pos);
 
-                        // * Safety:
-                        // * Safe, because there's no chunk readers due to tasks drop above
-                        unsafe {
-                            let _ = mem::replace(Arc::get_mut_unchecked(&mut chunk), new_chunk);
-                        }
+                        // Tasks may still hold old chunk, so it is replaced instead of written.
+                        let idx = Self::pos_to_idx(sizes, chunk_pos)
+                            .expect("pos should be valid");
+                        self.chunks[idx] = Arc::new(new_chunk);
+                        chunk = Arc::clone(&self.chunks[idx]);
                     }
                 }
                 
                 else if self.can_start_tasks() {
-                    Self::start_task_gen_voxels(&mut self.voxels_gen_tasks, chunk_pos, sizes);
+                    Self::start_task_gen_voxels(&mut self.voxels_gen_tasks, chunk_pos, Arc::clone(&self.generator));
+                    stats.n_tasks_started += 1;
+                    stats.record(ChunkOutcome::Skipped);
                     continue;
                 }
 
                 else {
+                    stats.record(ChunkOutcome::Skipped);
                     continue;
                 }
             }
 
             const CHUNK_MESH_PARTITION_DIST: f32 = 128.0;
 
-            let chunk_is_close_to_be_partitioned = vec3::len(
-                vec3::from(Chunk::global_pos(chunk_pos))
-                - cam.pos + vec3::from(Chunk::SIZES / 2)
-            ) <= CHUNK_MESH_PARTITION_DIST;
+            let chunk_center = (
+                vec3::from(Chunk::global_pos(chunk_pos)) + vec3::from(Chunk::SIZES / 2)
+            ) * Voxel::SIZE;
+
+            // Smooth meshes are not partitioned.
+            let chunk_is_close_to_be_partitioned
+                = self.meshing_mode == MeshingMode::Blocky
+                && vec3::len(chunk_center - cam.pos) <= CHUNK_MESH_PARTITION_DIST;
 
             if chunk_is_close_to_be_partitioned &&
                !self.partition_tasks.contains_key(&chunk_pos) &&
                !mesh.borrow().is_partitioned()
             {
                 Self::start_task_partitioning(&mut self.partition_tasks, Arc::clone(&chunk), chunk_adj.clone());
+                stats.n_tasks_started += 1;
             }
 
             let chunnk_can_be_connected =
@@ -647,44 +1679,116 @@ impl ChunkArray {
                 mesh.borrow_mut().connect_partitions(facade);
             }
 
-            let can_set_new_lod =
-                mesh.borrow().get_available_lods().contains(&lod) ||
-                Self::is_mesh_task_running(&self.full_tasks, &self.low_tasks, chunk_pos, lod) &&
-                Self::try_finish_mesh_task(
-                    &mut self.full_tasks, &mut self.low_tasks,
-                    chunk_pos, lod, &mut mesh.borrow_mut(), facade,
-                ).await.is_ok();
+            self.update_active_lod(&chunk, chunk_adj, &mesh, lod, facade, &mut stats).await;
 
-            if can_set_new_lod {
-                chunk.set_active_lod(&mesh.borrow(), lod);
+            // FIXME: make cam vis-check for light.
+            if !visible.contains(&chunk_pos) {
+                stats.record(ChunkOutcome::FrustumCulled);
             }
             
-            else if self.can_start_tasks() {
-                Self::start_task_gen_vertices(
-                    &mut self.full_tasks,
-                    &mut self.low_tasks,
-                    Arc::clone(&chunk),
-                    chunk_adj.clone(),
-                    lod,
-                ).await;
-            }
-
-            Self::drop_all_useless_tasks(&mut self.full_tasks, &mut self.low_tasks, lod, chunk_pos);
+            else if chunk.can_render_active_lod(&mesh.borrow()) {
+                let active_lod = chunk.info.load(Relaxed).active_lod.unwrap();
 
-            if !chunk.can_render_active_lod(&mesh.borrow()) {
-                chunk.try_set_best_fit_lod(&mesh.borrow(), lod);
-            }
+                stats.record(ChunkOutcome::Drawn {
+                    n_triangles: mesh.borrow().triangle_count(active_lod),
+                    n_draw_calls: mesh.borrow().draw_call_count(active_lod),
+                });
 
-            // FIXME: make cam vis-check for light.
-            if chunk.can_render_active_lod(&mesh.borrow()) && chunk.is_visible_by_camera(cam) {
-                let active_lod = chunk.info.load(Relaxed).active_lod.unwrap();
                 chunk.render(&mut mesh.borrow_mut(), target, draw_bundle, uniforms, active_lod)?
             }
+            
+            else {
+                stats.record(ChunkOutcome::Skipped);
+            }
         }
 
+        self.render_stats = stats;
+
         Ok(())
     }
 
+    /// Sets `lod` as active one if its mesh is cached or its task is finished.
+    /// Otherwise makes the mesh or starts a task for it and sets the best fit [LOD][Lod].
+    pub async fn update_active_lod(
+        &mut self, chunk: &ChunkRef, chunk_adj: ChunkAdj, mesh: &MeshRef, lod: Lod,
+        facade: &dyn gl::backend::Facade, stats: &mut RenderStats,
+    ) {
+        #![allow(clippy::await_holding_refcell_ref)]
+
+        let chunk_pos = chunk.pos.load(Relaxed);
+        let is_lod_available = mesh.borrow().get_available_lods().contains(&lod);
+
+        let is_mesh_task_finished =
+            !is_lod_available &&
+            Self::is_mesh_task_running(&self.full_tasks, &self.low_tasks, chunk_pos, lod) &&
+            Self::try_finish_mesh_task(
+                &mut self.full_tasks, &mut self.low_tasks,
+                chunk_pos, lod, &mut mesh.borrow_mut(), facade,
+            ).await.is_ok();
+
+        if is_mesh_task_finished {
+            stats.n_tasks_finished += 1;
+        }
+
+        let can_set_new_lod = is_lod_available || is_mesh_task_finished;
+
+        if can_set_new_lod {
+            chunk.set_active_lod(&mut mesh.borrow_mut(), lod)
+                .unwrap_or_else(|err| logger::log!(
+                    Error, from = "chunk-array", "failed to set LOD of chunk in {chunk_pos}: {err}",
+                ));
+        }
+        
+        else if let Some(vertices) = self.mesh_or_start_task(chunk, chunk_adj, lod, stats).await {
+            vertices.upload(&mut mesh.borrow_mut(), facade);
+
+            chunk.set_active_lod(&mut mesh.borrow_mut(), lod)
+                .unwrap_or_else(|err| logger::log!(
+                    Error, from = "chunk-array", "failed to set LOD of chunk in {chunk_pos}: {err}",
+                ));
+        }
+
+        Self::drop_all_useless_tasks(&mut self.full_tasks, &mut self.low_tasks, lod, chunk_pos);
+
+        if !chunk.can_render_active_lod(&mesh.borrow()) {
+            chunk.try_set_best_fit_lod(&mut mesh.borrow_mut(), lod);
+        }
+    }
+
+    /// Checks winding of all full detail meshes and logs [mismatching][crate::terrain::chunk::mesh::find_winding_mismatches]
+    /// triangles. Gives total number of mismatches.
+    pub fn validate_winding(&self) -> usize {
+        let mut n_mismatches = 0;
+
+        for (chunk, mesh) in self.chunks.iter().zip(self.meshes.iter()) {
+            let mismatches = mesh.borrow().find_winding_mismatches();
+            if mismatches.is_empty() { continue }
+
+            logger::log!(
+                Error, from = "chunk-array",
+                "{n} triangles of chunk in {pos} have wrong winding: {mismatches:?}",
+                n = mismatches.len(), pos = chunk.pos.load(Relaxed),
+            );
+
+            n_mismatches += mismatches.len();
+        }
+
+        logger::log!(Info, from = "chunk-array", "winding validation found {n_mismatches} mismatches");
+        n_mismatches
+    }
+
+    /// Gives positions of [chunks][Chunk] to draw. They are in camera frustum and render
+    /// distance with [margin][cfg::terrain::VISIBILITY_MARGIN] so they don't pop in on fast rotation.
+    pub fn visible_chunks(&self, cam: &mut Camera) -> impl Iterator<Item = Int3> {
+        let frustum = cam.get_frustum().clone();
+        let (cam_pos, render_distance) = (cam.pos, cam.far_plane_dist);
+
+        Self::pos_iter(self.sizes)
+            .filter(move |&pos| Chunk::is_visible_with_margin(
+                pos, cam_pos, &frustum, render_distance, cfg::terrain::VISIBILITY_MARGIN,
+            ))
+    }
+
     pub fn drop_all_useless_tasks(
         full_tasks: &mut HashMap<Int3, FullTask>,
         low_tasks: &mut HashMap<(Int3, Lod), LowTask>,
@@ -761,16 +1865,13 @@ impl ChunkArray {
         for (pos, voxels) in Task::try_take_results(iter).await {
             self.voxels_gen_tasks.remove(&pos);
 
-            let mut chunk = self.get_chunk_by_pos(pos)
+            let idx = Self::pos_to_idx(self.sizes, pos)
                 .expect("pos should be valid");
 
             Self::drop_reader_tasks(&mut self.full_tasks, &mut self.low_tasks, pos);
-            
-            // * Safety:
-            // * Safe, because there's no chunk readers due to tasks drop above.
-            unsafe {
-                let _ = mem::replace(Arc::get_mut_unchecked(&mut chunk), Chunk::from_voxels(voxels, pos));
-            }
+
+            // Tasks may still hold old chunk, so it is replaced instead of written.
+            self.chunks[idx] = Arc::new(Chunk::from_voxels_or_air(voxels, pos));
         }
     }
 
@@ -814,9 +1915,11 @@ impl ChunkArray {
         }
     }
 
-    pub fn start_task_gen_voxels(tasks: &mut HashMap<Int3, GenTask>, pos: Int3, sizes: USize3) {
+    pub fn start_task_gen_voxels(
+        tasks: &mut HashMap<Int3, GenTask>, pos: Int3, generator: Arc<dyn WorldGenerator>,
+    ) {
         let prev_value = tasks.insert(pos, Task::spawn(async move {
-            Chunk::generate_voxels(pos, sizes)
+            Chunk::generate_voxels_by(generator.as_ref(), pos)
         }));
 
         assert!(prev_value.is_none(), "threre should be only one task");
@@ -833,19 +1936,29 @@ impl ChunkArray {
     pub async fn start_task_gen_vertices(
         full_tasks: &mut HashMap<Int3, FullTask>,
         low_tasks: &mut HashMap<(Int3, Lod), LowTask>,
-        chunk: ChunkRef, adj: ChunkAdj, lod: Lod,
-    ) {
+        chunk: ChunkRef, adj: ChunkAdj, lod: Lod, mode: MeshingMode,
+    ) -> bool {
         let chunk_pos = chunk.pos.load(Relaxed);
         if lod == 0 && full_tasks.contains_key(&chunk_pos) ||
            lod != 0 && low_tasks.contains_key(&(chunk_pos, lod)) ||
            !chunk.is_generated() ||
            !Self::is_adj_generated(&adj).await
-        { return }
+        { return false }
 
         match lod {
+            0 if mode == MeshingMode::Smooth => {
+                let prev = full_tasks.insert(chunk_pos, Task::spawn(async move {
+                    chunk.make_vertices_smooth(adj, 0)
+                }));
+                assert!(prev.is_none(), "there should be only one task");
+            },
+
             0 => {
+                // Edits copy shared chunks, so task copies boundary of adjacent chunks
+                // it holds itself instead of doing it on the render thread.
                 let prev = full_tasks.insert(chunk_pos, Task::spawn(async move {
-                    chunk.make_vertices_detailed(adj)
+                    let boundary = AdjBoundary::new(chunk_pos, &adj);
+                    chunk.make_vertices_detailed_from_boundary(&boundary)
                 }));
                 assert!(prev.is_none(), "there should be only one task");
             },
@@ -857,6 +1970,56 @@ impl ChunkArray {
                 assert!(prev.is_none(), "there should be only one task");
             },
         }
+
+        true
+    }
+
+    /// Makes vertices of `chunk` on the current thread. Gives [`None`] if `chunk`
+    /// or its [adjacent chunks][ChunkAdj] are not generated yet.
+    pub async fn make_vertices_sync(
+        chunk: &Chunk, adj: ChunkAdj, lod: Lod, mode: MeshingMode,
+    ) -> Option<ChunkVertices> {
+        if !chunk.is_generated() || !Self::is_adj_generated(&adj).await {
+            return None;
+        }
+
+        Some(match lod {
+            0 => ChunkVertices::Full(chunk.make_vertices_full(adj, mode)),
+            lod => ChunkVertices::Low(chunk.make_vertices_low(adj, lod), lod),
+        })
+    }
+
+    /// Makes `lod` vertices of `chunk` [synchronously][ChunkArray::synchronous_meshing] if it is
+    /// allowed, otherwise starts vertices task. Gives vertices to be uploaded to `chunk` mesh.
+    pub async fn mesh_or_start_task(
+        &mut self, chunk: &ChunkRef, adj: ChunkAdj, lod: Lod, stats: &mut RenderStats,
+    ) -> Option<ChunkVertices> {
+        if self.can_mesh_synchronously() {
+            return Self::make_vertices_sync(chunk, adj, lod, self.meshing_mode).await;
+        }
+
+        if self.can_start_tasks() {
+            let is_started = Self::start_task_gen_vertices(
+                &mut self.full_tasks,
+                &mut self.low_tasks,
+                Arc::clone(chunk),
+                adj,
+                lod,
+                self.meshing_mode,
+            ).await;
+
+            if is_started {
+                stats.n_tasks_started += 1;
+            }
+        }
+
+        None
+    }
+
+    /// Checks that meshes can be made [synchronously][ChunkArray::synchronous_meshing].
+    /// It is not done while saving or reading so the main thread doesn't wait for IO.
+    pub fn can_mesh_synchronously(&self) -> bool {
+        self.synchronous_meshing && self.saving_handle.is_none() && self.reading_handle.is_none()
     }
 
     pub fn start_task_partitioning(
@@ -873,7 +2036,7 @@ impl ChunkArray {
         if let Some(task) = tasks.get_mut(&pos) {
             if let Some(voxel_ids) = task.try_take_result().await {
                 tasks.remove(&pos);
-                return Some(Chunk::from_voxels(voxel_ids, pos))
+                return Some(Chunk::from_voxels_or_air(voxel_ids, pos))
             }
         }
 
@@ -931,9 +2094,30 @@ impl ChunkArray {
         }
     }
 
+    /// Replaces all chunks with empty ones of `sizes` so they will be generated again.
+    /// Generator kind and world bounds are kept.
+    pub fn regenerate(&mut self, sizes: USize3) -> Result<(), UserFacingError> {
+        let new_chunks = Self::new_empty_chunks(sizes)?;
+        self.drop_tasks();
+
+        let (generator_kind, world_bounds) = (self.generator_kind, self.world_bounds);
+        let _ = mem::replace(self, new_chunks);
+
+        self.generator = generator_kind.make(self.sizes, world_bounds);
+        self.generator_kind = generator_kind;
+        self.world_bounds = world_bounds;
+
+        Ok(())
+    }
+
     pub fn can_start_tasks(&self) -> bool {
+        self.can_start_tasks_with(&runtime_cfg::get())
+    }
+
+    /// Same as [`can_start_tasks`][ChunkArray::can_start_tasks] but with given [config][runtime_cfg::RuntimeCfg].
+    fn can_start_tasks_with(&self, cfg: &runtime_cfg::RuntimeCfg) -> bool {
         self.saving_handle.is_none() && self.reading_handle.is_none() &&
-        self.low_tasks.len() + self.full_tasks.len() <= cfg::terrain::MAX_TASKS
+        self.low_tasks.len() + self.full_tasks.len() <= cfg.max_tasks
     }
 
     pub fn drop_tasks(&mut self) {
@@ -941,13 +2125,24 @@ impl ChunkArray {
         drop(mem::take(&mut self.low_tasks));
         drop(mem::take(&mut self.voxels_gen_tasks));
         drop(mem::take(&mut self.partition_tasks));
+        drop(mem::take(&mut self.evict_tasks));
+    }
+
+    /// Gives number of running tasks of all kinds.
+    pub fn n_tasks(&self) -> usize {
+        self.low_tasks.len() +
+        self.full_tasks.len() +
+        self.voxels_gen_tasks.len() +
+        self.partition_tasks.len() +
+        self.evict_tasks.len()
     }
 
     pub fn any_task_running(&self) -> bool {
         !self.low_tasks.is_empty() ||
         !self.full_tasks.is_empty() ||
         !self.voxels_gen_tasks.is_empty() ||
-        !self.partition_tasks.is_empty()
+        !self.partition_tasks.is_empty() ||
+        !self.evict_tasks.is_empty()
     }
 
     pub fn spawn_control_window(&mut self, ui: &imgui::Ui) {
@@ -971,11 +2166,104 @@ impl ChunkArray {
                     n = self.partition_tasks.len(),
                 ));
 
-                ui.slider(
-                    "Chunks lod threashold",
-                    0.01, 20.0,
-                    &mut self.lod_threashold,
-                );
+                ui.text(format!(
+                    "{n} voxel eviction tasks.",
+                    n = self.evict_tasks.len(),
+                ));
+
+                ui.text(format!(
+                    "{n} non-air voxels.",
+                    n = self.count_non_air(),
+                ));
+
+                ui.checkbox("Picking debug", &mut self.is_picking_debug);
+                ui.checkbox("Disable backface culling", &mut self.is_backface_culling_disabled);
+
+                if ui.button("Validate winding") {
+                    self.validate_winding();
+                }
+                ui.checkbox("Freeze LOD", &mut self.freeze_lod);
+                ui.checkbox("Synchronous meshing", &mut self.synchronous_meshing);
+
+                let mut is_smooth = self.meshing_mode == MeshingMode::Smooth;
+                if ui.checkbox("Smooth terrain", &mut is_smooth) {
+                    self.meshing_mode = match is_smooth {
+                        true => MeshingMode::Smooth,
+                        false => MeshingMode::Blocky,
+                    };
+
+                    self.is_mesh_rebuild_requested = true;
+                }
+
+                if ui.checkbox("Fill mode", &mut self.is_fill_mode) {
+                    self.fill_drag = Default::default();
+                }
+
+                ui.checkbox("Brush", &mut self.brush.is_enabled);
+                if self.brush.is_enabled {
+                    self.brush.build_controls(ui);
+                }
+
+                if self.is_fill_mode {
+                    let mut fill_id = self.fill_id as i32;
+                    if ui.input_int("Fill voxel id", &mut fill_id).build() {
+                        match Id::try_from(fill_id).ok().filter(|&id| voxel::is_id_valid(id)) {
+                            Some(id) => self.fill_id = id,
+                            None => logger::log!(Error, from = "chunk-array", "invalid fill voxel id {fill_id}"),
+                        }
+                    }
+                }
+
+                let mut runtime_cfg = runtime_cfg::get();
+                if ui.slider("Chunks lod threashold", 0.01, 20.0, &mut runtime_cfg.lod_threashold) {
+                    runtime_cfg::set(runtime_cfg);
+                }
+
+                ui.text(format!("Rendered triangles: {}", self.render_stats.n_triangles));
+
+                ui.checkbox("Shared save dictionary", &mut self.is_dictionary_shared);
+                ui.checkbox("Streamed save", &mut self.is_save_streamed);
+                ui.checkbox("Teleport to surface on load", &mut self.is_surface_teleport_enabled);
+
+                if self.is_overwrite_requested {
+                    ui.separator();
+
+                    ui.text(format!("Save '{name}' already exists. Overwrite it?", name = Self::WORLD_SAVE_NAME));
+
+                    if ui.button("Overwrite") {
+                        self.start_saving(true);
+                    }
+
+                    ui.same_line();
+
+                    if ui.button("Cancel") {
+                        self.is_overwrite_requested = false;
+                    }
+                }
+
+                if let Some(progress) = self.saving_progress() {
+                    ui.separator();
+
+                    imgui::ProgressBar::new(progress)
+                        .overlay_text(&format!("Saving: {percent:.1}%", percent = 100.0 * progress))
+                        .build(ui);
+
+                    if ui.button("Cancel saving") {
+                        self.cancel_saving();
+                    }
+                }
+
+                if let Some(progress) = self.reading_progress() {
+                    ui.separator();
+
+                    imgui::ProgressBar::new(progress)
+                        .overlay_text(&format!("Reading: {percent:.1}%", percent = 100.0 * progress))
+                        .build(ui);
+
+                    if ui.button("Cancel reading") {
+                        self.cancel_reading();
+                    }
+                }
 
                 ui.separator();
 
@@ -984,56 +2272,71 @@ impl ChunkArray {
                 let mut sizes = GENERATOR_SIZES.lock()
                     .unwrap();
 
-                ui.input_scalar_n("Sizes", &mut *sizes).build();
+                let mut input = sizes.map(|size| size as i32);
+                if ui.input_int3("Sizes", &mut input).build() {
+                    match Self::sizes_from_input(Int3::from(input)) {
+                        Ok(valid_sizes) => {
+                            *sizes = valid_sizes.as_array();
+                            self.sizes_input_error = None;
+                        },
+                        Err(err) => self.sizes_input_error = Some(err.to_string()),
+                    }
+                }
+
+                if let Some(msg) = &self.sizes_input_error {
+                    ui.text_colored([1.0, 0.3, 0.3, 1.0], msg);
+                }
+
+                if let Some(_combo) = ui.begin_combo("Generator", self.generator_kind.to_string()) {
+                    for kind in GeneratorKind::ALL {
+                        if ui.selectable_config(kind.to_string())
+                            .selected(kind == self.generator_kind)
+                            .build()
+                        {
+                            self.generator_kind = kind;
+                        }
+                    }
+                }
+
+                ui.input_int("World min Y", &mut self.world_bounds.min_y).build();
+                ui.input_int("World max Y", &mut self.world_bounds.max_y).build();
 
                 if ui.button("Generate") {
-                    self.drop_tasks();
-                    match Self::new_empty_chunks(USize3::from(*sizes)) {
-                        Ok(new_chunks) => {
-                            let _ = mem::replace(self, new_chunks);
-                        },
-                        Err(err) => logger::log!(Error, from = "chunk-array", "{err}")
+                    // Stored sizes may be zero before any input.
+                    let new_sizes = USize3::from(*sizes);
+                    let new_sizes = USize3::new(new_sizes.x.max(1), new_sizes.y.max(1), new_sizes.z.max(1));
+                    *sizes = new_sizes.as_array();
+
+                    if let Err(err) = self.regenerate(new_sizes) {
+                        logger::log!(Error, from = "chunk-array", "{err}");
                     }
                 }
             });
     }
 
-    pub async fn process_commands(&mut self, facade: &dyn Facade) {
-        #![allow(clippy::await_holding_lock)]
-
-        use crate::app::utils::terrain::chunk::commands::*;
+    /// Applies all queued [commands][Command] without touching meshes.
+    /// Gives [tracker][ChangeTracker] of chunks to reload.
+    pub fn receive_commands(&mut self) -> ChangeTracker {
+        use crate::app::utils::terrain::chunk::commands::{COMMAND_CHANNEL, WORLD_SIZES, record};
 
         let mut commands = COMMAND_CHANNEL.lock().unwrap();
         let mut change_tracker = ChangeTracker::new(self.sizes);
 
-        use Command::*;
-        while let Ok(command) = commands.receiver.try_recv() {
-            match command {
-                SetVoxel { pos, new_id } => {
-                    let old_id = self.set_voxel(pos, new_id)
-                        .unwrap_or_else(|err| {
-                            logger::log!(Error, from = "chunk-array", "failed to set voxel: {err}");
-                            0
-                        });
-
-                    if old_id != new_id {
-                        change_tracker.track_voxel(pos);
-                    }
-                },
-
-                FillVoxels { pos_from, pos_to, new_id } => {
-                    let _is_changed = self.fill_voxels(pos_from, pos_to, new_id)
-                        .unwrap_or_else(|err| {
-                            logger::log!(Error, from = "chunk-array", "failed to fill voxels: {err}");
-                            false
-                        });
-                }
+        while let Ok(request) = commands.receiver.try_recv() {
+            // Requester has stopped waiting for the result.
+            if !request.claim() { continue }
 
-                DropAllMeshes => self.drop_all_meshes(),
-            }
+            record(request.command);
+            self.handle_request(request, &mut change_tracker);
         }
 
-        drop(commands);
+        *WORLD_SIZES.lock().unwrap() = self.sizes.as_array();
+
+        change_tracker
+    }
+
+    pub async fn process_commands(&mut self, facade: &dyn Facade) {
+        let change_tracker = self.receive_commands();
 
         let idxs_to_reload = change_tracker.idxs_to_reload_partitioning();
         let n_changed = idxs_to_reload.len();
@@ -1046,18 +2349,136 @@ impl ChunkArray {
         }
     }
 
-    pub async fn reload_chunk(&self, idx: usize, facade: &dyn Facade) {
-        let chunk_pos = Self::idx_to_pos(idx, self.sizes);
-        let adj = self.get_adj_chunks(chunk_pos);
+    /// Applies [requested][CommandRequest] command, logs its failure and replies with result.
+    pub fn handle_request(&mut self, request: CommandRequest, change_tracker: &mut ChangeTracker) {
+        let result = self.apply_command(request.command, change_tracker);
 
-        if let Some(chunk) = self.chunks.get(idx) {
-            let mut mesh = self.meshes[idx].borrow_mut();
-            chunk.generate_mesh(&mut mesh, 0, adj, facade);
+        if let Err(err) = &result {
+            logger::log!(Error, from = "chunk-array", "{err}");
         }
+
+        request.reply(result);
     }
 
-    pub async fn reload_chunk_partitioning(
-        &self, chunk_idx: usize, partition_idx: usize, facade: &dyn Facade,
+    /// Applies one [command][Command] to chunk array. Changed voxels are tracked by `change_tracker`.
+    /// Voxel edits are [emitted][events::emit] as [events][events::VoxelEvent].
+    pub fn apply_command(&mut self, command: Command, change_tracker: &mut ChangeTracker) -> CommandResult {
+        use Command::*;
+
+        match command {
+            SetVoxel { pos, new_id } => {
+                let old_id = self.set_voxel(pos, new_id).map_err(|err| CommandError(
+                    format!("failed to set voxel in {pos} to {new_id}: {err}")
+                ))?;
+
+                if old_id != new_id {
+                    change_tracker.track_voxel(pos);
+                    events::emit_edit(pos, old_id, new_id);
+                }
+            },
+
+            FillVoxels { pos_from, pos_to, new_id } => {
+                let is_changed = self.fill_voxels(pos_from, pos_to, new_id).map_err(|err| CommandError(
+                    format!("failed to fill voxels from {pos_from} to {pos_to}: {err}")
+                ))?;
+
+                if is_changed {
+                    change_tracker.track_region(pos_from, pos_to);
+                    events::emit(events::VoxelEvent::Filled { pos_from, pos_to, id: new_id });
+                }
+            },
+
+            Interact { pos } => {
+                let voxel = self.get_voxel(pos).ok_or_else(|| CommandError(
+                    format!("there is no voxel to interact with in {pos}")
+                ))?;
+
+                let new_id = self.interact(voxel).map_err(|err| CommandError(
+                    format!("failed to interact with voxel in {pos}: {err}")
+                ))?;
+
+                if let Some(new_id) = new_id {
+                    change_tracker.track_voxel(pos);
+                    events::emit_edit(pos, voxel.data.id, new_id);
+                }
+            },
+
+            Explode { center, radius, seed } => {
+                for (pos, old_id) in self.explode(center, radius, seed) {
+                    change_tracker.track_voxel(pos);
+                    events::emit_edit(pos, old_id, AIR_VOXEL_DATA.id);
+                }
+            },
+
+            ForceFullDetail { pos_from, pos_to, enabled } =>
+                self.force_full_detail(pos_from, pos_to, enabled),
+
+            SetSeed { seed } => {
+                generator::set_seed(seed);
+                generator::rebuild_noise();
+
+                self.regenerate(self.sizes).map_err(|err| CommandError(
+                    format!("failed to regenerate world with seed {seed}: {err}")
+                ))?;
+
+                // Tracked voxels belong to the old world.
+                *change_tracker = ChangeTracker::new(self.sizes);
+            },
+
+            DropAllMeshes => self.drop_all_meshes(),
+
+            RebuildMeshes => self.is_mesh_rebuild_requested = true,
+
+            Paint { center, shape, radius, new_id } => {
+                if !(0..=Brush::MAX_RADIUS).contains(&radius) {
+                    return Err(CommandError(format!(
+                        "brush radius {radius} is not in 0..={max}", max = Brush::MAX_RADIUS,
+                    )));
+                }
+
+                // Cube without unbreakable voxels is filled at once.
+                if shape == BrushShape::Cube {
+                    let offset = Int3::all(radius);
+                    let (pos_from, pos_to) = (center - offset, center + offset + Int3::ONE);
+
+                    if let Ok(is_changed) = self.fill_voxels(pos_from, pos_to, new_id) {
+                        if is_changed {
+                            change_tracker.track_region(pos_from, pos_to);
+                            events::emit(events::VoxelEvent::Filled { pos_from, pos_to, id: new_id });
+                        }
+
+                        return Ok(());
+                    }
+                }
+
+                let brush = Brush { shape, radius, id: new_id, ..Brush::DEFAULT };
+
+                let changed = self.paint(&brush, center).map_err(|err| CommandError(
+                    format!("failed to paint voxels around {center} with {new_id}: {err}")
+                ))?;
+
+                for (pos, old_id) in changed {
+                    change_tracker.track_voxel(pos);
+                    events::emit_edit(pos, old_id, new_id);
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    pub async fn reload_chunk(&self, idx: usize, facade: &dyn Facade) {
+        let chunk_pos = Self::idx_to_pos(idx, self.sizes);
+        let adj = self.get_adj_chunks(chunk_pos);
+
+        if let Some(chunk) = self.chunks.get(idx) {
+            let mut mesh = self.meshes[idx].borrow_mut();
+            chunk.generate_mesh(&mut mesh, 0, adj, self.meshing_mode, facade);
+        }
+    }
+
+    pub async fn reload_chunk_partitioning(
+        &self, chunk_idx: usize, partition_idx: usize, facade: &dyn Facade,
     ) {
         let chunk_pos = Self::idx_to_pos(chunk_idx, self.sizes);
         let adj = self.get_adj_chunks(chunk_pos);
@@ -1073,44 +2494,421 @@ impl ChunkArray {
         }
     }
 
+    /// Gives position of voxel that contains world point `pos` if voxels are `voxel_size` large.
+    pub fn world_to_voxel_pos(pos: vec3, voxel_size: f32) -> Int3 {
+        Int3::new(
+            (pos.x / voxel_size).round() as i32,
+            (pos.y / voxel_size).round() as i32,
+            (pos.z / voxel_size).round() as i32,
+        )
+    }
+
     pub fn trace_ray(&self, ray: Line, max_steps: usize) -> impl Iterator<Item = Voxel> + '_ {
+        let step = Self::TRACE_STEP_IN_VOXELS * Voxel::SIZE;
+
         (0..max_steps)
             .filter_map(move |i| {
-                let pos = ray.point_along(i as f32 * 0.125);
-                let pos = Int3::new(
-                    pos.x.round() as i32,
-                    pos.y.round() as i32,
-                    pos.z.round() as i32,
-                );
-
-                self.get_voxel(pos)
+                let pos = ray.point_along(i as f32 * step);
+                self.get_voxel(Self::world_to_voxel_pos(pos, Voxel::SIZE))
             })
     }
 
-    pub async fn proccess_camera_input(&mut self, cam: &Camera) {
+    /// Gives first non-air voxel on the `ray` with the face it was hit from.
+    pub fn trace_ray_hit(&self, ray: Line, max_steps: usize) -> Option<RayHit> {
+        self.trace_ray_hit_scaled(ray, max_steps, Voxel::SIZE)
+    }
+
+    /// Same as [`ChunkArray::trace_ray_hit`] but voxels are treated as `voxel_size` large.
+    /// Ray and hit distance are in world units, trace step scales with `voxel_size`.
+    pub fn trace_ray_hit_scaled(&self, ray: Line, max_steps: usize, voxel_size: f32) -> Option<RayHit> {
+        let step = Self::TRACE_STEP_IN_VOXELS * voxel_size;
+        let mut prev_pos = None;
+
+        for i in 0..max_steps {
+            let distance = i as f32 * step;
+            let pos = Self::world_to_voxel_pos(ray.point_along(distance), voxel_size);
+
+            let Some(voxel) = self.get_voxel(pos) else { continue };
+
+            if !voxel.is_air() {
+                let normal = match prev_pos {
+                    Some(prev_pos) => Self::entry_normal(ray, pos, prev_pos, voxel_size),
+                    None => Int3::ZERO,
+                };
+
+                return Some(RayHit { voxel, normal, distance });
+            }
+
+            prev_pos = Some(pos);
+        }
+
+        None
+    }
+
+    /// Gives unit normal of the face of voxel in `pos` that `ray` enters through coming
+    /// from voxel in `prev_pos`. If the step crosses several faces at once (diagonal step),
+    /// the ray has entered through the last crossed one.
+    fn entry_normal(ray: Line, pos: Int3, prev_pos: Int3, voxel_size: f32) -> Int3 {
+        let origin = ray.point_along(0.0);
+        let dir = ray.point_along(1.0) - origin;
+        let diff: Int3 = prev_pos - pos;
+
+        [
+            (diff.x.signum(), origin.x, dir.x, pos.x, veci!(diff.x.signum(), 0, 0)),
+            (diff.y.signum(), origin.y, dir.y, pos.y, veci!(0, diff.y.signum(), 0)),
+            (diff.z.signum(), origin.z, dir.z, pos.z, veci!(0, 0, diff.z.signum())),
+        ]
+        .into_iter()
+        .filter(|&(sign, ..)| sign != 0)
+        .map(|(sign, origin, dir, pos, normal)| {
+            // Voxels are centered on their positions.
+            let face = (pos as f32 + 0.5 * sign as f32) * voxel_size;
+
+            let entry = match dir == 0.0 {
+                true => f32::NEG_INFINITY,
+                false => (face - origin) / dir,
+            };
+
+            (entry, normal)
+        })
+        .max_by(|(lhs, _), (rhs, _)| lhs.total_cmp(rhs))
+        .map_or(Int3::ZERO, |(_, normal)| normal)
+    }
+
+    /// Gives height of the highest non-air voxel in `(x, z)` column.
+    /// Returns [`None`] if column is out of the array or has no solid voxels.
+    pub fn ground_height_at(&self, x: i32, z: i32) -> Option<i32> {
+        let (min, max) = self.voxel_world_bounds();
+
+        (min.y..=max.y).rev()
+            .find(|&y| self.get_voxel(veci!(x, y, z)).is_some_and(|voxel| !voxel.is_air()))
+    }
+
+    /// Gives position one voxel above the ground under `cam_pos` if camera is buried
+    /// inside a solid voxel or is too deep under the surface. Otherwise gives [`None`].
+    pub fn surface_teleport_pos(&self, cam_pos: vec3) -> Option<vec3> {
+        let pos = Self::world_to_voxel_pos(cam_pos, Voxel::SIZE);
+        let ground = self.ground_height_at(pos.x, pos.z)?;
+
+        let is_buried = self.get_voxel(pos)
+            .is_some_and(|voxel| !voxel.is_air());
+        let is_too_deep = pos.y < ground - Self::MAX_DEPTH_UNDER_SURFACE;
+
+        (is_buried || is_too_deep)
+            .then(|| vec3::from(veci!(pos.x, ground + 1, pos.z)) * Voxel::SIZE)
+    }
+
+    /// Moves camera onto the surface if it is [buried][ChunkArray::surface_teleport_pos].
+    pub fn teleport_to_surface(&self, cam: &mut Camera) {
+        let Some(pos) = self.surface_teleport_pos(cam.pos) else { return };
+
+        cam.set_position(pos.x, pos.y, pos.z);
+        logger::log!(Info, from = "chunk-array", "camera is moved to the surface at {pos:?}");
+    }
+
+    /// Gives [id][Id] of the first non-air voxel on the `ray` like an eyedropper does.
+    pub fn pick_voxel_id(&self, ray: Line) -> Option<Id> {
+        self.trace_ray_hit(ray, Self::MAX_TRACE_STEPS)
+            .map(|hit| hit.voxel.data.id)
+    }
+
+    /// Computes voxel box spanned by dragging from `from` to `to` voxels (both included).
+    /// Gives `(pos_from, pos_to)` as [`ChunkArray::fill_voxels`] expects, `pos_to` is excluded.
+    pub fn drag_box(from: Int3, to: Int3) -> (Int3, Int3) {
+        (from.min(to), from.max(to) + Int3::ONE)
+    }
+
+    /// Gives position of voxel being broken and its breaking progress from 0 to 1.
+    pub fn breaking_voxel(&self) -> Option<(Int3, f32)> {
+        self.breaking.current(|pos| self.get_voxel(pos).map(|voxel| voxel.data.hardness))
+    }
+
+    /// Gives box that is being filled by dragging, if any.
+    pub fn fill_preview_box(&self) -> Option<(Int3, Int3)> {
+        let (from, to) = self.fill_drag.dragging()?;
+        Some(Self::drag_box(from, to))
+    }
+
+    /// Drags fill box with left mouse button and fills it on release.
+    fn process_fill_drag(&mut self, hit: Option<RayHit>, cam: &Camera) {
+        use super::commands::{command, Command};
+
+        let is_pressed = mouse::is_left_pressed() && cam.grabbes_cursor;
+        self.fill_drag.update(is_pressed, hit.map(|hit| hit.voxel.pos));
+
+        if let Some((from, to)) = self.fill_drag.take_released() {
+            let (pos_from, pos_to) = Self::drag_box(from, to);
+            command(Command::FillVoxels { pos_from, pos_to, new_id: self.fill_id });
+        }
+    }
+
+    /// Counts non-air voxels on the segment between `from` and `to` (both included).
+    pub fn count_solid_between(&self, from: Int3, to: Int3) -> usize {
+        iterator::line_voxels(from, to)
+            .filter_map(|pos| self.get_voxel(pos))
+            .filter(|voxel| !voxel.is_air())
+            .count()
+    }
+
+    /// Measures distances between two voxels and counts solid voxels between them.
+    pub fn measure(&self, from: Int3, to: Int3) -> Measurement {
+        Measurement::new(from, to, self.count_solid_between(from, to))
+    }
+
+    /// Replaces voxels within `radius` of `center` with air. Voxels closer than
+    /// [core radius][ChunkArray::EXPLOSION_CORE_RATIO] are cleared always, further ones
+    /// are cleared with probability falling to zero on `radius`. Same `seed` gives same explosion.
+    /// Returns positions and old [ids][Id] of cleared voxels.
+    pub fn explode(&mut self, center: Int3, radius: f32, seed: u64) -> Vec<(Int3, Id)> {
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let core_radius = radius * Self::EXPLOSION_CORE_RATIO;
+        let offset = Int3::all(radius.ceil() as i32);
+
+        let mut cleared = vec![];
+
+        for pos in SpaceIter::new(center - offset ..= center + offset) {
+            let dist = ((pos - center).len_squared() as f32).sqrt();
+
+            let is_destroyed = if dist <= core_radius {
+                true
+            } else if dist <= radius {
+                let survive_chance = (dist - core_radius) / (radius - core_radius);
+                rng.gen::<f32>() >= survive_chance
+            } else {
+                false
+            };
+
+            let is_breakable = self.get_voxel(pos)
+                .is_some_and(|voxel| !voxel.is_air() && !voxel.data.unbreakable);
+
+            if is_destroyed && is_breakable {
+                match self.set_voxel(pos, AIR_VOXEL_DATA.id) {
+                    Ok(old_id) => cleared.push((pos, old_id)),
+                    Err(err) => logger::log!(Error, from = "chunk-array", "failed to explode voxel in {pos}: {err}"),
+                }
+            }
+        }
+
+        cleared
+    }
+
+    /// Sets measure start to `pos` or, if it is already set, measures from it to `pos`.
+    fn measure_to(&mut self, pos: Int3) {
+        match self.measure_from.take() {
+            None => {
+                logger::log!(Info, from = "chunk-array", "measuring from {pos}");
+                self.measure_from = Some(pos);
+            },
+
+            Some(from) => {
+                let measurement = self.measure(from, pos);
+                logger::log!(Info, from = "chunk-array", "measured {measurement}");
+            },
+        }
+    }
+
+    /// Gives voxel edits of `brush` placed at `center`.
+    /// Voxels that already have brush id, unbreakable or not loaded ones are skipped.
+    pub fn brush_edits(&self, brush: &Brush, center: Int3) -> Vec<(Int3, Id)> {
+        brush.voxels(center)
+            .filter(|&pos| self.get_voxel(pos).is_some_and(|voxel|
+                voxel.data.id != brush.id && !voxel.data.unbreakable
+            ))
+            .map(|pos| (pos, brush.id))
+            .collect()
+    }
+
+    /// Applies [brush edits][ChunkArray::brush_edits] with one batched edit per [chunk][Chunk].
+    /// Returns changed positions with their old [ids][Id].
+    /// # Error
+    /// Returns [`Err`] if brush id is not valid.
+    pub fn paint(&mut self, brush: &Brush, center: Int3) -> Result<Vec<(Int3, Id)>, EditError> {
+        if !voxel::is_id_valid(brush.id) {
+            return Err(EditError::InvalidId(brush.id));
+        }
+
+        let mut chunk_poses = HashMap::<Int3, Vec<Int3>>::new();
+        for (pos, _) in self.brush_edits(brush, center) {
+            chunk_poses.entry(Chunk::local_pos(pos)).or_default().push(pos);
+        }
+
+        let mut changed = vec![];
+
+        for (chunk_pos, poses) in chunk_poses {
+            let idx = Self::pos_to_idx(self.sizes, chunk_pos)
+                .expect("edited voxels are loaded");
+
+            let pos_from = poses.iter().copied().reduce(Int3Ext::min).expect("chunk has edits");
+            let pos_to   = poses.iter().copied().reduce(Int3Ext::max).expect("chunk has edits") + Int3::ONE;
+
+            let chunk_changed = self.chunk_for_edit(idx)
+                .set_voxels(poses, brush.id)?;
+
+            if !chunk_changed.is_empty() {
+                for idx in Self::get_touched_adj_chunks_idxs(self.sizes, chunk_pos, pos_from, pos_to) {
+                    self.meshes[idx].borrow_mut().drop_all();
+                }
+            }
+
+            changed.extend(chunk_changed);
+        }
+
+        Ok(changed)
+    }
+
+    pub async fn proccess_camera_input(&mut self, cam: &Camera, dt: f32) {
         use super::commands::{command, Command};
 
-        let first_voxel = self.trace_ray(Line::new(cam.pos, cam.front), Self::MAX_TRACE_STEPS)
-            .find(|voxel| !voxel.is_air());
+        if keyboard::just_pressed(cfg::key_bindings::PICKING_DEBUG_SWITCH) {
+            self.is_picking_debug = !self.is_picking_debug;
+        }
+
+        if keyboard::just_pressed(cfg::key_bindings::FREEZE_LOD_SWITCH) {
+            self.freeze_lod = !self.freeze_lod;
+        }
+
+        if keyboard::just_pressed(cfg::key_bindings::FILL_MODE_SWITCH) {
+            self.is_fill_mode = !self.is_fill_mode;
+            self.fill_drag = mouse::DragState::new(mouse::is_left_pressed());
+        }
+
+        let hit = self.trace_ray_hit(Line::new(cam.pos, cam.front), Self::MAX_TRACE_STEPS);
+
+        if keyboard::just_pressed(cfg::key_bindings::COPY_POSITION) {
+            let mut text = logger::camera_set_command(cam.pos);
+
+            if let Some(hit) = hit {
+                text += "^;";
+                text += &logger::voxel_set_command(hit.voxel.pos, hit.voxel.data.id);
+            }
+
+            user_io::clipboard::copy(text);
+        }
+
+        if self.is_fill_mode {
+            self.process_fill_drag(hit, cam);
+        }
+
+        self.interaction_cooldown.tick(dt);
+        if !mouse::is_left_pressed() && !mouse::is_right_pressed() {
+            self.interaction_cooldown.reset();
+        }
+
+        let is_left_held = !self.is_fill_mode && !self.is_picking_debug
+            && mouse::is_left_pressed() && cam.grabbes_cursor
+            && self.interaction_cooldown.is_ready();
+
+        let is_breaking = is_left_held && !self.brush.is_enabled;
+
+        if let Some(hit) = hit.filter(|_| is_left_held && self.brush.is_enabled) {
+            // Painted voxels grow out of the aimed surface, cleared ones cut into it.
+            let center = match self.brush.id == AIR_VOXEL_DATA.id {
+                true => hit.voxel.pos,
+                false => hit.voxel.pos + hit.normal,
+            };
+
+            command(Command::Paint {
+                center, shape: self.brush.shape, radius: self.brush.radius, new_id: self.brush.id,
+            });
+
+            self.interaction_cooldown.trigger();
+        }
+
+        match hit {
+            Some(hit) if is_breaking && !hit.voxel.data.unbreakable => {
+                if self.breaking.hit(hit.voxel.pos, hit.voxel.data.hardness, dt) {
+                    command(Command::SetVoxel { pos: hit.voxel.pos, new_id: AIR_VOXEL_DATA.id });
+                    self.interaction_cooldown.trigger();
+                }
+            },
+
+            _ => self.breaking.reset(),
+        }
+
+        let placement_base = hit
+            .filter(|hit| self.held_id.is_some() && hit.normal != Int3::ZERO)
+            .map(|hit| hit.voxel.pos + hit.normal);
+        self.placement.aim(placement_base);
+
+        let nudges = [
+            (cfg::key_bindings::NUDGE_NEG_X, veci!(-1, 0, 0)),
+            (cfg::key_bindings::NUDGE_POS_X, veci!( 1, 0, 0)),
+            (cfg::key_bindings::NUDGE_NEG_Y, veci!(0, -1, 0)),
+            (cfg::key_bindings::NUDGE_POS_Y, veci!(0,  1, 0)),
+            (cfg::key_bindings::NUDGE_NEG_Z, veci!(0, 0, -1)),
+            (cfg::key_bindings::NUDGE_POS_Z, veci!(0, 0,  1)),
+        ];
+
+        let eye = Self::world_to_voxel_pos(cam.pos, Voxel::SIZE);
+        for (key, step) in nudges {
+            if keyboard::just_pressed(key) {
+                self.placement.nudge(step, eye, cfg::terrain::PLACEMENT_REACH_IN_VOXELS);
+            }
+        }
+
+        if let (Some(new_id), Some(pos)) = (self.held_id, self.placement.target()) {
+            if keyboard::just_pressed(cfg::key_bindings::CONFIRM_PLACEMENT) {
+                command(Command::SetVoxel { pos, new_id });
+            }
+        }
+
+        // Clicks are not checked in fill mode as that would consume the held button.
+        match hit {
+            Some(hit) if !self.is_fill_mode && mouse::just_left_pressed()
+                      && cam.grabbes_cursor && self.is_picking_debug =>
+                logger::log!(Info, from = "chunk-array", "picked {hit}"),
+
+            Some(hit) if keyboard::just_pressed(cfg::key_bindings::MEASURE) =>
+                self.measure_to(hit.voxel.pos),
+
+            Some(hit) if keyboard::just_pressed(cfg::key_bindings::PICK_VOXEL) => {
+                self.fill_id = hit.voxel.data.id;
+                self.held_id = Some(hit.voxel.data.id);
+                logger::log!(Info, from = "chunk-array", "picked {name}", name = hit.voxel.data.name);
+            },
+
+            // Held button would shadow key-triggered arms above, so it goes after them.
+            Some(_) if !self.is_fill_mode && mouse::is_right_pressed() && cam.grabbes_cursor
+                     && self.held_id.is_some() =>
+                if let (Some(new_id), Some(pos)) = (self.held_id, self.placement.target()) {
+                    if self.interaction_cooldown.try_trigger() {
+                        command(Command::SetVoxel { pos, new_id });
+                    }
+                },
 
-        match first_voxel {
-            Some(voxel) if mouse::just_left_pressed() && cam.grabbes_cursor =>
-                command(Command::SetVoxel { pos: voxel.pos, new_id: AIR_VOXEL_DATA.id }),
+            Some(hit) if !self.is_fill_mode && mouse::just_right_pressed() && cam.grabbes_cursor =>
+                command(Command::Interact { pos: hit.voxel.pos }),
 
             _ => (),
         }
+
+        if keyboard::just_pressed(cfg::key_bindings::DROP_HELD_VOXEL) {
+            self.held_id = None;
+        }
     }
 
-    pub async fn update(&mut self, facade: &dyn Facade, cam: &Camera) -> Result<(), UpdateError> {
-        self.proccess_camera_input(cam).await;
+    pub async fn update(&mut self, facade: &dyn Facade, cam: &mut Camera, dt: f32) -> Result<(), UpdateError> {
+        if cfg!(debug_assertions) {
+            self.validate_invariants();
+        }
+
+        self.render_distance = cam.far_plane_dist;
+
+        self.proccess_camera_input(cam, dt).await;
         self.process_commands(facade).await;
+        self.evict_far_voxels(cam.pos, dt).await;
+        self.optimize_edited_chunks(dt);
 
-        if keyboard::just_pressed_combo([Key::LControl, Key::S]) {
-            let chunks: Vec<_> = self.chunks.iter().map(Arc::clone).collect();
-            let handle = tokio::spawn(
-                ChunkArray::save_to_file(self.sizes, chunks, "world", "world")
-            );
-            self.saving_handle = Some(handle);
+        if mem::take(&mut self.is_mesh_rebuild_requested) {
+            self.rebuild_all_meshes(facade, cam);
+        }
+
+        if keyboard::just_pressed_combo([Key::LControl, Key::LShift, Key::S]) {
+            self.start_saving(true);
+        } else if keyboard::just_pressed_combo([Key::LControl, Key::S]) {
+            self.start_saving(false);
         }
 
         if self.saving_handle.is_some() && self.saving_handle.as_ref().unwrap().is_finished() {
@@ -1118,15 +2916,34 @@ impl ChunkArray {
             handle.await??;
         }
 
-        if keyboard::just_pressed_combo([Key::LControl, Key::O]) {
-            let handle = tokio::spawn(ChunkArray::read_from_file("world", "world"));
+        if keyboard::just_pressed_combo([Key::LControl, Key::O]) && self.reading_handle.is_none() {
+            self.reading_cancel = Arc::new(AtomicBool::new(false));
+            let handle = tokio::spawn(ChunkArray::read_from_file_or_backup(
+                Self::WORLD_SAVE_NAME, Self::WORLD_SAVE_PATH, Arc::clone(&self.reading_cancel),
+            ));
             self.reading_handle = Some(handle);
         }
 
+        if keyboard::just_pressed(cfg::key_bindings::CANCEL_READING) {
+            self.cancel_reading();
+        }
+
+        if keyboard::just_pressed_combo([Key::LControl, Key::LShift, Key::R]) {
+            Self::start_replay();
+        } else if keyboard::just_pressed_combo([Key::LControl, Key::R]) {
+            Self::switch_command_recording();
+        }
+
         if self.reading_handle.is_some() && self.reading_handle.as_ref().unwrap().is_finished() {
             let handle = self.reading_handle.take().unwrap();
-            let (sizes, arr) = handle.await??;
-            self.apply_new(sizes, arr)?;
+            let result = handle.await?;
+            let is_read = result.is_ok();
+
+            self.apply_read_result(result)?;
+
+            if is_read && self.is_surface_teleport_enabled {
+                self.teleport_to_surface(cam);
+            }
         }
 
         Ok(())
@@ -1157,21 +2974,139 @@ pub enum TaskError {
     },
 }
 
+#[derive(Debug, Error)]
+pub enum ChunkDecodeError {
+    #[error(transparent)]
+    Reinterpret(#[from] ReinterpretError),
+
+    #[error("invalid voxel id {0}")]
+    InvalidId(Id),
+
+    #[error("chunk should have {expected} voxels but has {count}", expected = Chunk::VOLUME)]
+    VoxelCount {
+        count: usize,
+    },
+}
+
+/// Result of [save verification][ChunkArray::verify_file].
+#[derive(Debug)]
+pub struct VerifyReport {
+    pub sizes: USize3,
+    pub n_chunks: usize,
+
+    /// Indices of chunks that failed to decode.
+    pub bad_chunks: Vec<(usize, ChunkDecodeError)>,
+}
+
+impl VerifyReport {
+    /// Checks that chunk count matches sizes.
+    pub fn is_volume_valid(&self) -> bool {
+        ChunkArray::checked_volume(self.sizes) == Some(self.n_chunks)
+    }
+
+    /// Checks that save has no problems.
+    pub fn is_clean(&self) -> bool {
+        self.is_volume_valid() && self.bad_chunks.is_empty()
+    }
+}
+
+/// Result of [ray tracing][ChunkArray::trace_ray_hit].
+#[derive(Clone, Copy, Debug, PartialEq, Display)]
+#[display("{voxel}, face normal = {normal}, distance = {distance}")]
+pub struct RayHit {
+    pub voxel: Voxel,
+
+    /// Normal of the face the voxel was hit from. It is zero if ray starts inside the voxel.
+    pub normal: Int3,
+    pub distance: f32,
+}
+
+/// Distances between two voxels and number of solid voxels on the segment between them.
+#[derive(Clone, Copy, Debug, PartialEq, Display)]
+#[display("from {from} to {to}: distance = {euclidean}, manhattan distance = {manhattan}, {n_solid} solid voxels")]
+pub struct Measurement {
+    pub from: Int3,
+    pub to: Int3,
+    pub euclidean: f32,
+    pub manhattan: i32,
+    pub n_solid: usize,
+}
+
+impl Measurement {
+    pub fn new(from: Int3, to: Int3, n_solid: usize) -> Self {
+        let diff = to - from;
+        let (x, y, z) = (diff.x as f32, diff.y as f32, diff.z as f32);
+
+        Self {
+            from, to, n_solid,
+            euclidean: (x * x + y * y + z * z).sqrt(),
+            manhattan: diff.x.abs() + diff.y.abs() + diff.z.abs(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct ChangeTracker {
     pub sizes: USize3,
     pub voxel_poses: HashSet<Int3>,
+    pub regions: Vec<Range<Int3>>,
 }
 
 impl ChangeTracker {
     pub fn new(sizes: USize3) -> Self {
-        Self { sizes, voxel_poses: HashSet::new() }
+        Self { sizes, voxel_poses: HashSet::new(), regions: vec![] }
     }
 
     pub fn track_voxel(&mut self, voxel_pos: Int3) {
         self.voxel_poses.insert(voxel_pos);
     }
 
+    /// Tracks voxel volume from `pos_from` to `pos_to` (excluded) without enumerating its voxels.
+    pub fn track_region(&mut self, pos_from: Int3, pos_to: Int3) {
+        let is_empty = pos_from.x >= pos_to.x
+            || pos_from.y >= pos_to.y
+            || pos_from.z >= pos_to.z;
+
+        if !is_empty {
+            self.regions.push(pos_from..pos_to);
+        }
+    }
+
+    /// Splits `region` by cubic cells with `cell_size` voxels edge. Gives position of each touched
+    /// cell and positions of cells adjacent to it by the faces the region touches.
+    fn region_cells(region: Range<Int3>, cell_size: i32) -> impl Iterator<Item = (Int3, SmallVec<[Int3; 6]>)> {
+        let cell_sizes = Int3::all(cell_size);
+        let cell_from = region.start.div_euclid(cell_sizes);
+        let cell_to = (region.end - Int3::ONE).div_euclid(cell_sizes) + Int3::ONE;
+
+        SpaceIter::new(cell_from..cell_to).map(move |cell_pos| {
+            let min_pos = cell_pos * cell_size;
+
+            let local_from = region.start.max(min_pos) - min_pos;
+            let local_last = region.end.min(min_pos + cell_sizes) - min_pos - Int3::ONE;
+
+            let bounds = Int3::ZERO..cell_sizes;
+            let adj_cells = iterator::offsets_from_border(local_from, bounds.clone()).into_iter()
+                .chain(iterator::offsets_from_border(local_last, bounds))
+                .unique()
+                .map(|offset| cell_pos + offset)
+                .collect();
+
+            (cell_pos, adj_cells)
+        })
+    }
+
+    /// Converts position of chunk's half-sized cell to chunk and partition indices.
+    fn partition_cell_to_idxs(sizes: USize3, cell_pos: Int3) -> Option<(usize, usize)> {
+        let chunk_idx = ChunkArray::pos_to_idx(sizes, cell_pos.div_euclid(Int3::all(2)))?;
+        let partition_idx = ChunkArray::coord_idx_to_idx(
+            USize3::all(2),
+            USize3::from(cell_pos.rem_euclid(Int3::all(2))),
+        );
+
+        Some((chunk_idx, partition_idx))
+    }
+
     pub fn idxs_to_reload(&self) -> HashSet<usize> {
         let mut result = HashSet::new();
 
@@ -1194,6 +3129,16 @@ impl ChangeTracker {
             result.insert(chunk_idx);
         }
 
+        for region in self.regions.iter().cloned() {
+            for (chunk_pos, adj_poses) in Self::region_cells(region, Chunk::SIZE as i32) {
+                result.extend(
+                    std::iter::once(chunk_pos)
+                        .chain(adj_poses)
+                        .filter_map(|pos| ChunkArray::pos_to_idx(self.sizes, pos))
+                );
+            }
+        }
+
         result
     }
 
@@ -1240,10 +3185,1517 @@ impl ChangeTracker {
             }
         }
 
+        for region in self.regions.iter().cloned() {
+            for (cell_pos, adj_poses) in Self::region_cells(region, Chunk::SIZE as i32 / 2) {
+                result.extend(
+                    std::iter::once(cell_pos)
+                        .chain(adj_poses)
+                        .filter_map(|pos| Self::partition_cell_to_idxs(self.sizes, pos))
+                );
+            }
+        }
+
         result
     }
 }
 
+/// Shared [chunk][Chunk]. Tasks own their chunk and [adjacent][ChunkAdj] ones this way
+/// so moving or reallocating [chunk array][ChunkArray] storage doesn't invalidate them.
+/// Edits [copy][Arc::make_mut] chunk still held by tasks instead of writing to it.
 pub type ChunkRef = Arc<Chunk>;
 pub type MeshRef = Rc<RefCell<ChunkMesh>>;
-pub type ChunkAdj = Sides<Option<Arc<Chunk>>>;
\ No newline at end of file
+pub type ChunkAdj = Sides<Option<Arc<Chunk>>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serializes tests that use global command channel.
+    static COMMAND_CHANNEL_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    fn single_chunk_array(fill_id: Id) -> ChunkArray {
+        let chunk = Chunk::new_same_filled(Int3::ZERO, fill_id);
+        ChunkArray::from_chunks(USize3::all(1), vec![Arc::new(chunk)])
+            .expect("sizes should be valid")
+    }
+
+    /// Gives headless OpenGL renderer for tests that upload real meshes.
+    /// Event loop should outlive the renderer.
+    fn headless_facade() -> (gl::glutin::event_loop::EventLoop<()>, gl::HeadlessRenderer) {
+        use gl::glutin::{
+            ContextBuilder, dpi::PhysicalSize, event_loop::EventLoopBuilder,
+            platform::windows::EventLoopBuilderExtWindows,
+        };
+
+        // Tests are not run on the main thread.
+        let event_loop = EventLoopBuilder::new().with_any_thread(true).build();
+
+        let context = ContextBuilder::new()
+            .build_headless(&event_loop, PhysicalSize::new(1, 1))
+            .expect("failed to create headless context");
+
+        let facade = gl::HeadlessRenderer::new(context)
+            .expect("failed to create headless renderer");
+
+        (event_loop, facade)
+    }
+
+    #[test]
+    fn valid_array_passes_invariants() {
+        ChunkArray::new_empty_chunks(USize3::new(3, 2, 1))
+            .expect("sizes should be valid")
+            .validate_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk count should be equal to volume")]
+    fn corrupted_length_is_caught() {
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        arr.chunks.push(Arc::new(Chunk::new_empty(veci!(1, 0, 0))));
+
+        arr.validate_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "should be on")]
+    fn misplaced_chunk_is_caught() {
+        let mut arr = ChunkArray::new_empty_chunks(USize3::new(2, 1, 1))
+            .expect("sizes should be valid");
+        arr.chunks.swap(0, 1);
+
+        arr.validate_invariants();
+    }
+
+    #[test]
+    fn task_chunks_outlive_unrelated_array_mutation() {
+        let sizes = USize3::new(3, 1, 1);
+        let chunks = ChunkArray::pos_iter(sizes)
+            .map(|pos| Arc::new(Chunk::new_same_filled(pos, AIR_VOXEL_DATA.id)))
+            .collect();
+
+        let mut arr = ChunkArray::from_chunks(sizes, chunks)
+            .expect("sizes should be valid");
+
+        let (left_pos, right_pos) = (veci!(-1, 0, 0), veci!(1, 0, 0));
+        let left_from = Chunk::global_pos(left_pos);
+        arr.fill_voxels(
+            left_from,
+            left_from + Int3::from(Chunk::SIZES) - veci!(0, Chunk::SIZE as i32 / 2, 0),
+            STONE_VOXEL_DATA.id,
+        ).unwrap();
+
+        let left = arr.get_chunk_by_pos(left_pos).expect("chunk should exist");
+        let adj = arr.get_adj_chunks(left_pos);
+        let expected = left.make_vertices_low(adj.clone(), 1);
+        assert!(!expected.is_empty());
+
+        let (mut full_tasks, mut low_tasks) = (HashMap::new(), HashMap::new());
+        let is_started = RUNTIME.block_on(ChunkArray::start_task_gen_vertices(
+            &mut full_tasks, &mut low_tasks, left, adj, 1, MeshingMode::Blocky,
+        ));
+        assert!(is_started);
+
+        // Edits, replaces and reallocates chunks that are not held by the task.
+        arr.set_voxel(Chunk::global_pos(right_pos), STONE_VOXEL_DATA.id).unwrap();
+        let right_idx = ChunkArray::pos_to_idx(sizes, right_pos).unwrap();
+        arr.chunks[right_idx] = Arc::new(Chunk::new_same_filled(right_pos, DIRT_VOXEL_DATA.id));
+        arr.chunks.reserve(16 * arr.chunks.capacity());
+        arr.validate_invariants();
+
+        let vertices = RUNTIME.block_on(
+            low_tasks.get_mut(&(left_pos, 1)).expect("task should exist").take_result()
+        );
+
+        assert_eq!(vertices, expected);
+    }
+
+    #[test]
+    fn interaction_flips_voxel() {
+        use crate::terrain::voxel::voxel_data::{VoxelData, TextureSides};
+
+        fn flip_to_dirt(_: &Voxel) -> Option<Id> {
+            Some(DIRT_VOXEL_DATA.id)
+        }
+
+        static LEVER_VOXEL_DATA: VoxelData = VoxelData {
+            name: "Lever",
+            id: Id::MAX,
+            textures: TextureSides::all(0),
+            avarage_color: Color::new(0.5, 0.5, 0.5),
+            is_transparent: false,
+            unbreakable: false,
+            hardness: 0.5,
+            on_interact: Some(flip_to_dirt),
+        };
+
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        let pos = veci!(1, 2, 3);
+
+        let stone = arr.get_voxel(pos).expect("voxel should exist");
+        assert_eq!(arr.interact(stone).unwrap(), None);
+        assert_eq!(arr.get_voxel(pos).unwrap().data.id, STONE_VOXEL_DATA.id);
+
+        assert_eq!(arr.interact(Voxel::new(pos, &LEVER_VOXEL_DATA)).unwrap(), Some(DIRT_VOXEL_DATA.id));
+        assert_eq!(arr.get_voxel(pos).unwrap().data.id, DIRT_VOXEL_DATA.id);
+    }
+
+    #[test]
+    fn edit_copies_chunk_held_by_task() {
+        let mut arr = single_chunk_array(AIR_VOXEL_DATA.id);
+        let held = arr.get_chunk_by_pos(Int3::ZERO).expect("chunk should exist");
+
+        arr.set_voxel(Int3::ZERO, STONE_VOXEL_DATA.id).unwrap();
+
+        assert!(!Arc::ptr_eq(&held, &arr.chunks[0]));
+        let held_id = held.get_voxel_global(Int3::ZERO).into_option().map(|voxel| voxel.data.id);
+        assert_eq!(held_id, Some(AIR_VOXEL_DATA.id));
+        assert_eq!(arr.get_voxel(Int3::ZERO).map(|voxel| voxel.data.id), Some(STONE_VOXEL_DATA.id));
+
+        // Unshared chunk is edited in place.
+        drop(held);
+        let ptr = Arc::as_ptr(&arr.chunks[0]);
+        arr.set_voxel(Int3::ONE, STONE_VOXEL_DATA.id).unwrap();
+        assert_eq!(Arc::as_ptr(&arr.chunks[0]), ptr);
+    }
+
+    #[test]
+    fn measure_counts_solid_voxels_on_segment() {
+        let mut arr = single_chunk_array(AIR_VOXEL_DATA.id);
+
+        for x in [2, 3, 7] {
+            arr.set_voxel(veci!(x, 0, 0), STONE_VOXEL_DATA.id).unwrap();
+        }
+
+        // Off the segment.
+        arr.set_voxel(veci!(5, 1, 0), STONE_VOXEL_DATA.id).unwrap();
+
+        let measurement = arr.measure(veci!(0, 0, 0), veci!(9, 0, 0));
+
+        assert_eq!(measurement.n_solid, 3);
+        assert_eq!(measurement.manhattan, 9);
+        assert_eq!(measurement.euclidean, 9.0);
+    }
+
+    #[test]
+    fn pick_gives_first_solid_voxel_id() {
+        let mut arr = single_chunk_array(AIR_VOXEL_DATA.id);
+
+        arr.set_voxel(veci!(10, 5, 5), STONE_VOXEL_DATA.id).unwrap();
+        arr.set_voxel(veci!(14, 5, 5), BEDROCK_VOXEL_DATA.id).unwrap();
+
+        let towards = Line::new(vec3::new(2.0, 5.0, 5.0), vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(arr.pick_voxel_id(towards), Some(STONE_VOXEL_DATA.id));
+
+        let away = Line::new(vec3::new(2.0, 5.0, 5.0), vec3::new(-1.0, 0.0, 0.0));
+        assert_eq!(arr.pick_voxel_id(away), None);
+    }
+
+    #[test]
+    fn diagonal_ray_hit_has_single_axis_normal() {
+        let mut arr = single_chunk_array(AIR_VOXEL_DATA.id);
+        arr.set_voxel(veci!(10, 10, 5), STONE_VOXEL_DATA.id).unwrap();
+
+        // Ray enters the voxel through its `-X` face, `-Y` face plane is crossed earlier.
+        let dir = vec3::new(1.0, 1.0, 0.0) * std::f32::consts::FRAC_1_SQRT_2;
+        let ray = Line::new(vec3::new(2.0, 2.2, 5.0), dir);
+
+        let hit = arr.trace_ray_hit(ray, 1024)
+            .expect("ray should hit the voxel");
+
+        assert_eq!(hit.voxel.pos, veci!(10, 10, 5));
+        assert_eq!(hit.normal, veci!(-1, 0, 0));
+
+        // Exact diagonal step still gives axis normal.
+        let normal = ChunkArray::entry_normal(ray, veci!(10, 10, 5), veci!(9, 9, 5), 1.0);
+        assert_eq!([normal.x, normal.y, normal.z].map(i32::abs).iter().sum::<i32>(), 1);
+    }
+
+    #[test]
+    fn scaled_ray_hits_voxel_in_world_units() {
+        let mut arr = single_chunk_array(AIR_VOXEL_DATA.id);
+        arr.set_voxel(veci!(10, 5, 5), STONE_VOXEL_DATA.id).unwrap();
+
+        for voxel_size in [0.5, 1.0, 2.0] {
+            let ray = Line::new(vec3::new(2.0, 5.0, 5.0) * voxel_size, vec3::new(1.0, 0.0, 0.0));
+            let hit = arr.trace_ray_hit_scaled(ray, 1024, voxel_size)
+                .expect("ray should hit the voxel");
+
+            assert_eq!(hit.voxel.pos, veci!(10, 5, 5), "voxel size = {voxel_size}");
+            assert_eq!(hit.normal, veci!(-1, 0, 0), "voxel size = {voxel_size}");
+            assert!(
+                (hit.distance - 7.5 * voxel_size).abs() <= 0.125 * voxel_size,
+                "voxel size = {voxel_size}, distance = {}", hit.distance,
+            );
+        }
+    }
+
+    #[test]
+    fn voxel_world_bounds_are_symmetric_for_centered_array() {
+        let sizes = USize3::new(2, 4, 6);
+        let (start_pos, end_pos) = ChunkArray::pos_bounds(sizes);
+        let chunks = SpaceIter::new(start_pos..end_pos)
+            .map(|pos| Arc::new(Chunk::new_same_filled(pos, STONE_VOXEL_DATA.id)))
+            .collect();
+
+        let arr = ChunkArray::from_chunks(sizes, chunks)
+            .expect("sizes should be valid");
+
+        let half_extent = Int3::new(1, 2, 3) * Chunk::SIZE as i32;
+        let (min, max) = arr.voxel_world_bounds();
+
+        assert_eq!(min, Int3::ZERO - half_extent);
+        assert_eq!(max, half_extent - Int3::ONE);
+        assert!(arr.get_voxel(min).is_some());
+        assert!(arr.get_voxel(max).is_some());
+        assert!(arr.get_voxel(max + Int3::ONE).is_none());
+    }
+
+    #[test]
+    fn buried_camera_is_placed_above_ground() {
+        let mut arr = single_chunk_array(AIR_VOXEL_DATA.id);
+        arr.fill_voxels(veci!(0, 0, 0), veci!(8, 10, 8), STONE_VOXEL_DATA.id).unwrap();
+
+        assert_eq!(arr.ground_height_at(4, 4), Some(9));
+        assert_eq!(arr.ground_height_at(20, 20), None);
+
+        let buried = vec3::new(4.0, 5.0, 4.0) * Voxel::SIZE;
+        let new_pos = arr.surface_teleport_pos(buried)
+            .expect("buried camera should be moved");
+        assert_eq!(ChunkArray::world_to_voxel_pos(new_pos, Voxel::SIZE), veci!(4, 10, 4));
+
+        let above = vec3::new(4.0, 12.0, 4.0) * Voxel::SIZE;
+        assert!(arr.surface_teleport_pos(above).is_none());
+    }
+
+    #[test]
+    fn chunk_behind_camera_is_visible_within_margin() {
+        let mut cam = Camera::new();
+        let frustum = cam.get_frustum().clone();
+        let render_distance = cam.far_plane_dist;
+
+        // Camera looks to `-z` having the chunk on origin right behind it.
+        let (lo, _) = Chunk::global_bounds(Int3::ZERO);
+        assert!(cam.pos.z < lo.z && lo.z - cam.pos.z < 4.0);
+
+        assert!(!Chunk::is_visible_with_margin(Int3::ZERO, cam.pos, &frustum, render_distance, 0.0));
+        assert!(Chunk::is_visible_with_margin(Int3::ZERO, cam.pos, &frustum, render_distance, 4.0));
+        assert!(!Chunk::is_visible_with_margin(veci!(0, 0, 1), cam.pos, &frustum, render_distance, 4.0));
+    }
+
+    #[test]
+    fn interior_fill_touches_no_neighbors() {
+        let sizes = USize3::all(3);
+        let center = Int3::ZERO;
+        let origin = Chunk::global_pos(center);
+        let idx_of = |offset: Int3| ChunkArray::pos_to_idx(sizes, center + offset).unwrap();
+
+        let touched = |from: Int3, to: Int3| -> HashSet<usize> {
+            ChunkArray::get_touched_adj_chunks_idxs(sizes, center, origin + from, origin + to)
+                .into_iter()
+                .collect()
+        };
+
+        assert!(touched(Int3::ONE, Int3::all(4)).is_empty());
+
+        assert_eq!(
+            touched(Int3::ZERO, Int3::all(4)),
+            HashSet::from([
+                idx_of(veci!(-1, 0, 0)), idx_of(veci!(0, -1, 0)), idx_of(veci!(0, 0, -1)),
+            ]),
+        );
+
+        let size = Chunk::SIZE as i32;
+        assert_eq!(
+            touched(veci!(1, 1, 1), veci!(size, 4, 4)),
+            HashSet::from([idx_of(veci!(1, 0, 0))]),
+        );
+
+        assert_eq!(
+            touched(Int3::ZERO, Int3::all(size)).len(),
+            6,
+        );
+    }
+
+    #[test]
+    fn synchronous_meshing_spawns_no_tasks() {
+        let mut arr = single_chunk_array(AIR_VOXEL_DATA.id);
+        arr.fill_voxels(Int3::ZERO, veci!(64, 32, 64), STONE_VOXEL_DATA.id).unwrap();
+
+        for is_synchronous in [true, false] {
+            arr.synchronous_meshing = is_synchronous;
+            let mut stats = RenderStats::default();
+
+            for (chunk, adj, _, lod) in arr.get_targets_sorted(vec3::zero()) {
+                let vertices = RUNTIME.block_on(arr.mesh_or_start_task(&chunk, adj, lod, &mut stats));
+
+                match vertices {
+                    Some(ChunkVertices::Full(vertices)) => assert!(lod == 0 && !vertices.is_empty()),
+                    Some(ChunkVertices::Low(vertices, vertices_lod)) => assert!(vertices_lod == lod && !vertices.is_empty()),
+                    None => assert!(!is_synchronous, "chunk should be meshed with LOD {lod}"),
+                }
+            }
+
+            assert_eq!(arr.any_task_running(), !is_synchronous);
+            assert_eq!(stats.n_tasks_started, arr.n_tasks());
+        }
+    }
+
+    #[test]
+    fn set_invalid_id_leaves_voxel_unchanged() {
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        let pos = veci!(1, 2, 3);
+        let invalid_id = VOXEL_DATA.len() as Id;
+
+        assert!(matches!(
+            arr.set_voxel(pos, invalid_id),
+            Err(EditError::InvalidId(id)) if id == invalid_id,
+        ));
+
+        assert!(matches!(
+            arr.fill_voxels(Int3::ZERO, Int3::all(4), invalid_id),
+            Err(EditError::InvalidId(id)) if id == invalid_id,
+        ));
+
+        let voxel = arr.get_voxel(pos).expect("voxel should be in the array");
+        assert_eq!(voxel.data.id, STONE_VOXEL_DATA.id);
+    }
+
+    #[test]
+    fn progress_fraction() {
+        assert_eq!(ChunkArray::progress_fraction(0, 4), 0.25);
+        assert_eq!(ChunkArray::progress_fraction(1, 4), 0.5);
+        assert_eq!(ChunkArray::progress_fraction(3, 4), 1.0);
+        assert_eq!(ChunkArray::progress_fraction(0, 1), 1.0);
+        assert_eq!(ChunkArray::progress_fraction(0, 0), 1.0);
+    }
+
+    #[test]
+    fn cancelled_reading_leaves_array_unchanged() {
+        RUNTIME.block_on(async {
+            let name = "cancelled_reading_leaves_array_unchanged";
+            let dir = std::env::temp_dir().join(format!("terramine_{name}_{}", std::process::id()));
+            let path = dir.to_str().expect("temp dir should be valid UTF-8");
+            let _ = std::fs::remove_dir_all(&dir);
+
+            let saved = vec![Arc::new(Chunk::new_same_filled(Int3::ZERO, STONE_VOXEL_DATA.id))];
+            let save_result = ChunkArray::save_to_file(USize3::all(1), saved, name, path, false, false).await;
+
+            let mut arr = single_chunk_array(AIR_VOXEL_DATA.id);
+
+            let cancel = Arc::new(AtomicBool::new(true));
+            let result = ChunkArray::read_from_file(name, path, cancel).await;
+            let _ = std::fs::remove_dir_all(&dir);
+
+            save_result.expect("failed to save chunks");
+
+            assert!(matches!(&result, Err(err) if err.kind() == io::ErrorKind::Interrupted));
+
+            arr.apply_read_result(result)
+                .expect("cancelled reading should not fail");
+
+            assert_eq!(arr.chunks[0].fill_id(), Some(AIR_VOXEL_DATA.id));
+        });
+    }
+
+    #[test]
+    fn truncated_save_falls_back_to_backup() {
+        RUNTIME.block_on(async {
+            let name = "truncated_save_falls_back_to_backup";
+            let dir = std::env::temp_dir().join(format!("terramine_{name}_{}", std::process::id()));
+            let path = dir.to_str().expect("temp dir should be valid UTF-8");
+            let _ = std::fs::remove_dir_all(&dir);
+
+            let chunks = |id| -> Vec<ChunkRef> { vec![
+                Arc::new(Chunk::new_same_filled(Int3::ZERO, id)),
+                Arc::new(Chunk::new_same_filled(Int3::ZERO, AIR_VOXEL_DATA.id)),
+            ] };
+
+            for id in [STONE_VOXEL_DATA.id, DIRT_VOXEL_DATA.id] {
+                ChunkArray::save_to_file(USize3::new(2, 1, 1), chunks(id), name, path, false, false)
+                    .await
+                    .expect("failed to save chunks");
+            }
+
+            let stack_path = dir.join(name).with_extension(cfg::save::STACK_FILE_EXTENSION);
+            let stack_len = std::fs::metadata(&stack_path).expect("stack file should exist").len();
+            std::fs::OpenOptions::new()
+                .write(true)
+                .open(&stack_path)
+                .and_then(|file| file.set_len(stack_len / 2))
+                .expect("failed to truncate stack file");
+
+            let primary = ChunkArray::read_from_file(name, path, Default::default()).await;
+            let backup = ChunkArray::read_from_file_or_backup(name, path, Default::default()).await;
+            let _ = std::fs::remove_dir_all(&dir);
+
+            assert!(matches!(&primary, Err(err) if err.kind() != io::ErrorKind::Interrupted));
+
+            let (sizes, chunks) = backup.expect("backup should be read");
+            assert_eq!(sizes, USize3::new(2, 1, 1));
+            assert!(matches!(chunks[..], [(_, FillType::AllSame(id)), _] if id == STONE_VOXEL_DATA.id));
+        });
+    }
+
+    /// Encodes chunk the way saves without [format version][ChunkArray::SAVE_FORMAT_VERSION] did.
+    fn legacy_chunk_bytes(chunk: &Chunk) -> Vec<u8> {
+        let freqs = ChunkArray::count_voxel_frequencies(chunk.ids());
+        let (book, _) = hc::CodeBuilder::from_iter(freqs.iter().map(|(&id, &freq)| (id, freq))).finish();
+        let bits = ChunkArray::encode_voxel_ids(chunk, &book);
+
+        itertools::chain! {
+            FillType::Default.as_bytes(),
+            freqs.as_bytes(),
+            bits.as_bytes(),
+        }.collect()
+    }
+
+    #[test]
+    fn save_format_version_selects_decoding() {
+        RUNTIME.block_on(async {
+            let name = "save_format_version_selects_decoding";
+            let dir = std::env::temp_dir().join(format!("terramine_{name}_{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir(&dir).expect("failed to create temp dir");
+
+            let path = |case: &str| dir.join(case).to_str()
+                .expect("temp dir should be valid UTF-8")
+                .to_owned();
+            let (legacy, unsupported) = (path("legacy"), path("unsupported"));
+
+            // Distinct frequencies give the same Huffman code for any order of them.
+            let chunk = Chunk::from_voxels(
+                (0..Chunk::VOLUME)
+                    .map(|i| Atomic::new(match i % 10 {
+                        0 => DIRT_VOXEL_DATA.id,
+                        1 | 2 => GRASS_VOXEL_DATA.id,
+                        3..=5 => LOG_VOXEL_DATA.id,
+                        _ => STONE_VOXEL_DATA.id,
+                    }))
+                    .collect(),
+                Int3::ZERO,
+            ).expect("voxel count should be valid");
+
+            for (path, version) in [(&legacy, None), (&unsupported, Some(ChunkArray::SAVE_FORMAT_VERSION + 1))] {
+                let mut save = Save::builder(name)
+                    .create(path).await
+                    .expect("failed to create save");
+
+                if let Some(version) = version {
+                    save = save.write(&version, ChunkArrSaveType::FormatVersion).await;
+                }
+
+                save.write(&USize3::all(1), ChunkArrSaveType::Sizes).await
+                    .pointer_array(1, ChunkArrSaveType::Array, |_| {
+                        let bytes = legacy_chunk_bytes(&chunk);
+                        async move { bytes }
+                    }).await
+                    .save().await
+                    .expect("failed to save");
+            }
+
+            let legacy_read = ChunkArray::read_from_file(name, &legacy, Default::default()).await;
+            let unsupported_read = ChunkArray::read_from_file(name, &unsupported, Default::default()).await;
+            let _ = std::fs::remove_dir_all(&dir);
+
+            let (_, chunks) = legacy_read.expect("legacy save should be read");
+            let ids = chunks[0].0.iter().map(|id| id.load(Relaxed)).collect_vec();
+            assert_eq!(ids, chunk.ids().collect_vec());
+
+            assert!(matches!(unsupported_read, Err(err) if err.kind() == io::ErrorKind::InvalidData));
+        });
+    }
+
+    #[test]
+    fn save_works_on_current_thread_runtime() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build runtime");
+
+        let name = "save_works_on_current_thread_runtime";
+        let dir = std::env::temp_dir().join(format!("terramine_{name}_{}", std::process::id()));
+        let path = dir.to_str().expect("temp dir should be valid UTF-8");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut result = Ok(());
+        for (is_dictionary_shared, is_streamed) in [(false, false), (true, true)] {
+            let chunks = vec![Arc::new(Chunk::new_same_filled(Int3::ZERO, STONE_VOXEL_DATA.id))];
+            result = result.and_then(|()| runtime.block_on(
+                ChunkArray::save_to_file(USize3::all(1), chunks, name, path, is_dictionary_shared, is_streamed)
+            ));
+        }
+
+        let read = runtime.block_on(ChunkArray::read_from_file(name, path, Default::default()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        result.expect("failed to save chunks");
+        let (_, chunks) = read.expect("failed to read chunks");
+        assert!(matches!(chunks[..], [(_, FillType::AllSame(id))] if id == STONE_VOXEL_DATA.id));
+    }
+
+    #[test]
+    fn streamed_save_reads_same_as_buffered() {
+        RUNTIME.block_on(async {
+            let chunks = || -> Vec<ChunkRef> {
+                let striped = Chunk::from_voxels(
+                    (0..Chunk::VOLUME)
+                        .map(|i| Atomic::new((i % VOXEL_DATA.len()) as Id))
+                        .collect(),
+                    Int3::ZERO,
+                ).expect("voxel count should be valid");
+
+                vec![
+                    Arc::new(striped),
+                    Arc::new(Chunk::new_same_filled(Int3::ZERO, STONE_VOXEL_DATA.id)),
+                    Arc::new(Chunk::new_same_filled(Int3::ZERO, AIR_VOXEL_DATA.id)),
+                    Arc::new(Chunk::new_same_filled(Int3::ZERO, DIRT_VOXEL_DATA.id)),
+                ]
+            };
+
+            let sizes = USize3::new(2, 1, 2);
+
+            // Saves are read back from separate directories which are removed afterwards.
+            let read = |name: &'static str, is_streamed: bool| async move {
+                let dir = std::env::temp_dir().join(format!("terramine_{name}_{}", std::process::id()));
+                let path = dir.to_str().expect("temp dir should be valid UTF-8");
+                let _ = std::fs::remove_dir_all(&dir);
+
+                let result = async {
+                    ChunkArray::save_to_file(sizes, chunks(), name, path, false, is_streamed).await?;
+                    ChunkArray::read_from_file(name, path, Default::default()).await
+                }.await;
+
+                let _ = std::fs::remove_dir_all(&dir);
+                let (sizes, chunks) = result?;
+
+                let chunks: Vec<(Vec<Id>, FillType)> = chunks.into_iter()
+                    .map(|(ids, fill_type)| (ids.iter().map(|id| id.load(Relaxed)).collect(), fill_type))
+                    .collect();
+
+                io::Result::Ok((sizes, chunks))
+            };
+
+            let buffered_read = read("streamed_save_reads_same_as_buffered_0", false).await;
+            let streamed_read = read("streamed_save_reads_same_as_buffered_1", true).await;
+
+            assert_eq!(
+                buffered_read.expect("failed to save and read buffered chunks"),
+                streamed_read.expect("failed to save and read streamed chunks"),
+            );
+        });
+    }
+
+    #[test]
+    fn verify_reports_corrupted_chunk() {
+        RUNTIME.block_on(async {
+            let name = "verify_reports_corrupted_chunk";
+            let dir = std::env::temp_dir().join(format!("terramine_{name}_{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir(&dir).expect("failed to create temp dir");
+
+            let path = |case: &str| dir.join(case).to_str()
+                .expect("temp dir should be valid UTF-8")
+                .to_owned();
+            let (good, corrupted, bad_dictionary) = (path("good"), path("corrupted"), path("bad_dictionary"));
+
+            let chunks = || -> Vec<ChunkRef> { vec![
+                Arc::new(Chunk::new_same_filled(Int3::ZERO, STONE_VOXEL_DATA.id)),
+                Arc::new(Chunk::new_same_filled(Int3::ZERO, AIR_VOXEL_DATA.id)),
+            ] };
+
+            ChunkArray::save_to_file(USize3::new(2, 1, 1), chunks(), name, &good, false, false)
+                .await
+                .expect("failed to save chunks");
+
+            let good_report = ChunkArray::verify_file(name, &good).await;
+
+            let mut encoded = ChunkArray::chunks_as_bytes(&chunks(), |_| ());
+            encoded[1] = FillType::AllSame(Id::MAX).as_bytes();
+
+            Save::builder(name)
+                .create(&corrupted).await
+                .expect("failed to create save")
+                .write(&USize3::new(2, 1, 1), ChunkArrSaveType::Sizes).await
+                .pointer_array(2, ChunkArrSaveType::Array, |i| {
+                    let bytes = mem::take(&mut encoded[i]);
+                    async move { bytes }
+                }).await
+                .save().await
+                .expect("failed to save");
+
+            let corrupted_report = ChunkArray::verify_file(name, &corrupted).await;
+
+            Save::builder(name)
+                .create(&bad_dictionary).await
+                .expect("failed to create save")
+                .write(&USize3::new(2, 1, 1), ChunkArrSaveType::Sizes).await
+                .pointer(vec![1], ChunkArrSaveType::SharedDictionary).await
+                .pointer_array(0, ChunkArrSaveType::Array, |_| async { vec![] }).await
+                .save().await
+                .expect("failed to save");
+
+            let bad_dictionary_report = ChunkArray::verify_file(name, &bad_dictionary).await;
+            let _ = std::fs::remove_dir_all(&dir);
+
+            let report = good_report.expect("failed to verify save");
+            assert!(report.is_clean());
+            assert_eq!(report.n_chunks, 2);
+
+            assert!(matches!(bad_dictionary_report, Err(err) if err.kind() == io::ErrorKind::InvalidData));
+
+            let report = corrupted_report.expect("failed to verify save");
+            assert!(!report.is_clean());
+            assert!(report.is_volume_valid());
+            assert!(matches!(report.bad_chunks[..], [(1, ChunkDecodeError::InvalidId(Id::MAX))]));
+        });
+    }
+
+    #[test]
+    fn parallel_encoding_matches_sequential() {
+        let striped = Chunk::from_voxels(
+            (0..Chunk::VOLUME)
+                .map(|i| Atomic::new((i % VOXEL_DATA.len()) as Id))
+                .collect(),
+            Int3::ZERO,
+        ).expect("voxel count should be valid");
+
+        let chunks: Vec<ChunkRef> = vec![
+            Arc::new(striped),
+            Arc::new(Chunk::new_same_filled(Int3::ZERO, STONE_VOXEL_DATA.id)),
+            Arc::new(Chunk::new_same_filled(Int3::ZERO, AIR_VOXEL_DATA.id)),
+        ];
+
+        let sequential: Vec<_> = chunks.iter()
+            .map(|chunk| ChunkArray::chunk_as_bytes(chunk))
+            .collect();
+
+        let parallel = ChunkArray::chunks_as_bytes(&chunks, |_| ());
+
+        assert_eq!(sequential, parallel);
+    }
+
+    #[test]
+    fn shared_dictionary_round_trips_and_is_smaller() {
+        let chunks: Vec<ChunkRef> = (0..8)
+            .map(|offset| Chunk::from_voxels(
+                (0..Chunk::VOLUME)
+                    .map(|i| Atomic::new(((i + offset) % 3) as Id))
+                    .collect(),
+                Int3::ZERO,
+            ).expect("voxel count should be valid"))
+            .map(Arc::new)
+            .collect();
+
+        let dictionary = SharedDictionary::from_chunks(&chunks);
+        let dictionary = SharedDictionary::from_bytes(&dictionary.as_bytes());
+
+        let per_chunk = ChunkArray::chunks_as_bytes(&chunks, |_| ());
+        let shared = ChunkArray::chunks_as_bytes_with(&chunks, Some(&dictionary), |_| ());
+
+        for (chunk, bytes) in chunks.iter().zip(&shared) {
+            let (voxel_ids, fill_type) = ChunkArray::array_filltype_from_bytes(bytes, Some(&dictionary.tree));
+
+            assert_eq!(fill_type, FillType::Default);
+            assert!(voxel_ids.iter().map(|id| id.load(Relaxed)).eq(chunk.ids()));
+        }
+
+        let per_chunk_len: usize = per_chunk.iter().map(Vec::len).sum();
+        let shared_len = dictionary.as_bytes().len() + shared.iter().map(Vec::len).sum::<usize>();
+
+        assert!(shared_len < per_chunk_len, "{shared_len} >= {per_chunk_len}");
+    }
+
+    #[test]
+    fn count_non_air_on_mixed_array() {
+        let striped = Chunk::from_voxels(
+            (0..Chunk::VOLUME)
+                .map(|i| Atomic::new((i % VOXEL_DATA.len()) as Id))
+                .collect(),
+            Int3::ZERO,
+        ).expect("voxel count should be valid");
+
+        let n_striped = (0..Chunk::VOLUME)
+            .filter(|i| (i % VOXEL_DATA.len()) as Id != AIR_VOXEL_DATA.id)
+            .count();
+
+        let chunks = vec![
+            Arc::new(striped),
+            Arc::new(Chunk::new_same_filled(veci!(1, 0, 0), STONE_VOXEL_DATA.id)),
+            Arc::new(Chunk::new_same_filled(veci!(2, 0, 0), AIR_VOXEL_DATA.id)),
+        ];
+
+        let per_chunk: Vec<_> = chunks.iter()
+            .map(|chunk| chunk.count_non_air())
+            .collect();
+
+        assert_eq!(per_chunk, vec![n_striped, Chunk::VOLUME, 0]);
+
+        let arr = ChunkArray::from_chunks(USize3::from([3, 1, 1]), chunks)
+            .expect("sizes should be valid");
+
+        assert_eq!(arr.count_non_air(), per_chunk.into_iter().sum::<usize>());
+    }
+
+    #[test]
+    fn chunks_within_radius() {
+        let arr = ChunkArray::new_empty_chunks(USize3::from([3, 1, 1]))
+            .expect("sizes should be valid");
+
+        let near_pos = |radius| -> Vec<Int3> {
+            arr.chunks_within_radius(veci!(10, 32, 32), radius)
+                .map(|chunk| chunk.pos.load(Relaxed))
+                .sorted_by_key(|pos| pos.x)
+                .collect()
+        };
+
+        assert_eq!(near_pos(5.0), vec![Int3::ZERO]);
+        assert_eq!(near_pos(20.0), vec![veci!(-1, 0, 0), Int3::ZERO]);
+        assert_eq!(near_pos(60.0), vec![veci!(-1, 0, 0), Int3::ZERO, veci!(1, 0, 0)]);
+    }
+
+    #[test]
+    fn chunks_within_radius_mut_copies_shared_chunks() {
+        let mut arr = ChunkArray::new_empty_chunks(USize3::from([3, 1, 1]))
+            .expect("sizes should be valid");
+
+        let shared = arr.chunks_within_radius(Int3::all(32), 0.0)
+            .next()
+            .expect("center chunk should be near");
+
+        assert_eq!(arr.chunks_within_radius_mut(Int3::all(32), 60.0).count(), 3);
+        assert!(arr.chunks.iter().all(|chunk| !Arc::ptr_eq(chunk, &shared)));
+    }
+
+    #[test]
+    fn chunks_within_radius_does_not_overflow() {
+        let arr = ChunkArray::new_empty_chunks(USize3::from([3, 1, 1]))
+            .expect("sizes should be valid");
+
+        assert_eq!(arr.chunks_within_radius(Int3::all(i32::MAX), 1.0).count(), 0);
+        assert_eq!(arr.chunks_within_radius(Int3::all(i32::MIN), f32::INFINITY).count(), 3);
+    }
+
+    #[test]
+    fn brush_edits_skip_unchanged_voxels() {
+        let center = Int3::all(32);
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        let brush = Brush { radius: 3, id: AIR_VOXEL_DATA.id, ..Brush::DEFAULT };
+
+        let edits = arr.brush_edits(&brush, center);
+        assert_eq!(edits.len(), brush.voxels(center).count());
+        assert!(edits.iter().all(|&(_, id)| id == AIR_VOXEL_DATA.id));
+
+        let paint = Command::Paint { center, shape: brush.shape, radius: brush.radius, new_id: brush.id };
+        let mut change_tracker = ChangeTracker::new(arr.sizes);
+        arr.apply_command(paint, &mut change_tracker)
+            .expect("voxels should be painted");
+
+        assert!(arr.brush_edits(&brush, center).is_empty());
+        assert!(arr.paint(&brush, center).expect("brush should be valid").is_empty());
+        assert!(arr.get_voxel(center + veci!(3, 0, 0)).unwrap().is_air());
+        assert!(!arr.get_voxel(center + veci!(3, 1, 0)).unwrap().is_air());
+    }
+
+    #[test]
+    fn revisited_lod_reuses_cached_buffer_without_task() {
+        use gl::GlObject;
+
+        assert!(cfg::terrain::N_CACHED_LODS >= 2);
+
+        let (_event_loop, facade) = headless_facade();
+
+        let mut arr = single_chunk_array(AIR_VOXEL_DATA.id);
+        arr.fill_voxels(Int3::all(8), Int3::all(12), STONE_VOXEL_DATA.id).unwrap();
+
+        let (chunk, mesh) = (Arc::clone(&arr.chunks[0]), Rc::clone(&arr.meshes[0]));
+        let adj = arr.get_adj_chunks(Int3::ZERO);
+
+        let buffer_id = |lod: Lod| mesh.borrow().low_meshes[lod as usize - 1]
+            .as_ref()
+            .map(|mesh| mesh.vertices.get_id());
+
+        RUNTIME.block_on(async {
+            // Camera moves away through LODs 1 and 2.
+            arr.synchronous_meshing = true;
+            for lod in [1, 2] {
+                arr.update_active_lod(&chunk, adj.clone(), &mesh, lod, &facade, &mut RenderStats::default()).await;
+            }
+
+            let cached = buffer_id(1).expect("LOD 1 mesh should be cached");
+
+            // And comes back, so the cached buffer is used instead of meshing in a task.
+            arr.synchronous_meshing = false;
+            let mut stats = RenderStats::default();
+            arr.update_active_lod(&chunk, adj, &mesh, 1, &facade, &mut stats).await;
+
+            assert_eq!(buffer_id(1), Some(cached));
+            assert_eq!(chunk.info.load(Relaxed).active_lod, Some(1));
+            assert_eq!(stats.n_tasks_started, 0);
+            assert_eq!(arr.n_tasks(), 0);
+        });
+    }
+
+    #[test]
+    fn paint_rejects_radius_out_of_range() {
+        let center = Int3::all(32);
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        let mut change_tracker = ChangeTracker::new(arr.sizes);
+
+        for radius in [-1, Brush::MAX_RADIUS + 1, i32::MAX] {
+            let paint = Command::Paint { center, shape: BrushShape::Sphere, radius, new_id: AIR_VOXEL_DATA.id };
+            let result = arr.apply_command(paint, &mut change_tracker);
+
+            assert!(result.is_err_and(|err| err.0.contains("is not in")), "radius {radius} should be rejected");
+        }
+
+        assert!(!arr.get_voxel(center).unwrap().is_air());
+    }
+
+    #[test]
+    fn cube_paint_fills_or_skips_unbreakable() {
+        let center = Int3::all(32);
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        let mut change_tracker = ChangeTracker::new(arr.sizes);
+
+        let paint = Command::Paint { center, shape: BrushShape::Cube, radius: 2, new_id: AIR_VOXEL_DATA.id };
+        arr.apply_command(paint, &mut change_tracker).expect("cube should be painted");
+        assert!(arr.get_voxel(center + Int3::all(2)).unwrap().is_air());
+
+        arr.set_voxel(center, STONE_VOXEL_DATA.id).unwrap();
+        arr.set_voxel(center + Int3::ONE, BEDROCK_VOXEL_DATA.id).unwrap();
+
+        arr.apply_command(paint, &mut change_tracker).expect("cube should be painted around bedrock");
+        assert!(arr.get_voxel(center).unwrap().is_air());
+        assert_eq!(arr.get_voxel(center + Int3::ONE).unwrap().data.id, BEDROCK_VOXEL_DATA.id);
+    }
+
+    #[test]
+    fn explode_clears_core_and_keeps_outside() {
+        let center = Int3::all(32);
+        let radius = 10.0;
+
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        let cleared = arr.explode(center, radius, 42);
+
+        let core_offset = Int3::all((radius * ChunkArray::EXPLOSION_CORE_RATIO) as i32);
+        for pos in SpaceIter::new(center - core_offset ..= center + core_offset) {
+            let diff = pos - center;
+            let dist = ((diff.x.pow(2) + diff.y.pow(2) + diff.z.pow(2)) as f32).sqrt();
+
+            if dist <= radius * ChunkArray::EXPLOSION_CORE_RATIO {
+                assert!(arr.get_voxel(pos).unwrap().is_air(), "voxel in {pos} should be air");
+            }
+        }
+
+        for pos in [center + veci!(11, 0, 0), center - veci!(0, 0, 12), Int3::ZERO, Int3::all(63)] {
+            assert_eq!(arr.get_voxel(pos).unwrap().data.id, STONE_VOXEL_DATA.id);
+        }
+
+        let mut same_seed_arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        assert_eq!(same_seed_arr.explode(center, radius, 42), cleared);
+    }
+
+    #[test]
+    fn adj_get_by_direction() {
+        use super::iterator::Direction;
+
+        let arr = ChunkArray::new_empty_chunks(USize3::from([3, 1, 1]))
+            .expect("sizes should be valid");
+
+        let adj = arr.get_adj_chunks(Int3::ZERO);
+
+        let pos_x = adj.get(Direction::PosX).expect("chunk should have +x neighbor");
+        assert_eq!(pos_x.pos.load(Relaxed), veci!(1, 0, 0));
+
+        let neg_x = adj.get(Direction::NegX).expect("chunk should have -x neighbor");
+        assert_eq!(neg_x.pos.load(Relaxed), veci!(-1, 0, 0));
+
+        assert!(adj.get(Direction::PosY).is_none());
+        assert!(adj.get(Direction::NegZ).is_none());
+    }
+
+    #[test]
+    fn boundary_meshing_matches_adj_meshing() {
+        let sizes = USize3::from([3, 1, 1]);
+
+        let chunks = (0..ChunkArray::volume(sizes))
+            .map(|idx| {
+                let pos = ChunkArray::idx_to_pos(idx, sizes);
+                let voxel_ids = (0..Chunk::VOLUME)
+                    .map(|i| match (i + idx) % 3 {
+                        0 => AIR_VOXEL_DATA.id,
+                        1 => STONE_VOXEL_DATA.id,
+                        _ => DIRT_VOXEL_DATA.id,
+                    })
+                    .map(Atomic::new)
+                    .collect();
+
+                Chunk::from_voxels(voxel_ids, pos)
+                    .map(Arc::new)
+                    .expect("voxel count should be valid")
+            })
+            .collect();
+
+        let arr = ChunkArray::from_chunks(sizes, chunks)
+            .expect("sizes should be valid");
+
+        for (chunk, adj) in arr.chunks_with_adj() {
+            let boundary = AdjBoundary::new(chunk.pos.load(Relaxed), &adj);
+
+            assert_eq!(
+                chunk.make_vertices_detailed(adj),
+                chunk.make_vertices_detailed_from_boundary(&boundary),
+            );
+        }
+    }
+
+    #[test]
+    fn loaded_runtime_cfg_changes_max_tasks_and_lods() {
+        let path = std::env::temp_dir().join(format!("terramine_runtime_cfg_{}.txt", std::process::id()));
+        std::fs::write(&path, "max_tasks = 0\nlod_threashold = 10000\n").expect("failed to write config");
+
+        let cfg = RUNTIME.block_on(runtime_cfg::RuntimeCfg::load(&path));
+        let _ = std::fs::remove_file(&path);
+        let cfg = cfg.expect("config should be valid");
+
+        let mut arr = single_chunk_array(AIR_VOXEL_DATA.id);
+        arr.full_tasks.insert(Int3::ZERO, Task { handle: None });
+
+        assert!(arr.can_start_tasks_with(&runtime_cfg::RuntimeCfg::DEFAULT));
+        assert!(!arr.can_start_tasks_with(&cfg));
+
+        let far_cam_pos = vec3::new(1000.0, 0.0, 0.0);
+        let default_threashold = runtime_cfg::RuntimeCfg::DEFAULT.lod_threashold;
+
+        assert_ne!(arr.target_lod_iter_with(far_cam_pos, default_threashold).collect_vec(), vec![0]);
+        assert_eq!(arr.target_lod_iter_with(far_cam_pos, cfg.lod_threashold).collect_vec(), vec![0]);
+    }
+
+    #[test]
+    fn ray_hit_message() {
+        let hit = RayHit {
+            voxel: Voxel::new(veci!(1, 2, 3), STONE_VOXEL_DATA),
+            normal: veci!(0, 1, 0),
+            distance: 2.5,
+        };
+
+        assert_eq!(
+            hit.to_string(),
+            format!(
+                "Stone with id = {id} in {pos}, face normal = {normal}, distance = 2.5",
+                id = STONE_VOXEL_DATA.id, pos = veci!(1, 2, 3), normal = veci!(0, 1, 0),
+            ),
+        );
+    }
+
+    #[test]
+    fn frozen_lod_ignores_camera_move() {
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+
+        let chunk = &arr.chunks[0];
+        let mut info = chunk.info.load(Relaxed);
+        info.active_lod = Some(0);
+        chunk.info.store(info, Relaxed);
+
+        let far_cam_pos = vec3::new(1000.0, 0.0, 0.0);
+        assert_ne!(arr.target_lod_iter(far_cam_pos).collect_vec(), vec![0]);
+
+        arr.freeze_lod = true;
+        assert_eq!(arr.target_lod_iter(far_cam_pos).collect_vec(), vec![0]);
+        assert_eq!(arr.chunks[0].info.load(Relaxed).active_lod, Some(0));
+    }
+
+    #[test]
+    fn forced_chunk_has_full_detail_far_from_camera() {
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        let far_cam_pos = vec3::new(1000.0, 0.0, 0.0);
+
+        assert_ne!(arr.target_lod_iter(far_cam_pos).collect_vec(), vec![0]);
+
+        arr.force_full_detail(veci!(10, 10, 10), veci!(20, 20, 20), true);
+        assert_eq!(arr.target_lod_iter(far_cam_pos).collect_vec(), vec![0]);
+
+        arr.force_full_detail(veci!(0, 0, 0), veci!(64, 64, 64), false);
+        assert_ne!(arr.target_lod_iter(far_cam_pos).collect_vec(), vec![0]);
+    }
+
+    #[test]
+    fn forced_full_detail_is_clamped_to_render_distance_and_array() {
+        let mut arr = ChunkArray::new_empty_chunks(USize3::new(5, 1, 1))
+            .expect("sizes should be valid");
+
+        // Chunks out of array are not forced.
+        arr.force_full_detail(Int3::all(-1000), Int3::all(1000), true);
+        assert_eq!(arr.full_detail_chunks.len(), 5);
+
+        arr.full_detail_chunks.clear();
+        arr.render_distance = Voxel::SIZE * Chunk::SIZE as f32;
+        arr.force_full_detail(veci!(-128, 0, 0), veci!(1000, 64, 64), true);
+        assert_eq!(arr.full_detail_chunks, HashSet::from([veci!(-2, 0, 0)]));
+
+        arr.force_full_detail(Int3::all(i32::MIN / 2), Int3::all(i32::MAX / 2), false);
+        assert!(arr.full_detail_chunks.is_empty());
+    }
+
+    #[test]
+    fn set_seed_command_regenerates_world() {
+        use super::super::commands::{command, COMMAND_CHANNEL};
+
+        let _lock = COMMAND_CHANNEL_TEST_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+        /// Restores generator seed and noise even if test fails.
+        struct RestoreSeed(u64);
+
+        impl Drop for RestoreSeed {
+            fn drop(&mut self) {
+                generator::set_seed(self.0);
+                generator::rebuild_noise();
+            }
+        }
+
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        let _restore = RestoreSeed(generator::seed());
+
+        command(Command::SetSeed { seed: 42 });
+
+        let received = COMMAND_CHANNEL.lock().unwrap()
+            .receiver
+            .try_recv()
+            .expect("command should be sent");
+        assert_eq!(received.command, Command::SetSeed { seed: 42 });
+
+        arr.apply_command(received.command, &mut ChangeTracker::new(arr.sizes))
+            .expect("seed should be set");
+
+        assert_eq!(generator::seed(), 42);
+        assert_eq!(arr.sizes, USize3::all(1));
+        assert!(arr.chunks.iter().all(|chunk| !chunk.is_generated()));
+    }
+
+    #[test]
+    fn update_processes_queued_set_voxel() {
+        use super::super::commands::command;
+
+        let _lock = COMMAND_CHANNEL_TEST_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        let pos = veci!(1, 2, 3);
+
+        command(Command::SetVoxel { pos, new_id: AIR_VOXEL_DATA.id });
+
+        let change_tracker = arr.receive_commands();
+
+        assert_eq!(arr.get_voxel(pos).map(|voxel| voxel.data.id), Some(AIR_VOXEL_DATA.id));
+        assert!(change_tracker.idxs_to_reload().contains(&0));
+    }
+
+    #[test]
+    fn command_and_wait_times_out_without_processing() {
+        use {super::super::commands::{command_and_wait, COMMAND_CHANNEL}, std::time::Duration};
+
+        let _lock = COMMAND_CHANNEL_TEST_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+        let command = Command::SetVoxel { pos: Int3::ZERO, new_id: AIR_VOXEL_DATA.id };
+        let result = command_and_wait(command, Duration::from_millis(10));
+        assert!(result.is_err_and(|err| err.0.contains("is not processed")));
+
+        // Timed out request is cancelled, so it is received but not applied.
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        arr.receive_commands();
+
+        assert!(COMMAND_CHANNEL.lock().unwrap().receiver.try_recv().is_err());
+        assert_eq!(arr.get_voxel(Int3::ZERO).map(|voxel| voxel.data.id), Some(STONE_VOXEL_DATA.id));
+    }
+
+    #[test]
+    fn replayed_commands_reproduce_world() {
+        use super::super::commands::{self, command};
+
+        let _lock = COMMAND_CHANNEL_TEST_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+        let path = std::env::temp_dir().join("replayed_commands_reproduce_world.rec");
+        let edits = [
+            Command::SetVoxel { pos: veci!(1, 2, 3), new_id: AIR_VOXEL_DATA.id },
+            Command::FillVoxels { pos_from: Int3::all(4), pos_to: Int3::all(8), new_id: DIRT_VOXEL_DATA.id },
+            Command::Explode { center: Int3::all(32), radius: 5.0, seed: 7 },
+            Command::SetVoxel { pos: Int3::all(32), new_id: GRASS_VOXEL_DATA.id },
+        ];
+
+        let mut recorded = single_chunk_array(STONE_VOXEL_DATA.id);
+        commands::start_recording(&path).unwrap();
+
+        for edit in edits {
+            command(edit);
+        }
+
+        recorded.receive_commands();
+        assert!(commands::stop_recording());
+
+        let recording = commands::read_recording(&path).unwrap();
+        assert!(recording.iter().map(|recorded| recorded.command).eq(edits));
+
+        let mut replayed = single_chunk_array(STONE_VOXEL_DATA.id);
+        assert_eq!(RUNTIME.block_on(commands::replay(&path, 0.0)).unwrap(), edits.len());
+        replayed.receive_commands();
+
+        let ids = |arr: &ChunkArray| arr.voxels().map(|voxel| voxel.data.id).collect::<Vec<_>>();
+        assert_eq!(ids(&replayed), ids(&recorded));
+        assert_ne!(ids(&replayed), ids(&single_chunk_array(STONE_VOXEL_DATA.id)));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn truncated_recording_is_invalid_data() {
+        use super::super::commands::{self, RecordedCommand};
+
+        let path = std::env::temp_dir().join("truncated_recording_is_invalid_data.rec");
+        let recorded = RecordedCommand {
+            time: 1.5,
+            command: Command::SetVoxel { pos: veci!(1, 2, 3), new_id: DIRT_VOXEL_DATA.id },
+        };
+
+        let bytes = recorded.as_bytes();
+
+        for len in [1, 8, bytes.len() - 1] {
+            std::fs::write(&path, &bytes[..len]).unwrap();
+
+            let err = commands::read_recording(&path).expect_err("recording should be truncated");
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn chunks_in_pos_order_matches_pos_iter() {
+        let sizes = USize3::new(2, 3, 1);
+        let arr = ChunkArray::new_empty_chunks(sizes).unwrap();
+
+        let poses: Vec<_> = arr.chunks_in_pos_order().map(|(pos, _)| pos).collect();
+        assert!(poses.iter().copied().eq(ChunkArray::pos_iter(sizes)));
+
+        for (idx, (pos, chunk)) in arr.chunks_in_pos_order().enumerate() {
+            assert_eq!(chunk.pos.load(Relaxed), pos);
+            assert_eq!(ChunkArray::idx_to_pos(idx, sizes), pos);
+        }
+    }
+
+    #[test]
+    fn breaking_voxel_emits_event() {
+        use super::super::events::{self, VoxelEvent};
+
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        let pos = veci!(4, 5, 6);
+        let events = events::subscribe();
+
+        arr.apply_command(Command::SetVoxel { pos, new_id: AIR_VOXEL_DATA.id }, &mut ChangeTracker::new(arr.sizes))
+            .expect("voxel should be set");
+        arr.apply_command(Command::SetVoxel { pos, new_id: AIR_VOXEL_DATA.id }, &mut ChangeTracker::new(arr.sizes))
+            .expect("voxel should be set");
+
+        // Other tests may emit events concurrently.
+        let received: Vec<_> = events.try_iter()
+            .filter(|event| event.pos() == pos)
+            .collect();
+
+        assert_eq!(received, [VoxelEvent::Broken { pos, id: STONE_VOXEL_DATA.id }]);
+    }
+
+    #[test]
+    fn filling_voxels_emits_event() {
+        use super::super::events::{self, VoxelEvent};
+
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        let (pos_from, pos_to) = (veci!(7, 8, 9), veci!(10, 12, 14));
+        let events = events::subscribe();
+
+        let fill = Command::FillVoxels { pos_from, pos_to, new_id: DIRT_VOXEL_DATA.id };
+        for _ in 0..2 {
+            arr.apply_command(fill, &mut ChangeTracker::new(arr.sizes))
+                .expect("voxels should be filled");
+        }
+
+        // Other tests may emit events concurrently.
+        let received: Vec<_> = events.try_iter()
+            .filter(|event| event.pos() == pos_from)
+            .collect();
+
+        assert_eq!(received, [VoxelEvent::Filled { pos_from, pos_to, id: DIRT_VOXEL_DATA.id }]);
+    }
+
+    #[test]
+    fn merged_frequencies_equal_concatenation() {
+        let lhs = [0, 1, 1, Id::MAX, 3];
+        let rhs = [Id::MAX, 1, 2, 0, 0];
+
+        let mut merged = ChunkArray::count_voxel_frequencies(lhs);
+        ChunkArray::accumulate_voxel_frequencies(&mut merged, rhs);
+
+        let concatenated = ChunkArray::count_voxel_frequencies(lhs.into_iter().chain(rhs));
+
+        assert_eq!(merged, concatenated);
+        assert_eq!(merged[&Id::MAX], 2);
+    }
+
+    #[test]
+    fn neighbors_generated_needs_all_in_bounds_neighbors() {
+        let mut arr = ChunkArray::new_empty_chunks(USize3::new(3, 1, 1))
+            .expect("sizes should be valid");
+
+        let generate = |arr: &mut ChunkArray, pos: Int3| {
+            let idx = ChunkArray::pos_to_idx(arr.sizes, pos).expect("pos should be in bounds");
+            arr.chunks[idx] = Arc::new(Chunk::new_same_filled(pos, STONE_VOXEL_DATA.id));
+        };
+
+        generate(&mut arr, veci!(0, 0, 0));
+        generate(&mut arr, veci!(1, 0, 0));
+
+        assert!(!arr.neighbors_generated(veci!(0, 0, 0)));
+        assert!(arr.neighbors_generated(veci!(1, 0, 0)));
+
+        generate(&mut arr, veci!(-1, 0, 0));
+        assert!(arr.neighbors_generated(veci!(0, 0, 0)));
+    }
+
+    #[test]
+    fn mesh_rebuild_plan_covers_buildable_chunks() {
+        let mut arr = ChunkArray::new_empty_chunks(USize3::new(3, 1, 1))
+            .expect("sizes should be valid");
+
+        for pos in [veci!(0, 0, 0), veci!(1, 0, 0)] {
+            let idx = ChunkArray::pos_to_idx(arr.sizes, pos).expect("pos should be in bounds");
+            arr.chunks[idx] = Arc::new(Chunk::new_same_filled(pos, STONE_VOXEL_DATA.id));
+        }
+
+        let cam_pos = vec3::new(1000.0, 0.0, 0.0);
+        let target_lods = arr.target_lod_iter(cam_pos).collect_vec();
+        let right_idx = ChunkArray::pos_to_idx(arr.sizes, veci!(1, 0, 0)).unwrap();
+
+        // Center chunk has ungenerated neighbor and left one is not generated.
+        assert_eq!(arr.mesh_rebuild_plan(cam_pos), vec![(right_idx, target_lods[right_idx])]);
+    }
+
+    #[test]
+    fn rebuilt_meshes_are_renderable_with_planned_lods() {
+        let (_event_loop, facade) = headless_facade();
+
+        let mut arr = single_chunk_array(AIR_VOXEL_DATA.id);
+        arr.fill_voxels(Int3::all(8), Int3::all(12), STONE_VOXEL_DATA.id).unwrap();
+
+        let cam = Camera::new();
+        let plan = arr.mesh_rebuild_plan(cam.pos);
+        assert!(!plan.is_empty(), "generated chunk should be planned for rebuild");
+
+        arr.rebuild_all_meshes(&facade, &cam);
+
+        for (idx, lod) in plan {
+            let mesh = arr.meshes[idx].borrow();
+
+            assert!(mesh.get_available_lods().contains(&lod), "LOD {lod} mesh should be built");
+            assert_ne!(mesh.triangle_count(lod), 0);
+            assert_eq!(arr.chunks[idx].info.load(Relaxed).active_lod, Some(lod));
+        }
+
+        assert_eq!(arr.n_tasks(), 0);
+    }
+
+    /// Makes [chunk][Chunk] which voxels stay [dense][FillType::Default] after optimization.
+    fn noisy_chunk() -> Chunk {
+        let noisy = Chunk::local_pos_iter()
+            .map(|pos| match (pos.x + 3 * pos.y + 7 * pos.z) % 3 {
+                0 => Atomic::new(AIR_VOXEL_DATA.id),
+                1 => Atomic::new(STONE_VOXEL_DATA.id),
+                _ => Atomic::new(DIRT_VOXEL_DATA.id),
+            })
+            .collect();
+
+        Chunk::from_voxels(noisy, Int3::ZERO).expect("voxel count should be valid")
+    }
+
+    #[test]
+    fn edited_chunks_are_optimized_lazily() {
+        let mut arr = ChunkArray::from_chunks(USize3::all(1), vec![Arc::new(noisy_chunk())])
+            .expect("sizes should be valid");
+
+        // Two partial fills make dense chunk uniform.
+        let end = Int3::from(Chunk::SIZES);
+        arr.fill_voxels(Int3::ZERO, end - veci!(0, 0, 1), STONE_VOXEL_DATA.id).unwrap();
+        arr.fill_voxels(veci!(0, 0, end.z - 1), end, STONE_VOXEL_DATA.id).unwrap();
+        assert_eq!(arr.chunks[0].info.load(Relaxed).fill_type, FillType::Default);
+
+        let held = Arc::clone(&arr.chunks[0]);
+        arr.optimize_edited_chunks(0.0);
+        assert_eq!(arr.chunks[0].info.load(Relaxed).fill_type, FillType::Default);
+        assert!(arr.edited_chunks.contains(&Int3::ZERO), "shared chunk should wait");
+
+        drop(held);
+        arr.optimize_edited_chunks(0.0);
+        assert!(arr.chunks[0].fill_id().is_none(), "pass should wait for cooldown");
+
+        arr.optimize_edited_chunks(10.0);
+        assert_eq!(arr.chunks[0].fill_id(), Some(STONE_VOXEL_DATA.id));
+        assert!(arr.edited_chunks.is_empty());
+    }
+
+    #[test]
+    fn far_voxels_are_evicted_in_tasks_once_per_period() {
+        let chunk = noisy_chunk();
+        let ids = chunk.ids().collect_vec();
+
+        let mut arr = ChunkArray::from_chunks(USize3::all(1), vec![Arc::new(chunk)])
+            .expect("sizes should be valid");
+        let far_pos = vec3::all(100.0 * Chunk::GLOBAL_SIZE);
+
+        RUNTIME.block_on(async {
+            arr.evict_far_voxels(far_pos, 0.0).await;
+            assert_eq!(arr.evict_tasks.len(), 1);
+            assert!(!arr.chunks[0].is_evicted(), "voxels should be compressed in task");
+
+            // Edit makes compressed voxels outdated.
+            arr.set_voxel(Int3::ZERO, STONE_VOXEL_DATA.id).expect("edit should be valid");
+            assert!(arr.evict_tasks.is_empty());
+
+            // Next pass waits for the whole period.
+            arr.evict_far_voxels(far_pos, 0.0).await;
+            assert!(arr.evict_tasks.is_empty());
+
+            arr.evict_far_voxels(far_pos, 10.0).await;
+            while !arr.evict_tasks.is_empty() {
+                tokio::task::yield_now().await;
+                arr.try_finish_evict_tasks().await;
+            }
+        });
+
+        assert!(arr.chunks[0].is_evicted());
+        assert_eq!(arr.chunks[0].ids().skip(1).collect_vec(), ids[1..]);
+        assert_eq!(arr.chunks[0].ids().next(), Some(STONE_VOXEL_DATA.id));
+
+        // Voxels read since the last pass stay decoded, unused ones are dropped.
+        RUNTIME.block_on(arr.evict_far_voxels(far_pos, 10.0));
+        assert!(arr.chunks[0].evicted.as_ref().unwrap().is_decoded());
+
+        RUNTIME.block_on(arr.evict_far_voxels(far_pos, 10.0));
+        assert!(!arr.chunks[0].evicted.as_ref().unwrap().is_decoded());
+    }
+
+    #[test]
+    fn failing_edit_replies_with_error() {
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        let mut change_tracker = ChangeTracker::new(arr.sizes);
+
+        let out_of_bounds = Command::SetVoxel { pos: veci!(100, 0, 0), new_id: AIR_VOXEL_DATA.id };
+        let (request, reply) = CommandRequest::with_reply(out_of_bounds);
+        arr.handle_request(request, &mut change_tracker);
+
+        assert!(reply.try_recv().expect("result should be sent").is_err());
+        assert!(change_tracker.voxel_poses.is_empty());
+
+        let in_bounds = Command::SetVoxel { pos: veci!(1, 2, 3), new_id: AIR_VOXEL_DATA.id };
+        let (request, reply) = CommandRequest::with_reply(in_bounds);
+        arr.handle_request(request, &mut change_tracker);
+
+        assert_eq!(reply.try_recv(), Ok(Ok(())));
+        assert!(change_tracker.voxel_poses.contains(&veci!(1, 2, 3)));
+    }
+
+    #[test]
+    fn region_spanning_two_chunks_reloads_them_and_boundary_neighbors() {
+        let sizes = USize3::all(3);
+        let idx_of = |pos: Int3| ChunkArray::pos_to_idx(sizes, pos).unwrap();
+        let size = Chunk::SIZE as i32;
+
+        let mut change_tracker = ChangeTracker::new(sizes);
+        change_tracker.track_region(veci!(size - 2, 0, 2), veci!(size + 2, 4, 4));
+        change_tracker.track_region(Int3::ONE, Int3::ONE);
+
+        assert_eq!(change_tracker.regions.len(), 1);
+
+        let expected = HashSet::from([
+            idx_of(veci!(0, 0, 0)),
+            idx_of(veci!(1, 0, 0)),
+            idx_of(veci!(0, -1, 0)),
+            idx_of(veci!(1, -1, 0)),
+        ]);
+
+        assert_eq!(change_tracker.idxs_to_reload(), expected);
+
+        let partitioned: HashSet<usize> = change_tracker.idxs_to_reload_partitioning()
+            .into_iter()
+            .map(|(idx, _)| idx)
+            .collect();
+
+        assert_eq!(partitioned, expected);
+    }
+
+    #[test]
+    fn bedrock_edits_are_rejected() {
+        let mut arr = single_chunk_array(STONE_VOXEL_DATA.id);
+        let bedrock_pos = veci!(4, 0, 4);
+
+        arr.set_voxel(bedrock_pos, BEDROCK_VOXEL_DATA.id).unwrap();
+
+        assert!(matches!(
+            arr.set_voxel(bedrock_pos, AIR_VOXEL_DATA.id),
+            Err(EditError::Unbreakable(pos)) if pos == bedrock_pos,
+        ));
+
+        assert!(matches!(
+            arr.fill_voxels(Int3::ZERO, veci!(8, 8, 8), AIR_VOXEL_DATA.id),
+            Err(EditError::Unbreakable(pos)) if pos == bedrock_pos,
+        ));
+
+        assert_eq!(arr.get_voxel(bedrock_pos).unwrap().data, BEDROCK_VOXEL_DATA);
+        assert_eq!(arr.get_voxel(veci!(1, 1, 1)).unwrap().data, STONE_VOXEL_DATA);
+        assert!(arr.explode(bedrock_pos, 3.0, 0).iter().all(|&(pos, _)| pos != bedrock_pos));
+    }
+
+    #[test]
+    fn find_unbreakable_checks_same_filled_and_mixed_chunks() {
+        let size = Chunk::SIZE as i32;
+        let chunks = vec![
+            Arc::new(Chunk::new_same_filled(veci!(0, 0, 0), STONE_VOXEL_DATA.id)),
+            Arc::new(Chunk::new_same_filled(veci!(1, 0, 0), BEDROCK_VOXEL_DATA.id)),
+        ];
+        let mut arr = ChunkArray::from_chunks(USize3::new(2, 1, 1), chunks)
+            .expect("sizes should be valid");
+
+        assert_eq!(arr.find_unbreakable(Int3::ZERO, Int3::all(size)), None);
+        assert_eq!(arr.find_unbreakable(Int3::ZERO, veci!(size + 1, 1, 1)), Some(veci!(size, 0, 0)));
+        assert_eq!(arr.find_unbreakable(veci!(size, 0, 0), veci!(size, 1, 1)), None);
+
+        let bedrock_pos = veci!(3, 5, 7);
+        arr.set_voxel(bedrock_pos, BEDROCK_VOXEL_DATA.id).unwrap();
+
+        assert_eq!(arr.find_unbreakable(Int3::ZERO, Int3::all(size)), Some(bedrock_pos));
+        assert_eq!(arr.find_unbreakable(Int3::ZERO, veci!(3, size, size)), None);
+    }
+
+    #[test]
+    fn sizes_input_validation() {
+        assert_eq!(ChunkArray::sizes_from_input(veci!(3, 1, 2)).unwrap(), USize3::new(3, 1, 2));
+        assert_eq!(ChunkArray::sizes_from_input(veci!(0, 0, 5)).unwrap(), USize3::new(1, 1, 5));
+        assert!(ChunkArray::sizes_from_input(veci!(-1, 1, 1)).is_err());
+        assert!(ChunkArray::sizes_from_input(veci!(1, 1, i32::MAX)).is_err());
+    }
+
+    #[test]
+    fn drag_box_across_chunks() {
+        let (from, to) = ChunkArray::drag_box(veci!(70, 5, -3), veci!(60, 10, 2));
+
+        assert_eq!(from, veci!(60, 5, -3));
+        assert_eq!(to, veci!(71, 11, 3));
+        assert_ne!(Chunk::local_pos(from), Chunk::local_pos(to - Int3::ONE));
+
+        let (from, to) = ChunkArray::drag_box(veci!(1, 2, 3), veci!(1, 2, 3));
+        assert_eq!(to - from, Int3::ONE);
+    }
+
+    #[test]
+    fn measurement_distances() {
+        let measurement = Measurement::new(veci!(1, 2, 3), veci!(4, 6, 3), 0);
+
+        assert_eq!(measurement.euclidean, 5.0);
+        assert_eq!(measurement.manhattan, 7);
+    }
+}
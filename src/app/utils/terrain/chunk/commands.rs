@@ -1,18 +1,31 @@
 use {
     crate::app::utils::{
-        terrain::voxel::voxel_data::Id,
+        terrain::{voxel::voxel_data::Id, chunk::brush::BrushShape},
         concurrency::channel::Channel,
+        reinterpreter::*,
+        logger,
     },
     math_linear::prelude::*,
     lazy_static::lazy_static,
-    std::sync::Mutex,
+    thiserror::Error,
+    std::{
+        sync::{Arc, Mutex, mpsc, atomic::{AtomicBool, Ordering::AcqRel}},
+        time::{Instant, Duration},
+        fs::{self, File},
+        io::{self, Write},
+        path::Path,
+    },
 };
 
 lazy_static! {
-    pub(super) static ref COMMAND_CHANNEL: Mutex<Channel<Command>> = Mutex::new(Channel::default());
+    pub(super) static ref COMMAND_CHANNEL: Mutex<Channel<CommandRequest>> = Mutex::new(Channel::default());
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Sizes of current chunk array. It is updated on [command][Command] processing so that
+/// console can read it without sending a command.
+pub static WORLD_SIZES: Mutex<[usize; 3]> = Mutex::new([0; 3]);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Command {
     SetVoxel {
         pos: Int3,
@@ -25,13 +38,303 @@ pub enum Command {
         new_id: Id,
     },
 
+    /// Calls [interaction hook][crate::terrain::voxel::voxel_data::VoxelData::on_interact] of voxel.
+    Interact {
+        pos: Int3,
+    },
+
+    Explode {
+        center: Int3,
+        radius: f32,
+        seed: u64,
+    },
+
+    ForceFullDetail {
+        pos_from: Int3,
+        pos_to: Int3,
+        enabled: bool,
+    },
+
+    /// Replaces generator seed and regenerates the world.
+    SetSeed {
+        seed: u64,
+    },
+
     DropAllMeshes,
+
+    /// Rebuilds all meshes with current [LODs][crate::terrain::chunk::Lod] on next update.
+    RebuildMeshes,
+
+    /// Sets voxels under [brush][crate::terrain::chunk::brush::Brush] placed at `center`.
+    Paint {
+        center: Int3,
+        shape: BrushShape,
+        radius: i32,
+        new_id: Id,
+    },
 }
 
-pub fn command(command: Command) {
+impl AsBytes for Command {
+    fn as_bytes(&self) -> Vec<u8> {
+        use Command::*;
+
+        match *self {
+            SetVoxel { pos, new_id } => compose! {
+                0_u8.as_bytes(), pos.as_bytes(), new_id.as_bytes(),
+            }.collect(),
+
+            FillVoxels { pos_from, pos_to, new_id } => compose! {
+                1_u8.as_bytes(), pos_from.as_bytes(), pos_to.as_bytes(), new_id.as_bytes(),
+            }.collect(),
+
+            Interact { pos } => compose! {
+                2_u8.as_bytes(), pos.as_bytes(),
+            }.collect(),
+
+            Explode { center, radius, seed } => compose! {
+                3_u8.as_bytes(), center.as_bytes(), radius.as_bytes(), seed.as_bytes(),
+            }.collect(),
+
+            ForceFullDetail { pos_from, pos_to, enabled } => compose! {
+                4_u8.as_bytes(), pos_from.as_bytes(), pos_to.as_bytes(), enabled.as_bytes(),
+            }.collect(),
+
+            SetSeed { seed } => compose! {
+                5_u8.as_bytes(), seed.as_bytes(),
+            }.collect(),
+
+            DropAllMeshes => vec![6],
+
+            RebuildMeshes => vec![7],
+
+            Paint { center, shape, radius, new_id } => compose! {
+                8_u8.as_bytes(), center.as_bytes(), shape.as_bytes(), radius.as_bytes(), new_id.as_bytes(),
+            }.collect(),
+        }
+    }
+}
+
+impl FromBytes for Command {
+    fn from_bytes(source: &[u8]) -> Result<Self, ReinterpretError> {
+        use Command::*;
+
+        let mut reader = ByteReader::new(source);
+        let variant: u8 = reader.read()?;
+
+        Ok(match variant {
+            0 => SetVoxel { pos: reader.read()?, new_id: reader.read()? },
+            1 => FillVoxels { pos_from: reader.read()?, pos_to: reader.read()?, new_id: reader.read()? },
+            2 => Interact { pos: reader.read()? },
+            3 => Explode { center: reader.read()?, radius: reader.read()?, seed: reader.read()? },
+            4 => ForceFullDetail { pos_from: reader.read()?, pos_to: reader.read()?, enabled: reader.read()? },
+            5 => SetSeed { seed: reader.read()? },
+            6 => DropAllMeshes,
+            7 => RebuildMeshes,
+            8 => Paint { center: reader.read()?, shape: reader.read()?, radius: reader.read()?, new_id: reader.read()? },
+            _ => return Err(ReinterpretError::Conversion(
+                format!("conversion of too large byte ({variant}) to Command")
+            )),
+        })
+    }
+}
+
+impl DynamicSize for Command {
+    fn dynamic_size(&self) -> usize {
+        self.as_bytes().len()
+    }
+}
+
+/// Gives sizes of current chunk array.
+pub fn world_sizes() -> USize3 {
+    USize3::from(*WORLD_SIZES.lock().unwrap())
+}
+
+/// Failure of [command][Command] processing.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+#[error("{0}")]
+pub struct CommandError(pub String);
+
+pub type CommandResult = Result<(), CommandError>;
+
+/// [Command] with optional sender of its [result][CommandResult].
+#[derive(Debug)]
+pub struct CommandRequest {
+    pub command: Command,
+    pub reply: Option<mpsc::Sender<CommandResult>>,
+
+    /// Set once, either by processor [claiming][CommandRequest::claim] the request
+    /// or by requester that stopped waiting for it.
+    is_claimed: Arc<AtomicBool>,
+}
+
+impl CommandRequest {
+    /// Makes request whose result is ignored.
+    pub fn new(command: Command) -> Self {
+        Self { command, reply: None, is_claimed: Default::default() }
+    }
+
+    /// Makes request and receiver of its result.
+    pub fn with_reply(command: Command) -> (Self, mpsc::Receiver<CommandResult>) {
+        let (sender, receiver) = mpsc::channel();
+        (Self { command, reply: Some(sender), is_claimed: Default::default() }, receiver)
+    }
+
+    /// Claims request for processing. Gives `false` if requester has cancelled it,
+    /// such request should be dropped without applying.
+    pub fn claim(&self) -> bool {
+        !self.is_claimed.swap(true, AcqRel)
+    }
+
+    /// Sends `result` to requester if it waits for one.
+    pub fn reply(self, result: CommandResult) {
+        if let Some(reply) = self.reply {
+            // Requester may have stopped waiting.
+            let _ = reply.send(result);
+        }
+    }
+}
+
+fn send_request(request: CommandRequest) {
     COMMAND_CHANNEL.lock()
         .unwrap()
         .sender
-        .send(command)
+        .send(request)
         .expect("failed to send command");
 }
+
+pub fn command(command: Command) {
+    send_request(CommandRequest::new(command));
+}
+
+/// Sends [command][Command] and blocks until it is processed or `timeout` passes.
+/// Timed out command is cancelled and will not be applied. If its processing has
+/// already started then its result is waited for.
+///
+/// # Deadlock
+///
+/// Commands are processed on the main thread, so calling it from there always times out.
+pub fn command_and_wait(command: Command, timeout: Duration) -> CommandResult {
+    let (request, receiver) = CommandRequest::with_reply(command);
+    let is_claimed = Arc::clone(&request.is_claimed);
+    send_request(request);
+
+    let dropped = || Err(CommandError(format!("{command:?} was dropped without result")));
+
+    receiver.recv_timeout(timeout).unwrap_or_else(|err| match err {
+        mpsc::RecvTimeoutError::Timeout if !is_claimed.swap(true, AcqRel) =>
+            Err(CommandError(format!("{command:?} is not processed in {timeout:?}"))),
+        mpsc::RecvTimeoutError::Timeout =>
+            receiver.recv().unwrap_or_else(|_| dropped()),
+        mpsc::RecvTimeoutError::Disconnected =>
+            dropped(),
+    })
+}
+
+/// Recording of [received][record] commands. See [`start_recording`].
+static RECORDER: Mutex<Option<CommandRecorder>> = Mutex::new(None);
+
+/// Writes [received][record] commands to file as they come.
+#[derive(Debug)]
+pub struct CommandRecorder {
+    pub file: File,
+    pub start: Instant,
+}
+
+/// [Command] with time in seconds since recording start.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RecordedCommand {
+    pub time: f64,
+    pub command: Command,
+}
+
+impl AsBytes for RecordedCommand {
+    fn as_bytes(&self) -> Vec<u8> {
+        compose! {
+            self.time.as_bytes(),
+            self.command.as_bytes(),
+        }.collect()
+    }
+}
+
+impl FromBytes for RecordedCommand {
+    fn from_bytes(source: &[u8]) -> Result<Self, ReinterpretError> {
+        read! {
+            source,
+            let time,
+            let command,
+        }
+
+        Ok(Self { time, command })
+    }
+}
+
+impl DynamicSize for RecordedCommand {
+    fn dynamic_size(&self) -> usize {
+        self.time.dynamic_size() + self.command.dynamic_size()
+    }
+}
+
+/// Starts writing all [received][record] commands to file on `path` replacing previous recording.
+pub fn start_recording(path: impl AsRef<Path>) -> io::Result<()> {
+    let file = File::create(path)?;
+    *RECORDER.lock().unwrap() = Some(CommandRecorder { file, start: Instant::now() });
+    Ok(())
+}
+
+/// Stops recording. Returns `true` if it was running.
+pub fn stop_recording() -> bool {
+    RECORDER.lock().unwrap().take().is_some()
+}
+
+/// Checks if commands are being recorded.
+pub fn is_recording() -> bool {
+    RECORDER.lock().unwrap().is_some()
+}
+
+/// Writes `command` to current recording if there is one. Recording stops on write failure.
+pub fn record(command: Command) {
+    let mut recorder = RECORDER.lock().unwrap();
+    let Some(CommandRecorder { file, start }) = recorder.as_mut() else { return };
+
+    let recorded = RecordedCommand { time: start.elapsed().as_secs_f64(), command };
+
+    if let Err(err) = file.write_all(&recorded.as_bytes()) {
+        logger::log!(Error, from = "commands", "failed to record {command:?}, recording is stopped: {err}");
+        *recorder = None;
+    }
+}
+
+/// Reads commands recorded by [`start_recording`].
+pub fn read_recording(path: impl AsRef<Path>) -> io::Result<Vec<RecordedCommand>> {
+    let bytes = fs::read(path)?;
+    let mut reader = ByteReader::new(&bytes);
+    let mut result = vec![];
+
+    while !reader.bytes.is_empty() {
+        let recorded = reader.read()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        result.push(recorded);
+    }
+
+    Ok(result)
+}
+
+/// Sends commands recorded on `path` again in same order. Delays between them are
+/// multiplied by `time_scale`, so zero sends all of them at once. Gives number of sent commands.
+pub async fn replay(path: impl AsRef<Path>, time_scale: f64) -> io::Result<usize> {
+    let recording = read_recording(path)?;
+    let mut prev_time = 0.0;
+
+    for recorded in recording.iter() {
+        let delay = (recorded.time - prev_time) * time_scale;
+        if delay > 0.0 {
+            tokio::time::sleep(Duration::from_secs_f64(delay)).await;
+        }
+
+        prev_time = recorded.time;
+        command(recorded.command);
+    }
+
+    Ok(recording.len())
+}
@@ -0,0 +1,80 @@
+//! Voxel edit events for subscribers such as audio.
+
+use {
+    crate::{prelude::*, terrain::voxel::voxel_data::{Id, data::AIR_VOXEL_DATA}},
+    std::sync::{Mutex, mpsc},
+};
+
+/// Senders of all [subscribers][subscribe]. Disconnected ones are removed on [emission][emit].
+static SUBSCRIBERS: Mutex<Vec<mpsc::Sender<VoxelEvent>>> = Mutex::new(vec![]);
+
+/// Edit of voxel made by [command][super::commands::Command] processing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoxelEvent {
+    /// Voxel with `id` is placed on `pos`.
+    Placed { pos: Int3, id: Id },
+
+    /// Voxel with `id` is broken on `pos`.
+    Broken { pos: Int3, id: Id },
+
+    /// Voxels in `pos_from..pos_to` are set to `id`. Single voxel edits are not reported for it.
+    Filled { pos_from: Int3, pos_to: Int3, id: Id },
+}
+
+impl VoxelEvent {
+    /// Gives event of voxel on `pos` changing from `old_id` to `new_id`.
+    /// Replacing non-air voxel counts as placement. Gives [`None`] if nothing has changed.
+    pub fn from_edit(pos: Int3, old_id: Id, new_id: Id) -> Option<Self> {
+        if old_id == new_id {
+            None
+        } else if new_id == AIR_VOXEL_DATA.id {
+            Some(Self::Broken { pos, id: old_id })
+        } else {
+            Some(Self::Placed { pos, id: new_id })
+        }
+    }
+
+    /// Gives position of edited voxel. Fills give their lowest corner.
+    pub fn pos(&self) -> Int3 {
+        match *self {
+            Self::Placed { pos, .. } | Self::Broken { pos, .. } => pos,
+            Self::Filled { pos_from, .. } => pos_from,
+        }
+    }
+}
+
+/// Gives receiver of all [voxel events][VoxelEvent] emitted from now on.
+/// Events are sent on the main thread, so receiver should be polled with `try_recv`.
+pub fn subscribe() -> mpsc::Receiver<VoxelEvent> {
+    let (sender, receiver) = mpsc::channel();
+    SUBSCRIBERS.lock().unwrap().push(sender);
+    receiver
+}
+
+/// Sends `event` to all subscribers.
+pub fn emit(event: VoxelEvent) {
+    SUBSCRIBERS.lock()
+        .unwrap()
+        .retain(|sender| sender.send(event).is_ok());
+}
+
+/// Sends [event][VoxelEvent] of voxel edit if something has changed.
+pub fn emit_edit(pos: Int3, old_id: Id, new_id: Id) {
+    if let Some(event) = VoxelEvent::from_edit(pos, old_id, new_id) {
+        emit(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::terrain::voxel::voxel_data::data::STONE_VOXEL_DATA};
+
+    #[test]
+    fn edit_gives_event_kind() {
+        let (pos, stone, air) = (veci!(1, 2, 3), STONE_VOXEL_DATA.id, AIR_VOXEL_DATA.id);
+
+        assert_eq!(VoxelEvent::from_edit(pos, air, stone), Some(VoxelEvent::Placed { pos, id: stone }));
+        assert_eq!(VoxelEvent::from_edit(pos, stone, air), Some(VoxelEvent::Broken { pos, id: stone }));
+        assert_eq!(VoxelEvent::from_edit(pos, stone, stone), None);
+    }
+}
@@ -0,0 +1,72 @@
+//! Compressed voxel storage of far chunks.
+
+use {
+    crate::{prelude::*, terrain::voxel::voxel_data::Id},
+    super::{Chunk, chunk_array::ChunkArray},
+    std::sync::{OnceLock, atomic::AtomicBool},
+};
+
+/// Voxels of [evicted][Chunk::evict_voxels] chunk kept as [save bytes][ChunkArray::chunk_as_bytes].
+/// They are decompressed on first access.
+#[derive(Debug)]
+pub struct EvictedVoxels {
+    pub bytes: Vec<u8>,
+    decoded: OnceLock<Vec<Id>>,
+
+    /// Set on each [access][EvictedVoxels::ids], see [`EvictedVoxels::forget_unused_decoded`].
+    is_accessed: AtomicBool,
+}
+
+impl Clone for EvictedVoxels {
+    fn clone(&self) -> Self {
+        Self {
+            bytes: self.bytes.clone(),
+            decoded: self.decoded.clone(),
+            is_accessed: AtomicBool::new(self.is_accessed.load(Relaxed)),
+        }
+    }
+}
+
+impl EvictedVoxels {
+    /// Compresses voxels of generated `chunk`.
+    pub fn new(chunk: &Chunk) -> Self {
+        Self {
+            bytes: ChunkArray::chunk_as_bytes(chunk),
+            decoded: OnceLock::new(),
+            is_accessed: AtomicBool::new(false),
+        }
+    }
+
+    /// Gives voxel [ids][Id] in [`Chunk::local_pos_iter`] order decompressing them if needed.
+    pub fn ids(&self) -> &[Id] {
+        self.is_accessed.store(true, Relaxed);
+
+        self.decoded.get_or_init(|| {
+            let (voxel_ids, _) = ChunkArray::array_filltype_from_bytes(&self.bytes, None);
+            voxel_ids.iter().map(|id| id.load(Relaxed)).collect()
+        })
+    }
+
+    /// Checks if voxels are decompressed.
+    pub fn is_decoded(&self) -> bool {
+        self.decoded.get().is_some()
+    }
+
+    /// Drops decompressed voxels keeping only bytes.
+    pub fn forget_decoded(&mut self) {
+        self.decoded.take();
+    }
+
+    /// Drops decompressed voxels if they were not accessed since the previous call.
+    pub fn forget_unused_decoded(&mut self) {
+        if !mem::take(self.is_accessed.get_mut()) {
+            self.forget_decoded();
+        }
+    }
+
+    /// Gives number of bytes allocated for voxels.
+    pub fn heap_size(&self) -> usize {
+        self.bytes.capacity()
+            + self.decoded.get().map_or(0, |ids| ids.capacity() * mem::size_of::<Id>())
+    }
+}
@@ -3,6 +3,7 @@
 #![allow(dead_code)]
 
 use {
+    crate::app::utils::vec_ext::Int3Ext,
     math_linear::prelude::*,
     std::ops::{Range, RangeBounds},
     smallvec::SmallVec,
@@ -294,6 +295,17 @@ impl SpaceIter {
         ].into_iter()
     }
 
+    /// Checks that `pos` is not yet yielded by this iterator.
+    pub fn contains(&self, pos: Int3) -> bool {
+        let Some(coord_idx) = (pos - self.back_shift).try_into_usize3() else { return false };
+
+        if self.sizes.x <= coord_idx.x || self.sizes.y <= coord_idx.y || self.sizes.z <= coord_idx.z {
+            return false;
+        }
+
+        (self.idx..self.size).contains(&coord_idx_to_idx(coord_idx, self.sizes))
+    }
+
     fn coord_idx_from_idx(idx: usize, sizes: USize3) -> USize3 {
         idx_to_coord_idx(idx, sizes)
     }
@@ -323,12 +335,15 @@ impl Iterator for SpaceIter {
             false => None,
         }
     }
-}
 
-impl ExactSizeIterator for SpaceIter {
-    fn len(&self) -> usize { self.size }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.size.saturating_sub(self.idx);
+        (len, Some(len))
+    }
 }
 
+impl ExactSizeIterator for SpaceIter { }
+
 impl DoubleEndedIterator for SpaceIter {
     // FIXME: wrong next_back() impl
     fn next_back(&mut self) -> Option<Self::Item> {
@@ -355,7 +370,10 @@ pub fn idx_to_coord_idx(idx: usize, sizes: USize3) -> USize3 {
     vecs!(x, y, z)
 }
 
-
+/// Inverse of [`idx_to_coord_idx`].
+pub fn coord_idx_to_idx(coord_idx: USize3, sizes: USize3) -> usize {
+    (coord_idx.x * sizes.y + coord_idx.y) * sizes.z + coord_idx.z
+}
 
 /// Walks around 3D array in very specific way.
 /// It breaks standart 3-fold cycle into chunks
@@ -428,6 +446,47 @@ impl Iterator for ChunkSplitten {
     }
 }
 
+/// Direction to adjacent position along one of the axes.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Direction {
+    PosX,
+    NegX,
+    PosY,
+    NegY,
+    PosZ,
+    NegZ,
+}
+
+impl Direction {
+    /// All directions in [`Sides`] order.
+    pub const ALL: [Self; 6] = [
+        Self::PosX, Self::NegX, Self::PosY, Self::NegY, Self::PosZ, Self::NegZ,
+    ];
+
+    /// Gives unit offset in this direction.
+    pub fn offset(self) -> Int3 {
+        match self {
+            Self::PosX => veci!( 1,  0,  0),
+            Self::NegX => veci!(-1,  0,  0),
+            Self::PosY => veci!( 0,  1,  0),
+            Self::NegY => veci!( 0, -1,  0),
+            Self::PosZ => veci!( 0,  0,  1),
+            Self::NegZ => veci!( 0,  0, -1),
+        }
+    }
+
+    /// Gives direction by unit offset or [`None`] if `offset` is not adjacent.
+    pub fn from_offset(offset: Int3) -> Option<Self> {
+        Self::ALL.into_iter()
+            .find(|dir| dir.offset() == offset)
+    }
+
+    /// Gives index of this direction in [`Sides`].
+    pub fn idx(self) -> usize {
+        self as usize
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
 pub struct Sides<T> {
     /// Adjacent chunks in order: `back[0] -> front[1] -> top[2] -> 
@@ -495,6 +554,11 @@ impl<T> Sides<T> {
         }
     }
 
+    /// Gives side in direction `dir`.
+    pub fn get(&self, dir: Direction) -> T where T: Clone {
+        self.inner[dir.idx()].clone()
+    }
+
     pub fn back_mut(&mut self)   -> &mut T { &mut self.inner[0] }
     pub fn front_mut(&mut self)  -> &mut T { &mut self.inner[1] }
     pub fn top_mut(&mut self)    -> &mut T { &mut self.inner[2] }
@@ -550,6 +614,24 @@ pub fn offsets_from_border(pos: Int3, bounds: Range<Int3>) -> SmallVec<[Int3; 6]
     result
 }
 
+/// Walks over voxel positions on the segment between `from` and `to` (both included) using DDA.
+/// Gives `max(|d.x|, |d.y|, |d.z|) + 1` positions where `d = to - from`.
+pub fn line_voxels(from: Int3, to: Int3) -> impl Iterator<Item = Int3> {
+    let diff = to - from;
+    let n_steps = i32::max(diff.x.abs(), i32::max(diff.y.abs(), diff.z.abs()));
+
+    (0..=n_steps).map(move |i| {
+        if n_steps == 0 { return from }
+
+        let t = i as f32 / n_steps as f32;
+        from + Int3::new(
+            (diff.x as f32 * t).round() as i32,
+            (diff.y as f32 * t).round() as i32,
+            (diff.z as f32 * t).round() as i32,
+        )
+    })
+}
+
 #[cfg(test)]
 mod space_iter_tests {
     use {super::*, math_linear::veci};
@@ -666,6 +748,31 @@ mod space_iter_tests {
         assert_eq!(res1, res2);
     }
 
+    #[test]
+    fn len_matches_count() {
+        let mut iter = SpaceIter::new(veci!(-8, 2, -10) .. veci!(9, 5, -5));
+        assert_eq!(iter.len(), iter.clone().count());
+
+        iter.next();
+        iter.next_back();
+        assert_eq!(iter.len(), iter.clone().count());
+        assert_eq!(iter.len(), 17 * 3 * 5 - 2);
+    }
+
+    #[test]
+    fn contains_matches_iteration() {
+        let mut iter = SpaceIter::new(veci!(-3, 0, -2) .. veci!(2, 3, 1));
+        let first = iter.next().unwrap();
+
+        let yielded: std::collections::HashSet<_> = iter.clone().collect();
+
+        for pos in SpaceIter::new_cubed(-5..5) {
+            assert_eq!(iter.contains(pos), yielded.contains(&pos), "{pos}");
+        }
+
+        assert!(!iter.contains(first));
+    }
+
     #[test]
     fn uniqueness() {
         let iter = SpaceIter::new(veci!(-8, 2, -10) .. veci!(9, 5, -5));
@@ -803,4 +910,36 @@ mod splitten_tests {
             );
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod line_tests {
+    use {super::*, math_linear::veci};
+
+    #[test]
+    fn single_point() {
+        let line: Vec<_> = line_voxels(veci!(3, -2, 1), veci!(3, -2, 1)).collect();
+        assert_eq!(line, vec![veci!(3, -2, 1)]);
+    }
+
+    #[test]
+    fn axis_aligned() {
+        let line: Vec<_> = line_voxels(veci!(0, 0, 0), veci!(0, 0, -4)).collect();
+        let expected: Vec<_> = (0..=4).map(|z| veci!(0, 0, -z)).collect();
+        assert_eq!(line, expected);
+    }
+
+    #[test]
+    fn diagonal_is_connected() {
+        let (from, to) = (veci!(-3, 1, 2), veci!(5, -4, 9));
+        let line: Vec<_> = line_voxels(from, to).collect();
+
+        assert_eq!(line.first(), Some(&from));
+        assert_eq!(line.last(), Some(&to));
+        assert_eq!(line.len(), 8 + 1);
+
+        for pair in line.windows(2) {
+            let diff = pair[1] - pair[0];
+            assert!(diff.x.abs() <= 1 && diff.y.abs() <= 1 && diff.z.abs() <= 1);
+        }
+    }
+}
@@ -4,25 +4,29 @@ use {
         graphics::{
             glium_mesh::{Mesh, UnindexedMesh},
             glium_shader::Shader,
+            grid::GridLines,
         },
-        terrain::chunk::prelude::*,
+        terrain::{chunk::prelude::*, voxel::Voxel},
     },
     glium::{
-        DrawError, uniforms::Uniforms, Surface, VertexBuffer,
+        DrawError, uniforms::{Uniforms, UniformValue}, Surface, VertexBuffer,
         DrawParameters, backend::Facade, index::PrimitiveType,
     },
 };
 
 /// Full-detailed vertex.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct FullVertex {
     pub position: (f32, f32, f32),
     pub tex_coords: (f32, f32),
     pub face_idx: u8,
+
+    /// Texture animation as `(n_frames, fps)`, see [`TextureAnimation`][crate::terrain::voxel::voxel_data::TextureAnimation].
+    pub anim: (u8, u8),
 }
 
 /// Low-detailed vertex.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct LowVertex {
     pub position: (f32, f32, f32),
     pub color: (f32, f32, f32),
@@ -30,9 +34,109 @@ pub struct LowVertex {
 }
 
 /* Implement Vertex structs as glium intended */
-glium::implement_vertex!(FullVertex, position, tex_coords, face_idx);
+glium::implement_vertex!(FullVertex, position, tex_coords, face_idx, anim);
 glium::implement_vertex!(LowVertex, position, color, face_idx);
 
+/// Caller [uniforms][Uniforms] extended with ones set by chunk drawing itself.
+#[derive(Clone, Copy, Debug)]
+pub struct ChunkUniforms<'u, U> {
+    pub inner: &'u U,
+    pub grid: GridLines,
+
+    /// See [`Camera::log_depth_coef`][crate::graphics::camera::Camera::log_depth_coef].
+    pub log_depth_coef: f32,
+}
+
+impl<'u, U> ChunkUniforms<'u, U> {
+    pub fn new(inner: &'u U, grid: GridLines, log_depth_coef: f32) -> Self {
+        Self { inner, grid, log_depth_coef }
+    }
+}
+
+impl<U: Uniforms> Uniforms for ChunkUniforms<'_, U> {
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut output: F) {
+        self.inner.visit_values(&mut output);
+
+        output("grid_color", UniformValue::Vec4(self.grid.color));
+        output("grid_uv_width", UniformValue::Float(self.grid.uv_width()));
+        output("grid_world_width", UniformValue::Float(self.grid.world_width()));
+        output("log_depth_coef", UniformValue::Float(self.log_depth_coef));
+        output("voxel_size", UniformValue::Float(Voxel::SIZE));
+    }
+}
+
+/// Gives declared normal of cube face by its index.
+pub fn face_normal(face_idx: u8) -> Option<vec3> {
+    use cfg::terrain::*;
+
+    let normal = match face_idx as usize {
+        BACK_IDX   => BACK_NORMAL,
+        FRONT_IDX  => FRONT_NORMAL,
+        TOP_IDX    => TOP_NORMAL,
+        BOTTOM_IDX => BOTTOM_NORMAL,
+        RIGHT_IDX  => RIGHT_NORMAL,
+        LEFT_IDX   => LEFT_NORMAL,
+        _ => return None,
+    };
+
+    let (x, y, z) = normal;
+    Some(vec3::new(x, y, z))
+}
+
+/// Gives indices of triangles which winding disagrees with [normal][face_normal] of their face.
+/// Front side of triangle `(v0, v1, v2)` faces along `(v2 - v0) x (v1 - v0)`.
+pub fn find_winding_mismatches(vertices: &[FullVertex]) -> Vec<usize> {
+    vertices.chunks_exact(3)
+        .enumerate()
+        .filter(|(_, triangle)| {
+            let [v0, v1, v2] = [0, 1, 2].map(|i| {
+                let (x, y, z) = triangle[i].position;
+                vec3::new(x, y, z)
+            });
+            let winding_normal = (v2 - v0).cross(v1 - v0);
+
+            match face_normal(triangle[0].face_idx) {
+                Some(normal) => {
+                    let dot = winding_normal.x * normal.x
+                            + winding_normal.y * normal.y
+                            + winding_normal.z * normal.z;
+                    dot <= 0.0
+                },
+                None => true,
+            }
+        })
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Shape of full detail [chunk][Chunk] meshes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MeshingMode {
+    /// Voxels are cubes.
+    #[default]
+    Blocky,
+
+    /// Terrain surface is [smoothed][super::smooth] over voxel solidity.
+    Smooth,
+}
+
+/// Vertices of [chunk][Chunk] mesh of some [LOD][Lod] that are ready to be uploaded.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChunkVertices {
+    Full(Vec<FullVertex>),
+    Low(Vec<LowVertex>, Lod),
+}
+
+impl ChunkVertices {
+    /// Uploads vertices to `mesh`.
+    pub fn upload(&self, mesh: &mut ChunkMesh, facade: &dyn Facade) {
+        match self {
+            Self::Full(vertices) => mesh.upload_full_detail_vertices(vertices, facade),
+            Self::Low(vertices, lod) => mesh.upload_low_detail_vertices(vertices, *lod, facade),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ChunkDetailedMesh {
     Standart(Box<UnindexedMesh<FullVertex>>),
@@ -48,6 +152,24 @@ impl ChunkDetailedMesh {
         }
     }
 
+    /// Gives number of triangles in all partitions.
+    pub fn triangle_count(&self) -> usize {
+        match self {
+            Self::Standart(mesh) => mesh.triangle_count(),
+            Self::Partial(meshes) => meshes.iter()
+                .map(Mesh::triangle_count)
+                .sum(),
+        }
+    }
+
+    /// Gives number of draw calls made by [render][ChunkDetailedMesh::render].
+    pub fn draw_call_count(&self) -> usize {
+        match self {
+            Self::Standart(_) => 1,
+            Self::Partial(meshes) => meshes.len(),
+        }
+    }
+
     pub fn render(
         &self, target: &mut impl Surface, shader: &Shader,
         draw_params: &DrawParameters<'_>, uniforms: &impl Uniforms,
@@ -67,10 +189,34 @@ impl ChunkDetailedMesh {
     }
 }
 
+/// Order of recently used [LOD][Lod]s, the most recent is the last.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LodUsage {
+    pub recent: SmallVec<[Lod; Chunk::N_LODS + 1]>,
+}
+
+impl LodUsage {
+    /// Marks `lod` as the most recently used one and gives least recently
+    /// used [LOD][Lod]s that exceed `capacity`. Used `lod` is never evicted.
+    pub fn use_lod(&mut self, lod: Lod, capacity: usize) -> SmallVec<[Lod; Chunk::N_LODS + 1]> {
+        self.recent.retain(|used| *used != lod);
+        self.recent.push(lod);
+
+        let n_evicted = self.recent.len().saturating_sub(capacity.max(1));
+        self.recent.drain(..n_evicted).collect()
+    }
+
+    /// Forgets all used [LOD][Lod]s.
+    pub fn clear(&mut self) {
+        self.recent.clear();
+    }
+}
+
 #[derive(Debug)]
 pub struct ChunkMesh {
     pub detailed_mesh: Option<ChunkDetailedMesh>,
     pub low_meshes: [Option<UnindexedMesh<LowVertex>>; Chunk::N_LODS],
+    pub lod_usage: LodUsage,
 }
 
 impl Default for ChunkMesh {
@@ -78,6 +224,7 @@ impl Default for ChunkMesh {
         Self {
             detailed_mesh: None,
             low_meshes: array_init(|_| None),
+            lod_usage: LodUsage::default(),
         }
     }
 }
@@ -121,6 +268,25 @@ impl ChunkMesh {
     pub fn drop_all(&mut self) {
         let _ = self.detailed_mesh.take();
         for _ in self.low_meshes.iter_mut().filter_map(|m| m.take()) { }        
+        self.lod_usage.clear();
+    }
+
+    /// Drops mesh of given [LOD][Lod], if it exists.
+    pub fn drop_lod(&mut self, lod: Lod) {
+        match lod {
+            0 => drop(self.detailed_mesh.take()),
+            lod => if let Some(mesh) = self.low_meshes.get_mut(lod as usize - 1) {
+                drop(mesh.take());
+            },
+        }
+    }
+
+    /// Marks `lod` as used so its mesh will be kept while the camera moves around.
+    /// Drops least recently used meshes if there are more than [`cfg::terrain::N_CACHED_LODS`].
+    pub fn use_lod(&mut self, lod: Lod) {
+        for evicted in self.lod_usage.use_lod(lod, cfg::terrain::N_CACHED_LODS) {
+            self.drop_lod(evicted);
+        }
     }
 
     pub fn upload_partition(
@@ -204,6 +370,51 @@ impl ChunkMesh {
         Ok(())
     }
 
+    /// Reads full detail vertices back and [finds][find_winding_mismatches] triangles with wrong winding.
+    /// Indices of partitioned mesh triangles are counted through all partitions.
+    pub fn find_winding_mismatches(&self) -> Vec<usize> {
+        let read = |mesh: &UnindexedMesh<FullVertex>| mesh.vertices
+            .as_slice()
+            .read()
+            .expect("failed to read vertex buffer");
+
+        let vertices: Vec<FullVertex> = match self.detailed_mesh {
+            None => return vec![],
+            Some(ChunkDetailedMesh::Standart(ref mesh)) => read(mesh),
+            Some(ChunkDetailedMesh::Partial(ref meshes)) => meshes.iter()
+                .flat_map(read)
+                .collect(),
+        };
+
+        find_winding_mismatches(&vertices)
+    }
+
+    /// Gives number of triangles in mesh of given [LOD][Lod]. It is zero if there is no such mesh.
+    pub fn triangle_count(&self, lod: Lod) -> usize {
+        match lod {
+            0 => self.detailed_mesh.as_ref()
+                .map_or(0, ChunkDetailedMesh::triangle_count),
+
+            lod => self.low_meshes.get(lod as usize - 1)
+                .and_then(Option::as_ref)
+                .map_or(0, Mesh::triangle_count),
+        }
+    }
+
+    /// Gives number of draw calls made by [render][ChunkMesh::render] of given `lod`.
+    pub fn draw_call_count(&self, lod: Lod) -> usize {
+        match lod {
+            0 => self.detailed_mesh.as_ref()
+                .filter(|mesh| !mesh.is_empty())
+                .map_or(0, ChunkDetailedMesh::draw_call_count),
+
+            lod => self.low_meshes.get(lod as usize - 1)
+                .and_then(Option::as_ref)
+                .filter(|mesh| !mesh.is_empty())
+                .map_or(0, |_| 1),
+        }
+    }
+
     /// Gives list of available LODs.
     pub fn get_available_lods(&self) -> SmallVec<[Lod; Chunk::N_LODS]> {
         let mut result = smallvec![];
@@ -220,4 +431,67 @@ impl ChunkMesh {
 
         result
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_uniforms_extend_caller_ones() {
+        let inner = glium::uniform! { time: 1.5_f32 };
+        let grid = GridLines { is_enabled: true, ..GridLines::DEFAULT };
+        let uniforms = ChunkUniforms::new(&inner, grid, 0.25);
+
+        let mut names = vec![];
+        uniforms.visit_values(|name, value| {
+            match name {
+                "log_depth_coef" => assert!(matches!(value, UniformValue::Float(coef) if coef == 0.25)),
+                "voxel_size" => assert!(matches!(value, UniformValue::Float(size) if size == Voxel::SIZE)),
+                _ => (),
+            }
+
+            names.push(name.to_owned());
+        });
+
+        assert_eq!(names, ["time", "grid_color", "grid_uv_width", "grid_world_width", "log_depth_coef", "voxel_size"]);
+    }
+
+    #[test]
+    fn cached_lod_is_not_evicted() {
+        let mut usage = LodUsage::default();
+
+        assert!(usage.use_lod(0, 2).is_empty());
+        assert!(usage.use_lod(1, 2).is_empty());
+
+        // Revisiting cached LOD keeps it.
+        assert!(usage.use_lod(0, 2).is_empty());
+
+        // LOD 1 is the least recently used one.
+        assert_eq!(usage.use_lod(2, 2).as_slice(), &[1]);
+        assert_eq!(usage.recent.as_slice(), &[0, 2]);
+    }
+
+    #[test]
+    fn cube_faces_winding_matches_normals() {
+        use crate::terrain::voxel::{shape::CubeDetailed, voxel_data::data::*};
+
+        let mut vertices = SmallVec::<[FullVertex; 36]>::new();
+        CubeDetailed::new(STONE_VOXEL_DATA).all(vec3::new(1.0, -2.0, 3.0), &mut vertices);
+
+        assert_eq!(vertices.len(), 36);
+        assert!(find_winding_mismatches(&vertices).is_empty());
+
+        vertices.swap(1, 2);
+        assert_eq!(find_winding_mismatches(&vertices), vec![0]);
+    }
+
+    #[test]
+    fn used_lod_is_never_evicted() {
+        let mut usage = LodUsage::default();
+
+        assert!(usage.use_lod(3, 0).is_empty());
+        assert_eq!(usage.use_lod(4, 0).as_slice(), &[3]);
+        assert_eq!(usage.recent.as_slice(), &[4]);
+    }
+}
@@ -4,14 +4,26 @@ pub mod iterator;
 pub mod chunk_array;
 pub mod tasks;
 pub mod commands;
+pub mod events;
 pub mod mesh;
+pub mod boundary;
+pub mod smooth;
+pub mod render_stats;
+pub mod breaking;
+pub mod placement;
+pub mod brush;
+pub mod octree;
+pub mod evicted;
+
+#[cfg(test)]
+mod bench;
 
 use {
     crate::{
         prelude::*,
         graphics::{
             glium_shader::Shader,
-            camera::Camera,
+            camera::{Camera, frustum::Frustum},
         },
     },
     super::voxel::{
@@ -22,14 +34,17 @@ use {
         voxel_data::{data::*, Id},
         generator as gen,
     },
-    mesh::{LowVertex, FullVertex, ChunkMesh},
+    mesh::{LowVertex, FullVertex, ChunkMesh, MeshingMode},
+    boundary::AdjBoundary,
     chunk_array::ChunkAdj,
+    octree::{VoxelOctree, OctreeNode},
+    evicted::EvictedVoxels,
     glium::{
         self as gl,
         DrawError,
         uniforms::Uniforms,
     },
-    iterator::{CubeBorder, Sides},
+    iterator::{CubeBorder, Sides, Direction},
 };
 
 pub mod prelude {
@@ -37,6 +52,7 @@ pub mod prelude {
         Chunk,
         SetLodError,
         ChunkRenderError,
+        ChunkError,
         ChunkDrawBundle,
         Info as ChunkInfo,
         Lod,
@@ -52,12 +68,20 @@ pub struct Chunk {
     pub pos: Atomic<Int3>,
     pub voxel_ids: Vec<Atomic<Id>>,
     pub info: Atomic<Info>,
+
+    /// Voxels of [sparse][FillType::Sparse] chunk. [`Chunk::voxel_ids`] is empty then.
+    pub octree: Option<VoxelOctree>,
+
+    /// Compressed voxels of [evicted][FillType::Evicted] chunk. [`Chunk::voxel_ids`] is empty then.
+    pub evicted: Option<EvictedVoxels>,
 }
 
 impl Default for Chunk {
     fn default() -> Self {
         Self {
             voxel_ids: Default::default(),
+            octree: None,
+            evicted: None,
             pos: Default::default(),
             info: Atomic::new(Info {
                 fill_type: FillType::AllSame(AIR_VOXEL_DATA.id),
@@ -68,6 +92,20 @@ impl Default for Chunk {
     }
 }
 
+impl Clone for Chunk {
+    fn clone(&self) -> Self {
+        Self {
+            pos: Atomic::new(self.pos.load(Relaxed)),
+            voxel_ids: self.voxel_ids.iter()
+                .map(|id| Atomic::new(id.load(Relaxed)))
+                .collect(),
+            info: Atomic::new(self.info.load(Acquire)),
+            octree: self.octree.clone(),
+            evicted: self.evicted.clone(),
+        }
+    }
+}
+
 impl Chunk {
     /// [Chunk] size in voxels.
     pub const SIZE: usize = cfg::terrain::CHUNK_SIZE;
@@ -86,43 +124,88 @@ impl Chunk {
     
     /// Gives iterator over all voxels in chunk.
     pub fn voxels(&self) -> impl Iterator<Item = Voxel> + '_ {
-        self.voxel_ids.iter()
-            .map(|id| id.load(Relaxed))
+        self.ids()
             .zip(Chunk::global_pos_iter(self.pos.load(Relaxed)))
             .map(|(id, pos)| Voxel::new(pos, &VOXEL_DATA[id as usize]))
     }
 
+    /// Gives iterator over voxels with their local positions in [`Chunk::local_pos_iter`] order.
+    /// [Same-filled][FillType::AllSame] chunks yield all [`Chunk::VOLUME`] voxels.
+    /// Yields nothing for not [generated][Chunk::is_generated] chunks.
+    pub fn iter_voxels_with_pos(&self) -> impl Iterator<Item = (Int3, Voxel)> + '_ {
+        let chunk_pos = self.pos.load(Relaxed);
+        let fill_id = self.fill_id();
+
+        Chunk::local_pos_iter()
+            .take(if self.is_generated() { Chunk::VOLUME } else { 0 })
+            .enumerate()
+            .map(move |(idx, local_pos)| {
+                let id = fill_id.unwrap_or_else(|| self.get_id(idx).expect("idx should be valid"));
+                let global_pos = Chunk::local_to_global_pos(chunk_pos, local_pos);
+
+                (local_pos, Voxel::new(global_pos, &VOXEL_DATA[id as usize]))
+            })
+    }
+
     /// Gives iterator over low-detail voxels with their coords.
     pub fn low_voxel_iter(&self, lod: Lod) -> impl Iterator<Item = (LoweredVoxel, Int3)> + '_ {
         let sub_chunk_size = 2_i32.pow(lod);
 
-        Chunk::chunked_pos_iter(sub_chunk_size as usize)
-            .map(move |chunk_iter| {
-                let (color_sum, n_colors) = chunk_iter
-                    .filter_map(|pos| match self.get_voxel_local(pos) {
-                        None => {
-                            logger::log!(Error, from = "chunk", "failed to get voxel by pos {pos}");
-                            None
-                        },
-                        some => some,
-                    })
-                    .filter(|voxel| !voxel.is_air())
-                    .map(|voxel| voxel.data.avarage_color)
-                    .fold((Color::ZERO, 0_usize), |(col_acc, n_acc), col|
-                        (col_acc + col, n_acc + 1)
-                    );
-
-                match n_colors {
-                    0 => LoweredVoxel::Transparent,
-                    n => LoweredVoxel::Colored(color_sum / n as f32),
-                }
-            })
-            .zip(SpaceIter::zeroed_cubed(Chunk::SIZE as i32 / sub_chunk_size))
+        SpaceIter::zeroed_cubed(Chunk::SIZE as i32 / sub_chunk_size)
+            .map(move |lod_cell| (self.sample_lod(lod_cell, lod), lod_cell))
+    }
+
+    /// Samples low-detail voxel covering cube of `2^lod` voxels at `local_lod_cell`.
+    /// Color is taken from the most common opaque voxel so thin features don't vanish
+    /// at distance. Gives [transparent][LoweredVoxel::Transparent] voxel only if
+    /// all covered voxels are transparent.
+    pub fn sample_lod(&self, local_lod_cell: Int3, lod: Lod) -> LoweredVoxel {
+        let cell_size = 2_i32.pow(lod);
+        let cell_start = local_lod_cell * cell_size;
+
+        // Cell has few distinct ids so linear search beats hashing and avoids allocation.
+        let mut counts = SmallVec::<[(Id, usize); 8]>::new();
+
+        for pos in SpaceIter::new(cell_start..cell_start + Int3::all(cell_size)) {
+            let Some(voxel) = self.get_voxel_local(pos) else {
+                logger::log!(Error, from = "chunk", "failed to get voxel by pos {pos}");
+                continue;
+            };
+
+            if voxel.data.is_transparent { continue }
+
+            match counts.iter_mut().find(|(id, _)| *id == voxel.data.id) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((voxel.data.id, 1)),
+            }
+        }
+
+        // Ties are resolved to smaller id to keep sampling deterministic.
+        counts.into_iter()
+            .max_by_key(|&(id, count)| (count, std::cmp::Reverse(id)))
+            .map_or(LoweredVoxel::Transparent, |(id, _)|
+                LoweredVoxel::Colored(VOXEL_DATA[id as usize].avarage_color)
+            )
+    }
+
+    /// Gives iterator over all voxel [ids][Id] in [`Chunk::local_pos_iter`] order.
+    /// Yields nothing for not [generated][Chunk::is_generated] chunks.
+    pub fn ids(&self) -> Box<dyn Iterator<Item = Id> + '_> {
+        if !self.is_generated() {
+            return Box::new(std::iter::empty());
+        }
+
+        match self.info.load(Relaxed).fill_type {
+            FillType::Default => Box::new(self.voxel_ids.iter().map(|id| id.load(Relaxed))),
+            FillType::AllSame(id) => Box::new(std::iter::repeat(id).take(Self::VOLUME)),
+            FillType::Sparse => Box::new(self.sparse_to_dense().into_iter()),
+            FillType::Evicted => Box::new(self.evicted.iter().flat_map(|evicted| evicted.ids().iter().copied())),
+        }
     }
 
     /// Checks if chunk is empty.
     pub fn is_empty(&self) -> bool {
-        if self.voxel_ids.is_empty() {
+        if !self.is_generated() {
             return true
         }
 
@@ -132,6 +215,25 @@ impl Chunk {
         }
     }
 
+    /// Counts non-air voxels in chunk. It is O(1) for [same-filled][FillType::AllSame] chunks.
+    pub fn count_non_air(&self) -> usize {
+        if !self.is_generated() {
+            return 0
+        }
+
+        match self.info.load(Relaxed).fill_type {
+            FillType::AllSame(id) => if id == AIR_VOXEL_DATA.id { 0 } else { Self::VOLUME },
+            FillType::Default => self.voxel_ids.iter()
+                .filter(|id| id.load(Relaxed) != AIR_VOXEL_DATA.id)
+                .count(),
+            FillType::Sparse => self.octree.as_ref()
+                .map_or(0, |octree| octree.count(|id| id != AIR_VOXEL_DATA.id)),
+            FillType::Evicted => self.ids()
+                .filter(|&id| id != AIR_VOXEL_DATA.id)
+                .count(),
+        }
+    }
+
     /// Gives `Some()` with fill id or returns `None`.
     pub fn fill_id(&self) -> Option<Id> {
         match self.info.load(Relaxed).fill_type {
@@ -145,19 +247,104 @@ impl Chunk {
         self.fill_id().is_some()
     }
 
-    /// Checks if chunk is filled with non-air voxels.
+    /// Checks if chunk is filled with opaque voxels, so it hides faces of adjacent chunks.
     pub fn is_filled(&self) -> bool {
         self.info.load(Relaxed).is_filled
     }
 
     /// Gives [`Vec`] with full detail vertices mesh of [`Chunk`].
     pub fn make_vertices_detailed(&self, chunk_adj: ChunkAdj) -> Vec<FullVertex> {
-        let is_filled_and_blocked = self.is_filled() && Self::is_adj_filled(&chunk_adj);
+        let is_adj_filled = Self::is_adj_filled(&chunk_adj);
+
+        self.make_vertices_detailed_by(is_adj_filled, |dir, pos| chunk_adj.get(dir).and_then(|chunk|
+            chunk.get_voxel_global(pos)
+                .inspect_failed(|| logger::log!(Error, from = "chunk", "caught on failed chunk voxel in {pos}"))
+                .into_option()
+        ))
+    }
+
+    /// Gives [`Vec`] with full detail vertices mesh of [`Chunk`] of given [shape][MeshingMode].
+    pub fn make_vertices_full(&self, chunk_adj: ChunkAdj, mode: MeshingMode) -> Vec<FullVertex> {
+        match mode {
+            MeshingMode::Blocky => self.make_vertices_detailed(chunk_adj),
+            MeshingMode::Smooth => self.make_vertices_smooth(chunk_adj, 0),
+        }
+    }
+
+    /// Gives [`Vec`] with [smooth][smooth] mesh vertices of [`Chunk`]. Density is sampled
+    /// every `2^lod` voxels. Chunk meshes cubes that start inside of it, so cubes on positive
+    /// sides and normals near any side sample adjacent chunks. Voxels of missing adjacent
+    /// chunks are unknown, so cubes touching them are left for the chunks to be generated.
+    pub fn make_vertices_smooth(&self, chunk_adj: ChunkAdj, lod: Lod) -> Vec<FullVertex> {
+        if self.is_empty() { return vec![] }
+
+        let chunk_pos = self.pos.load(Relaxed);
+        let size = Self::SIZE as i32;
+        let step = 1 << lod;
+
+        let sample = |local_pos: Int3| {
+            let out_dirs: SmallVec<[Direction; 3]> = [
+                (local_pos.x, Direction::PosX, Direction::NegX),
+                (local_pos.y, Direction::PosY, Direction::NegY),
+                (local_pos.z, Direction::PosZ, Direction::NegZ),
+            ]
+            .into_iter()
+            .filter_map(|(coord, pos_dir, neg_dir)| match coord {
+                coord if size <= coord => Some(pos_dir),
+                coord if coord < 0 => Some(neg_dir),
+                _ => None,
+            })
+            .collect();
+
+            let voxel = match out_dirs.as_slice() {
+                [] => self.get_voxel_local(local_pos),
+
+                &[dir] => chunk_adj.get(dir).and_then(|adj|
+                    adj.get_voxel_global(Self::local_to_global_pos(chunk_pos, local_pos)).into_option()
+                ),
+
+                // Diagonal chunks are not adjacent, so nearest voxel of this chunk is used.
+                dirs => match dirs.iter().all(|&dir| chunk_adj.get(dir).is_some()) {
+                    true => self.get_voxel_local(local_pos.clamp(Int3::ZERO, Int3::all(size - 1))),
+                    false => None,
+                },
+            };
+
+            voxel.map(smooth::Sample::from_voxel)
+        };
+
+        let origin = Self::global_pos(chunk_pos);
+        let mut vertices = vec![];
+
+        for min in SpaceIter::zeroed_cubed(size / step) {
+            smooth::polygonize_cube(origin, min * step, step, &sample, &mut vertices);
+        }
+
+        vertices
+    }
+
+    /// Gives [`Vec`] with full detail vertices mesh of [`Chunk`] using
+    /// copied [boundary][AdjBoundary] instead of adjacent chunks.
+    pub fn make_vertices_detailed_from_boundary(&self, boundary: &AdjBoundary) -> Vec<FullVertex> {
+        let chunk_pos = self.pos.load(Relaxed);
+
+        self.make_vertices_detailed_by(boundary.is_all_filled, |dir, pos| {
+            boundary.get_id(chunk_pos, dir, pos)
+                .map(|id| Voxel::new(pos, &VOXEL_DATA[id as usize]))
+        })
+    }
+
+    /// Gives [`Vec`] with full detail vertices mesh of [`Chunk`].
+    /// `get_adj_voxel` gives voxel of adjacent chunk in given direction by its position.
+    fn make_vertices_detailed_by(
+        &self, is_adj_filled: bool, get_adj_voxel: impl Fn(Direction, Int3) -> Option<Voxel>,
+    ) -> Vec<FullVertex> {
+        let is_filled_and_blocked = self.is_filled() && is_adj_filled;
         if self.is_empty() || is_filled_and_blocked { return vec![] }
 
         let info = self.info.load(Relaxed);
         let pos_iter: Box<dyn Iterator<Item = Int3>> = match info.fill_type {
-            FillType::Default =>
+            FillType::Default | FillType::Sparse | FillType::Evicted =>
                 Box::new(Chunk::local_pos_iter()),
 
             FillType::AllSame(id) => if id == AIR_VOXEL_DATA.id {
@@ -177,46 +364,25 @@ impl Chunk {
             })
             .filter(|voxel| !voxel.is_air())
             .flat_map(|voxel| {
-                let side_iter = SpaceIter::adj_iter(Int3::ZERO)
-                    .filter(|&offset| {
-                        let adj = chunk_adj.by_offset(offset);
-                        match self.get_voxel_global(voxel.pos + offset) {
-                            ChunkOption::Voxel(voxel) => voxel.is_air(),
-
-                            ChunkOption::OutsideChunk => match adj {
-                                None => true,
-
-                                Some(chunk) => match chunk.get_voxel_global(voxel.pos + offset) {
-                                    ChunkOption::Voxel(voxel) => voxel.is_air(),
-                                    ChunkOption::OutsideChunk => true,
-                                    ChunkOption::Failed => {
-                                        logger::log!(
-                                            Error, from = "chunk",
-                                            "caught on failed chunk voxel in {pos}",
-                                            pos = voxel.pos + offset,
-                                        );
-                                        true
-                                    },
-                                }
-                            },
-
-                            ChunkOption::Failed => {
-                                logger::log!(
-                                    Error, from = "chunk",
-                                    "caught on failed chunk voxel in {pos}",
-                                    pos = voxel.pos + offset,
-                                );
-                                true
-                            },
-                        }
+                let side_iter = Direction::ALL.into_iter()
+                    .filter(|&dir| {
+                        let pos = voxel.pos + dir.offset();
+                        let neighbor = match self.get_voxel_global(pos) {
+                            ChunkOption::OutsideChunk => get_adj_voxel(dir, pos),
+                            inner => inner
+                                .inspect_failed(|| logger::log!(Error, from = "chunk", "caught on failed chunk voxel in {pos}"))
+                                .into_option(),
+                        };
+
+                        voxel.face_visible_against(neighbor)
                     });
 
                 const N_CUBE_VERTICES: usize = 36;
                 let mut vertices = SmallVec::<[_; N_CUBE_VERTICES]>::new();
 
                 let mesh_builder = CubeDetailed::new(voxel.data);
-                for offset in side_iter {
-                    mesh_builder.by_offset(offset, voxel.pos.into(), &mut vertices);
+                for dir in side_iter {
+                    mesh_builder.by_offset(dir.offset(), voxel.pos.into(), &mut vertices);
                 }
 
                 vertices
@@ -260,46 +426,30 @@ impl Chunk {
             })
             .filter(|voxel| !voxel.is_air())
             .flat_map(|voxel| {
-                let offset_iter = SpaceIter::adj_iter(Int3::ZERO)
-                    .filter(|&offset| {
-                        let adj = chunk_adj.by_offset(offset);
-                        match self.get_voxel_global(voxel.pos + offset) {
-                            ChunkOption::Voxel(voxel) => voxel.is_air(),
-
-                            ChunkOption::OutsideChunk => match adj {
-                                None => true,
-
-                                Some(chunk) => match chunk.get_voxel_global(voxel.pos + offset) {
-                                    ChunkOption::Voxel(voxel) => voxel.is_air(),
-                                    ChunkOption::OutsideChunk => true,
-                                    ChunkOption::Failed => {
-                                        logger::log!(
-                                            Error, from = "chunk",
-                                            "caught on failed chunk voxel in {pos}",
-                                            pos = voxel.pos + offset,
-                                        );
-                                        true
-                                    },
-                                }
+                let side_iter = Direction::ALL.into_iter()
+                    .filter(|&dir| {
+                        let pos = voxel.pos + dir.offset();
+                        let neighbor = match self.get_voxel_global(pos) {
+                            ChunkOption::OutsideChunk => match chunk_adj.get(dir) {
+                                None => ChunkOption::OutsideChunk,
+                                Some(chunk) => chunk.get_voxel_global(pos),
                             },
+                            inner => inner,
+                        };
 
-                            ChunkOption::Failed => {
-                                logger::log!(
-                                    Error, from = "chunk",
-                                    "caught on failed chunk voxel in {pos}",
-                                    pos = voxel.pos + offset,
-                                );
-                                true
-                            },
-                        }
+                        let neighbor = neighbor
+                            .inspect_failed(|| logger::log!(Error, from = "chunk", "caught on failed chunk voxel in {pos}"))
+                            .into_option();
+
+                        voxel.face_visible_against(neighbor)
                     });
 
                 const N_CUBE_VERTICES: usize = 36;
                 let mut vertices = SmallVec::<[_; N_CUBE_VERTICES]>::new();
 
                 let mesh_builder = CubeDetailed::new(voxel.data);
-                for offset in offset_iter {
-                    mesh_builder.by_offset(offset, voxel.pos.into(), &mut vertices);
+                for dir in side_iter {
+                    mesh_builder.by_offset(dir.offset(), voxel.pos.into(), &mut vertices);
                 }
 
                 vertices
@@ -347,29 +497,24 @@ impl Chunk {
                           + 0.5 * vec3::all(sub_chunk_size as f32)) * Voxel::SIZE
                           - 0.5 * vec3::all(Voxel::SIZE);
                          
-                let is_blocking_voxel = |pos: Int3, offset: Int3| match self.get_voxel_global(pos) {
-                    ChunkOption::OutsideChunk => {
-                        match chunk_adj.by_offset(offset) {
+                let is_blocking_voxel = |pos: Int3, offset: Int3| {
+                    let voxel = match self.get_voxel_global(pos) {
+                        ChunkOption::OutsideChunk => match chunk_adj.by_offset(offset) {
                             /* There is no chunk so voxel isn't blocked */
-                            None => false,
-                            
+                            None => return false,
+
                             Some(chunk) => match chunk.get_voxel_global(pos) {
                                 ChunkOption::OutsideChunk => unreachable!("Can't fall out of an adjacent chunk"),
-                                ChunkOption::Voxel(voxel) => !voxel.is_air(),
-                                ChunkOption::Failed => {
-                                    logger::log!(Error, from = "chunk", "caught failed chunk voxel in {pos}");
-                                    false
-                                },
+                                inner => inner,
                             },
-                        }
-                    },
-
-                    ChunkOption::Voxel(voxel) => !voxel.is_air(),
+                        },
+                        inner => inner,
+                    };
 
-                    ChunkOption::Failed => {
-                        logger::log!(Error, from = "chunk", "caught failed chunk voxel in {pos}");
-                        false
-                    },
+                    voxel
+                        .inspect_failed(|| logger::log!(Error, from = "chunk", "caught failed chunk voxel in {pos}"))
+                        .map(|voxel| voxel.is_opaque())
+                        .unwrap_or(false)
                 };
 
                 let is_blocked_subchunk = |offset: Int3| -> bool {
@@ -420,7 +565,9 @@ impl Chunk {
 
         match self.info.load(Relaxed).fill_type {
             FillType::AllSame(id) => Some(id),
-            FillType::Default => Some(self.voxel_ids[idx].load(Relaxed))
+            FillType::Default => Some(self.voxel_ids[idx].load(Relaxed)),
+            FillType::Sparse => self.octree.as_ref()?.get(Self::idx_to_voxel_pos(idx)),
+            FillType::Evicted => self.evicted.as_ref()?.ids().get(idx).copied(),
         }
     }
 
@@ -460,86 +607,133 @@ impl Chunk {
 
     /// Tests that chunk is visible by camera.
     pub fn is_visible_by_camera(&self, camera: &mut Camera) -> bool {
-        let global_chunk_pos = Chunk::global_pos(self.pos.load(Relaxed));
-        let global_chunk_pos = vec3::from(global_chunk_pos) * Voxel::SIZE;
+        let (lo, hi) = Self::global_bounds(self.pos.load(Relaxed));
+        camera.is_aabb_in_view(AABB::from_float3(lo, hi))
+    }
+
+    /// Gives world space bounding box corners of chunk on `chunk_pos`.
+    pub fn global_bounds(chunk_pos: Int3) -> (vec3, vec3) {
+        let global_chunk_pos = vec3::from(Chunk::global_pos(chunk_pos)) * Voxel::SIZE;
 
         let lo = global_chunk_pos - 0.5 * vec3::all(Voxel::SIZE);
         let hi = lo + vec3::all(Chunk::GLOBAL_SIZE) - 0.5 * vec3::all(Voxel::SIZE);
 
-        camera.is_aabb_in_view(AABB::from_float3(lo, hi))
+        (lo, hi)
+    }
+
+    /// Tests that chunk on `chunk_pos` is within `render_distance` from `cam_pos` and is in `frustum`.
+    /// Chunk bounds are expanded by `margin` so chunks don't pop in at screen edges.
+    pub fn is_visible_with_margin(
+        chunk_pos: Int3, cam_pos: vec3, frustum: &Frustum, render_distance: f32, margin: f32,
+    ) -> bool {
+        let (lo, hi) = Self::global_bounds(chunk_pos);
+        let (lo, hi) = (lo - vec3::all(margin), hi + vec3::all(margin));
+
+        let closest = vec3::new(
+            cam_pos.x.clamp(lo.x, hi.x),
+            cam_pos.y.clamp(lo.y, hi.y),
+            cam_pos.z.clamp(lo.z, hi.z),
+        );
+
+        vec3::len(closest - cam_pos) <= render_distance
+            && frustum.is_aabb_in_frustum(AABB::from_float3(lo, hi))
     }
 
     /// Checks if [`Chunk`] is not already generated.
     pub fn is_generated(&self) -> bool {
-        !self.voxel_ids.is_empty()
+        !self.voxel_ids.is_empty() || self.octree.is_some() || self.evicted.is_some()
     }
 
     /// Generates voxel id array.
     pub fn generate_voxels(chunk_pos: Int3, chunk_array_sizes: USize3) -> Vec<Atomic<Id>> {
-        let mut result = Vec::with_capacity(Self::VOLUME);
-
-        for pos in Self::global_pos_iter(chunk_pos) {
-            let height = gen::perlin(pos, chunk_array_sizes);
-            let id = if pos.y <= height - 5 {
-                STONE_VOXEL_DATA.id
-            } else if pos.y < height {
-                DIRT_VOXEL_DATA.id
-            } else if pos.y <= height {
-                GRASS_VOXEL_DATA.id
-            } else {
-                AIR_VOXEL_DATA.id
-            };
-
-            result.push(Atomic::new(id));
-        }
+        let generator = gen::DefaultGenerator { chunk_array_sizes };
+        Self::generate_voxels_by(&generator, chunk_pos)
+    }
 
-        result
+    /// Generates voxel id array by given [generator][gen::WorldGenerator].
+    pub fn generate_voxels_by(generator: &dyn gen::WorldGenerator, chunk_pos: Int3) -> Vec<Atomic<Id>> {
+        generator.generate(chunk_pos, gen::seed())
+            .into_iter()
+            .map(Atomic::new)
+            .collect()
     }
 
     /// Generates a chunk.
     pub fn new(chunk_pos: Int3, chunk_array_sizes: USize3) -> Self {
-        Self::from_voxels(Self::generate_voxels(chunk_pos, chunk_array_sizes), chunk_pos)
+        Self::from_voxels_or_air(Self::generate_voxels(chunk_pos, chunk_array_sizes), chunk_pos)
     }
 
     /// Constructs empty chunk.
     pub fn new_empty(chunk_pos: Int3) -> Self {
-        Self::from_voxels(vec![], chunk_pos)
+        Self {
+            pos: Atomic::new(chunk_pos),
+            voxel_ids: vec![],
+            info: Default::default(),
+            octree: None,
+            evicted: None,
+        }
     }
 
-    pub fn new_same_filled(chunk_pos: Int3, fill_id: Id) -> Self {
-        Self {
+    /// Constructs [chunk][Chunk] filled with `fill_id` voxels.
+    /// 
+    /// # Error
+    /// 
+    /// Returns [`Err`] if `fill_id` is not valid.
+    pub fn try_new_same_filled(chunk_pos: Int3, fill_id: Id) -> Result<Self, ChunkError> {
+        if !voxel::is_id_valid(fill_id) {
+            return Err(ChunkError::InvalidId(fill_id));
+        }
+
+        Ok(Self {
             voxel_ids: vec![Atomic::new(fill_id)],
             info: Atomic::new(Info {
                 fill_type: FillType::AllSame(fill_id),
-                is_filled: true,
+                is_filled: voxel::is_id_opaque(fill_id),
                 active_lod: None,
             }),
             ..Self::new_empty(chunk_pos)
-        }
+        })
     }
 
-    /// Makes a [chunk][Chunk] out of voxel_ids.
+    /// Constructs [chunk][Chunk] filled with `fill_id` voxels.
     /// 
     /// # Panic
     /// 
-    /// Panics if `voxel_ids.len()` is not equal to `Chunk::VOLUME` or `0`.
-    pub fn from_voxels(voxel_ids: Vec<Atomic<Id>>, chunk_pos: Int3) -> Self {
+    /// Panics if `fill_id` is not valid.
+    pub fn new_same_filled(chunk_pos: Int3, fill_id: Id) -> Self {
+        Self::try_new_same_filled(chunk_pos, fill_id)
+            .expect("fill id should be valid")
+    }
+
+    /// Makes a [chunk][Chunk] out of voxel_ids.
+    /// 
+    /// # Error
+    /// 
+    /// Returns [`Err`] if `voxel_ids.len()` is not equal to `Chunk::VOLUME` or `0`.
+    pub fn from_voxels(voxel_ids: Vec<Atomic<Id>>, chunk_pos: Int3) -> Result<Self, ChunkError> {
         let len = voxel_ids.len();
-        assert!(
-            len == Self::VOLUME || len == 0,
-            "`voxel_ids.len()` should be equal to `Chunk::VOLUME` or `0`, but it's {len}",
-        );
+        if len != Self::VOLUME && len != 0 {
+            return Err(ChunkError::WrongVoxelCount { expected: Self::VOLUME, got: len });
+        }
 
-        Self {
-            pos: Atomic::new(chunk_pos),
+        Ok(Self {
             voxel_ids,
-            info: Default::default(),
-        }.as_optimized()
+            ..Self::new_empty(chunk_pos)
+        }.as_optimized())
+    }
+
+    /// Makes a [chunk][Chunk] out of voxel_ids. If it fails then logs an error
+    /// and gives air-filled [chunk][Chunk] instead.
+    pub fn from_voxels_or_air(voxel_ids: Vec<Atomic<Id>>, chunk_pos: Int3) -> Self {
+        Self::from_voxels(voxel_ids, chunk_pos).unwrap_or_else(|err| {
+            logger::log!(Error, from = "chunk", "failed to make chunk in {chunk_pos}: {err}, replacing it with air");
+            Self::new_same_filled(chunk_pos, AIR_VOXEL_DATA.id)
+        })
     }
 
     /// Sets [voxel id][Id] to `new_id` by it's index in array.
     /// Note that it does not drop all meshes that can possibly hold old id.
-    /// And note that it may unoptimize chunk even if it can be.
+    /// Storage is changed in place, so chunk may stay unoptimized until [`Chunk::optimize`].
     /// Returns old [id][Id].
     /// 
     /// # Error
@@ -553,31 +747,54 @@ impl Chunk {
         }
 
         let old_id = match self.info.load(Relaxed).fill_type {
-            FillType::Default => {
-                let old_id = self.voxel_ids[idx].swap(new_id, AcqRel);
-                if old_id != new_id { self.optimize() }
-                old_id
-            },
+            FillType::Default => self.voxel_ids[idx].swap(new_id, AcqRel),
 
             FillType::AllSame(old_id) => if old_id != new_id {
-                self.unoptimyze();
-                self.voxel_ids[idx].swap(new_id, AcqRel)
+                self.same_filled_to_sparse(old_id);
+                return self.set_id(idx, new_id);
             } else {
                 old_id
             },
+
+            FillType::Sparse => {
+                let octree = self.octree.as_mut()
+                    .expect("sparse chunk should have octree");
+
+                let old_id = octree.set(Self::idx_to_voxel_pos(idx), new_id)
+                    .expect("idx should be valid");
+
+                // Edit can merge the whole octree into single leaf.
+                if octree.root.leaf_id().is_some() {
+                    self.try_compact();
+                }
+
+                old_id
+            },
+
+            FillType::Evicted => {
+                self.ensure_voxels();
+                return self.set_id(idx, new_id);
+            },
         };
 
+        if old_id != new_id && !voxel::is_id_opaque(new_id) {
+            let mut info = self.info.load(Acquire);
+            info.is_filled = false;
+            self.info.store(info, Release);
+        }
+
         Ok(old_id)
     }
 
-    /// Sets new voxel [`id`][Id] to voxel by index.
-    /// 
-    /// # Safety
-    /// 
-    /// 1. `idx` < `Chunk::VOLUME`
-    /// 2. `self.info.fill_type` should be [`FillType::Default`].
-    pub unsafe fn set_id_fast(&self, idx: usize, new_id: Id) -> Id {
-        self.voxel_ids.get_unchecked(idx).swap(new_id, AcqRel)
+    /// Converts [same-filled][FillType::AllSame] chunk to [sparse][FillType::Sparse] one with single leaf.
+    fn same_filled_to_sparse(&mut self, id: Id) {
+        let mut info = self.info.load(Acquire);
+
+        self.voxel_ids = vec![];
+        self.octree = Some(VoxelOctree { size: Self::SIZE, root: OctreeNode::Leaf(id) });
+
+        info.fill_type = FillType::Sparse;
+        self.info.store(info, Release);
     }
 
     /// Sets voxel's id with position `pos` to `new_id` and returns old [id][Id]. If voxel is 
@@ -594,14 +811,7 @@ impl Chunk {
         let local_pos = Self::global_to_local_pos_checked(self.pos.load(Relaxed), pos)?;
         let idx = Self::voxel_pos_to_idx_unchecked(local_pos);
 
-        // We know that idx is valid so we can get-by-index.
-        let old_id = self.get_id(idx).expect("idx should be valid");
-        if old_id != new_id {
-            self.set_id(idx, new_id)?;
-            self.optimize();
-        }
-
-        Ok(old_id)
+        self.set_id(idx, new_id)
     }
 
     /// Sets voxel's ids in range `pos_from..pos_to` to index [`new_id`][Id].
@@ -616,29 +826,57 @@ impl Chunk {
         Self::global_to_local_pos_checked(pos, pos_to - Int3::ONE)?;
         let local_pos_to = Self::global_to_local_pos(pos, pos_to);
 
-        self.unoptimyze();
+        if local_pos_from == Int3::ZERO && local_pos_to == Int3::from(Self::SIZES) {
+            let is_changed = self.ids().any(|id| id != new_id);
+
+            let active_lod = self.info.load(Relaxed).active_lod;
+            *self = Self::new_same_filled(pos, new_id);
+            self.info.store(Info { active_lod, ..self.info.load(Relaxed) }, Release);
+
+            return Ok(is_changed);
+        }
 
         let mut is_changed = false;
 
         for local_pos in SpaceIter::new(local_pos_from..local_pos_to) {
             // We can safely not to check idx due to previous check.
             let idx = Self::voxel_pos_to_idx_unchecked(local_pos);
-            
-            let old_id = self.get_id(idx).expect("idx should be valid");
-            if old_id != new_id {
-                is_changed = true;
+            is_changed |= self.set_id(idx, new_id)? != new_id;
+        }
 
-                // * Safety:
-                // * Safe, because `idx` is valid and `self` is unoptimized.
-                unsafe {
-                    self.set_id_fast(idx, new_id);
-                }
-            }
+        Ok(is_changed)
+    }
+
+    /// Sets voxels with positions `poses` to [`new_id`][Id] in place.
+    /// Returns changed positions with their old [ids][Id].
+    ///
+    /// # Error
+    ///
+    /// Returns `Err` if `new_id` is not valid or some of `poses` is not in this [`Chunk`].
+    pub fn set_voxels(
+        &mut self, poses: impl IntoIterator<Item = Int3>, new_id: Id,
+    ) -> Result<Vec<(Int3, Id)>, EditError> {
+        if !voxel::is_id_valid(new_id) {
+            return Err(EditError::InvalidId(new_id));
         }
 
-        self.optimize();
+        let chunk_pos = self.pos.load(Relaxed);
+        let idxs: Vec<_> = poses.into_iter()
+            .map(|pos| Ok((pos, Self::voxel_pos_to_idx_unchecked(
+                Self::global_to_local_pos_checked(chunk_pos, pos)?
+            ))))
+            .collect::<Result<_, EditError>>()?;
 
-        Ok(is_changed)
+        let mut changed = Vec::with_capacity(idxs.len());
+
+        for (pos, idx) in idxs {
+            let old_id = self.set_id(idx, new_id)?;
+            if old_id != new_id {
+                changed.push((pos, old_id));
+            }
+        }
+
+        Ok(changed)
     }
 
     /// Gives iterator over all id-vectors in chunk (or relative to chunk voxel positions).
@@ -677,21 +915,159 @@ impl Chunk {
             ..Default::default()
         };
 
+        let is_all_opaque = self.voxel_ids.iter()
+            .all(|voxel_id| voxel::is_id_opaque(voxel_id.load(Relaxed)));
+        info.is_filled = is_all_opaque;
+
+        self.info.store(info, Release);
+
         /* All-same pass */
+        if !self.try_compact() {
+            /* Sparse pass */
+            self.try_sparsify();
+        }
+    }
+
+    /// Converts [`FillType::Default`] chunk to [`FillType::Sparse`] if its [octree][VoxelOctree]
+    /// takes at most [`cfg::terrain::SPARSE_CHUNK_SIZE_RATIO`] of dense storage.
+    /// Returns `true` if chunk was converted.
+    pub fn try_sparsify(&mut self) -> bool {
+        let mut info = self.info.load(Acquire);
+
+        if info.fill_type != FillType::Default || !self.is_generated() {
+            return false
+        }
+
+        let get_id = |pos| self.voxel_ids[Self::voxel_pos_to_idx_unchecked(pos)].load(Relaxed);
+
+        // Octree is measured before it's built to not allocate it for dense chunks.
+        let dense_size = self.voxel_ids.len() * mem::size_of::<Atomic<Id>>();
+        let octree_size = VoxelOctree::heap_size_of(Self::SIZE, get_id);
+        if octree_size as f32 > dense_size as f32 * cfg::terrain::SPARSE_CHUNK_SIZE_RATIO {
+            return false
+        }
+
+        let octree = VoxelOctree::new(Self::SIZE, get_id);
+
+        self.voxel_ids = vec![];
+        self.octree = Some(octree);
+
+        info.fill_type = FillType::Sparse;
+        self.info.store(info, Release);
+
+        true
+    }
+
+    /// Drops in-memory voxels of [`FillType::Default`] or [`FillType::Sparse`] chunk keeping
+    /// only their [compressed bytes][EvictedVoxels]. Voxels are decompressed lazily on access.
+    /// Evicting already evicted chunk drops its decompressed voxels.
+    /// Returns `true` if chunk is evicted.
+    pub fn evict_voxels(&mut self) -> bool {
+        if self.is_evicted() {
+            if let Some(evicted) = &mut self.evicted {
+                evicted.forget_decoded();
+            }
+
+            return true;
+        }
+
+        if !self.can_evict_voxels() {
+            return false;
+        }
+
+        self.set_evicted(EvictedVoxels::new(self));
+
+        true
+    }
+
+    /// Checks if chunk is generated [`FillType::Default`] or [`FillType::Sparse`] one.
+    pub fn can_evict_voxels(&self) -> bool {
+        matches!(self.info.load(Relaxed).fill_type, FillType::Default | FillType::Sparse)
+            && self.is_generated()
+    }
+
+    /// Replaces in-memory voxels with already compressed `evicted` ones.
+    /// `evicted` should be made from current voxels of this chunk.
+    pub fn set_evicted(&mut self, evicted: EvictedVoxels) {
+        let mut info = self.info.load(Acquire);
+
+        self.evicted = Some(evicted);
+        self.voxel_ids = vec![];
+        self.octree = None;
+
+        info.fill_type = FillType::Evicted;
+        self.info.store(info, Release);
+    }
+
+    /// Restores in-memory voxels of [evicted][Chunk::evict_voxels] chunk.
+    pub fn ensure_voxels(&mut self) {
+        if self.info.load(Relaxed).fill_type == FillType::Evicted {
+            self.optimize();
+        }
+    }
+
+    /// Checks if chunk voxels are [evicted][Chunk::evict_voxels].
+    pub fn is_evicted(&self) -> bool {
+        self.info.load(Relaxed).fill_type == FillType::Evicted
+    }
+
+    /// Gives voxel ids of [sparse][FillType::Sparse] chunk in dense array order.
+    fn sparse_to_dense(&self) -> Vec<Id> {
+        let mut dense = vec![AIR_VOXEL_DATA.id; Self::VOLUME];
+
+        if let Some(octree) = &self.octree {
+            octree.write_dense(&mut dense, Self::voxel_pos_to_idx_unchecked);
+        }
+
+        dense
+    }
+
+    /// Gives number of bytes allocated for voxels.
+    pub fn heap_size(&self) -> usize {
+        self.voxel_ids.capacity() * mem::size_of::<Atomic<Id>>()
+            + self.octree.as_ref().map_or(0, VoxelOctree::heap_size)
+            + self.evicted.as_ref().map_or(0, EvictedVoxels::heap_size)
+    }
+
+    /// Converts [`FillType::Default`] chunk with uniform voxel array or [`FillType::Sparse`]
+    /// chunk with single leaf to [`FillType::AllSame`]. Returns `true` if chunk was compacted.
+    pub fn try_compact(&mut self) -> bool {
+        let mut info = self.info.load(Acquire);
+
+        if info.fill_type == FillType::Sparse {
+            let Some(all) = self.octree.as_ref().and_then(|octree| octree.root.leaf_id()) else {
+                return false
+            };
+
+            self.octree = None;
+            self.voxel_ids = vec![Atomic::new(all)];
+
+            info.fill_type = FillType::AllSame(all);
+            info.is_filled = voxel::is_id_opaque(all);
+            self.info.store(info, Release);
+
+            return true
+        }
+
+        if info.fill_type != FillType::Default || !self.is_generated() {
+            return false
+        }
+
         let is_all_same = self.voxel_ids.iter()
             .map(|id| id.load(Relaxed))
             .all_equal();
-        if is_all_same {
-            let all = self.voxel_ids[0].load(Relaxed);
-            self.voxel_ids = vec![Atomic::new(all)];
-            info.fill_type = FillType::AllSame(all);
+        if !is_all_same {
+            return false
         }
 
-        let is_all_not_air = self.voxel_ids.iter()
-            .all(|voxel_id| voxel_id.load(Relaxed) != AIR_VOXEL_DATA.id);
-        info.is_filled = is_all_not_air;
+        let all = self.voxel_ids[0].load(Relaxed);
+        self.voxel_ids = vec![Atomic::new(all)];
 
+        info.fill_type = FillType::AllSame(all);
+        info.is_filled = voxel::is_id_opaque(all);
         self.info.store(info, Release);
+
+        true
     }
 
     /// Disapplies storage optimizations.
@@ -704,6 +1080,17 @@ impl Chunk {
                 self.voxel_ids = std::iter::from_fn(|| Some(Atomic::new(id)))
                     .take(Self::VOLUME)
                     .collect(),
+            FillType::Sparse => {
+                self.voxel_ids = self.sparse_to_dense()
+                    .into_iter()
+                    .map(Atomic::new)
+                    .collect();
+                self.octree = None;
+            },
+            FillType::Evicted => {
+                self.voxel_ids = self.evicted.take()
+                    .map_or_else(Vec::new, |evicted| evicted.ids().iter().copied().map(Atomic::new).collect());
+            },
         }
 
         info.fill_type = FillType::Default;
@@ -750,15 +1137,30 @@ impl Chunk {
         }
     }
 
+    /// Computes distance in voxels from `voxel_pos` to the closest voxel of [chunk][Chunk].
+    /// Gives `0.0` if `voxel_pos` is inside of [chunk][Chunk].
+    pub fn distance_to_voxel(chunk_pos: Int3, voxel_pos: Int3) -> f32 {
+        let min = Self::global_pos(chunk_pos);
+        let max = min + Int3::from(Self::SIZES) - Int3::ONE;
+
+        let closest = voxel_pos.clamp(min, max);
+
+        // Far offsets overflow `i32` when squared, so they are computed in floats.
+        let len_squared = [voxel_pos.x, voxel_pos.y, voxel_pos.z].into_iter()
+            .zip([closest.x, closest.y, closest.z])
+            .map(|(pos, closest)| (pos as i64 - closest as i64) as f64)
+            .map(|offset| offset * offset)
+            .sum::<f64>();
+
+        len_squared.sqrt() as f32
+    }
+
     /// Gives index in voxel array by it's 3D-index (or relative to chunk position)
     /// # Error
     /// Returns [`None`] if `pos` < [`Int3::ZERO`][Int3] or `pos` >= [`Chunk::SIZES`][Chunk].
     pub fn voxel_pos_to_idx(pos: Int3) -> Option<usize> {
-        if pos.x < 0 || pos.y < 0 || pos.z < 0 {
-            return None;
-        }
-
-        let idx = sdex::get_index(&USize3::from(pos).as_array(), &[Self::SIZE; 3]);
+        let pos = pos.try_into_usize3()?;
+        let idx = sdex::get_index(&pos.as_array(), &[Self::SIZE; 3]);
 
         (idx < Self::VOLUME).then_some(idx)
     }
@@ -769,11 +1171,19 @@ impl Chunk {
         sdex::get_index(&USize3::from(pos).as_array(), &[Self::SIZE; 3])
     }
 
+    /// Converts voxel array index to local voxel position.
+    pub fn idx_to_voxel_pos(idx: usize) -> Int3 {
+        iterator::idx_to_coord_idx(idx, Self::SIZES).into()
+    }
+
     /// Generates and sets [mesh][Mesh] to [chunk][Chunk].
-    pub fn generate_mesh(&self, mesh: &mut ChunkMesh, lod: Lod, chunk_adj: ChunkAdj, facade: &dyn gl::backend::Facade) {
+    pub fn generate_mesh(
+        &self, mesh: &mut ChunkMesh, lod: Lod, chunk_adj: ChunkAdj,
+        mode: MeshingMode, facade: &dyn gl::backend::Facade,
+    ) {
         match lod {
             0 => {
-                let vertices = self.make_vertices_detailed(chunk_adj);
+                let vertices = self.make_vertices_full(chunk_adj, mode);
                 mesh.upload_full_detail_vertices(&vertices, facade);
             },
             
@@ -803,9 +1213,20 @@ impl Chunk {
     }
 
     /// Sets active LOD to given value.
-    pub fn set_active_lod(&self, mesh: &ChunkMesh, lod: Lod) {
-        self.try_set_active_lod(mesh, lod)
-            .expect("new LOD value should be available")
+    ///
+    /// # Error
+    ///
+    /// Returns [`Err`] if `lod` is not one of [possible LODs][Chunk::get_possible_lods]
+    /// or `mesh` has no mesh for it. Active LOD is left unchanged then.
+    pub fn set_active_lod(&self, mesh: &mut ChunkMesh, lod: Lod) -> Result<(), ChunkError> {
+        if !Self::get_possible_lods().contains(&lod) {
+            return Err(ChunkError::LodOutOfRange { lod, n_lods: Self::N_LODS });
+        }
+
+        self.try_set_active_lod(mesh, lod)?;
+        mesh.use_lod(lod);
+
+        Ok(())
     }
 
     /// Tries to set active LOD to given value.
@@ -821,18 +1242,25 @@ impl Chunk {
         Ok(())
     }
 
-    /// Tries to set LOD value that have least difference with given value.
+    /// Tries to set available LOD value chosen by [`Chunk::best_fit_lod`].
     /// If there is at least one LOD it will return `Some(..)` with that value, otherwise, `None`.
-    pub fn try_set_best_fit_lod(&self, mesh: &ChunkMesh, lod: Lod) -> Option<Lod> {
-        let best_fit = mesh.get_available_lods()
-            .into_iter()
-            .min_by_key(|elem| elem.abs_diff(lod))?;
+    pub fn try_set_best_fit_lod(&self, mesh: &mut ChunkMesh, lod: Lod) -> Option<Lod> {
+        let best_fit = Self::best_fit_lod(mesh.get_available_lods(), lod)?;
 
-        self.set_active_lod(mesh, best_fit);
+        self.set_active_lod(mesh, best_fit)
+            .map_err(|err| logger::log!(Error, from = "chunk", "failed to set best fit LOD: {err}"))
+            .ok()?;
 
         Some(best_fit)
     }
 
+    /// Picks LOD from `available` that have least difference with `desired`.
+    /// Coarser LOD wins a tie so distant chunks don't briefly render at full detail.
+    pub fn best_fit_lod(available: impl IntoIterator<Item = Lod>, desired: Lod) -> Option<Lod> {
+        available.into_iter()
+            .min_by_key(|&lod| (lod.abs_diff(desired), std::cmp::Reverse(lod)))
+    }
+
     /// Gives list of all possible LODs.
     pub fn get_possible_lods() -> [Lod; Self::N_LODS] {
         array_init(|i| i as Lod)
@@ -847,7 +1275,7 @@ impl Chunk {
     }
 }
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum SetLodError {
     #[error("failed to set LOD value to {tried} because \
              there's no mesh for it. Active LOD value is {active:?}")]
@@ -857,6 +1285,27 @@ pub enum SetLodError {
     },
 }
 
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ChunkError {
+    #[error("wrong voxel count: expected {expected} or 0 but got {got}")]
+    WrongVoxelCount {
+        expected: usize,
+        got: usize,
+    },
+
+    #[error("LOD value {lod} is out of range 0..{n_lods}")]
+    LodOutOfRange {
+        lod: Lod,
+        n_lods: usize,
+    },
+
+    #[error(transparent)]
+    SetLod(#[from] SetLodError),
+
+    #[error("invalid voxel id {0}")]
+    InvalidId(Id),
+}
+
 #[derive(Error, Debug, Clone)]
 pub enum ChunkRenderError {
     #[error(transparent)]
@@ -877,6 +1326,14 @@ pub struct ChunkDrawBundle<'s> {
 }
 
 impl<'s> ChunkDrawBundle<'s> {
+    /// Enables or disables culling of clockwise triangles.
+    pub fn set_backface_culling(&mut self, is_enabled: bool) {
+        self.draw_params.backface_culling = match is_enabled {
+            true  => gl::BackfaceCullingMode::CullClockwise,
+            false => gl::BackfaceCullingMode::CullingDisabled,
+        };
+    }
+
     pub fn new(facade: &dyn gl::backend::Facade) -> ChunkDrawBundle<'s> {
         /* Chunk draw parameters */
         let draw_params = gl::DrawParameters {
@@ -911,6 +1368,12 @@ pub enum FillType {
     #[default]
     Default,
     AllSame(Id),
+
+    /// Voxels are stored in [octree][VoxelOctree]. Saved same as [`FillType::Default`].
+    Sparse,
+
+    /// Voxels are stored [compressed][EvictedVoxels]. Saved same as [`FillType::Default`].
+    Evicted,
 }
 
 
@@ -918,7 +1381,7 @@ pub enum FillType {
 impl AsBytes for FillType {
     fn as_bytes(&self) -> Vec<u8> {
         match self {
-            Self::Default => vec![0],
+            Self::Default | Self::Sparse | Self::Evicted => vec![0],
             Self::AllSame(id) => compose! {
                 std::iter::once(1),
                 id.as_bytes(),
@@ -946,7 +1409,7 @@ impl DynamicSize for FillType {
     fn dynamic_size(&self) -> usize {
         u8::static_size() +
         match self {
-            Self::Default => 0,
+            Self::Default | Self::Sparse | Self::Evicted => 0,
             Self::AllSame(_) => Id::static_size(),
         }
     }
@@ -961,6 +1424,60 @@ pub enum ChunkOption<T> {
     Failed,
 }
 
+impl<T> ChunkOption<T> {
+    /// Checks if option holds a [voxel][ChunkOption::Voxel].
+    pub fn is_voxel(&self) -> bool {
+        matches!(self, Self::Voxel(_))
+    }
+
+    /// Maps held [voxel][ChunkOption::Voxel] with `f`. Other variants are kept.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ChunkOption<U> {
+        match self {
+            Self::Voxel(voxel) => ChunkOption::Voxel(f(voxel)),
+            Self::OutsideChunk => ChunkOption::OutsideChunk,
+            Self::Failed => ChunkOption::Failed,
+        }
+    }
+
+    /// Gives held [voxel][ChunkOption::Voxel] or `default`.
+    pub fn unwrap_or(self, default: T) -> T {
+        match self {
+            Self::Voxel(voxel) => voxel,
+            _ => default,
+        }
+    }
+
+    /// Gives held [voxel][ChunkOption::Voxel] or computes it from `f`.
+    pub fn unwrap_or_else(self, f: impl FnOnce() -> T) -> T {
+        match self {
+            Self::Voxel(voxel) => voxel,
+            _ => f(),
+        }
+    }
+
+    /// Calls `f` if option is [`Failed`][ChunkOption::Failed] and gives option back.
+    pub fn inspect_failed(self, f: impl FnOnce()) -> Self {
+        if let Self::Failed = self {
+            f();
+        }
+
+        self
+    }
+
+    /// Converts to [`Option`]. Both [`OutsideChunk`][ChunkOption::OutsideChunk]
+    /// and [`Failed`][ChunkOption::Failed] become [`None`].
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            Self::Voxel(voxel) => Some(voxel),
+            _ => None,
+        }
+    }
+}
+
+impl<T> From<ChunkOption<T>> for Option<T> {
+    fn from(value: ChunkOption<T>) -> Self { value.into_option() }
+}
+
 pub type Lod = u32;
 
 #[derive(Debug, Error)]
@@ -982,4 +1499,393 @@ pub enum EditError {
 
     #[error("invalid id {0}")]
     InvalidId(Id),
-}
\ No newline at end of file
+
+    #[error("voxel in {0} is unbreakable")]
+    Unbreakable(Int3),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_option_combinators() {
+        let voxel: ChunkOption<i32> = ChunkOption::Voxel(2);
+        let outside: ChunkOption<i32> = ChunkOption::OutsideChunk;
+        let failed: ChunkOption<i32> = ChunkOption::Failed;
+
+        assert!(voxel.is_voxel());
+        assert!(!outside.is_voxel());
+        assert!(!failed.is_voxel());
+
+        assert_eq!(voxel.map(|x| x * 10), ChunkOption::Voxel(20));
+        assert_eq!(outside.map(|x| x * 10), ChunkOption::OutsideChunk);
+        assert_eq!(failed.map(|x| x * 10), ChunkOption::Failed);
+
+        assert_eq!(voxel.unwrap_or(7), 2);
+        assert_eq!(outside.unwrap_or(7), 7);
+        assert_eq!(failed.unwrap_or(7), 7);
+
+        assert_eq!(voxel.unwrap_or_else(|| 7), 2);
+        assert_eq!(outside.unwrap_or_else(|| 7), 7);
+        assert_eq!(failed.unwrap_or_else(|| 7), 7);
+
+        let mut n_failed = 0;
+        assert_eq!(voxel.inspect_failed(|| n_failed += 1), voxel);
+        assert_eq!(outside.inspect_failed(|| n_failed += 1), outside);
+        assert_eq!(failed.inspect_failed(|| n_failed += 1), failed);
+        assert_eq!(n_failed, 1);
+
+        assert_eq!(voxel.into_option(), Some(2));
+        assert_eq!(outside.into_option(), None);
+        assert_eq!(Option::from(failed), None::<i32>);
+    }
+
+    #[test]
+    fn lod_sampling_takes_most_common_opaque_voxel() {
+        let mut chunk = Chunk::new_same_filled(Int3::ZERO, STONE_VOXEL_DATA.id);
+        let mut set = |pos: Int3, id: Id| {
+            let idx = Chunk::voxel_pos_to_idx(pos).expect("pos should be local");
+            chunk.set_id(idx, id).expect("id should be valid");
+        };
+
+        set(veci!(0, 0, 0), AIR_VOXEL_DATA.id);
+        set(veci!(1, 0, 0), DIRT_VOXEL_DATA.id);
+
+        for pos in SpaceIter::new(veci!(2, 0, 0)..veci!(4, 2, 2)) {
+            set(pos, AIR_VOXEL_DATA.id);
+        }
+        set(veci!(3, 1, 1), DIRT_VOXEL_DATA.id);
+
+        for pos in SpaceIter::new(veci!(4, 0, 0)..veci!(6, 2, 2)) {
+            set(pos, AIR_VOXEL_DATA.id);
+        }
+
+        let stone = LoweredVoxel::Colored(STONE_VOXEL_DATA.avarage_color);
+        let dirt = LoweredVoxel::Colored(DIRT_VOXEL_DATA.avarage_color);
+
+        assert_eq!(chunk.sample_lod(veci!(0, 0, 0), 1), stone);
+        assert_eq!(chunk.sample_lod(veci!(1, 0, 0), 1), dirt);
+        assert_eq!(chunk.sample_lod(veci!(2, 0, 0), 1), LoweredVoxel::Transparent);
+    }
+
+    #[test]
+    fn voxels_with_pos_cover_chunk_once() {
+        let striped = Chunk::from_voxels(
+            (0..Chunk::VOLUME)
+                .map(|i| Atomic::new((i % 2) as Id))
+                .collect(),
+            veci!(1, -1, 2),
+        ).expect("voxel count should be valid");
+
+        for chunk in [striped, Chunk::new_same_filled(veci!(1, -1, 2), STONE_VOXEL_DATA.id)] {
+            let mut seen = HashSet::new();
+
+            for (local_pos, voxel) in chunk.iter_voxels_with_pos() {
+                assert!(seen.insert(local_pos), "{local_pos} is yielded twice");
+                assert_eq!(voxel.pos, Chunk::local_to_global_pos(veci!(1, -1, 2), local_pos));
+                assert_eq!(Some(voxel), chunk.get_voxel_local(local_pos));
+            }
+
+            assert_eq!(seen, Chunk::local_pos_iter().collect());
+        }
+    }
+
+    #[test]
+    fn out_of_range_lod_is_rejected() {
+        let chunk = Chunk::new_same_filled(Int3::ZERO, STONE_VOXEL_DATA.id);
+        let mut mesh = ChunkMesh::default();
+
+        let mut info = chunk.info.load(Relaxed);
+        info.active_lod = Some(2);
+        chunk.info.store(info, Relaxed);
+
+        let out_of_range = Chunk::N_LODS as Lod;
+        assert_eq!(
+            chunk.set_active_lod(&mut mesh, out_of_range),
+            Err(ChunkError::LodOutOfRange { lod: out_of_range, n_lods: Chunk::N_LODS }),
+        );
+
+        assert!(matches!(chunk.set_active_lod(&mut mesh, 1), Err(ChunkError::SetLod(_))));
+        assert_eq!(chunk.info.load(Relaxed).active_lod, Some(2));
+    }
+
+    #[test]
+    fn best_fit_lod_prefers_nearest_then_coarser() {
+        assert_eq!(Chunk::best_fit_lod([0, 3], 2), Some(3));
+        assert_eq!(Chunk::best_fit_lod([1, 3], 2), Some(3));
+        assert_eq!(Chunk::best_fit_lod([0, 3], 1), Some(0));
+        assert_eq!(Chunk::best_fit_lod([], 2), None);
+    }
+
+    #[test]
+    fn from_voxels_wrong_count() {
+        let voxel_ids = (0..5).map(|_| Atomic::new(AIR_VOXEL_DATA.id)).collect();
+
+        assert_eq!(
+            Chunk::from_voxels(voxel_ids, Int3::ZERO).unwrap_err(),
+            ChunkError::WrongVoxelCount { expected: Chunk::VOLUME, got: 5 },
+        );
+    }
+
+    #[test]
+    fn half_filled_chunk_has_connected_smooth_mesh() {
+        let mut chunk = Chunk::new_same_filled(Int3::ZERO, AIR_VOXEL_DATA.id);
+        for pos in Chunk::local_pos_iter().filter(|pos| pos.y < Chunk::SIZE as i32 / 2) {
+            chunk.set_voxel(pos, STONE_VOXEL_DATA.id).unwrap();
+        }
+
+        let vertices = chunk.make_vertices_smooth(ChunkAdj::default(), 0);
+        assert!(!vertices.is_empty());
+        assert_eq!(vertices.len() % 3, 0);
+
+        // Joins triangles that share a vertex and checks there is only one component.
+        let key = |vertex: &FullVertex| {
+            let (x, y, z) = vertex.position;
+            ((x * 1024.0).round() as i64, (y * 1024.0).round() as i64, (z * 1024.0).round() as i64)
+        };
+
+        let n_triangles = vertices.len() / 3;
+        let mut parents: Vec<usize> = (0..n_triangles).collect();
+
+        fn root(parents: &mut [usize], mut idx: usize) -> usize {
+            while parents[idx] != idx {
+                parents[idx] = parents[parents[idx]];
+                idx = parents[idx];
+            }
+            idx
+        }
+
+        let mut owners = HashMap::new();
+        for (idx, vertex) in vertices.iter().enumerate() {
+            let triangle = idx / 3;
+            let owner = *owners.entry(key(vertex)).or_insert(triangle);
+            let (lhs, rhs) = (root(&mut parents, owner), root(&mut parents, triangle));
+            parents[lhs] = rhs;
+        }
+
+        let n_components = (0..n_triangles)
+            .filter(|&triangle| root(&mut parents, triangle) == triangle)
+            .count();
+
+        assert_eq!(n_components, 1);
+    }
+
+    #[test]
+    fn smooth_normals_follow_density_and_unknown_voxels_make_no_walls() {
+        // Missing adjacent chunks are unknown rather than air, so filled chunk has no walls on sides.
+        let stone = Chunk::new_same_filled(Int3::ZERO, STONE_VOXEL_DATA.id);
+        assert!(stone.make_vertices_smooth(ChunkAdj::default(), 0).is_empty());
+
+        let mut chunk = Chunk::new_same_filled(Int3::ZERO, AIR_VOXEL_DATA.id);
+        for pos in Chunk::local_pos_iter().filter(|pos| pos.y < Chunk::SIZE as i32 / 2) {
+            chunk.set_voxel(pos, STONE_VOXEL_DATA.id).unwrap();
+        }
+
+        let vertices = chunk.make_vertices_smooth(ChunkAdj::default(), 0);
+        assert!(!vertices.is_empty());
+        assert!(vertices.iter().all(|vertex| vertex.face_idx == cfg::terrain::TOP_IDX as u8));
+    }
+
+    #[test]
+    fn same_filled_validates_id() {
+        assert!(Chunk::try_new_same_filled(Int3::ZERO, STONE_VOXEL_DATA.id).is_ok());
+        assert_eq!(
+            Chunk::try_new_same_filled(Int3::ZERO, Id::MAX).unwrap_err(),
+            ChunkError::InvalidId(Id::MAX),
+        );
+
+        let air = Chunk::new_same_filled(Int3::ZERO, AIR_VOXEL_DATA.id);
+        assert!(air.is_empty());
+        assert!(!air.is_filled());
+        assert!(air.make_vertices_detailed(ChunkAdj::default()).is_empty());
+        assert!(air.make_vertices_smooth(ChunkAdj::default(), 0).is_empty());
+    }
+
+    #[test]
+    fn set_differing_voxel_promotes_same_filled() {
+        let mut chunk = Chunk::new_same_filled(Int3::ZERO, STONE_VOXEL_DATA.id);
+
+        let old_id = chunk.set_voxel(Int3::ZERO, DIRT_VOXEL_DATA.id).unwrap();
+        assert_eq!(old_id, STONE_VOXEL_DATA.id);
+
+        // Single differing voxel makes chunk sparse.
+        assert_eq!(chunk.info.load(Relaxed).fill_type, FillType::Sparse);
+        assert_eq!(chunk.fill_id(), None);
+        assert_eq!(chunk.ids().count(), Chunk::VOLUME);
+        assert_eq!(chunk.get_voxel_local(Int3::ZERO).unwrap().data.id, DIRT_VOXEL_DATA.id);
+        assert_eq!(chunk.get_voxel_local(veci!(1, 0, 0)).unwrap().data.id, STONE_VOXEL_DATA.id);
+    }
+
+    #[test]
+    fn set_same_voxel_keeps_same_filled() {
+        let mut chunk = Chunk::new_same_filled(Int3::ZERO, STONE_VOXEL_DATA.id);
+
+        let old_id = chunk.set_voxel(Int3::ZERO, STONE_VOXEL_DATA.id).unwrap();
+        assert_eq!(old_id, STONE_VOXEL_DATA.id);
+
+        assert_eq!(chunk.info.load(Relaxed).fill_type, FillType::AllSame(STONE_VOXEL_DATA.id));
+        assert_eq!(chunk.voxel_ids.len(), 1);
+    }
+
+    #[test]
+    fn single_voxel_edit_keeps_storage() {
+        let mut dense = Chunk::from_voxels(
+            (0..Chunk::VOLUME)
+                .map(|i| Atomic::new((i % 2) as Id))
+                .collect(),
+            Int3::ZERO,
+        ).expect("voxel count should be valid");
+        let ptr = dense.voxel_ids.as_ptr();
+
+        dense.set_voxel(Int3::ZERO, STONE_VOXEL_DATA.id).unwrap();
+        assert_eq!(dense.voxel_ids.as_ptr(), ptr);
+        assert_eq!(dense.info.load(Relaxed).fill_type, FillType::Default);
+
+        let mut sparse = Chunk::new_same_filled(Int3::ZERO, STONE_VOXEL_DATA.id);
+        sparse.set_voxel(Int3::ZERO, AIR_VOXEL_DATA.id).unwrap();
+        assert!(!sparse.is_filled());
+        assert!(sparse.voxel_ids.is_empty());
+
+        // Reverting the edit merges octree back.
+        sparse.set_voxel(Int3::ZERO, STONE_VOXEL_DATA.id).unwrap();
+        assert_eq!(sparse.fill_id(), Some(STONE_VOXEL_DATA.id));
+        assert!(sparse.is_filled());
+    }
+
+    #[test]
+    fn filled_chunk_is_compacted() {
+        use chunk_array::ChunkArray;
+
+        let mut chunk = Chunk::from_voxels(
+            (0..Chunk::VOLUME)
+                .map(|i| Atomic::new((i % 2) as Id))
+                .collect(),
+            Int3::ZERO,
+        ).expect("voxel count should be valid");
+
+        let n_bytes_before = ChunkArray::chunk_as_bytes(&chunk).len();
+
+        let is_changed = chunk.fill_voxels(
+            Int3::ZERO, Int3::from(Chunk::SIZES), STONE_VOXEL_DATA.id,
+        ).unwrap();
+        assert!(is_changed);
+
+        assert_eq!(chunk.fill_id(), Some(STONE_VOXEL_DATA.id));
+        assert_eq!(chunk.voxel_ids.len(), 1);
+        assert!(chunk.is_filled());
+        assert!(!chunk.try_compact());
+
+        let bytes = ChunkArray::chunk_as_bytes(&chunk);
+        assert_eq!(bytes, FillType::AllSame(STONE_VOXEL_DATA.id).as_bytes());
+        assert!(bytes.len() < n_bytes_before);
+    }
+
+    #[test]
+    fn sparse_chunk_matches_dense() {
+        let half_empty = || Chunk::local_pos_iter()
+            .map(|pos| match pos.y < Chunk::SIZE as i32 / 2 {
+                true => Atomic::new(STONE_VOXEL_DATA.id),
+                false => Atomic::new(AIR_VOXEL_DATA.id),
+            })
+            .collect();
+
+        let mut sparse = Chunk::from_voxels(half_empty(), Int3::ZERO)
+            .expect("voxel count should be valid");
+
+        let mut dense = Chunk { voxel_ids: half_empty(), ..Chunk::new_empty(Int3::ZERO) };
+        dense.info.store(Info { is_filled: false, ..Default::default() }, Relaxed);
+
+        assert_eq!(sparse.info.load(Relaxed).fill_type, FillType::Sparse);
+        assert_eq!(dense.info.load(Relaxed).fill_type, FillType::Default);
+        assert!(sparse.heap_size() * 100 < dense.heap_size());
+        assert_eq!(sparse.count_non_air(), dense.count_non_air());
+
+        let edits = [
+            (veci!(0, 0, 0), AIR_VOXEL_DATA.id),
+            (veci!(5, 40, 7), DIRT_VOXEL_DATA.id),
+            (veci!(63, 63, 63), GRASS_VOXEL_DATA.id),
+            (veci!(5, 40, 7), AIR_VOXEL_DATA.id),
+        ];
+
+        for (pos, new_id) in edits {
+            let old_id = sparse.set_voxel(pos, new_id).unwrap();
+            assert_eq!(old_id, dense.set_id(Chunk::voxel_pos_to_idx(pos).unwrap(), new_id).unwrap());
+            // Keep `dense` unoptimized to compare against plain array.
+            dense.unoptimyze();
+        }
+
+        assert_eq!(sparse.info.load(Relaxed).fill_type, FillType::Sparse);
+        assert!(sparse.ids().eq(dense.ids()));
+
+        for pos in Chunk::local_pos_iter() {
+            assert_eq!(sparse.get_voxel_local(pos), dense.get_voxel_local(pos), "pos {pos}");
+        }
+
+        assert_eq!(
+            chunk_array::ChunkArray::chunk_as_bytes(&sparse),
+            chunk_array::ChunkArray::chunk_as_bytes(&dense),
+        );
+
+        sparse.fill_voxels(Int3::ZERO, Int3::from(Chunk::SIZES), AIR_VOXEL_DATA.id).unwrap();
+        assert_eq!(sparse.fill_id(), Some(AIR_VOXEL_DATA.id));
+    }
+
+    #[test]
+    fn evicted_chunk_gives_same_voxels() {
+        let noisy = Chunk::local_pos_iter()
+            .map(|pos| match (pos.x + 3 * pos.y + 7 * pos.z) % 3 {
+                0 => Atomic::new(AIR_VOXEL_DATA.id),
+                1 => Atomic::new(STONE_VOXEL_DATA.id),
+                _ => Atomic::new(DIRT_VOXEL_DATA.id),
+            })
+            .collect();
+
+        let original = Chunk::from_voxels(noisy, Int3::ZERO)
+            .expect("voxel count should be valid");
+        let mut chunk = Chunk::from_voxels(original.ids().map(Atomic::new).collect(), Int3::ZERO)
+            .expect("voxel count should be valid");
+
+        assert!(chunk.evict_voxels());
+        assert!(chunk.is_evicted() && chunk.is_generated());
+        assert!(chunk.heap_size() < original.heap_size());
+        assert!(!chunk.evicted.as_ref().unwrap().is_decoded());
+
+        for pos in Chunk::local_pos_iter() {
+            assert_eq!(chunk.get_voxel_local(pos), original.get_voxel_local(pos), "pos {pos}");
+        }
+
+        assert!(chunk.ids().eq(original.ids()));
+        assert_eq!(chunk.count_non_air(), original.count_non_air());
+        assert_eq!(
+            chunk_array::ChunkArray::chunk_as_bytes(&chunk),
+            chunk_array::ChunkArray::chunk_as_bytes(&original),
+        );
+
+        // Evicting again drops decompressed voxels, next access decompresses them back.
+        assert!(chunk.evict_voxels());
+        assert!(!chunk.evicted.as_ref().unwrap().is_decoded());
+        assert_eq!(chunk.get_voxel_local(veci!(5, 6, 7)), original.get_voxel_local(veci!(5, 6, 7)));
+
+        let pos = veci!(1, 2, 3);
+        chunk.set_voxel(pos, GRASS_VOXEL_DATA.id).unwrap();
+        assert!(!chunk.is_evicted());
+        assert_eq!(chunk.get_voxel_local(pos).map(|voxel| voxel.data.id), Some(GRASS_VOXEL_DATA.id));
+
+        chunk.evict_voxels();
+        chunk.ensure_voxels();
+        assert_eq!(chunk.info.load(Relaxed).fill_type, FillType::Default);
+        assert_eq!(chunk.get_voxel_local(pos).map(|voxel| voxel.data.id), Some(GRASS_VOXEL_DATA.id));
+
+        let mut same_filled = Chunk::new_same_filled(Int3::ZERO, STONE_VOXEL_DATA.id);
+        assert!(!same_filled.evict_voxels());
+    }
+
+    #[test]
+    fn from_voxels_or_air_replaces_with_air() {
+        let voxel_ids = (0..5).map(|_| Atomic::new(STONE_VOXEL_DATA.id)).collect();
+        let chunk = Chunk::from_voxels_or_air(voxel_ids, Int3::ZERO);
+
+        assert_eq!(chunk.fill_id(), Some(AIR_VOXEL_DATA.id));
+    }
+}
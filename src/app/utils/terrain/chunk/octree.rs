@@ -0,0 +1,288 @@
+//! Sparse voxel storage that compresses homogeneous regions.
+
+use {
+    crate::{prelude::*, terrain::voxel::voxel_data::Id},
+};
+
+/// Node of [`VoxelOctree`]. Branch children are ordered by `x << 2 | y << 1 | z`
+/// bits of their position in parent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OctreeNode {
+    /// Whole region is filled with one [id][Id].
+    Leaf(Id),
+    Branch(Box<[OctreeNode; 8]>),
+}
+
+impl OctreeNode {
+    /// Gives id if node is a [leaf][OctreeNode::Leaf].
+    pub fn leaf_id(&self) -> Option<Id> {
+        match *self {
+            Self::Leaf(id) => Some(id),
+            Self::Branch(_) => None,
+        }
+    }
+
+    /// Merges branch into leaf if all its children are same leaves.
+    fn try_merge(&mut self) {
+        let Self::Branch(children) = self else { return };
+
+        let Some(id) = children[0].leaf_id() else { return };
+        if children.iter().all(|child| child.leaf_id() == Some(id)) {
+            *self = Self::Leaf(id);
+        }
+    }
+}
+
+/// Octree over cube of voxels with side [`size`][VoxelOctree::size].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoxelOctree {
+    /// Cube side length. It is a power of 2.
+    pub size: usize,
+    pub root: OctreeNode,
+}
+
+impl VoxelOctree {
+    /// Builds octree of `size` by voxel ids at local positions.
+    /// # Panic
+    /// Panics if `size` is not a power of 2.
+    pub fn new(size: usize, get_id: impl Fn(Int3) -> Id) -> Self {
+        assert!(size.is_power_of_two(), "octree size {size} should be a power of 2");
+
+        Self { size, root: Self::build(Int3::ZERO, size as i32, &get_id) }
+    }
+
+    fn build(origin: Int3, size: i32, get_id: &impl Fn(Int3) -> Id) -> OctreeNode {
+        if size == 1 {
+            return OctreeNode::Leaf(get_id(origin));
+        }
+
+        let half = size / 2;
+        let children = array_init(|child_idx| Self::build(origin + Self::child_offset(child_idx, half), half, get_id));
+
+        let mut node = OctreeNode::Branch(Box::new(children));
+        node.try_merge();
+
+        node
+    }
+
+    /// Gives offset of child with index `child_idx` in parent with children of `half` size.
+    fn child_offset(child_idx: usize, half: i32) -> Int3 {
+        let bit = |shift: usize| ((child_idx >> shift) & 1) as i32 * half;
+        veci!(bit(2), bit(1), bit(0))
+    }
+
+    /// Gives index of child containing `pos` given relative to parent with children of `half` size.
+    fn child_idx(pos: Int3, half: i32) -> usize {
+        let bit = |coord: i32| (coord >= half) as usize;
+        bit(pos.x) << 2 | bit(pos.y) << 1 | bit(pos.z)
+    }
+
+    /// Checks that `pos` is inside the cube.
+    pub fn contains(&self, pos: Int3) -> bool {
+        let size = self.size as i32;
+        (0..size).contains(&pos.x) && (0..size).contains(&pos.y) && (0..size).contains(&pos.z)
+    }
+
+    /// Gives [id][Id] of voxel on `pos` or [`None`] if it is out of the cube.
+    pub fn get(&self, mut pos: Int3) -> Option<Id> {
+        if !self.contains(pos) { return None }
+
+        let (mut node, mut half) = (&self.root, self.size as i32 / 2);
+
+        loop {
+            match node {
+                OctreeNode::Leaf(id) => return Some(*id),
+                OctreeNode::Branch(children) => {
+                    let child_idx = Self::child_idx(pos, half);
+                    pos = pos - Self::child_offset(child_idx, half);
+                    node = &children[child_idx];
+                    half /= 2;
+                },
+            }
+        }
+    }
+
+    /// Sets voxel on `pos` to `new_id` splitting and merging nodes on the way.
+    /// Gives old [id][Id] or [`None`] if `pos` is out of the cube.
+    pub fn set(&mut self, pos: Int3, new_id: Id) -> Option<Id> {
+        if !self.contains(pos) { return None }
+
+        Some(Self::set_in(&mut self.root, pos, self.size as i32, new_id))
+    }
+
+    fn set_in(node: &mut OctreeNode, pos: Int3, size: i32, new_id: Id) -> Id {
+        if let OctreeNode::Leaf(id) = *node {
+            if id == new_id || size == 1 {
+                *node = OctreeNode::Leaf(new_id);
+                return id;
+            }
+
+            *node = OctreeNode::Branch(Box::new(array_init(|_| OctreeNode::Leaf(id))));
+        }
+
+        let OctreeNode::Branch(children) = node else { unreachable!("leaf is split above") };
+
+        let half = size / 2;
+        let child_idx = Self::child_idx(pos, half);
+        let child_pos = pos - Self::child_offset(child_idx, half);
+
+        let old_id = Self::set_in(&mut children[child_idx], child_pos, half, new_id);
+        node.try_merge();
+
+        old_id
+    }
+
+    /// Calls `f` for each leaf with its origin, side length and [id][Id].
+    pub fn for_each_leaf(&self, mut f: impl FnMut(Int3, usize, Id)) {
+        fn visit(node: &OctreeNode, origin: Int3, size: usize, f: &mut impl FnMut(Int3, usize, Id)) {
+            match node {
+                OctreeNode::Leaf(id) => f(origin, size, *id),
+                OctreeNode::Branch(children) => {
+                    let half = size / 2;
+                    for (child_idx, child) in children.iter().enumerate() {
+                        let offset = VoxelOctree::child_offset(child_idx, half as i32);
+                        visit(child, origin + offset, half, f);
+                    }
+                },
+            }
+        }
+
+        visit(&self.root, Int3::ZERO, self.size, &mut f);
+    }
+
+    /// Counts voxels which [id][Id] satisfy `predicate`.
+    pub fn count(&self, predicate: impl Fn(Id) -> bool) -> usize {
+        let mut count = 0;
+
+        self.for_each_leaf(|_, size, id| if predicate(id) {
+            count += size.pow(3);
+        });
+
+        count
+    }
+
+    /// Writes all voxel ids to `dense` by index given by `pos_to_idx`.
+    pub fn write_dense(&self, dense: &mut [Id], pos_to_idx: impl Fn(Int3) -> usize) {
+        self.for_each_leaf(|origin, size, id| {
+            for pos in SpaceIter::new(origin..origin + Int3::all(size as i32)) {
+                dense[pos_to_idx(pos)] = id;
+            }
+        });
+    }
+
+    /// Counts [branch][OctreeNode::Branch] nodes.
+    pub fn n_branches(&self) -> usize {
+        fn count(node: &OctreeNode) -> usize {
+            match node {
+                OctreeNode::Leaf(_) => 0,
+                OctreeNode::Branch(children) => 1 + children.iter().map(count).sum::<usize>(),
+            }
+        }
+
+        count(&self.root)
+    }
+
+    /// Gives number of bytes allocated by the tree.
+    pub fn heap_size(&self) -> usize {
+        self.n_branches() * mem::size_of::<[OctreeNode; 8]>()
+    }
+
+    /// Gives [heap size][VoxelOctree::heap_size] of octree [built][VoxelOctree::new]
+    /// with the same arguments without allocating it.
+    /// # Panic
+    /// Panics if `size` is not a power of 2.
+    pub fn heap_size_of(size: usize, get_id: impl Fn(Int3) -> Id) -> usize {
+        assert!(size.is_power_of_two(), "octree size {size} should be a power of 2");
+
+        /// Gives [id][Id] of uniform region or number of branches of not uniform one.
+        fn measure(origin: Int3, size: i32, get_id: &impl Fn(Int3) -> Id) -> Result<Id, usize> {
+            if size == 1 {
+                return Ok(get_id(origin));
+            }
+
+            let half = size / 2;
+            let (mut first_id, mut is_uniform, mut n_branches) = (None, true, 0);
+
+            for child_idx in 0..8 {
+                match measure(origin + VoxelOctree::child_offset(child_idx, half), half, get_id) {
+                    Ok(id) => {
+                        is_uniform &= *first_id.get_or_insert(id) == id;
+                    },
+                    Err(n_child_branches) => {
+                        is_uniform = false;
+                        n_branches += n_child_branches;
+                    },
+                }
+            }
+
+            match (is_uniform, first_id) {
+                (true, Some(id)) => Ok(id),
+                _ => Err(1 + n_branches),
+            }
+        }
+
+        let n_branches = measure(Int3::ZERO, size as i32, &get_id).err().unwrap_or(0);
+        n_branches * mem::size_of::<[OctreeNode; 8]>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn octree_get_set_matches_dense() {
+        const SIZE: usize = 8;
+
+        let idx = |pos: Int3| (pos.x as usize * SIZE + pos.y as usize) * SIZE + pos.z as usize;
+        let mut dense: Vec<Id> = (0..SIZE.pow(3)).map(|i| (i % 7 == 0) as Id).collect();
+
+        let mut octree = VoxelOctree::new(SIZE, |pos| dense[idx(pos)]);
+
+        let mut state = 12345_u32;
+        for _ in 0..2000 {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let pos = veci!(
+                (state >> 4) as i32 % SIZE as i32,
+                (state >> 10) as i32 % SIZE as i32,
+                (state >> 16) as i32 % SIZE as i32,
+            );
+            let new_id = (state >> 24) as Id % 3;
+
+            assert_eq!(octree.set(pos, new_id), Some(dense[idx(pos)]));
+            dense[idx(pos)] = new_id;
+        }
+
+        for pos in SpaceIter::new(Int3::ZERO..Int3::all(SIZE as i32)) {
+            assert_eq!(octree.get(pos), Some(dense[idx(pos)]));
+        }
+
+        let mut written = vec![Id::MAX; SIZE.pow(3)];
+        octree.write_dense(&mut written, idx);
+        assert_eq!(written, dense);
+
+        assert_eq!(VoxelOctree::heap_size_of(SIZE, |pos| dense[idx(pos)]), octree.heap_size());
+
+        assert_eq!(octree.get(Int3::all(SIZE as i32)), None);
+        assert_eq!(octree.set(veci!(-1, 0, 0), 0), None);
+    }
+
+    #[test]
+    fn uniform_regions_are_merged() {
+        let mut octree = VoxelOctree::new(16, |pos| (pos.y < 8) as Id);
+        assert_eq!(octree.n_branches(), 1);
+        assert_eq!(octree.count(|id| id == 1), 16 * 16 * 8);
+
+        octree.set(veci!(3, 12, 5), 1);
+        assert!(octree.n_branches() > 1);
+
+        octree.set(veci!(3, 12, 5), 0);
+        assert_eq!(octree.n_branches(), 1);
+
+        for pos in SpaceIter::new(Int3::ZERO..Int3::all(16)) {
+            octree.set(pos, 2);
+        }
+
+        assert_eq!(octree.root, OctreeNode::Leaf(2));
+    }
+}
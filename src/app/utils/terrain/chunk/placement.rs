@@ -0,0 +1,67 @@
+//! Fine voxel placement by nudging the aimed position.
+
+use crate::prelude::*;
+
+/// Offset of placement position from the aimed one. It is reset when aim moves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlacementNudge {
+    pub base: Option<Int3>,
+    pub offset: Int3,
+}
+
+impl PlacementNudge {
+    /// Sets aimed placement position. Offset is reset if the position has changed.
+    pub fn aim(&mut self, base: Option<Int3>) {
+        if self.base != base {
+            *self = Self { base, offset: Int3::ZERO };
+        }
+    }
+
+    /// Gives nudged placement position.
+    pub fn target(&self) -> Option<Int3> {
+        self.base.map(|base| base + self.offset)
+    }
+
+    /// Moves offset by `step` if nudged position stays within `reach` voxels from `eye`.
+    /// Returns `true` if offset is changed.
+    pub fn nudge(&mut self, step: Int3, eye: Int3, reach: f32) -> bool {
+        let Some(base) = self.base else { return false };
+
+        let diff = base + self.offset + step - eye;
+
+        let is_reachable = (diff.len_squared() as f32).sqrt() <= reach;
+        if is_reachable {
+            self.offset = self.offset + step;
+        }
+
+        is_reachable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nudge_is_clamped_within_reach() {
+        let mut nudge = PlacementNudge::default();
+        assert!(!nudge.nudge(veci!(1, 0, 0), Int3::ZERO, 2.0));
+
+        nudge.aim(Some(Int3::ZERO));
+
+        assert!(nudge.nudge(veci!(1, 0, 0), Int3::ZERO, 2.0));
+        assert!(nudge.nudge(veci!(1, 0, 0), Int3::ZERO, 2.0));
+        assert!(!nudge.nudge(veci!(1, 0, 0), Int3::ZERO, 2.0));
+        assert!(!nudge.nudge(veci!(0, 0, 1), Int3::ZERO, 2.0));
+        assert_eq!(nudge.target(), Some(veci!(2, 0, 0)));
+
+        assert!(nudge.nudge(veci!(-1, 0, 0), Int3::ZERO, 2.0));
+        assert_eq!(nudge.target(), Some(veci!(1, 0, 0)));
+
+        nudge.aim(Some(Int3::ZERO));
+        assert_eq!(nudge.target(), Some(veci!(1, 0, 0)));
+
+        nudge.aim(Some(veci!(0, 1, 0)));
+        assert_eq!(nudge.target(), Some(veci!(0, 1, 0)));
+    }
+}
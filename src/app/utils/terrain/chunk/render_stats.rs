@@ -0,0 +1,100 @@
+//! Per-frame statistics of [chunk array][super::chunk_array::ChunkArray] rendering.
+
+/// What happened to a [chunk][super::Chunk] during one frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkOutcome {
+    /// Chunk is out of frustum or render distance.
+    FrustumCulled,
+
+    /// Chunk is drawn with given number of triangles and draw calls.
+    Drawn { n_triangles: usize, n_draw_calls: usize },
+
+    /// Chunk is visible but has no voxels or mesh to draw yet.
+    Skipped,
+}
+
+/// Counters filled by [render][super::chunk_array::ChunkArray::render]. Each considered
+/// [chunk][super::Chunk] is counted by exactly one [outcome][ChunkOutcome].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    pub n_considered: usize,
+    pub n_frustum_culled: usize,
+    pub n_drawn: usize,
+    pub n_skipped: usize,
+
+    pub n_draw_calls: usize,
+    pub n_triangles: usize,
+
+    pub n_tasks_started: usize,
+    pub n_tasks_finished: usize,
+}
+
+impl RenderStats {
+    /// Counts chunk with its outcome.
+    pub fn record(&mut self, outcome: ChunkOutcome) {
+        self.n_considered += 1;
+
+        match outcome {
+            ChunkOutcome::FrustumCulled => self.n_frustum_culled += 1,
+            ChunkOutcome::Skipped => self.n_skipped += 1,
+            ChunkOutcome::Drawn { n_triangles, n_draw_calls } => {
+                self.n_drawn += 1;
+                self.n_triangles += n_triangles;
+                self.n_draw_calls += n_draw_calls;
+            },
+        }
+    }
+
+    /// Spawns overlay with stats and `fps` in the window corner.
+    pub fn spawn_overlay(&self, ui: &imgui::Ui, fps: f32) {
+        ui.window("Render stats")
+            .position([10.0, 10.0], imgui::Condition::Always)
+            .bg_alpha(0.35)
+            .no_decoration()
+            .always_auto_resize(true)
+            .no_inputs()
+            .build(|| {
+                ui.text(format!("FPS: {fps:.0}"));
+                ui.separator();
+
+                ui.text(format!("Chunks considered: {}", self.n_considered));
+                ui.text(format!("Frustum culled: {}", self.n_frustum_culled));
+                ui.text(format!("Drawn: {}", self.n_drawn));
+                ui.text(format!("Skipped: {}", self.n_skipped));
+                ui.separator();
+
+                ui.text(format!("Draw calls: {}", self.n_draw_calls));
+                ui.text(format!("Triangles: {}", self.n_triangles));
+                ui.text(format!("Tasks started: {}", self.n_tasks_started));
+                ui.text(format!("Tasks finished: {}", self.n_tasks_finished));
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn considered_chunks_are_culled_drawn_or_skipped() {
+        let mut stats = RenderStats::default();
+
+        let outcomes = [
+            ChunkOutcome::FrustumCulled,
+            ChunkOutcome::Drawn { n_triangles: 12, n_draw_calls: 1 },
+            ChunkOutcome::Skipped,
+            ChunkOutcome::Drawn { n_triangles: 30, n_draw_calls: 8 },
+            ChunkOutcome::FrustumCulled,
+        ];
+
+        for outcome in outcomes {
+            stats.record(outcome);
+        }
+
+        assert_eq!(stats.n_considered, outcomes.len());
+        assert_eq!(stats.n_considered, stats.n_frustum_culled + stats.n_drawn + stats.n_skipped);
+        assert_eq!(stats.n_frustum_culled, 2);
+        assert_eq!(stats.n_triangles, 42);
+        assert_eq!(stats.n_draw_calls, 9);
+    }
+}
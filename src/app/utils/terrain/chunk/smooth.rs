@@ -0,0 +1,218 @@
+//! Smooth terrain mesher. It runs marching tetrahedra, a marching cubes variant
+//! that splits each cube into 6 tetrahedra, over a density field of voxel solidity.
+
+use {
+    crate::{
+        prelude::*,
+        terrain::voxel::{Voxel, atlas::UV, voxel_data::{VoxelData, data::*}},
+    },
+    super::mesh::FullVertex,
+    cfg::terrain::{BACK_IDX, FRONT_IDX, TOP_IDX, BOTTOM_IDX, RIGHT_IDX, LEFT_IDX},
+};
+
+/// Density that separates solid space from air.
+pub const ISO_LEVEL: f32 = 0.5;
+
+/// Offsets of cube corners. Bits of corner index are its `x`, `y` and `z` offsets.
+const CORNERS: [(i32, i32, i32); 8] = [
+    (0, 0, 0), (1, 0, 0), (0, 1, 0), (1, 1, 0),
+    (0, 0, 1), (1, 0, 1), (0, 1, 1), (1, 1, 1),
+];
+
+/// Cube corners split into tetrahedra along `0-7` diagonal.
+/// Adjacent cubes split their shared faces the same way so the surface has no cracks.
+const TETRAHEDRA: [[usize; 4]; 6] = [
+    [0, 1, 3, 7], [0, 1, 5, 7], [0, 2, 3, 7],
+    [0, 2, 6, 7], [0, 4, 5, 7], [0, 4, 6, 7],
+];
+
+/// Density field value in some voxel.
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub density: f32,
+    pub data: &'static VoxelData,
+}
+
+impl Sample {
+    /// Solid voxels have density of 1 and air has 0.
+    pub fn from_voxel(voxel: Voxel) -> Self {
+        match voxel.is_air() {
+            false => Self { density: 1.0, data: voxel.data },
+            true => Self { density: 0.0, data: AIR_VOXEL_DATA },
+        }
+    }
+
+    pub fn is_solid(&self) -> bool {
+        self.density > ISO_LEVEL
+    }
+}
+
+/// Polygonizes cube with `min` corner and `step` side length (in voxels).
+/// `sample` gives density by voxel position or [`None`] if the voxel is unknown.
+/// Vertices are placed to `(origin + pos) * Voxel::SIZE` in world space.
+///
+/// Cubes with unknown corners are skipped, so surface near not generated
+/// chunks appears when they are generated instead of being closed by a wall.
+///
+/// [`FullVertex`] has no normal attribute, so smooth normal of each
+/// triangle is quantized to the nearest cube face.
+pub fn polygonize_cube(
+    origin: Int3, min: Int3, step: i32,
+    sample: impl Fn(Int3) -> Option<Sample>, vertices: &mut Vec<FullVertex>,
+) {
+    let corner_pos = |corner: usize| {
+        let (x, y, z) = CORNERS[corner];
+        min + veci!(x, y, z) * step
+    };
+
+    let Some(samples) = (0..CORNERS.len())
+        .map(|corner| sample(corner_pos(corner)))
+        .collect::<Option<SmallVec<[Sample; 8]>>>()
+    else { return };
+
+    let n_solid = samples.iter().filter(|sample| sample.is_solid()).count();
+    if n_solid == 0 || n_solid == 8 { return }
+
+    let density = |pos: Int3| sample(pos).map(|sample| sample.density);
+    let gradients: [vec3; 8] = array_init(|corner| {
+        gradient(corner_pos(corner), samples[corner].density, step, &density)
+    });
+
+    let world_pos = |corner: usize| vec3::from(origin + corner_pos(corner)) * Voxel::SIZE;
+
+    // Point where the surface crosses edge from solid corner to air corner and
+    // normal in it. Density grows towards solid voxels so the normal is opposite to the gradient.
+    let edge_point = |solid: usize, air: usize| {
+        let (from, to) = (world_pos(solid), world_pos(air));
+        let t = (ISO_LEVEL - samples[solid].density) / (samples[air].density - samples[solid].density);
+        let gradient = gradients[solid] + (gradients[air] - gradients[solid]) * t;
+
+        (from + (to - from) * t, gradient * -1.0)
+    };
+
+    for tetrahedron in TETRAHEDRA {
+        let (solid, air): (SmallVec<[usize; 4]>, SmallVec<[usize; 4]>) = tetrahedron.into_iter()
+            .partition(|&corner| samples[corner].is_solid());
+
+        let outward = centroid(air.iter().map(|&corner| world_pos(corner)))
+                    - centroid(solid.iter().map(|&corner| world_pos(corner)));
+
+        match (solid.as_slice(), air.as_slice()) {
+            (&[s], &[a0, a1, a2]) => push_triangle(
+                [edge_point(s, a0), edge_point(s, a1), edge_point(s, a2)],
+                samples[s].data, outward, vertices,
+            ),
+
+            (&[s0, s1, s2], &[a]) => push_triangle(
+                [edge_point(s0, a), edge_point(s1, a), edge_point(s2, a)],
+                samples[s0].data, outward, vertices,
+            ),
+
+            (&[s0, s1], &[a0, a1]) => {
+                let quad = [edge_point(s0, a0), edge_point(s0, a1), edge_point(s1, a1), edge_point(s1, a0)];
+
+                for [i, j, k] in [[0, 1, 2], [0, 2, 3]] {
+                    push_triangle([quad[i], quad[j], quad[k]], samples[s0].data, outward, vertices);
+                }
+            },
+
+            _ => (),
+        }
+    }
+}
+
+/// Pushes triangle facing `outward`. Its normal is the sum of `(point, normal)` vertex normals.
+fn push_triangle(
+    points: [(vec3, vec3); 3], data: &VoxelData, outward: vec3, vertices: &mut Vec<FullVertex>,
+) {
+    let normal = points.iter()
+        .fold(vec3::zero(), |sum, &(_, normal)| sum + normal);
+
+    let mut points = points.map(|(point, _)| point);
+
+    // Cube faces have `(v2 - v0) x (v1 - v0)` pointing along their normal.
+    let face_normal = (points[2] - points[0]).cross(points[1] - points[0]);
+    if dot(face_normal, outward) < 0.0 {
+        points.swap(1, 2);
+    }
+
+    let normal = match normal.len() > f32::EPSILON {
+        true => normal,
+        false => outward,
+    };
+
+    let (face_idx, texture) = nearest_face(normal, data);
+    let uv = UV::new(texture);
+
+    for point in points {
+        let voxel_pos = point / Voxel::SIZE;
+        let (u, v) = match face_idx as usize {
+            BACK_IDX | FRONT_IDX => (voxel_pos.z, voxel_pos.y),
+            TOP_IDX | BOTTOM_IDX => (voxel_pos.x, voxel_pos.z),
+            _ => (voxel_pos.x, voxel_pos.y),
+        };
+
+        let (u, v) = ((u + 0.5).rem_euclid(1.0), (v + 0.5).rem_euclid(1.0));
+
+        vertices.push(FullVertex {
+            position: point.as_tuple(),
+            tex_coords: (
+                uv.lo.x + (uv.hi.x - uv.lo.x) * u,
+                uv.hi.y + (uv.lo.y - uv.hi.y) * v,
+            ),
+            face_idx,
+            anim: data.textures.animation.as_attribute(),
+        });
+    }
+}
+
+/// Gives cube face index and texture that are the closest to `normal`.
+fn nearest_face(normal: vec3, data: &VoxelData) -> (u8, u16) {
+    let (x, y, z) = (normal.x.abs(), normal.y.abs(), normal.z.abs());
+    let textures = &data.textures;
+
+    let (idx, texture) = if x >= y && x >= z {
+        match normal.x >= 0.0 {
+            true  => (BACK_IDX, textures.back),
+            false => (FRONT_IDX, textures.front),
+        }
+    } else if y >= z {
+        match normal.y >= 0.0 {
+            true  => (TOP_IDX, textures.top),
+            false => (BOTTOM_IDX, textures.bottom),
+        }
+    } else {
+        match normal.z >= 0.0 {
+            true  => (RIGHT_IDX, textures.right),
+            false => (LEFT_IDX, textures.left),
+        }
+    };
+
+    (idx as u8, texture)
+}
+
+/// Gradient of density field in `pos` with `center` density by central differences
+/// over `step` voxels. One-sided difference is used if a neighbor is unknown.
+fn gradient(pos: Int3, center: f32, step: i32, density: impl Fn(Int3) -> Option<f32>) -> vec3 {
+    let derivative = |axis: Int3| match (density(pos + axis * step), density(pos - axis * step)) {
+        (Some(hi), Some(lo)) => (hi - lo) / 2.0,
+        (Some(hi), None) => hi - center,
+        (None, Some(lo)) => center - lo,
+        (None, None) => 0.0,
+    };
+
+    vec3::new(
+        derivative(veci!(1, 0, 0)),
+        derivative(veci!(0, 1, 0)),
+        derivative(veci!(0, 0, 1)),
+    )
+}
+
+fn centroid(points: impl ExactSizeIterator<Item = vec3>) -> vec3 {
+    let n_points = points.len().max(1) as f32;
+    points.fold(vec3::zero(), |sum, point| sum + point) / n_points
+}
+
+fn dot(lhs: vec3, rhs: vec3) -> f32 {
+    lhs.x * rhs.x + lhs.y * rhs.y + lhs.z * rhs.z
+}
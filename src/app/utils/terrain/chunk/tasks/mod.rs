@@ -1,7 +1,7 @@
 use {
     crate::{
         prelude::*,
-        terrain::chunk::{FullVertex, LowVertex, Id},
+        terrain::chunk::{FullVertex, LowVertex, Id, evicted::EvictedVoxels},
     },
     std::future::Future,
     tokio::task::JoinHandle,
@@ -28,6 +28,7 @@ pub type FullTask = Task<Vec<FullVertex>>;
 pub type LowTask  = Task<Vec<LowVertex>>;
 pub type GenTask  = Task<Vec<Atomic<Id>>>;
 pub type PartitionTask = Task<[Vec<FullVertex>; 8]>;
+pub type EvictTask = Task<EvictedVoxels>;
 
 impl<Item: Send + 'static> Task<Item> {
     pub fn spawn(f: impl Future<Output = Item> + Send + 'static) -> Self {
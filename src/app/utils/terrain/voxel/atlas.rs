@@ -52,4 +52,63 @@ impl UV {
         self.hi.y = 1.0 - self.hi.y;
         self
     }
-}
\ No newline at end of file
+}
+
+/// Computes average color of not transparent pixels of atlas item with `id`.
+/// Gives [`None`] if item is out of `atlas` or has no visible pixels.
+pub fn average_color(atlas: &image::RgbaImage, id: u16) -> Option<Color> {
+    const CELL_SIZE: usize = ITEM_SIZE_IN_PIXELS + 2 * ITEM_PADDING_IN_PIXELS;
+
+    let from_x = (id as usize % ITEMS_COUNT_IN_ROW * CELL_SIZE + ITEM_PADDING_IN_PIXELS) as u32;
+    let from_y = (id as usize / ITEMS_COUNT_IN_ROW * CELL_SIZE + ITEM_PADDING_IN_PIXELS) as u32;
+    let size = ITEM_SIZE_IN_PIXELS as u32;
+
+    if atlas.width() < from_x + size || atlas.height() < from_y + size {
+        return None;
+    }
+
+    let (mut sum, mut n_pixels) = ([0.0_f32; 3], 0_usize);
+
+    for y in from_y..from_y + size {
+        for x in from_x..from_x + size {
+            let image::Rgba([r, g, b, a]) = *atlas.get_pixel(x, y);
+            if a == 0 { continue }
+
+            for (sum, channel) in sum.iter_mut().zip([r, g, b]) {
+                *sum += channel as f32 / u8::MAX as f32;
+            }
+
+            n_pixels += 1;
+        }
+    }
+
+    (n_pixels != 0).then(|| {
+        let [r, g, b] = sum.map(|sum| sum / n_pixels as f32);
+        Color::new(r, g, b)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_color_of_atlas_item() {
+        const CELL_SIZE: u32 = (ITEM_SIZE_IN_PIXELS + 2 * ITEM_PADDING_IN_PIXELS) as u32;
+
+        let mut atlas = image::RgbaImage::new(ATLAS_ROW_SIZE_IN_PIXELS as u32, 2 * CELL_SIZE);
+
+        // Item 33 is the second one in the second row, half red and half blue.
+        let (from_x, from_y) = (CELL_SIZE + ITEM_PADDING_IN_PIXELS as u32, CELL_SIZE + ITEM_PADDING_IN_PIXELS as u32);
+        for y in 0..ITEM_SIZE_IN_PIXELS as u32 {
+            for x in 0..ITEM_SIZE_IN_PIXELS as u32 {
+                let color = if x % 2 == 0 { [255, 0, 0, 255] } else { [0, 0, 255, 255] };
+                atlas.put_pixel(from_x + x, from_y + y, image::Rgba(color));
+            }
+        }
+
+        assert_eq!(average_color(&atlas, 33), Some(Color::new(0.5, 0.0, 0.5)));
+        assert_eq!(average_color(&atlas, 0), None);
+        assert_eq!(average_color(&atlas, 64), None);
+    }
+}
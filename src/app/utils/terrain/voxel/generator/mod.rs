@@ -3,9 +3,12 @@ pub mod noise;
 use {
     crate::{
         prelude::*,
-        terrain::chunk::{Chunk, chunk_array::{GENERATOR_SIZES, ChunkArray}},
+        terrain::{
+            chunk::{Chunk, chunk_array::{GENERATOR_SIZES, ChunkArray}},
+            voxel::voxel_data::{Id, data::*},
+        },
     },
-    self::noise::Noise2d,
+    self::noise::{Noise2d, Fbm},
     spin::RwLock,
 };
 
@@ -13,18 +16,33 @@ static FREQUENCY: AtomicF32 = AtomicF32::new(0.05);
 static N_OCTAVES: AtomicUsize = AtomicUsize::new(6);
 static PERSISTENCE: AtomicF32 = AtomicF32::new(3.0);
 static LACUNARITY: AtomicF32 = AtomicF32::new(0.5);
-static SEED: AtomicU32 = AtomicU32::new(10);
+static SEED: AtomicU64 = AtomicU64::new(10);
 
 lazy_static! {
-    static ref NOISE_VALS: RwLock<Noise2d> = RwLock::new(
-        Noise2d::new(
-            SEED.load(Relaxed),
-            (Chunk::SIZES * USize3::from(*GENERATOR_SIZES.lock().unwrap())).xz(),
-            FREQUENCY.load(Relaxed),
-            LACUNARITY.load(Relaxed),
-            N_OCTAVES.load(Relaxed),
-            PERSISTENCE.load(Relaxed),
-        )
+    static ref NOISE_VALS: RwLock<Noise2d> = RwLock::new(make_noise(
+        fold_seed(SEED.load(Relaxed)),
+        USize3::from(*GENERATOR_SIZES.lock().unwrap()),
+    ));
+}
+
+/// Builds noise with `seed` for [chunk array][ChunkArray] of `chunk_array_sizes`
+/// from current generator settings.
+fn make_noise(seed: u32, chunk_array_sizes: USize3) -> Noise2d {
+    Noise2d::new(
+        seed,
+        (Chunk::SIZES * chunk_array_sizes).xz(),
+        FREQUENCY.load(Relaxed),
+        LACUNARITY.load(Relaxed),
+        N_OCTAVES.load(Relaxed),
+        PERSISTENCE.load(Relaxed),
+    )
+}
+
+/// Rebuilds noise with current generator settings.
+pub fn rebuild_noise() {
+    *NOISE_VALS.write() = make_noise(
+        fold_seed(SEED.load(Relaxed)),
+        USize3::from(*GENERATOR_SIZES.lock().unwrap()),
     );
 }
 
@@ -53,27 +71,438 @@ pub fn spawn_control_window(ui: &imgui::Ui) {
         });
 
         if ui.button("Build") {
-            let mut noise_vals = NOISE_VALS.write();
-            let _ = mem::replace(&mut *noise_vals, Noise2d::new(
-                SEED.load(Relaxed),
-                (Chunk::SIZES * USize3::from(*GENERATOR_SIZES.lock().unwrap())).xz(),
-                FREQUENCY.load(Relaxed),
-                LACUNARITY.load(Relaxed),
-                N_OCTAVES.load(Relaxed),
-                PERSISTENCE.load(Relaxed),
-            ));
+            rebuild_noise();
         }
     });
 }
 
-pub fn perlin(pos: Int3, chunk_array_sizes: USize3) -> i32 {
+/// Gives terrain height in `pos` by perlin noise seeded with `seed`.
+/// Noise is rebuilt if it was built with other seed or sizes.
+pub fn perlin(pos: Int3, chunk_array_sizes: USize3, seed: u64) -> i32 {
     let coord_idx = ChunkArray::voxel_pos_to_coord_idx(
         pos,
         chunk_array_sizes,
     ).expect("failed to convert voxel pos to coord idx");
 
-    NOISE_VALS.read()
-        .map
+    let (seed, sizes) = (fold_seed(seed), (Chunk::SIZES * chunk_array_sizes).xz());
+    let height = |noise: &Noise2d| noise
         .get_value(coord_idx.x, coord_idx.z)
-        .round() as i32
-}
\ No newline at end of file
+        .round() as i32;
+
+    {
+        let noise = NOISE_VALS.read();
+        if noise.seed == seed && noise.sizes == sizes {
+            return height(&noise);
+        }
+    }
+
+    let mut noise = NOISE_VALS.write();
+    if noise.seed != seed || noise.sizes != sizes {
+        *noise = make_noise(seed, chunk_array_sizes);
+    }
+
+    height(&noise)
+}
+
+/// Gives seed from generator settings.
+pub fn seed() -> u64 {
+    SEED.load(Relaxed)
+}
+
+/// Folds 64-bit seed to 32-bit noise seed so that all seed bits affect it.
+pub fn fold_seed(seed: u64) -> u32 {
+    (seed ^ (seed >> 32)) as u32
+}
+
+/// Replaces seed in generator settings. Noise should be [rebuilt][rebuild_noise] to apply it.
+pub fn set_seed(seed: u64) {
+    SEED.store(seed, Release);
+}
+
+/// Source of voxels for new [chunks][Chunk].
+pub trait WorldGenerator: std::fmt::Debug + Send + Sync {
+    /// Generates voxel ids of [chunk][Chunk] on `chunk_pos` in
+    /// [`Chunk::global_pos_iter`] order. Same arguments should give same result.
+    fn generate(&self, chunk_pos: Int3, seed: u64) -> Vec<Id>;
+}
+
+/// Heightmap terrain made of [perlin noise][perlin] from generator settings
+/// seeded with `seed` argument.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DefaultGenerator {
+    pub chunk_array_sizes: USize3,
+}
+
+impl WorldGenerator for DefaultGenerator {
+    fn generate(&self, chunk_pos: Int3, seed: u64) -> Vec<Id> {
+        Chunk::global_pos_iter(chunk_pos)
+            .map(|pos| {
+                let height = perlin(pos, self.chunk_array_sizes, seed);
+
+                if pos.y <= height - 5 {
+                    STONE_VOXEL_DATA.id
+                } else if pos.y < height {
+                    DIRT_VOXEL_DATA.id
+                } else if pos.y <= height {
+                    GRASS_VOXEL_DATA.id
+                } else {
+                    AIR_VOXEL_DATA.id
+                }
+            })
+            .collect()
+    }
+}
+
+/// Flat world made of horizontal layers. Layers are stacked from `y = 0` upwards
+/// and given as `(thickness, id)` pairs. Voxels below and above the stack are air.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlatGenerator {
+    pub layers: Vec<(i32, Id)>,
+}
+
+impl FlatGenerator {
+    /// Gives voxel id at height `y`. It does not depend on
+    /// horizontal position so chunks have no seams.
+    pub fn id_at(&self, y: i32) -> Id {
+        if y < 0 {
+            return AIR_VOXEL_DATA.id;
+        }
+
+        let mut layer_top = 0;
+        for &(thickness, id) in self.layers.iter() {
+            layer_top += thickness.max(0);
+
+            if y < layer_top {
+                return id;
+            }
+        }
+
+        AIR_VOXEL_DATA.id
+    }
+}
+
+impl From<FlatPreset> for FlatGenerator {
+    fn from(preset: FlatPreset) -> Self {
+        let layers = match preset {
+            FlatPreset::Classic => vec![
+                (1, STONE_VOXEL_DATA.id),
+                (3, DIRT_VOXEL_DATA.id),
+                (1, GRASS_VOXEL_DATA.id),
+            ],
+
+            FlatPreset::Deep => vec![
+                (32, STONE_VOXEL_DATA.id),
+                (4, DIRT_VOXEL_DATA.id),
+                (1, GRASS_VOXEL_DATA.id),
+            ],
+
+            FlatPreset::Stone => vec![(16, STONE_VOXEL_DATA.id)],
+        };
+
+        Self { layers }
+    }
+}
+
+impl Default for FlatGenerator {
+    fn default() -> Self {
+        FlatPreset::default().into()
+    }
+}
+
+impl WorldGenerator for FlatGenerator {
+    fn generate(&self, chunk_pos: Int3, _seed: u64) -> Vec<Id> {
+        Chunk::global_pos_iter(chunk_pos)
+            .map(|pos| self.id_at(pos.y))
+            .collect()
+    }
+}
+
+/// Layer stacks of [`FlatGenerator`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Display)]
+pub enum FlatPreset {
+    #[default]
+    Classic,
+    Deep,
+    Stone,
+}
+
+/// Floating islands with steep cliffs.
+///
+/// Unlike heightmap generators it decides each voxel by 3D density function:
+/// voxel is solid if `density(pos) > 0`, where
+///
+/// `density(pos) = detail(pos) + shape(y) + islands(x, z)`.
+///
+/// - `detail` is 3D fractal noise. It does not depend on height monotonically,
+///   so solid voxels can lie above air, which gives overhangs, arches and caves.
+/// - `shape` falls linearly from `0.5` on `center_height` to `-0.5` on
+///   `half_thickness` distance from it, so terrain stays in a floating band.
+/// - `islands` is low frequency noise that splits the band into separate islands.
+///   Its sharp slope on island edges makes cliffs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IslandGenerator {
+    pub frequency: f64,
+    pub n_octaves: usize,
+    pub center_height: f32,
+    pub half_thickness: f32,
+}
+
+impl IslandGenerator {
+    /// Depth of dirt under grass.
+    const DIRT_DEPTH: i32 = 3;
+    /// How much frequency of island layout is lower than detail frequency.
+    const ISLANDS_FREQUENCY_DIVIDER: f64 = 4.0;
+    const ISLANDS_SHARPNESS: f64 = 2.0;
+
+    /// Builds [seeded][WorldGenerator::generate] noise.
+    fn noise(&self, seed: u64) -> Fbm {
+        Fbm::new(
+            fold_seed(seed),
+            self.frequency,
+            Fbm::DEFAULT_LACUNARITY,
+            self.n_octaves,
+            Fbm::DEFAULT_PERSISTENCE,
+        )
+    }
+
+    /// Density in `pos`. See [`IslandGenerator`] docs.
+    fn density(&self, noise: &Fbm, pos: Int3) -> f64 {
+        let (x, y, z) = (pos.x as f64, pos.y as f64, pos.z as f64);
+
+        let detail = noise.get([x, y, z]);
+
+        let height_diff = (pos.y as f32 - self.center_height).abs();
+        let shape = 0.5 - (height_diff / self.half_thickness) as f64;
+
+        let islands = noise.get([
+            x / Self::ISLANDS_FREQUENCY_DIVIDER,
+            0.0,
+            z / Self::ISLANDS_FREQUENCY_DIVIDER,
+        ]) * Self::ISLANDS_SHARPNESS;
+
+        detail + shape + islands.clamp(-1.0, 0.5)
+    }
+}
+
+impl Default for IslandGenerator {
+    fn default() -> Self {
+        Self {
+            frequency: 0.04,
+            n_octaves: 4,
+            center_height: 32.0,
+            half_thickness: 24.0,
+        }
+    }
+}
+
+impl WorldGenerator for IslandGenerator {
+    fn generate(&self, chunk_pos: Int3, seed: u64) -> Vec<Id> {
+        let noise = self.noise(seed);
+        let is_solid = |pos: Int3| 0.0 < self.density(&noise, pos);
+
+        Chunk::global_pos_iter(chunk_pos)
+            .map(|pos| if !is_solid(pos) {
+                AIR_VOXEL_DATA.id
+            } else if !is_solid(pos + veci!(0, 1, 0)) {
+                GRASS_VOXEL_DATA.id
+            } else if !is_solid(pos + veci!(0, Self::DIRT_DEPTH, 0)) {
+                DIRT_VOXEL_DATA.id
+            } else {
+                STONE_VOXEL_DATA.id
+            })
+            .collect()
+    }
+}
+
+/// Vertical limits of the world. Voxels on `min_y` height are unbreakable
+/// bedrock, voxels below `min_y` or above `max_y` are air.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorldBounds {
+    pub min_y: i32,
+    pub max_y: i32,
+}
+
+impl WorldBounds {
+    pub const DEFAULT: Self = Self {
+        min_y: cfg::terrain::default::WORLD_MIN_Y,
+        max_y: cfg::terrain::default::WORLD_MAX_Y,
+    };
+
+    /// Replaces generated `id` on `y` height according to bounds.
+    pub fn apply(&self, y: i32, id: Id) -> Id {
+        if y == self.min_y {
+            BEDROCK_VOXEL_DATA.id
+        } else if y < self.min_y || self.max_y < y {
+            AIR_VOXEL_DATA.id
+        } else {
+            id
+        }
+    }
+}
+
+impl Default for WorldBounds {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+/// Limits other [generator][WorldGenerator] by [world bounds][WorldBounds].
+#[derive(Clone, Debug)]
+pub struct BoundedGenerator {
+    pub inner: Arc<dyn WorldGenerator>,
+    pub bounds: WorldBounds,
+}
+
+impl WorldGenerator for BoundedGenerator {
+    fn generate(&self, chunk_pos: Int3, seed: u64) -> Vec<Id> {
+        Chunk::global_pos_iter(chunk_pos)
+            .zip(self.inner.generate(chunk_pos, seed))
+            .map(|(pos, id)| self.bounds.apply(pos.y, id))
+            .collect()
+    }
+}
+
+/// [Generators][WorldGenerator] that can be chosen from UI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Display)]
+pub enum GeneratorKind {
+    #[default]
+    Default,
+
+    #[display("Flat ({0})")]
+    Flat(FlatPreset),
+
+    Islands,
+}
+
+impl GeneratorKind {
+    pub const ALL: [Self; 5] = [
+        Self::Default,
+        Self::Flat(FlatPreset::Classic),
+        Self::Flat(FlatPreset::Deep),
+        Self::Flat(FlatPreset::Stone),
+        Self::Islands,
+    ];
+
+    /// Makes [generator][WorldGenerator] for [chunk array][ChunkArray]
+    /// of `chunk_array_sizes` limited by `bounds`.
+    pub fn make(self, chunk_array_sizes: USize3, bounds: WorldBounds) -> Arc<dyn WorldGenerator> {
+        let inner: Arc<dyn WorldGenerator> = match self {
+            Self::Default => Arc::new(DefaultGenerator { chunk_array_sizes }),
+            Self::Flat(preset) => Arc::new(FlatGenerator::from(preset)),
+            Self::Islands => Arc::new(IslandGenerator::default()),
+        };
+
+        Arc::new(BoundedGenerator { inner, bounds })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folded_seed_depends_on_high_bits() {
+        assert_eq!(fold_seed(42), 42);
+        assert_ne!(fold_seed(42), fold_seed(42 | 1 << 40));
+        assert_ne!(fold_seed(u64::MAX), fold_seed(u32::MAX as u64));
+    }
+
+    #[test]
+    fn default_generator_depends_on_seed() {
+        let generator = DefaultGenerator { chunk_array_sizes: USize3::new(1, 1, 1) };
+
+        let ids = generator.generate(Int3::ZERO, 1);
+        assert_eq!(ids.len(), Chunk::VOLUME);
+
+        assert_eq!(generator.generate(Int3::ZERO, 1), ids);
+        assert_ne!(generator.generate(Int3::ZERO, 2), ids);
+        assert_eq!(generator.generate(Int3::ZERO, 1), ids);
+    }
+
+    #[test]
+    fn flat_generator_chunk() {
+        let generator = FlatGenerator { layers: vec![(3, STONE_VOXEL_DATA.id)] };
+
+        let ids = generator.generate(Int3::ZERO, 0);
+        assert_eq!(ids.len(), Chunk::VOLUME);
+
+        for (pos, id) in Chunk::global_pos_iter(Int3::ZERO).zip(ids) {
+            let expected = if pos.y < 3 { STONE_VOXEL_DATA.id } else { AIR_VOXEL_DATA.id };
+            assert_eq!(id, expected, "voxel in {pos}");
+        }
+
+        assert_eq!(generator.generate(veci!(2, 5, -1), 0), generator.generate(veci!(2, 5, -1), 1));
+    }
+
+    #[test]
+    fn flat_generator_column_matches_layers() {
+        let generator = FlatGenerator::from(FlatPreset::Classic);
+        let ids = generator.generate(Int3::ZERO, 0);
+
+        let column: Vec<_> = Chunk::global_pos_iter(Int3::ZERO)
+            .zip(ids.iter().copied())
+            .filter(|(pos, _)| pos.x == 5 && pos.z == 7)
+            .sorted_by_key(|(pos, _)| pos.y)
+            .map(|(_, id)| id)
+            .collect();
+
+        let mut expected = vec![
+            STONE_VOXEL_DATA.id,
+            DIRT_VOXEL_DATA.id, DIRT_VOXEL_DATA.id, DIRT_VOXEL_DATA.id,
+            GRASS_VOXEL_DATA.id,
+        ];
+        expected.resize(Chunk::SIZE, AIR_VOXEL_DATA.id);
+
+        assert_eq!(column, expected);
+        assert_eq!(generator.id_at(-1), AIR_VOXEL_DATA.id);
+
+        // No seams between neighbor chunks.
+        assert_eq!(generator.generate(veci!(1, 0, 0), 0), ids);
+    }
+
+    #[test]
+    fn island_generator_makes_overhang() {
+        let generator = IslandGenerator::default();
+        let ids = generator.generate(Int3::ZERO, 10);
+        let id_at = |x: i32, y: i32, z: i32| ids[Chunk::voxel_pos_to_idx_unchecked(veci!(x, y, z))];
+
+        let size = Chunk::SIZE as i32;
+        let has_overhang = (0..size).cartesian_product(0..size).any(|(x, z)| {
+            let mut was_solid = false;
+            let mut was_air_above_solid = false;
+
+            for y in 0..size {
+                let is_solid = id_at(x, y, z) != AIR_VOXEL_DATA.id;
+
+                if is_solid && was_air_above_solid {
+                    return true;
+                }
+
+                was_air_above_solid |= was_solid && !is_solid;
+                was_solid |= is_solid;
+            }
+
+            false
+        });
+
+        assert!(has_overhang, "there should be air between solid voxels in some column");
+        assert_eq!(ids, generator.generate(Int3::ZERO, 10));
+    }
+
+    #[test]
+    fn bounded_generator_has_bedrock_floor() {
+        let bounds = WorldBounds { min_y: 2, max_y: 10 };
+        let generator = BoundedGenerator {
+            inner: Arc::new(FlatGenerator::from(FlatPreset::Stone)),
+            bounds,
+        };
+
+        for (pos, id) in Chunk::global_pos_iter(Int3::ZERO).zip(generator.generate(Int3::ZERO, 0)) {
+            let expected = match pos.y {
+                2 => BEDROCK_VOXEL_DATA.id,
+                3..=10 => STONE_VOXEL_DATA.id,
+                _ => AIR_VOXEL_DATA.id,
+            };
+
+            assert_eq!(id, expected, "voxel in {pos}");
+        }
+    }
+}
@@ -1,29 +1,161 @@
 use {
     math_linear::prelude::*,
-    noise::{Fbm, Perlin, utils::{PlaneMapBuilder, NoiseMapBuilder, NoiseMap}},
+    rand::{SeedableRng, seq::SliceRandom, rngs::StdRng},
 };
 
+/// Seeded improved perlin gradient noise. Gives values in `-1.0..=1.0`.
+#[derive(Clone, Debug)]
+pub struct Perlin {
+    /// Shuffled `0..256` repeated twice so that hashing never wraps.
+    perm: [u8; 512],
+}
+
+impl Perlin {
+    pub fn new(seed: u32) -> Self {
+        let mut half: [u8; 256] = std::array::from_fn(|i| i as u8);
+        half.shuffle(&mut StdRng::seed_from_u64(seed as u64));
+
+        Self { perm: std::array::from_fn(|i| half[i % 256]) }
+    }
+
+    /// Samples noise in `pos`. Noise is zero on integer lattice.
+    pub fn get(&self, [x, y, z]: [f64; 3]) -> f64 {
+        let (fx, fy, fz) = (x.floor(), y.floor(), z.floor());
+        let (x, y, z) = (x - fx, y - fy, z - fz);
+
+        let cell = |v: f64| (v as i64).rem_euclid(256) as usize;
+        let (cx, cy, cz) = (cell(fx), cell(fy), cell(fz));
+
+        let hash = |x: usize, y: usize, z: usize| {
+            let p = &self.perm;
+            p[p[p[cx + x] as usize + cy + y] as usize + cz + z]
+        };
+
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        lerp(w,
+            lerp(v,
+                lerp(u, grad(hash(0, 0, 0), x, y, z), grad(hash(1, 0, 0), x - 1.0, y, z)),
+                lerp(u, grad(hash(0, 1, 0), x, y - 1.0, z), grad(hash(1, 1, 0), x - 1.0, y - 1.0, z)),
+            ),
+            lerp(v,
+                lerp(u, grad(hash(0, 0, 1), x, y, z - 1.0), grad(hash(1, 0, 1), x - 1.0, y, z - 1.0)),
+                lerp(u, grad(hash(0, 1, 1), x, y - 1.0, z - 1.0), grad(hash(1, 1, 1), x - 1.0, y - 1.0, z - 1.0)),
+            ),
+        ).clamp(-1.0, 1.0)
+    }
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Dot product of `(x, y, z)` with one of 12 cube edge gradients chosen by `hash`.
+fn grad(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 { y } else if h == 12 || h == 14 { x } else { z };
+
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+/// Fractal sum of [perlin noise][Perlin] octaves. Each octave has its own seed,
+/// `lacunarity` times higher frequency and `persistence` times amplitude
+/// of previous one. Sum is normalized to `-1.0..=1.0`.
+#[derive(Clone, Debug)]
+pub struct Fbm {
+    octaves: Vec<Perlin>,
+    pub frequency: f64,
+    pub lacunarity: f64,
+    pub persistence: f64,
+}
+
+impl Fbm {
+    pub const DEFAULT_LACUNARITY: f64 = 2.0;
+    pub const DEFAULT_PERSISTENCE: f64 = 0.5;
+
+    pub fn new(seed: u32, frequency: f64, lacunarity: f64, n_octaves: usize, persistence: f64) -> Self {
+        Self {
+            octaves: (0..n_octaves as u32)
+                .map(|i| Perlin::new(seed.wrapping_add(i)))
+                .collect(),
+            frequency,
+            lacunarity,
+            persistence,
+        }
+    }
+
+    pub fn get(&self, pos: [f64; 3]) -> f64 {
+        let (mut frequency, mut amplitude) = (self.frequency, 1.0);
+        let (mut sum, mut amplitude_sum) = (0.0, 0.0);
+
+        for perlin in self.octaves.iter() {
+            sum += amplitude * perlin.get(pos.map(|coord| coord * frequency));
+            amplitude_sum += amplitude;
+
+            frequency *= self.lacunarity;
+            amplitude *= self.persistence;
+        }
+
+        if amplitude_sum == 0.0 { 0.0 } else { sum / amplitude_sum }
+    }
+}
+
+/// Precomputed [fractal noise][Fbm] on integer points of `sizes` plane.
 pub struct Noise2d {
-    pub map: NoiseMap,
+    pub values: Vec<f64>,
+    pub seed: u32,
+    pub sizes: USize2,
 }
 
 impl Noise2d {
     pub fn new(seed: u32, sizes: USize2, frequency: f32, lacunarity: f32, n_octaves: usize, persistence: f32) -> Self {
-        let fbm = {
-            let mut fbm = Fbm::<Perlin>::new(seed);
-            fbm.frequency = frequency as f64;
-            fbm.lacunarity = lacunarity as f64;
-            fbm.octaves = n_octaves;
-            fbm.persistence = persistence as f64;
-            fbm
-        };
-        
-        let generator = PlaneMapBuilder::<_, 2>::new(&fbm)
-            .set_size(sizes.x, sizes.y)
-            .set_x_bounds(0.0, sizes.x as f64)
-            .set_y_bounds(0.0, sizes.y as f64)
-            .build();
+        let fbm = Fbm::new(seed, frequency as f64, lacunarity as f64, n_octaves, persistence as f64);
+
+        let values = (0..sizes.y)
+            .flat_map(|y| (0..sizes.x).map(move |x| (x, y)))
+            .map(|(x, y)| fbm.get([x as f64, y as f64, 0.0]))
+            .collect();
 
-        Self { map: generator }
+        Self { values, seed, sizes }
     }
-}
\ No newline at end of file
+
+    /// Gives noise value in `(x, y)`. Points out of the plane are zero.
+    pub fn get_value(&self, x: usize, y: usize) -> f64 {
+        if x < self.sizes.x && y < self.sizes.y {
+            self.values[y * self.sizes.x + x]
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perlin_is_seeded_and_bounded() {
+        let (a, b) = (Perlin::new(1), Perlin::new(2));
+        let points = (0..64).map(|i| [i as f64 * 0.37, i as f64 * 0.71, i as f64 * 0.13]);
+
+        assert!(points.clone().all(|pos| (-1.0..=1.0).contains(&a.get(pos))));
+        assert!(points.clone().all(|pos| a.get(pos) == Perlin::new(1).get(pos)));
+        assert!(points.clone().any(|pos| a.get(pos) != b.get(pos)));
+        assert_eq!(a.get([3.0, -5.0, 7.0]), 0.0);
+    }
+
+    #[test]
+    fn fbm_is_normalized() {
+        let fbm = Fbm::new(7, 0.05, Fbm::DEFAULT_LACUNARITY, 6, Fbm::DEFAULT_PERSISTENCE);
+
+        for i in 0..256 {
+            let value = fbm.get([i as f64 * 1.3, -(i as f64) * 0.7, i as f64 * 2.1]);
+            assert!((-1.0..=1.0).contains(&value), "{value} is out of range");
+        }
+    }
+}
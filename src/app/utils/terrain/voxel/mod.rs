@@ -29,6 +29,26 @@ impl Voxel {
     pub fn is_air(&self) -> bool {
         self.data.id == AIR_VOXEL_DATA.id
     }
+
+    /// Checks that voxel hides faces behind it.
+    pub fn is_opaque(&self) -> bool {
+        !self.data.is_transparent
+    }
+
+    /// Checks if face of this voxel towards `neighbor` should be emitted.
+    /// [`None`] means there is no neighbor (e.g. no adjacent chunk).
+    /// Faces between transparent voxels of the same type are hidden.
+    pub fn face_visible_against(&self, neighbor: Option<Voxel>) -> bool {
+        if self.is_air() {
+            return false;
+        }
+
+        match neighbor {
+            None => true,
+            Some(neighbor) if neighbor.is_opaque() => false,
+            Some(neighbor) => neighbor.data.id != self.data.id,
+        }
+    }
 }
 
 pub fn is_id_valid(id: Id) -> bool {
@@ -36,6 +56,11 @@ pub fn is_id_valid(id: Id) -> bool {
     (0..VOXEL_DATA.len()).contains(&id)
 }
 
+/// Checks that voxel with `id` hides faces behind it. Invalid ids are not opaque.
+pub fn is_id_opaque(id: Id) -> bool {
+    VOXEL_DATA.get(id as usize).is_some_and(|data| !data.is_transparent)
+}
+
 /// Generalization of voxel details.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum LoweredVoxel {
@@ -62,19 +87,23 @@ impl FromBytes for Voxel {
             let pos,
         }
 
+        if !is_id_valid(id) {
+            return Err(ReinterpretError::InvalidVoxelId(id));
+        }
+
         Ok(Self { pos, data: &VOXEL_DATA[id as usize] })
     }
 }
 
 impl StaticSize for Voxel {
-    fn static_size() -> usize { 16 }
+    fn static_size() -> usize { Id::static_size() + Int3::static_size() }
 }
 
 
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use {super::*, voxel_data::TextureSides};
 
     #[test]
     fn reinterpret_voxel1() {
@@ -91,6 +120,75 @@ mod tests {
 
         assert_eq!(before, after);
     }
+
+    #[test]
+    fn voxel_static_size() {
+        let voxel = Voxel::new(Int3::new(1, 2, 3), STONE_VOXEL_DATA);
+        assert_eq!(voxel.as_bytes().len(), Voxel::static_size());
+    }
+
+    static GLASS_VOXEL_DATA: VoxelData = VoxelData {
+        name: "Glass",
+        id: Id::MAX,
+        textures: TextureSides::all(0),
+        avarage_color: Color::new(0.8, 0.9, 0.95),
+        is_transparent: true,
+        unbreakable: false,
+        hardness: 0.3,
+        on_interact: None,
+    };
+
+    #[test]
+    fn solid_face_visible_against_air() {
+        let stone = Voxel::new(Int3::ZERO, STONE_VOXEL_DATA);
+        let air = Voxel::new(veci!(1, 0, 0), AIR_VOXEL_DATA);
+
+        assert!(stone.face_visible_against(Some(air)));
+        assert!(stone.face_visible_against(None));
+    }
+
+    #[test]
+    fn solid_face_hidden_by_solid() {
+        let stone = Voxel::new(Int3::ZERO, STONE_VOXEL_DATA);
+        let dirt = Voxel::new(veci!(1, 0, 0), DIRT_VOXEL_DATA);
+
+        assert!(!stone.face_visible_against(Some(dirt)));
+    }
+
+    #[test]
+    fn only_not_transparent_ids_are_opaque() {
+        assert!(is_id_opaque(STONE_VOXEL_DATA.id));
+        assert!(!is_id_opaque(AIR_VOXEL_DATA.id));
+        assert!(!is_id_opaque(VOXEL_DATA.len() as Id));
+
+        for data in VOXEL_DATA.iter() {
+            assert_eq!(is_id_opaque(data.id), Voxel::new(Int3::ZERO, data).is_opaque());
+        }
+    }
+
+    #[test]
+    fn solid_face_visible_against_glass() {
+        let stone = Voxel::new(Int3::ZERO, STONE_VOXEL_DATA);
+        let glass = Voxel::new(veci!(1, 0, 0), &GLASS_VOXEL_DATA);
+
+        assert!(stone.face_visible_against(Some(glass)));
+        assert!(!glass.face_visible_against(Some(stone)));
+        assert!(!glass.face_visible_against(Some(glass)));
+    }
+
+    #[test]
+    fn reinterpret_invalid_voxel_id() {
+        let invalid_id = VOXEL_DATA.len() as Id;
+        let bytes: Vec<u8> = compose! {
+            invalid_id.as_bytes(),
+            Int3::new(1, 2, 3).as_bytes(),
+        }.collect();
+
+        assert!(matches!(
+            Voxel::from_bytes(&bytes),
+            Err(ReinterpretError::InvalidVoxelId(id)) if id == invalid_id,
+        ));
+    }
 }
 
 
@@ -148,13 +246,14 @@ pub mod shape {
             /* Shortcuts */
             let (x, y, z) = position.as_tuple();
             let face_idx = FRONT_IDX as u8;
-
-            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx });
-            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx });
-            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx });
-            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx });
-            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx });
-            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx });
+            let anim = self.data.textures.animation.as_attribute();
+
+            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx, anim });
+            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx, anim });
+            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx, anim });
+            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx, anim });
+            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx, anim });
+            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx, anim });
         }
 
         /// Cube back face vertex array.
@@ -165,13 +264,14 @@ pub mod shape {
             /* Shortcuts */
             let (x, y, z) = position.as_tuple();
             let face_idx = BACK_IDX as u8;
-
-            vertices.push(FullVertex { position: (self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx });
-            vertices.push(FullVertex { position: (self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx });
-            vertices.push(FullVertex { position: (self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx });
-            vertices.push(FullVertex { position: (self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx });
-            vertices.push(FullVertex { position: (self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx });
-            vertices.push(FullVertex { position: (self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx });
+            let anim = self.data.textures.animation.as_attribute();
+
+            vertices.push(FullVertex { position: (self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx, anim });
+            vertices.push(FullVertex { position: (self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx, anim });
+            vertices.push(FullVertex { position: (self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx, anim });
+            vertices.push(FullVertex { position: (self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx, anim });
+            vertices.push(FullVertex { position: (self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx, anim });
+            vertices.push(FullVertex { position: (self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx, anim });
         }
 
         /// Cube top face vertex array.
@@ -182,13 +282,14 @@ pub mod shape {
             /* Shortcuts */
             let (x, y, z) = position.as_tuple();
             let face_idx = TOP_IDX as u8;
-
-            vertices.push(FullVertex { position: ( self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx });
-            vertices.push(FullVertex { position: ( self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx });
-            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx });
-            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx });
-            vertices.push(FullVertex { position: ( self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx });
-            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx });
+            let anim = self.data.textures.animation.as_attribute();
+
+            vertices.push(FullVertex { position: ( self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx, anim });
+            vertices.push(FullVertex { position: ( self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx, anim });
+            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx, anim });
+            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx, anim });
+            vertices.push(FullVertex { position: ( self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx, anim });
+            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx, anim });
         }
 
         /// Cube bottom face vertex array.
@@ -199,13 +300,14 @@ pub mod shape {
             /* Shortcuts */
             let (x, y, z) = position.as_tuple();
             let face_idx = BOTTOM_IDX as u8;
-
-            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx });
-            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx });
-            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx });
-            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx });
-            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx });
-            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx });
+            let anim = self.data.textures.animation.as_attribute();
+
+            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx, anim });
+            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx, anim });
+            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx, anim });
+            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx, anim });
+            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx, anim });
+            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx, anim });
         }
 
         /// Cube left face vertex array.
@@ -216,13 +318,14 @@ pub mod shape {
             /* Shortcuts */
             let (x, y, z) = position.as_tuple();
             let face_idx = LEFT_IDX as u8;
-
-            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx }); // 0 (uv.x_lo, uv.y_lo)
-            vertices.push(FullVertex { position: ( self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx }); // 1 (uv.x_lo, uv.y_hi)
-            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx }); // 2 (uv.x_hi, uv.y_hi)
-            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx }); // 0
-            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx }); // 2
-            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx }); // 3 (uv.x_hi, uv.y_lo)
+            let anim = self.data.textures.animation.as_attribute();
+
+            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx, anim }); // 0 (uv.x_lo, uv.y_lo)
+            vertices.push(FullVertex { position: ( self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx, anim }); // 1 (uv.x_lo, uv.y_hi)
+            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx, anim }); // 2 (uv.x_hi, uv.y_hi)
+            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx, anim }); // 0
+            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y, -self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx, anim }); // 2
+            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y, -self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx, anim }); // 3 (uv.x_hi, uv.y_lo)
         }
 
         /// Cube right face vertex array.
@@ -233,13 +336,14 @@ pub mod shape {
             /* Shortcuts */
             let (x, y, z) = position.as_tuple();
             let face_idx = RIGHT_IDX as u8;
-
-            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx }); // lolo (uv.x_lo, uv.y_lo)
-            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx }); // hihi
-            vertices.push(FullVertex { position: ( self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx }); // lohi (uv.x_lo, uv.y_hi)
-            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx }); // lolo (uv.x_lo, uv.y_lo)
-            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx }); // hilo
-            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx }); // hihi
+            let anim = self.data.textures.animation.as_attribute();
+
+            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx, anim }); // lolo (uv.x_lo, uv.y_lo)
+            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx, anim }); // hihi
+            vertices.push(FullVertex { position: ( self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.lo.x, uv.lo.y), face_idx, anim }); // lohi (uv.x_lo, uv.y_hi)
+            vertices.push(FullVertex { position: ( self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.lo.x, uv.hi.y), face_idx, anim }); // lolo (uv.x_lo, uv.y_lo)
+            vertices.push(FullVertex { position: (-self.half_size + x, -self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.hi.y), face_idx, anim }); // hilo
+            vertices.push(FullVertex { position: (-self.half_size + x,  self.half_size + y,  self.half_size + z), tex_coords: (uv.hi.x, uv.lo.y), face_idx, anim }); // hihi
         }
 
         /// Cube all sides.
@@ -1,36 +1,85 @@
 use {
-    crate::app::utils::cfg::terrain::voxel_types::VOXEL_DATA as CFG_VOXEL_DATA,
-    math_linear::prelude::*,
+    crate::{
+        prelude::*,
+        app::utils::cfg::terrain::voxel_types::VOXEL_DATA as CFG_VOXEL_DATA,
+    },
+    super::{Voxel, atlas},
+    std::{io, path::Path},
 };
 
 /// IDs type.
 pub type Id = u16;
 
 /// Represents shared data for group of voxels
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct VoxelData {
     pub name: &'static str,
     pub id: Id,
 
     pub textures: TextureSides,
     pub avarage_color: Color,
+
+    /// Faces behind this voxel are visible through it.
+    pub is_transparent: bool,
+
+    /// Voxel can not be edited.
+    pub unbreakable: bool,
+
+    /// Seconds of holding break button needed to break the voxel.
+    pub hardness: f32,
+
+    /// Called when voxel is used with empty hand. See [`InteractFn`].
+    pub on_interact: Option<InteractFn>,
+}
+
+/// Voxel interaction hook. Gives [id][Id] the voxel should be replaced with, if any.
+pub type InteractFn = fn(&Voxel) -> Option<Id>;
+
+/// [Interaction hooks][InteractFn] that [voxel table][parse_table] refers to by name.
+pub const INTERACT_HOOKS: &[(&str, InteractFn)] = &[
+    ("till", till),
+    ("grow", grow),
+];
+
+/// Turns voxel into dirt.
+fn till(_: &Voxel) -> Option<Id> {
+    Some(data::DIRT_VOXEL_DATA.id)
+}
+
+/// Turns voxel into grass.
+fn grow(_: &Voxel) -> Option<Id> {
+    Some(data::GRASS_VOXEL_DATA.id)
+}
+
+impl VoxelData {
+    /// [Hardness][VoxelData::hardness] of voxels with no given one.
+    pub const DEFAULT_HARDNESS: f32 = 0.5;
 }
 
 /// Represents textured sides of the voxel.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct TextureSides {
     pub front:	u16,
     pub back:	u16,
     pub left:	u16,
     pub right:	u16,
     pub top:	u16,
-    pub bottom:	u16
+    pub bottom:	u16,
+
+    /// Animation of all sides.
+    pub animation: TextureAnimation,
 }
 
 impl TextureSides {
     /// Constructs new voxel sides data
     pub const fn new(front: u16, back: u16, left: u16, right: u16, up: u16, bottom: u16) -> Self {
-        TextureSides { front, back, left, right, top: up, bottom }
+        TextureSides { front, back, left, right, top: up, bottom, animation: TextureAnimation::STILL }
+    }
+
+    /// Animates all sides.
+    pub const fn animated(mut self, animation: TextureAnimation) -> Self {
+        self.animation = animation;
+        self
     }
 
     /// Makes all sides to given id
@@ -51,14 +100,358 @@ impl TextureSides {
     }
 }
 
+/// Texture animation. Its frames are atlas items following the texture in the same atlas row.
+/// Frame is selected in `full_detail.vert` by `time` uniform so meshes are not rebuilt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureAnimation {
+    pub n_frames: u8,
+    pub fps: u8,
+}
+
+impl TextureAnimation {
+    /// Not animated texture.
+    pub const STILL: Self = Self::new(1, 0);
+
+    pub const fn new(n_frames: u8, fps: u8) -> Self {
+        Self { n_frames, fps }
+    }
+
+    /// Gives index of frame shown at `time` in seconds. Must match `full_detail.vert`.
+    pub fn frame_idx(self, time: f32) -> u8 {
+        if self.n_frames <= 1 { return 0 }
+
+        let n_frames_passed = (time.max(0.0) * self.fps as f32) as u32;
+        (n_frames_passed % self.n_frames as u32) as u8
+    }
+
+    /// Gives vertex attribute `(n_frames, fps)`.
+    pub const fn as_attribute(self) -> (u8, u8) {
+        (self.n_frames, self.fps)
+    }
+}
+
+impl Default for TextureAnimation {
+    fn default() -> Self {
+        Self::STILL
+    }
+}
+
 pub mod data {
     use super::*;
     
-    pub const VOXEL_DATA: &[VoxelData] = &CFG_VOXEL_DATA;
-
-    pub const AIR_VOXEL_DATA:    		&VoxelData = &VOXEL_DATA[0];
-    pub const LOG_VOXEL_DATA:			&VoxelData = &VOXEL_DATA[1];
-    pub const STONE_VOXEL_DATA:			&VoxelData = &VOXEL_DATA[2];
-    pub const GRASS_VOXEL_DATA:         &VoxelData = &VOXEL_DATA[3];
-    pub const DIRT_VOXEL_DATA:          &VoxelData = &VOXEL_DATA[4];
-}
\ No newline at end of file
+    pub const BUILTIN_VOXEL_DATA: &[VoxelData] = &CFG_VOXEL_DATA;
+
+    lazy_static! {
+        /// Voxel table [loaded][load_table_or_builtin] from [`TABLE_PATH`][cfg::terrain::voxel_types::TABLE_PATH]
+        /// if it exists, otherwise [built-in][BUILTIN_VOXEL_DATA] one. It is never empty.
+        pub static ref VOXEL_DATA: Cow<'static, [VoxelData]> = {
+            let path = Path::new(cfg::terrain::voxel_types::TABLE_PATH);
+
+            match path.exists() {
+                true => load_table_or_builtin(path),
+                false => Cow::Borrowed(BUILTIN_VOXEL_DATA),
+            }
+        };
+    }
+
+    pub const AIR_VOXEL_DATA:    		&VoxelData = &BUILTIN_VOXEL_DATA[0];
+    pub const LOG_VOXEL_DATA:			&VoxelData = &BUILTIN_VOXEL_DATA[1];
+    pub const STONE_VOXEL_DATA:			&VoxelData = &BUILTIN_VOXEL_DATA[2];
+    pub const GRASS_VOXEL_DATA:         &VoxelData = &BUILTIN_VOXEL_DATA[3];
+    pub const DIRT_VOXEL_DATA:          &VoxelData = &BUILTIN_VOXEL_DATA[4];
+    pub const BEDROCK_VOXEL_DATA:       &VoxelData = &BUILTIN_VOXEL_DATA[5];
+}
+
+// Built-in table always has air with id 0, other code relies on it.
+static_assertions::const_assert!(!CFG_VOXEL_DATA.is_empty());
+static_assertions::const_assert!(CFG_VOXEL_DATA[0].id == 0 && CFG_VOXEL_DATA[0].is_transparent);
+
+/// Parses voxel table from lines `name: texture[, transparent][, unbreakable][, hardness = seconds]
+/// [, animation = frames / fps][, interact = hook]`, where `hook` is name from [`INTERACT_HOOKS`].
+/// Empty lines and lines starting with `#` are skipped. Built-in [air][data::AIR_VOXEL_DATA]
+/// always goes first with id 0 so defined voxels get ids starting from 1.
+///
+/// Table should start with all [built-in][data::BUILTIN_VOXEL_DATA] voxels in the same order
+/// so their ids stay valid. Built-in voxel line without properties (`Stone:`) keeps its data.
+/// [Average colors][VoxelData::avarage_color] are computed from textures in `atlas`.
+///
+/// Names are leaked to be `'static` so table should be loaded once.
+pub fn parse_table(src: &str, atlas: &image::RgbaImage) -> Result<Vec<VoxelData>, VoxelTableError> {
+    let mut table = vec![data::AIR_VOXEL_DATA.clone()];
+
+    for (line_idx, text) in src.lines().enumerate() {
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+
+        let line = line_idx + 1;
+        let parse_error = |msg: String| VoxelTableError::Parse { line, msg };
+
+        let (name, props) = text.split_once(':')
+            .map(|(name, props)| (name.trim(), props.trim()))
+            .ok_or_else(|| parse_error("expected `name: texture`".into()))?;
+
+        if name.is_empty() {
+            return Err(parse_error("voxel name is empty".into()));
+        }
+
+        let builtin = data::BUILTIN_VOXEL_DATA.get(table.len());
+
+        if let Some(builtin) = builtin.filter(|builtin| builtin.name != name) {
+            return Err(VoxelTableError::BuiltinMismatch { line, expected: builtin.name, got: name.to_owned() });
+        }
+
+        if let (Some(builtin), true) = (builtin, props.is_empty()) {
+            table.push(builtin.clone());
+            continue;
+        }
+
+        let mut props = props.split(',').map(str::trim);
+
+        let texture = props.next().unwrap_or_default();
+        let texture: u16 = texture.parse()
+            .map_err(|err| parse_error(format!("invalid texture '{texture}': {err}")))?;
+
+        let (mut is_transparent, mut unbreakable) = (false, false);
+        let mut hardness = VoxelData::DEFAULT_HARDNESS;
+        let mut animation = TextureAnimation::STILL;
+        let mut on_interact = None;
+
+        for flag in props {
+            match flag.split_once('=').map(|(key, value)| (key.trim(), value.trim())) {
+                Some(("hardness", value)) => hardness = value.parse()
+                    .ok()
+                    .filter(|hardness: &f32| *hardness >= 0.0)
+                    .ok_or_else(|| parse_error(format!("invalid hardness '{value}'")))?,
+
+                Some(("animation", value)) => animation = value.split_once('/')
+                    .and_then(|(n_frames, fps)| Some(TextureAnimation::new(
+                        n_frames.trim().parse().ok()?,
+                        fps.trim().parse().ok()?,
+                    )))
+                    .filter(|animation| animation.n_frames > 0)
+                    .ok_or_else(|| parse_error(format!("invalid animation '{value}', expected `frames / fps`")))?,
+
+                Some(("interact", value)) => on_interact = INTERACT_HOOKS.iter()
+                    .find(|&&(name, _)| name == value)
+                    .map(|&(_, hook)| Some(hook))
+                    .ok_or_else(|| parse_error(format!("unknown interact hook '{value}'")))?,
+
+                Some(_) => return Err(parse_error(format!("unknown property '{flag}'"))),
+
+                None => match flag {
+                    "transparent" => is_transparent = true,
+                    "unbreakable" => unbreakable = true,
+                    _ => return Err(parse_error(format!("unknown flag '{flag}'"))),
+                },
+            }
+        }
+
+        // World borders are made of unbreakable built-in voxels.
+        if builtin.is_some_and(|builtin| builtin.unbreakable && !unbreakable) {
+            return Err(parse_error(format!("built-in voxel '{name}' should stay unbreakable")));
+        }
+
+        let id = Id::try_from(table.len())
+            .map_err(|_| parse_error("too many voxels".into()))?;
+
+        let avarage_color = atlas::average_color(atlas, texture)
+            .ok_or_else(|| parse_error(format!("texture {texture} is out of atlas or empty")))?;
+
+        table.push(VoxelData {
+            name: Box::leak(name.to_owned().into_boxed_str()),
+            id,
+            textures: TextureSides::all(texture).animated(animation),
+            avarage_color,
+            is_transparent,
+            unbreakable,
+            hardness,
+            on_interact,
+        });
+    }
+
+    if let Some(missing) = data::BUILTIN_VOXEL_DATA.get(table.len()) {
+        return Err(VoxelTableError::MissingBuiltin { name: missing.name });
+    }
+
+    Ok(table)
+}
+
+/// Loads voxel table from file with textures from [atlas][cfg::texture::ATLAS_FILE_NAME].
+/// On failure logs the error and gives [built-in][data::BUILTIN_VOXEL_DATA] table,
+/// so the table is never empty and built-in ids are always valid.
+pub fn load_table_or_builtin(path: impl AsRef<Path>) -> Cow<'static, [VoxelData]> {
+    let path = path.as_ref();
+    let atlas_path = Path::new(cfg::texture::DIRECTORY).join(cfg::texture::ATLAS_FILE_NAME);
+
+    let result = std::fs::read_to_string(path)
+        .map_err(VoxelTableError::from)
+        .and_then(|src| {
+            let atlas = image::open(&atlas_path)?.into_rgba8();
+            parse_table(&src, &atlas)
+        });
+
+    match result {
+        Ok(table) => Cow::Owned(table),
+        Err(err) => {
+            logger::log!(Error, from = "voxel-data", "failed to load voxel table from {path:?}, using built-in one: {err}");
+            Cow::Borrowed(data::BUILTIN_VOXEL_DATA)
+        },
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum VoxelTableError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("failed to load texture atlas: {0}")]
+    Atlas(#[from] image::ImageError),
+
+    #[error("failed to parse line {line}: {msg}")]
+    Parse {
+        line: usize,
+        msg: String,
+    },
+
+    #[error("line {line} defines '{got}' but built-in voxel '{expected}' should go there")]
+    BuiltinMismatch {
+        line: usize,
+        expected: &'static str,
+        got: String,
+    },
+
+    #[error("built-in voxel '{name}' is missing")]
+    MissingBuiltin {
+        name: &'static str,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lines keeping all built-in voxels except air.
+    const BUILTIN_LINES: &str = "Log:\nStone:\nGrass:\nDirt:\nBedrock:\n";
+
+    /// Makes atlas with all items filled with `color`.
+    fn filled_atlas(color: [u8; 4]) -> image::RgbaImage {
+        image::RgbaImage::from_pixel(atlas::ATLAS_ROW_SIZE_IN_PIXELS as u32, atlas::ATLAS_ROW_SIZE_IN_PIXELS as u32, image::Rgba(color))
+    }
+
+    #[test]
+    fn parsed_table_starts_with_air() {
+        let src = format!("# comment\n{BUILTIN_LINES}Glass: 7, transparent\nObsidian: 2, unbreakable, hardness = 4\n");
+        let table = parse_table(&src, &filled_atlas([255, 0, 0, 255]))
+            .expect("table should be valid");
+
+        let n_builtins = data::BUILTIN_VOXEL_DATA.len();
+        assert_eq!(&table[..n_builtins], data::BUILTIN_VOXEL_DATA);
+
+        let (glass, obsidian) = (&table[n_builtins], &table[n_builtins + 1]);
+        assert_eq!((glass.name, glass.id as usize), ("Glass", n_builtins));
+        assert_eq!((obsidian.name, obsidian.id as usize), ("Obsidian", n_builtins + 1));
+        assert!(glass.is_transparent && !glass.unbreakable);
+        assert!(obsidian.unbreakable);
+        assert_eq!(glass.hardness, VoxelData::DEFAULT_HARDNESS);
+        assert_eq!(obsidian.hardness, 4.0);
+        assert_eq!(glass.avarage_color, Color::new(1.0, 0.0, 0.0));
+
+        let atlas = filled_atlas([0, 0, 0, 255]);
+        assert!(parse_table(&format!("{BUILTIN_LINES}Glass: 7, hardness = -1"), &atlas).is_err());
+        assert!(parse_table(&format!("{BUILTIN_LINES}Glass: 60000"), &atlas).is_err());
+        assert_eq!(parse_table(BUILTIN_LINES, &atlas).expect("built-in table is valid"), data::BUILTIN_VOXEL_DATA);
+    }
+
+    #[test]
+    fn table_keeps_builtin_ids() {
+        let atlas = filled_atlas([0, 0, 0, 255]);
+
+        assert!(matches!(
+            parse_table("Log:\nGlass: 7\n", &atlas),
+            Err(VoxelTableError::BuiltinMismatch { line: 2, expected: "Stone", .. }),
+        ));
+
+        assert!(matches!(parse_table("", &atlas), Err(VoxelTableError::MissingBuiltin { name: "Log" })));
+        assert!(matches!(parse_table("Log:\nStone:\n", &atlas), Err(VoxelTableError::MissingBuiltin { name: "Grass" })));
+
+        // Built-in voxel can be redefined in place.
+        let table = parse_table(&BUILTIN_LINES.replace("Stone:", "Stone: 9, hardness = 2"), &atlas)
+            .expect("table should be valid");
+        assert_eq!(table[data::STONE_VOXEL_DATA.id as usize].hardness, 2.0);
+    }
+
+    #[test]
+    fn table_keeps_builtin_unbreakable_flag() {
+        let atlas = filled_atlas([0, 0, 0, 255]);
+        let bedrock_id = data::BEDROCK_VOXEL_DATA.id as usize;
+
+        let table = parse_table(BUILTIN_LINES, &atlas).expect("table should be valid");
+        assert!(table[bedrock_id].unbreakable);
+
+        let table = parse_table(&BUILTIN_LINES.replace("Bedrock:", "Bedrock: 7, unbreakable"), &atlas)
+            .expect("table should be valid");
+        assert!(table[bedrock_id].unbreakable);
+
+        assert!(matches!(
+            parse_table(&BUILTIN_LINES.replace("Bedrock:", "Bedrock: 7"), &atlas),
+            Err(VoxelTableError::Parse { line: 5, .. }),
+        ));
+    }
+
+    #[test]
+    fn table_sets_animation_and_interact_hook() {
+        let atlas = filled_atlas([0, 0, 0, 255]);
+        let src = format!("{BUILTIN_LINES}Water: 8, transparent, animation = 4 / 8\nFarmland: 5, interact = grow\n");
+        let table = parse_table(&src, &atlas).expect("table should be valid");
+
+        let (water, farmland) = (&table[table.len() - 2], &table[table.len() - 1]);
+        assert_eq!(water.textures.animation, TextureAnimation::new(4, 8));
+        assert_eq!(water.on_interact, None);
+        assert_eq!(farmland.textures.animation, TextureAnimation::STILL);
+
+        let hook = farmland.on_interact.expect("hook should be set");
+        assert_eq!(hook(&Voxel::new(Int3::ZERO, data::DIRT_VOXEL_DATA)), Some(data::GRASS_VOXEL_DATA.id));
+
+        for line in ["Water: 8, animation = 4", "Water: 8, animation = 0 / 8", "Farmland: 5, interact = explode"] {
+            assert!(
+                matches!(parse_table(&format!("{BUILTIN_LINES}{line}"), &atlas), Err(VoxelTableError::Parse { line: 6, .. })),
+                "'{line}' should not be parsed",
+            );
+        }
+    }
+
+    #[test]
+    fn animation_frame_follows_time() {
+        let animation = TextureAnimation::new(4, 8);
+
+        assert_eq!(animation.frame_idx(0.0), 0);
+        assert_eq!(animation.frame_idx(0.124), 0);
+        assert_eq!(animation.frame_idx(0.125), 1);
+        assert_eq!(animation.frame_idx(0.49), 3);
+        assert_eq!(animation.frame_idx(0.5), 0);
+        assert_eq!(animation.frame_idx(10.3), 2);
+        assert_eq!(animation.frame_idx(-1.0), 0);
+
+        assert_eq!(TextureAnimation::STILL.frame_idx(123.0), 0);
+        assert_eq!(TextureAnimation::new(3, 0).frame_idx(123.0), 0);
+    }
+
+    #[test]
+    fn malformed_table_falls_back_to_builtin() {
+        let atlas = filled_atlas([0, 0, 0, 255]);
+        assert!(matches!(parse_table("Glass 7", &atlas), Err(VoxelTableError::Parse { line: 1, .. })));
+        assert!(matches!(parse_table("Log: glass", &atlas), Err(VoxelTableError::Parse { line: 1, .. })));
+
+        let path = std::env::temp_dir().join("terramine_malformed_voxel_table.txt");
+        std::fs::write(&path, "Glass: 7, shiny\n").expect("failed to write test file");
+
+        let table = load_table_or_builtin(&path);
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(table.as_ref(), data::BUILTIN_VOXEL_DATA);
+        assert!(table.iter().any(|data| data.id == 0 && data.is_transparent));
+        assert_eq!(data::VOXEL_DATA[0], *data::AIR_VOXEL_DATA);
+    }
+}
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+/// Limits rate of repeated action. Advanced by frame time, see [`Timer::dt`][super::timer::Timer::dt].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cooldown {
+    /// Minimal time between actions in seconds.
+    pub period: f32,
+
+    /// Time left until next action in seconds.
+    pub remaining: f32,
+}
+
+impl Cooldown {
+    /// Constructs ready cooldown.
+    pub fn new(period: Duration) -> Self {
+        Self { period: period.as_secs_f32(), remaining: 0.0 }
+    }
+
+    /// Advances time by `dt` seconds.
+    pub fn tick(&mut self, dt: f32) {
+        self.remaining = (self.remaining - dt).max(0.0);
+    }
+
+    /// Checks if action is allowed now.
+    pub fn is_ready(&self) -> bool {
+        self.remaining <= 0.0
+    }
+
+    /// Starts waiting for the whole period.
+    pub fn trigger(&mut self) {
+        self.remaining = self.period;
+    }
+
+    /// Triggers cooldown if it is ready. Returns `true` if action is allowed.
+    pub fn try_trigger(&mut self) -> bool {
+        let is_ready = self.is_ready();
+
+        if is_ready {
+            self.trigger();
+        }
+
+        is_ready
+    }
+
+    /// Makes cooldown ready, e.g. when button is released.
+    pub fn reset(&mut self) {
+        self.remaining = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cooldown_limits_action_rate() {
+        let mut cooldown = Cooldown::new(Duration::from_millis(100));
+
+        let frames = [(0.0, true), (0.05, false), (0.05, true), (0.02, false), (0.2, true), (0.01, false)];
+        for (frame_idx, (dt, is_allowed)) in frames.into_iter().enumerate() {
+            cooldown.tick(dt);
+            assert_eq!(cooldown.try_trigger(), is_allowed, "frame {frame_idx}");
+        }
+
+        cooldown.reset();
+        assert!(cooldown.try_trigger());
+
+        // Continuous action at 60 FPS fires ten times per second at most.
+        let mut cooldown = Cooldown::new(Duration::from_millis(100));
+        let n_fired = (0..60)
+            .filter(|_| {
+                cooldown.tick(1.0 / 60.0);
+                cooldown.try_trigger()
+            })
+            .count();
+
+        assert!((1..=10).contains(&n_fired), "fired {n_fired} times");
+    }
+}
@@ -1 +1,2 @@
-pub mod timer;
\ No newline at end of file
+pub mod timer;
+pub mod cooldown;
\ No newline at end of file
@@ -109,6 +109,37 @@ pub mod keyboard {
     }
 }
 
+pub mod clipboard {
+    use super::*;
+
+    lazy_static! {
+        /// Clipboard is kept alive for the whole app, because on X11 its contents
+        /// are served by the owner and get lost when it is dropped.
+        static ref CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+    }
+
+    /// Replaces system clipboard contents with `text`.
+    pub fn set_text(text: impl Into<String>) -> Result<(), arboard::Error> {
+        let mut clipboard = CLIPBOARD.lock().unwrap();
+
+        if clipboard.is_none() {
+            *clipboard = Some(arboard::Clipboard::new()?);
+        }
+
+        clipboard.as_mut().unwrap().set_text(text.into())
+    }
+
+    /// Copies `text` to clipboard and logs the result.
+    pub fn copy(text: impl Into<String>) {
+        let text = text.into();
+
+        match set_text(text.clone()) {
+            Ok(()) => crate::logger::log!(Info, from = "clipboard", "copied '{text}'"),
+            Err(err) => crate::logger::log!(Error, from = "clipboard", "failed to copy '{text}': {err}"),
+        }
+    }
+}
+
 pub mod mouse {
     #![allow(dead_code)]
 
@@ -184,6 +215,67 @@ pub mod mouse {
         just_pressed(MouseButton::Middle)
     }
 
+    /// State of dragging something (e.g. voxel position) with a button held.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+    pub enum DragState<T> {
+        #[default]
+        Idle,
+
+        /// Button is held but nothing is dragged. Drag waits for the next press.
+        Held,
+
+        Dragging { from: T, to: T },
+        Released { from: T, to: T },
+    }
+
+    impl<T: Copy> DragState<T> {
+        /// Gives state with no drag for button that is already held or not.
+        pub fn new(is_pressed: bool) -> Self {
+            match is_pressed {
+                true => Self::Held,
+                false => Self::Idle,
+            }
+        }
+
+        /// Advances dragging by button state and a value under cursor.
+        /// Drag starts only on button press and only if there's a value under cursor.
+        pub fn update(&mut self, is_pressed: bool, under_cursor: Option<T>) {
+            *self = match *self {
+                Self::Idle | Self::Released { .. } => match (is_pressed, under_cursor) {
+                    (true, Some(pos)) => Self::Dragging { from: pos, to: pos },
+                    (true, None) => Self::Held,
+                    (false, _) => Self::Idle,
+                },
+
+                Self::Held => Self::new(is_pressed),
+
+                Self::Dragging { from, to } => match is_pressed {
+                    true => Self::Dragging { from, to: under_cursor.unwrap_or(to) },
+                    false => Self::Released { from, to },
+                },
+            };
+        }
+
+        /// Gives dragged range if the button was just released and resets the state.
+        pub fn take_released(&mut self) -> Option<(T, T)> {
+            match *self {
+                Self::Released { from, to } => {
+                    *self = Self::Idle;
+                    Some((from, to))
+                },
+                _ => None,
+            }
+        }
+
+        /// Gives current dragged range if dragging is in progress.
+        pub fn dragging(&self) -> Option<(T, T)> {
+            match *self {
+                Self::Dragging { from, to } => Some((from, to)),
+                _ => None,
+            }
+        }
+    }
+
     /// Update mouse delta.
     pub fn update(window: &glium::glutin::window::Window) -> Result<(), MouseError> {
         {
@@ -278,13 +370,59 @@ pub mod mouse {
     }
 }
 
+/// Input device that UI can capture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputDevice {
+    Keyboard,
+    Mouse,
+}
+
+/// Describes which input devices UI wants to consume, so game should ignore them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UiCapture {
+    pub keyboard: bool,
+    pub mouse: bool,
+}
+
+impl UiCapture {
+    /// Checks that button event from `device` should be passed to game input.
+    /// Releases always pass so buttons pressed before capture will not stuck.
+    pub fn passes(self, device: InputDevice, state: ElementState) -> bool {
+        let is_captured = match device {
+            InputDevice::Keyboard => self.keyboard,
+            InputDevice::Mouse => self.mouse,
+        };
+
+        !is_captured || state == ElementState::Released
+    }
+}
+
+static UI_CAPTURE: Mutex<UiCapture> = Mutex::new(UiCapture { keyboard: false, mouse: false });
+
+/// Sets input devices that UI wants to consume.
+pub fn set_ui_capture(capture: UiCapture) {
+    keyboard::set_input_capture(capture.keyboard);
+    *UI_CAPTURE.lock().unwrap() = capture;
+}
+
+/// Gives input devices that UI wants to consume.
+pub fn ui_capture() -> UiCapture {
+    *UI_CAPTURE.lock().unwrap()
+}
+
 pub fn handle_event(event: &Event<()>, window: &glium::glutin::window::Window) {
     static CURSOR_REGRABBED: Mutex<bool> = Mutex::new(false);
 
+    let capture = ui_capture();
+
     if let Event::WindowEvent { event, .. } = event {
         match event {
             /* Close event */
             WindowEvent::KeyboardInput { input, .. } => if let Some(key) = input.virtual_keycode {
+                if !capture.passes(InputDevice::Keyboard, input.state) {
+                    return;
+                }
+
                 match input.state {
                     ElementState::Pressed => keyboard::press(key),
                     ElementState::Released => keyboard::release(key),
@@ -292,6 +430,8 @@ pub fn handle_event(event: &Event<()>, window: &glium::glutin::window::Window) {
             },
 
             /* Mouse buttons match. */
+            WindowEvent::MouseInput { state, .. } if !capture.passes(InputDevice::Mouse, *state) => (),
+
             WindowEvent::MouseInput { button, state, .. } => match state {
                 /* If button is pressed then press it on virtual mouse, if not then release it. */
                 ElementState::Pressed =>
@@ -325,4 +465,65 @@ pub fn handle_event(event: &Event<()>, window: &glium::glutin::window::Window) {
             _ => (),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captured_device_blocks_presses() {
+        let capture = UiCapture { keyboard: true, mouse: false };
+
+        assert!(!capture.passes(InputDevice::Keyboard, ElementState::Pressed));
+        assert!(capture.passes(InputDevice::Mouse, ElementState::Pressed));
+    }
+
+    #[test]
+    fn captured_device_passes_releases() {
+        let capture = UiCapture { keyboard: true, mouse: true };
+
+        assert!(capture.passes(InputDevice::Keyboard, ElementState::Released));
+        assert!(capture.passes(InputDevice::Mouse, ElementState::Released));
+    }
+
+    #[test]
+    fn drag_state_tracks_range() {
+        use mouse::DragState;
+
+        let mut drag = DragState::default();
+
+        // Press with nothing under cursor doesn't start dragging while held.
+        drag.update(true, None);
+        drag.update(true, Some(0));
+        assert_eq!(drag, DragState::Held);
+
+        drag.update(false, Some(0));
+        assert_eq!(drag, DragState::Idle);
+
+        drag.update(true, Some(1));
+        drag.update(true, Some(5));
+        drag.update(true, None);
+        assert_eq!(drag.dragging(), Some((1, 5)));
+
+        drag.update(false, Some(7));
+        assert_eq!(drag.take_released(), Some((1, 5)));
+        assert_eq!(drag, DragState::Idle);
+
+        // Button held before the drag state was made is not a press.
+        let mut drag = DragState::new(true);
+        drag.update(true, Some(1));
+        assert_eq!(drag.dragging(), None);
+    }
+
+    #[test]
+    fn no_capture_passes_everything() {
+        let capture = UiCapture::default();
+
+        for device in [InputDevice::Keyboard, InputDevice::Mouse] {
+            for state in [ElementState::Pressed, ElementState::Released] {
+                assert!(capture.passes(device, state));
+            }
+        }
+    }
+}
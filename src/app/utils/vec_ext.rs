@@ -0,0 +1,460 @@
+//! Additions to [`math_linear`] vector types.
+
+use {
+    math_linear::prelude::*,
+    directx_math::{
+        XMVectorSet, XMVectorGetX, XMVectorGetY, XMVectorGetZ, XMVectorGetW, XMVector3Dot, XMVector3Cross,
+        XMVectorMin, XMVectorMax, XMVectorClamp, XMVector3Transform, XMLoadFloat4x4, XMVECTOR, XMFLOAT4X4,
+    },
+};
+
+pub trait Int3Ext {
+    /// Converts to [`USize3`]. Gives [`None`] if some component is negative.
+    fn try_into_usize3(self) -> Option<USize3>;
+
+    /// Dot product.
+    fn dot(self, other: Self) -> i32;
+
+    /// Cross product.
+    fn cross(self, other: Self) -> Self;
+
+    /// Squared length. It is widened to [`i64`] so large positions don't overflow.
+    fn len_squared(self) -> i64;
+
+    /// Manhattan distance to `other`.
+    fn manhattan(self, other: Self) -> i32;
+
+    /// Component-wise minimum.
+    fn min(self, other: Self) -> Self;
+
+    /// Component-wise maximum.
+    fn max(self, other: Self) -> Self;
+
+    /// Component-wise clamp.
+    /// # Panic
+    /// Panics if some component of `lo` is greater than the same component of `hi`.
+    fn clamp(self, lo: Self, hi: Self) -> Self;
+
+    /// Gives component by `axis` index, `0` is `x`, `1` is `y` and `2` is `z`.
+    /// # Panic
+    /// Panics if `axis >= 3`.
+    fn axis(self, axis: usize) -> i32;
+
+    /// Gives mutable reference to component by `axis` index, see [`Int3Ext::axis`].
+    /// # Panic
+    /// Panics if `axis >= 3`.
+    fn axis_mut(&mut self, axis: usize) -> &mut i32;
+}
+
+impl Int3Ext for Int3 {
+    fn try_into_usize3(self) -> Option<USize3> {
+        Some(USize3::new(
+            usize::try_from(self.x).ok()?,
+            usize::try_from(self.y).ok()?,
+            usize::try_from(self.z).ok()?,
+        ))
+    }
+
+    fn dot(self, other: Self) -> i32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    fn len_squared(self) -> i64 {
+        let (x, y, z) = (self.x as i64, self.y as i64, self.z as i64);
+        x * x + y * y + z * z
+    }
+
+    fn manhattan(self, other: Self) -> i32 {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+
+    fn min(self, other: Self) -> Self {
+        Self::new(
+            Ord::min(self.x, other.x),
+            Ord::min(self.y, other.y),
+            Ord::min(self.z, other.z),
+        )
+    }
+
+    fn max(self, other: Self) -> Self {
+        Self::new(
+            Ord::max(self.x, other.x),
+            Ord::max(self.y, other.y),
+            Ord::max(self.z, other.z),
+        )
+    }
+
+    fn clamp(self, lo: Self, hi: Self) -> Self {
+        Self::new(
+            Ord::clamp(self.x, lo.x, hi.x),
+            Ord::clamp(self.y, lo.y, hi.y),
+            Ord::clamp(self.z, lo.z, hi.z),
+        )
+    }
+
+    fn axis(self, axis: usize) -> i32 {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            2 => self.z,
+            _ => panic!("axis index should be less than 3, got {axis}"),
+        }
+    }
+
+    fn axis_mut(&mut self, axis: usize) -> &mut i32 {
+        match axis {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("axis index should be less than 3, got {axis}"),
+        }
+    }
+}
+
+pub trait Float4Ext {
+    /// Gives components as `[x, y, z, w]`.
+    fn to_array(self) -> [f32; 4];
+
+    /// Drops `w` component.
+    fn xyz(self) -> vec3;
+
+    /// Makes vector from `xyz` and `w` components.
+    fn from_vec3(xyz: vec3, w: f32) -> Self;
+
+    /// Dot product of `xyz` components.
+    fn dot(self, other: Self) -> f32;
+
+    /// Cross product of `xyz` components. Result `w` is `0.0`.
+    fn cross(self, other: Self) -> Self;
+
+    /// Length of `xyz` components, `w` is ignored like in [`dot`][Float4Ext::dot].
+    fn len(self) -> f32;
+
+    /// Squared length of `xyz` components.
+    fn len_squared(self) -> f32;
+
+    /// Component-wise minimum.
+    fn min(self, other: Self) -> Self;
+
+    /// Component-wise maximum.
+    fn max(self, other: Self) -> Self;
+
+    /// Component-wise clamp.
+    /// # Panic
+    /// Panics if some component of `lo` is greater than the same component of `hi`.
+    fn clamp(self, lo: Self, hi: Self) -> Self;
+
+    /// Linear interpolation of all four components, `t = 0.0` gives `self`.
+    fn lerp(self, other: Self, t: f32) -> Self;
+
+    /// Reflects `xyz` components off plane with unit `normal`. `w` is kept.
+    fn reflect(self, normal: Self) -> Self;
+}
+
+impl Float4Ext for Float4 {
+    fn to_array(self) -> [f32; 4] {
+        [self.x(), self.y(), self.z(), self.w()]
+    }
+
+    fn xyz(self) -> vec3 {
+        vec3::new(self.x(), self.y(), self.z())
+    }
+
+    fn from_vec3(xyz: vec3, w: f32) -> Self {
+        Self::new(xyz.x, xyz.y, xyz.z, w)
+    }
+
+    fn dot(self, other: Self) -> f32 {
+        XMVectorGetX(XMVector3Dot(to_xm(self), to_xm(other)))
+    }
+
+    fn cross(self, other: Self) -> Self {
+        let cross = XMVector3Cross(to_xm(self), to_xm(other));
+        Self::new(XMVectorGetX(cross), XMVectorGetY(cross), XMVectorGetZ(cross), 0.0)
+    }
+
+    fn len(self) -> f32 {
+        self.len_squared().sqrt()
+    }
+
+    fn len_squared(self) -> f32 {
+        self.dot(self)
+    }
+
+    fn min(self, other: Self) -> Self {
+        from_xm(XMVectorMin(to_xm(self), to_xm(other)))
+    }
+
+    fn max(self, other: Self) -> Self {
+        from_xm(XMVectorMax(to_xm(self), to_xm(other)))
+    }
+
+    fn clamp(self, lo: Self, hi: Self) -> Self {
+        let ([lo_x, lo_y, lo_z, lo_w], [hi_x, hi_y, hi_z, hi_w]) = (lo.to_array(), hi.to_array());
+        assert!(
+            lo_x <= hi_x && lo_y <= hi_y && lo_z <= hi_z && lo_w <= hi_w,
+            "clamp bounds should be ordered: lo = {lo:?}, hi = {hi:?}",
+        );
+
+        from_xm(XMVectorClamp(to_xm(self), to_xm(lo), to_xm(hi)))
+    }
+
+    fn lerp(self, other: Self, t: f32) -> Self {
+        let ([x0, y0, z0, w0], [x1, y1, z1, w1]) = (self.to_array(), other.to_array());
+        let mix = |from: f32, to: f32| from + (to - from) * t;
+
+        Self::new(mix(x0, x1), mix(y0, y1), mix(z0, z1), mix(w0, w1))
+    }
+
+    fn reflect(self, normal: Self) -> Self {
+        let reflected = self.xyz() - normal.xyz() * (2.0 * self.dot(normal));
+        Self::from_vec3(reflected, self.w())
+    }
+}
+
+/// Converts to [`directx_math`] vector.
+fn to_xm(vec: Float4) -> XMVECTOR {
+    XMVectorSet(vec.x(), vec.y(), vec.z(), vec.w())
+}
+
+/// Converts from [`directx_math`] vector.
+fn from_xm(vec: XMVECTOR) -> Float4 {
+    Float4::new(XMVectorGetX(vec), XMVectorGetY(vec), XMVectorGetZ(vec), XMVectorGetW(vec))
+}
+
+/// Transforms each point by full `transform` in place treating its `w` as `1.0`,
+/// so translation is applied. Gives transformed `w` components.
+pub fn transform_points(points: &mut [Float4], transform: &mat4) {
+    let transform = XMLoadFloat4x4(&XMFLOAT4X4 { m: transform.as_2d_array() });
+
+    for point in points.iter_mut() {
+        *point = from_xm(XMVector3Transform(to_xm(*point), transform));
+    }
+}
+
+/// Normalizes `xyz` of all vectors in place. `w` components and zero vectors are kept.
+pub fn normalize_all(vectors: &mut [Float4]) {
+    for vector in vectors.iter_mut() {
+        let len = vector.len();
+
+        if len > 0.0 {
+            *vector = Float4::from_vec3(vector.xyz() / len, vector.w());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_into_usize3_rejects_negative() {
+        assert_eq!(veci!(1, 2, 3).try_into_usize3(), Some(USize3::new(1, 2, 3)));
+        assert_eq!(veci!(0, 0, 0).try_into_usize3(), Some(USize3::ZERO));
+        assert_eq!(veci!(1, -2, 3).try_into_usize3(), None);
+        assert_eq!(veci!(i32::MIN, 0, 0).try_into_usize3(), None);
+    }
+
+    #[test]
+    fn float4_to_array_keeps_components() {
+        let vec = Float4::new(1.0, -2.0, 3.5, 4.0);
+        assert_eq!(vec.to_array(), [1.0, -2.0, 3.5, 4.0]);
+    }
+
+    #[test]
+    fn float4_xyz_drops_w() {
+        let vec = Float4::new(1.0, -2.0, 3.5, 4.0);
+        assert_eq!(vec.xyz(), vecf!(1.0, -2.0, 3.5));
+    }
+
+    #[test]
+    fn float4_from_vec3() {
+        let from_vec = Float4::from_vec3(vecf!(0.5, 6.0, -7.0), 1.0);
+        assert_eq!(from_vec.to_array(), [0.5, 6.0, -7.0, 1.0]);
+        assert_eq!(from_vec.xyz(), vecf!(0.5, 6.0, -7.0));
+    }
+
+    #[test]
+    fn int3_dot_and_cross() {
+        let (x, y, z) = (veci!(1, 0, 0), veci!(0, 1, 0), veci!(0, 0, 1));
+
+        assert_eq!(x.dot(y), 0);
+        assert_eq!(x.cross(y), z);
+        assert_eq!(y.cross(x), Int3::ZERO - z);
+
+        let vec = veci!(2, -3, 4);
+        assert_eq!(vec.dot(vec), 29);
+        assert_eq!(vec.cross(veci!(-4, 6, -8)), Int3::ZERO);
+
+        let (a, b, c) = (veci!(1, 1, 1), veci!(3, 1, 1), veci!(1, 4, 1));
+        assert_eq!((b - a).cross(c - a), veci!(0, 0, 6));
+    }
+
+    #[test]
+    fn float4_dot_and_cross_ignore_w() {
+        let x = Float4::new(1.0, 0.0, 0.0, 5.0);
+        let y = Float4::new(0.0, 1.0, 0.0, 7.0);
+
+        assert_eq!(x.dot(y), 0.0);
+        assert_eq!(x.cross(y).to_array(), [0.0, 0.0, 1.0, 0.0]);
+
+        let vec = Float4::new(2.0, -3.0, 4.0, 1.0);
+        assert_eq!(vec.dot(vec), 29.0);
+        assert_eq!(vec.cross(Float4::new(-1.0, 1.5, -2.0, 1.0)).to_array(), [0.0; 4]);
+
+        let (a, b, c) = (
+            Float4::new(1.0, 1.0, 1.0, 1.0),
+            Float4::new(1.0, 1.0, 3.0, 1.0),
+            Float4::new(1.0, 4.0, 1.0, 1.0),
+        );
+        assert_eq!((b - a).cross(c - a).to_array(), [-6.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn int3_len_squared_and_manhattan() {
+        assert_eq!(veci!(3, 4, 0).len_squared(), 25);
+        assert_eq!(veci!(0, -3, 4).len_squared(), 25);
+        assert_eq!(veci!(3, 4, 0).manhattan(Int3::ZERO), 7);
+        assert_eq!(veci!(1, -2, 3).manhattan(veci!(-1, 2, 0)), 9);
+
+        let far = veci!(50_000, 50_000, 0);
+        assert!(far.len_squared() > i32::MAX as i64);
+        assert_eq!(far.len_squared(), 5_000_000_000);
+    }
+
+    #[test]
+    fn float4_len_of_3_4_5_triangle() {
+        let vec = Float4::new(3.0, 0.0, 4.0, 0.0);
+        assert_eq!(vec.len_squared(), 25.0);
+        assert_eq!(vec.len(), 5.0);
+
+        // `w` is ignored.
+        assert_eq!(Float4::new(3.0, 0.0, 4.0, 7.0).len(), 5.0);
+        assert_eq!(Float4::new(0.0, 0.0, 0.0, 1.0).len_squared(), 0.0);
+    }
+
+    #[test]
+    fn int3_min_max_clamp_are_component_wise() {
+        let (a, b) = (veci!(1, -5, 7), veci!(3, -8, 7));
+
+        assert_eq!(a.min(b), veci!(1, -8, 7));
+        assert_eq!(a.max(b), veci!(3, -5, 7));
+
+        let (lo, hi) = (veci!(0, 0, 0), veci!(4, 4, 4));
+        assert_eq!(veci!(-1, 2, 9).clamp(lo, hi), veci!(0, 2, 4));
+        assert_eq!(veci!(5, -3, 4).clamp(lo, hi), veci!(4, 0, 4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn int3_clamp_panics_on_unordered_bounds() {
+        veci!(1, 1, 1).clamp(veci!(0, 2, 0), veci!(2, 0, 2));
+    }
+
+    #[test]
+    fn int3_axis_reads_and_writes_components() {
+        let mut pos = veci!(4, -5, 6);
+        assert_eq!([pos.axis(0), pos.axis(1), pos.axis(2)], [4, -5, 6]);
+
+        for axis in 0..3 {
+            *pos.axis_mut(axis) += 10;
+        }
+
+        assert_eq!(pos, veci!(14, 5, 16));
+    }
+
+    #[test]
+    #[should_panic(expected = "axis index should be less than 3")]
+    fn int3_axis_out_of_bounds_panics() {
+        veci!(1, 2, 3).axis(3);
+    }
+
+    #[test]
+    fn float4_min_max_clamp_are_component_wise() {
+        let (a, b) = (Float4::new(1.0, -5.0, 7.0, 0.5), Float4::new(3.0, -8.0, 7.0, -0.5));
+
+        assert_eq!(a.min(b).to_array(), [1.0, -8.0, 7.0, -0.5]);
+        assert_eq!(a.max(b).to_array(), [3.0, -5.0, 7.0, 0.5]);
+
+        let (lo, hi) = (Float4::new(0.0, 0.0, 0.0, 0.0), Float4::new(1.0, 2.0, 3.0, 4.0));
+        assert_eq!(Float4::new(-1.0, 2.5, 1.5, 9.0).clamp(lo, hi).to_array(), [0.0, 2.0, 1.5, 4.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn float4_clamp_panics_on_unordered_bounds() {
+        let lo = Float4::new(0.0, 0.0, 0.0, 1.0);
+        let hi = Float4::new(1.0, 1.0, 1.0, 0.0);
+        Float4::new(0.5, 0.5, 0.5, 0.5).clamp(lo, hi);
+    }
+
+    #[test]
+    fn float4_lerp_ends_and_middle() {
+        let (from, to) = (Float4::new(0.0, 2.0, -4.0, 1.0), Float4::new(2.0, 6.0, 4.0, 0.0));
+
+        assert_eq!(from.lerp(to, 0.0).to_array(), from.to_array());
+        assert_eq!(from.lerp(to, 1.0).to_array(), to.to_array());
+        assert_eq!(from.lerp(to, 0.5).to_array(), [1.0, 4.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn float4_reflect_bounces_off_floor() {
+        let velocity = Float4::new(3.0, -2.0, 1.0, 7.0);
+        let up = Float4::new(0.0, 1.0, 0.0, 0.0);
+
+        assert_eq!(velocity.reflect(up).to_array(), [3.0, 2.0, 1.0, 7.0]);
+    }
+
+    #[test]
+    fn batch_transform_matches_per_point_transform() {
+        // View matrix has both rotation and translation.
+        let transform = mat4::look_at_lh(vecf!(1.0, 2.0, -3.0), vecf!(4.0, 0.0, 1.0), vecf!(0.0, 1.0, 0.0));
+        let m = transform.as_2d_array();
+
+        let points = [
+            Float4::new(1.0, 0.0, 0.0, 1.0),
+            Float4::new(0.0, -2.0, 5.0, 1.0),
+            Float4::new(3.0, 4.0, -6.0, 0.0),
+        ];
+
+        let mut batch = points;
+        transform_points(&mut batch, &transform);
+
+        for (point, transformed) in points.into_iter().zip(batch) {
+            let [x, y, z, _] = point.to_array();
+            let expected: [f32; 4] = array_init::array_init(|i| x * m[0][i] + y * m[1][i] + z * m[2][i] + m[3][i]);
+
+            for (lhs, rhs) in transformed.to_array().into_iter().zip(expected) {
+                assert!((lhs - rhs).abs() < 1e-5, "{transformed:?} should be {expected:?}");
+            }
+        }
+
+        let mut origin = [Float4::new(0.0, 0.0, 0.0, 0.0)];
+        transform_points(&mut origin, &transform);
+        assert_eq!(origin[0].to_array(), m[3], "translation should be applied");
+    }
+
+    #[test]
+    fn normalize_all_gives_unit_vectors() {
+        let mut vectors = [
+            Float4::new(3.0, 0.0, 4.0, 0.0),
+            Float4::new(0.0, 0.0, 0.0, 0.0),
+            Float4::new(1.0, 1.0, 1.0, 1.0),
+        ];
+
+        normalize_all(&mut vectors);
+
+        assert_eq!(vectors[0].to_array(), [0.6, 0.0, 0.8, 0.0]);
+        assert_eq!(vectors[1].to_array(), [0.0; 4]);
+        assert_eq!(vectors[2].xyz(), vecf!(1.0, 1.0, 1.0) / 3.0_f32.sqrt());
+        assert_eq!(vectors[2].w(), 1.0);
+    }
+}
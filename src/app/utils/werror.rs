@@ -6,6 +6,7 @@ use {
 pub fn set_panic_hook() {
     std::panic::set_hook(Box::new(|panic_info| {
         eprintln!("Panic occured: {panic_info}");
+        crate::app::utils::logger::try_flush_file_sink();
         error_message("Panic occured", panic_info)
             .expect("failed to make error message");
     }))
@@ -62,4 +62,79 @@ impl Window {
             .expect("length of data should be divisible by 4, \
                      and width * height must equal data.len() / 4")
     }
-}
\ No newline at end of file
+}
+
+/// How window occupies the screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display)]
+pub enum WindowMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Exclusive,
+}
+
+impl WindowMode {
+    /// Gives next mode in `Windowed -> Borderless -> Exclusive -> Windowed` cycle.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Windowed => Self::Borderless,
+            Self::Borderless => Self::Exclusive,
+            Self::Exclusive => Self::Windowed,
+        }
+    }
+}
+
+/// Position and size of window in windowed mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WindowGeometry {
+    pub pos: Int2,
+    pub size: UInt2,
+}
+
+/// Current [window mode][WindowMode] with windowed geometry to restore after fullscreen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WindowModeState {
+    pub mode: WindowMode,
+    pub saved: Option<WindowGeometry>,
+}
+
+impl WindowModeState {
+    /// Switches to `new_mode`. `current` geometry is saved when window leaves windowed mode.
+    /// Gives saved geometry to restore when window comes back to windowed mode.
+    pub fn switch(&mut self, new_mode: WindowMode, current: WindowGeometry) -> Option<WindowGeometry> {
+        let prev_mode = mem::replace(&mut self.mode, new_mode);
+
+        match (prev_mode, new_mode) {
+            (WindowMode::Windowed, WindowMode::Windowed) => None,
+
+            (WindowMode::Windowed, _) => {
+                self.saved = Some(current);
+                None
+            },
+
+            (_, WindowMode::Windowed) => self.saved.take(),
+
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windowed_geometry_is_restored_after_fullscreen() {
+        let windowed = WindowGeometry { pos: Int2::new(100, 50), size: UInt2::new(1024, 768) };
+        let fullscreen = WindowGeometry { pos: Int2::new(0, 0), size: UInt2::new(1920, 1080) };
+
+        let mut state = WindowModeState::default();
+
+        assert_eq!(state.switch(WindowMode::Borderless, windowed), None);
+        assert_eq!(state.switch(WindowMode::Exclusive, fullscreen), None);
+        assert_eq!(state.switch(WindowMode::Windowed, fullscreen), Some(windowed));
+
+        assert_eq!(state, WindowModeState::default());
+        assert_eq!(state.switch(WindowMode::Windowed, windowed), None);
+    }
+}
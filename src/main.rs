@@ -1,5 +1,6 @@
 #![cfg_attr(feature = "release", windows_subsystem = "windows")]
-#![feature(generators, generator_trait, get_mut_unchecked, exhaustive_patterns, associated_type_defaults, never_type)]
+#![feature(generators, generator_trait, exhaustive_patterns, associated_type_defaults, never_type)]
+#![cfg_attr(test, feature(test))]
 
 #[allow(unused_imports)]
 #[macro_use(vecf, veci, vecu, vecs)]
@@ -14,8 +15,10 @@ use app::App;
 use runtime::RUNTIME;
 
 fn main() {
-    env_logger::init();
+    app::utils::logger::init_log_bridge()
+        .expect("logger should be set only once");
     app::utils::werror::set_panic_hook();
+    app::utils::logger::init_file_sink();
 
     RUNTIME.block_on(App::new()).run();
 }
\ No newline at end of file
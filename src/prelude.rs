@@ -9,6 +9,8 @@ pub use {
         concurrency::loading,
         runtime::RUNTIME,
         time::timer::Timer,
+        vec_ext::{Int3Ext, Float4Ext},
+        aabb_ext::AabbExt,
     },
     smallvec::{SmallVec, smallvec},
     array_init::array_init,
@@ -0,0 +1,57 @@
+struct VertexInput {
+    @location(0)
+    pos: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position)
+    clip_pos: vec4<f32>,
+}
+
+struct CommonUniforms {
+    sun_dir: vec4<f32>,
+    sky_color: vec4<f32>,
+    grid_color: vec4<f32>,
+    sun_disc: vec4<f32>,
+    screen_resolution: vec2<f32>,
+    time: f32,
+    grid_uv_width: f32,
+    crosshair_sizes: vec2<f32>,
+    _padding: vec2<f32>,
+}
+
+@group(0)
+@binding(0)
+var<uniform> common: CommonUniforms;
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var output: VertexOutput;
+    output.clip_pos = vec4<f32>(input.pos, 0.0, 1.0);
+    return output;
+}
+
+struct FragmentOutput {
+    @location(0)
+    frag_color: vec4<f32>,
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    var out: FragmentOutput;
+
+    // Pixel centers are on half-pixel offsets as crosshair sizes are.
+    let pos = abs(in.clip_pos.xy - 0.5 * common.screen_resolution);
+    let sizes = common.crosshair_sizes;
+
+    let is_cross = pos.x <= sizes.x && pos.y <= sizes.y
+                || pos.x <= sizes.y && pos.y <= sizes.x;
+
+    if !is_cross {
+        discard;
+    }
+
+    out.frag_color = vec4<f32>(1.0);
+
+    return out;
+}
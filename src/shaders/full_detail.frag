@@ -15,8 +15,17 @@ uniform sampler2D texture_atlas;
 uniform sampler2D normal_atlas;
 uniform bool is_shadow_pass;
 
+/* Grid lines, see `graphics/grid.rs` */
+uniform vec4 grid_color;
+uniform float grid_uv_width;
+
+/* Must match `TEXTURE_SIZE_F` and `ATLAS_PADDING_F` in `atlas.rs` */
+const float ATLAS_ITEM_SIZE = 0.03125;
+const float ATLAS_PADDING = 0.0078125;
+
 void process_shadow();
 void shade_standart();
+float grid_line_coverage(vec2 uv);
 
 void main() {
     if (is_shadow_pass) {
@@ -46,7 +55,22 @@ void shade_standart() {
     if (tex_color.a < 0.001)
         discard;
 
-    out_albedo = tex_color.rgb;
+    out_albedo = mix(tex_color.rgb, grid_color.rgb, grid_line_coverage(v_tex_coords) * grid_color.a);
     out_normal = v_to_world * local_normal;
     out_position = v_position;
+}
+
+float grid_line_coverage(vec2 uv) {
+    if (grid_uv_width <= 0.0)
+        return 0.0;
+
+    float face_size = ATLAS_ITEM_SIZE - 2.0 * ATLAS_PADDING;
+    vec2 in_face = mod(uv, ATLAS_ITEM_SIZE) - ATLAS_PADDING;
+    vec2 dist = min(in_face, face_size - in_face);
+
+    /* Anti-aliasing by screen-space derivative */
+    vec2 aa = fwidth(dist);
+    vec2 coverage = 1.0 - smoothstep(grid_uv_width - aa, grid_uv_width + aa, dist);
+
+    return max(coverage.x, coverage.y);
 }
\ No newline at end of file
@@ -5,6 +5,9 @@ in vec3 position;
 in vec2 tex_coords;
 in uint face_idx;
 
+/* Texture animation as `(n_frames, fps)`, see `TextureAnimation` in `voxel_data.rs` */
+in uvec2 anim;
+
 /* Output compound */
 out vec2 v_tex_coords;
 out vec3 v_normal;
@@ -17,6 +20,9 @@ uniform float time;
 uniform mat4 proj;
 uniform mat4 view;
 
+/* Logarithmic depth coefficient, `2 / log2(far + 1)`. Zero disables it. */
+uniform float log_depth_coef;
+
 uniform vec3 light_dir0;
 uniform vec3 light_pos0;
 uniform mat4 light_proj0;
@@ -24,6 +30,9 @@ uniform mat4 light_view0;
 
 uniform bool is_shadow_pass;
 
+/* Must match `TEXTURE_SIZE_F` in `atlas.rs` */
+const float ATLAS_ITEM_SIZE = 0.03125;
+
 vec3 normals[] = {
     vec3(1, 0, 0),
     vec3(-1, 0, 0),
@@ -44,6 +53,7 @@ vec3 tangents[] = {
 
 void process_shadow();
 void shade_standart();
+vec2 animated_tex_coords();
 
 void main() {
     if (is_shadow_pass) {
@@ -63,7 +73,7 @@ void process_shadow() {
 
 void shade_standart() {
     /* Assembling output compound */
-    v_tex_coords = tex_coords;
+    v_tex_coords = animated_tex_coords();
     v_normal = normals[face_idx];
     v_tangent = tangents[face_idx];
     v_bitangent = cross(v_normal, v_tangent);
@@ -78,4 +88,18 @@ void shade_standart() {
 
     /* Writing to gl_Position */
     gl_Position = proj * view * vec4(position, 1.0);
+
+    /* Far plane maps to depth of 1 */
+    if (log_depth_coef > 0.0) {
+        gl_Position.z = (log2(max(1e-6, 1.0 + gl_Position.w)) * log_depth_coef - 1.0) * gl_Position.w;
+    }
+}
+
+/* Animation frames follow the texture in atlas row */
+vec2 animated_tex_coords() {
+    if (anim.x <= 1u)
+        return tex_coords;
+
+    uint frame = uint(max(time, 0.0) * float(anim.y)) % anim.x;
+    return tex_coords + vec2(float(frame) * ATLAS_ITEM_SIZE, 0.0);
 }
\ No newline at end of file
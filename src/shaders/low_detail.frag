@@ -21,8 +21,16 @@ uniform vec3 light_dir0;
 uniform bool is_shadow_pass;
 uniform float time;
 
+/* Grid lines, see `graphics/grid.rs`. Low meshes have no atlas UVs so width is in world units */
+uniform vec4 grid_color;
+uniform float grid_world_width;
+
+/* `Voxel::SIZE` */
+uniform float voxel_size;
+
 void process_shadow();
 void shade_standart();
+float grid_line_coverage(vec3 pos, vec3 normal);
 
 void main() {
     if (is_shadow_pass) {
@@ -39,6 +47,7 @@ void shade_standart() {
         pow(v_color.b, 0.4545)
     );
     out_albedo = 0.95 * v_color;
+    out_albedo = mix(out_albedo, grid_color.rgb, grid_line_coverage(v_position, v_normal) * grid_color.a);
     out_normal = v_normal;
     out_position = v_position;
 }
@@ -47,4 +56,22 @@ void process_shadow() {
     out_position = v_position;
     out_albedo = vec3(0.0);
     out_normal = vec3(0.0);
-}
\ No newline at end of file
+}
+
+float grid_line_coverage(vec3 pos, vec3 normal) {
+    if (grid_world_width <= 0.0)
+        return 0.0;
+
+    /* Voxel centers are on integer positions, so boundaries are on half-integers */
+    vec3 in_voxel = fract(pos / voxel_size + 0.5) * voxel_size;
+    vec3 dist = min(in_voxel, voxel_size - in_voxel);
+
+    /* Boundaries along the normal are not visible on the face */
+    dist = mix(dist, vec3(voxel_size), abs(normal));
+
+    /* Anti-aliasing by screen-space derivative */
+    vec3 aa = fwidth(dist);
+    vec3 coverage = 1.0 - smoothstep(grid_world_width - aa, grid_world_width + aa, dist);
+
+    return max(coverage.x, max(coverage.y, coverage.z));
+}
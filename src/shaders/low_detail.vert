@@ -16,6 +16,9 @@ uniform float time;
 uniform mat4 proj;
 uniform mat4 view;
 
+/* Logarithmic depth coefficient, `2 / log2(far + 1)`. Zero disables it. */
+uniform float log_depth_coef;
+
 uniform vec3 light_dir0;
 uniform vec3 light_pos0;
 uniform mat4 light_proj0;
@@ -51,6 +54,11 @@ void shade_standart() {
 
     /* Writing to gl_Position */
     gl_Position = proj * view * vec4(position, 1.0);
+
+    /* Far plane maps to depth of 1 */
+    if (log_depth_coef > 0.0) {
+        gl_Position.z = (log2(max(1e-6, 1.0 + gl_Position.w)) * log_depth_coef - 1.0) * gl_Position.w;
+    }
 }
 
 
@@ -27,10 +27,12 @@ uniform mat4 proj;
 uniform mat4 view;
 uniform bool render_shadows;
 
+/// Camera planes. See `Camera::planes_uniforms`.
+uniform float z_near;
+uniform float z_far;
+
 /// These constants are shared. See cfg module.
 const vec4 DEFAULT_COLOR = vec4(0.21, 0.61, 0.61, 1.0);
-const float Z_NEAR = 0.5;
-const float Z_FAR = 10000.0;
 
 const vec3 LIGHT_COLOR = vec3(0.4, 0.8, 0.2);
 const float SHADOW_BRIGHTNESS = 0.05;
@@ -41,7 +43,7 @@ float linearize_depth(float d, float z_near, float z_far) {
 
 float get_depth(in vec2 uv) {
     vec4 depth = textureLod(depth_texture, uv * 0.5 + 0.5, 0.0);
-    return linearize_depth(depth.r, Z_NEAR, Z_FAR);
+    return linearize_depth(depth.r, z_near, z_far);
 }
 
 vec3 get_albedo() {
@@ -89,8 +91,8 @@ float get_shadow(in vec3 pos, float current_depth) {
     { return 1.0; }
 
     float closest_depth = texture(light_depth_texture, proj_coords.xy).r;
-    closest_depth = linearize_depth(closest_depth, Z_NEAR, Z_FAR);
-    current_depth = linearize_depth(proj_coords.z, Z_NEAR, Z_FAR);
+    closest_depth = linearize_depth(closest_depth, z_near, z_far);
+    current_depth = linearize_depth(proj_coords.z, z_near, z_far);
     bool is_shadow = current_depth - 0.00003 > closest_depth;
 
     out_color = vec4(vec3(closest_depth), 1.0);
@@ -202,7 +204,7 @@ void main() {
     if (render_shadows)
         light_depth = get_light_depth();
 
-    if (depth > Z_FAR * 0.5)
+    if (depth > z_far * 0.5)
         out_color = DEFAULT_COLOR;
     else {
         vec3 to_light_dir = -light_dir0;
@@ -14,16 +14,53 @@ struct VertexOutput {
     tex_coords: vec2<f32>,
 }
 
+struct CommonUniforms {
+    sun_dir: vec4<f32>,
+    sky_color: vec4<f32>,
+    grid_color: vec4<f32>,
+    sun_disc: vec4<f32>,
+    screen_resolution: vec2<f32>,
+    time: f32,
+    grid_uv_width: f32,
+    crosshair_sizes: vec2<f32>,
+    _padding: vec2<f32>,
+}
+
+// Must match `TEXTURE_SIZE_F` and `ATLAS_PADDING_F` in `atlas.rs`.
+const ATLAS_ITEM_SIZE: f32 = 0.03125;
+const ATLAS_PADDING: f32 = 0.0078125;
+
+// Gives grid line coverage in `0.0..1.0` for atlas UV coordinates.
+fn grid_line_coverage(uv: vec2<f32>) -> f32 {
+    if common.grid_uv_width <= 0.0 {
+        return 0.0;
+    }
+
+    let face_size = ATLAS_ITEM_SIZE - 2.0 * ATLAS_PADDING;
+    let in_face = uv % vec2<f32>(ATLAS_ITEM_SIZE) - vec2<f32>(ATLAS_PADDING);
+    let dist = min(in_face, vec2<f32>(face_size) - in_face);
+
+    // Anti-aliasing by screen-space derivative.
+    let aa = fwidth(dist);
+    let coverage = vec2<f32>(1.0) - smoothstep(
+        vec2<f32>(common.grid_uv_width) - aa,
+        vec2<f32>(common.grid_uv_width) + aa,
+        dist,
+    );
+
+    return max(coverage.x, coverage.y);
+}
+
 @group(0)
 @binding(0)
-var<uniform> time: f32;
+var<uniform> common: CommonUniforms;
 
 @vertex
 fn vs_main(input: VertexInput) -> VertexOutput {
     var output: VertexOutput;
 
     output.tex_coords = input.tex_coords;
-    let sin_time = sin(time) * 0.5 + 0.5;
+    let sin_time = sin(common.time) * 0.5 + 0.5;
     output.clip_pos = vec4<f32>(input.pos * sin_time, 0.0, 1.0);
 
     return output;
@@ -48,7 +85,11 @@ var tex_sampler: sampler;
 fn fs_main(in: VertexOutput) -> FragmentOutput {
     var out: FragmentOutput;
 
-    out.frag_color = textureSample(texture, tex_sampler, in.tex_coords);
+    let sun_light = max(common.sun_dir.y, 0.0) * 0.7 + 0.3;
+    let color = textureSample(texture, tex_sampler, in.tex_coords);
+    let lit_color = color.rgb * sun_light;
+    let grid = grid_line_coverage(in.tex_coords) * common.grid_color.a;
+    out.frag_color = vec4<f32>(mix(lit_color, common.grid_color.rgb, grid), color.a);
 
     return out;
 }
\ No newline at end of file
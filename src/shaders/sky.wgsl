@@ -0,0 +1,65 @@
+struct VertexInput {
+    @location(0)
+    pos: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position)
+    clip_pos: vec4<f32>,
+}
+
+struct CommonUniforms {
+    sun_dir: vec4<f32>,
+    sky_color: vec4<f32>,
+    grid_color: vec4<f32>,
+    sun_disc: vec4<f32>,
+    screen_resolution: vec2<f32>,
+    time: f32,
+    grid_uv_width: f32,
+    crosshair_sizes: vec2<f32>,
+    _padding: vec2<f32>,
+}
+
+const SUN_COLOR: vec3<f32> = vec3<f32>(1.0, 0.95, 0.8);
+
+// Glow radius in sun radii.
+const SUN_GLOW_SCALE: f32 = 4.0;
+
+@group(0)
+@binding(0)
+var<uniform> common: CommonUniforms;
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var output: VertexOutput;
+    output.clip_pos = vec4<f32>(input.pos, 0.0, 1.0);
+    return output;
+}
+
+struct FragmentOutput {
+    @location(0)
+    frag_color: vec4<f32>,
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    var out: FragmentOutput;
+
+    // `clip_pos.xy` is in pixels from top-left corner as `sun_disc.xy` is.
+    let radius = common.sun_disc.z * common.screen_resolution.y;
+    let dist = distance(in.clip_pos.xy, common.sun_disc.xy);
+
+    // Anti-aliasing by screen-space derivative.
+    let aa = fwidth(dist);
+    let disc = 1.0 - smoothstep(radius - aa, radius + aa, dist);
+
+    let glow = pow(max(1.0 - dist / (radius * SUN_GLOW_SCALE), 0.0), 2.0) * 0.5;
+
+    // The sun fades out while setting below the horizon.
+    let height_factor = smoothstep(-0.05, 0.05, common.sun_dir.y);
+
+    let alpha = max(disc, glow) * height_factor * common.sun_disc.w;
+    out.frag_color = vec4<f32>(SUN_COLOR, alpha);
+
+    return out;
+}